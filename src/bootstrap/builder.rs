@@ -371,6 +371,7 @@ impl<'a> Builder<'a> {
                 test::Tidy,
                 test::Bootstrap,
                 test::Ui,
+                test::UiJson,
                 test::RunPass,
                 test::CompileFail,
                 test::ParseFail,
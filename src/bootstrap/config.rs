@@ -166,6 +166,7 @@ pub struct Target {
     pub crt_static: Option<bool>,
     pub musl_root: Option<PathBuf>,
     pub qemu_rootfs: Option<PathBuf>,
+    pub runner: Option<String>,
     pub no_std: bool,
 }
 
@@ -326,6 +327,7 @@ struct TomlTarget {
     crt_static: Option<bool>,
     musl_root: Option<String>,
     qemu_rootfs: Option<String>,
+    runner: Option<String>,
 }
 
 impl Config {
@@ -573,6 +575,7 @@ impl Config {
                 target.crt_static = cfg.crt_static.clone();
                 target.musl_root = cfg.musl_root.clone().map(PathBuf::from);
                 target.qemu_rootfs = cfg.qemu_rootfs.clone().map(PathBuf::from);
+                target.runner = cfg.runner.clone();
 
                 config.target_config.insert(INTERNER.intern_string(triple.clone()), target);
             }
@@ -776,8 +776,17 @@ impl Build {
     /// Returns whether the target will be tested using the `remote-test-client`
     /// and `remote-test-server` binaries.
     fn remote_tested(&self, target: Interned<String>) -> bool {
-        self.qemu_rootfs(target).is_some() || target.contains("android") ||
-        env::var_os("TEST_DEVICE_ADDR").is_some()
+        self.runner(target).is_none() &&
+        (self.qemu_rootfs(target).is_some() || target.contains("android") ||
+         env::var_os("TEST_DEVICE_ADDR").is_some())
+    }
+
+    /// Returns the command that test binaries for `target` are run under, if
+    /// one was configured.
+    fn runner(&self, target: Interned<String>) -> Option<&str> {
+        self.config.target_config.get(&target)
+            .and_then(|t| t.runner.as_ref())
+            .map(|s| &s[..])
     }
 
     /// Returns the root of the "rootfs" image that this target will be using,
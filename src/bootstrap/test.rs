@@ -735,6 +735,12 @@ default_test_with_compare_mode!(Ui {
     compare_mode: "nll"
 });
 
+default_test!(UiJson {
+    path: "src/test/ui-json",
+    mode: "ui-json",
+    suite: "ui-json"
+});
+
 default_test!(RunPass {
     path: "src/test/run-pass",
     mode: "run-pass",
@@ -1135,7 +1141,9 @@ impl Step for Compiletest {
                 .arg("");
         }
 
-        if builder.remote_tested(target) {
+        if let Some(runner) = builder.runner(target) {
+            cmd.arg("--runner").arg(runner);
+        } else if builder.remote_tested(target) {
             cmd.arg("--remote-test-client")
                 .arg(builder.tool_exe(Tool::RemoteTestClient));
         }
@@ -1628,7 +1636,9 @@ impl Step for Crate {
             cargo.arg("--quiet");
         }
 
-        if target.contains("emscripten") {
+        if let Some(runner) = builder.runner(target) {
+            cargo.env(format!("CARGO_TARGET_{}_RUNNER", envify(&target)), runner);
+        } else if target.contains("emscripten") {
             cargo.env(
                 format!("CARGO_TARGET_{}_RUNNER", envify(&target)),
                 builder
@@ -315,3 +315,55 @@ fn refcell_replace_borrows() {
     let _b = x.borrow();
     x.replace(1);
 }
+
+#[test]
+fn once_cell() {
+    let c = OnceCell::new();
+    assert!(c.get().is_none());
+    c.get_or_init(|| 92);
+    assert_eq!(c.get(), Some(&92));
+
+    c.get_or_init(|| panic!("Kabom!"));
+    assert_eq!(c.get(), Some(&92));
+    assert_eq!(c.set(62), Err(62));
+}
+
+#[test]
+fn once_cell_get_or_try_init() {
+    let cell: OnceCell<String> = OnceCell::new();
+    assert_eq!(cell.get_or_try_init(|| Err(())), Err(()));
+    assert!(cell.get().is_none());
+    assert_eq!(cell.get_or_try_init(|| Ok::<_, ()>("hello".to_string())),
+               Ok(&"hello".to_string()));
+    assert_eq!(cell.into_inner(), Some("hello".to_string()));
+}
+
+#[test]
+#[should_panic]
+fn once_cell_reentrant_init() {
+    let x: OnceCell<Box<i32>> = OnceCell::new();
+    let dangling_ref: Cell<Option<&i32>> = Cell::new(None);
+    x.get_or_init(|| {
+        let r = x.get_or_init(|| Box::new(92));
+        dangling_ref.set(Some(r));
+        Box::new(62)
+    });
+}
+
+#[test]
+fn lazy_new() {
+    let called = Cell::new(0);
+    let x = Lazy::new(|| {
+        called.set(called.get() + 1);
+        92
+    });
+    assert_eq!(called.get(), 0);
+
+    let y = *x - 30;
+    assert_eq!(y, 62);
+    assert_eq!(called.get(), 1);
+
+    let y = *x - 30;
+    assert_eq!(y, 62);
+    assert_eq!(called.get(), 1);
+}
@@ -26,6 +26,7 @@
 #![feature(iterator_step_by)]
 #![feature(iterator_flatten)]
 #![feature(iterator_repeat_with)]
+#![feature(once_cell)]
 #![feature(pattern)]
 #![feature(range_is_empty)]
 #![feature(raw)]
@@ -285,6 +285,30 @@ declare_lint! {
     "warns about duplicate associated type bindings in generics"
 }
 
+declare_lint! {
+    pub INTRA_DOC_LINK_RESOLUTION_FAILURE,
+    Warn,
+    "detects intra-doc links which rustdoc cannot resolve"
+}
+
+declare_lint! {
+    pub PRIVATE_INTRA_DOC_LINKS,
+    Warn,
+    "detects intra-doc links from the documentation of public items to private items"
+}
+
+declare_lint! {
+    pub BARE_URLS,
+    Warn,
+    "detects URLs in documentation which are not links"
+}
+
+declare_lint! {
+    pub INVALID_CODEBLOCK_ATTRIBUTES,
+    Warn,
+    "detects misspelled attributes of code blocks in documentation"
+}
+
 /// Does nothing as a lint pass, but registers some `Lint`s
 /// which are used by other parts of the compiler.
 #[derive(Copy, Clone)]
@@ -337,6 +361,10 @@ impl LintPass for HardwiredLints {
             ABSOLUTE_PATHS_NOT_STARTING_WITH_CRATE,
             UNSTABLE_NAME_COLLISIONS,
             DUPLICATE_ASSOCIATED_TYPE_BINDINGS,
+            INTRA_DOC_LINK_RESOLUTION_FAILURE,
+            PRIVATE_INTRA_DOC_LINKS,
+            BARE_URLS,
+            INVALID_CODEBLOCK_ATTRIBUTES,
         )
     }
 }
@@ -118,6 +118,11 @@ impl LintLevelSets {
         // Ensure that we never exceed the `--cap-lints` argument.
         level = cmp::min(level, self.lint_cap);
 
+        // Nor the cap set for this lint by the driver.
+        if let Some(&driver_level) = sess.driver_lint_caps.get(&LintId::of(lint)) {
+            level = cmp::min(level, driver_level);
+        }
+
         return (level, src)
     }
 
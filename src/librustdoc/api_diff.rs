@@ -0,0 +1,565 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Comparison of the public API of a crate against a previous build of it.
+//!
+//! With `--api-baseline`, the previous build of the crate is loaded from its
+//! metadata and cleaned the same way as inlined items are (see
+//! `clean::inline::build_external_crate`). Both APIs are then flattened into
+//! maps from item paths to entries, which are compared to find the changes
+//! between the two builds. Every change is classified as major if it can
+//! break code using the crate, and as minor otherwise, following the usual
+//! semver rules: removing items, changing signatures, adding trait bounds,
+//! losing an auto trait impl or adding a required trait method are major,
+//! while adding items or relaxing bounds are minor.
+//!
+//! Macros are not compared, since the metadata of a crate doesn't describe
+//! them the way they are documented.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use rustc::hir;
+use rustc::hir::def_id::{CrateNum, DefId};
+use rustc::util::nodemap::FxHashMap;
+use rustc_target::spec::abi::Abi;
+
+use clean::{self, GetDefId};
+use html::item_type::ItemType;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Severity::Minor => "minor",
+            Severity::Major => "major",
+        })
+    }
+}
+
+/// A change in the public API of a crate.
+#[derive(Clone, Debug)]
+pub struct Change {
+    pub severity: Severity,
+    pub path: String,
+    pub message: String,
+}
+
+/// Prints the changes between the API of `krate` and its `api_baseline`, most
+/// severe first, and returns the exit code of rustdoc, which is 1 if any of the
+/// changes require a major version bump.
+pub fn run(krate: &clean::Crate) -> isize {
+    let (old, new) = match (&krate.api_baseline, &krate.module) {
+        (&Some(ref old), &Some(ref new)) => (old, new),
+        _ => return 0,
+    };
+
+    let mut changes = compare(old, new);
+    changes.sort_by(|a, b| b.severity.cmp(&a.severity));
+
+    for change in &changes {
+        let path = if change.path.starts_with("impl ") || change.path.starts_with('<') {
+            change.path.clone()
+        } else {
+            format!("{}::{}", krate.name, change.path)
+        };
+        println!("{}: `{}`: {}", change.severity, path, change.message);
+    }
+
+    let major = changes.iter().filter(|c| c.severity == Severity::Major).count();
+    let minor = changes.len() - major;
+    let bump = if major > 0 {
+        "major"
+    } else if minor > 0 {
+        "minor"
+    } else {
+        "patch"
+    };
+    println!("{} major and {} minor change{}, a {} version bump is required",
+             major, minor, if changes.len() == 1 { "" } else { "s" }, bump);
+    if major > 0 { 1 } else { 0 }
+}
+
+/// Compares the API of the crate rooted at `old` against the one at `new`.
+pub fn compare(old: &clean::Item, new: &clean::Item) -> Vec<Change> {
+    let old = Api::new(old);
+    let new = Api::new(new);
+    let mut changes = Vec::new();
+
+    for (path, old_entry) in &old.entries {
+        match new.entries.get(path) {
+            Some(new_entry) => compare_entries(path, old_entry, new_entry, &mut changes),
+            None => {
+                if is_reported_by_parent(old_entry, &new) {
+                    continue;
+                }
+                changes.push(Change {
+                    severity: Severity::Major,
+                    path: path.clone(),
+                    message: match old_entry.kind {
+                        ItemType::Impl => "impl was removed".to_string(),
+                        kind => format!("{} was removed", kind_name(kind)),
+                    },
+                });
+            }
+        }
+    }
+
+    for (path, new_entry) in &new.entries {
+        if old.entries.contains_key(path) || is_reported_by_parent(new_entry, &old) {
+            continue;
+        }
+        let severity = match new_entry.item.inner {
+            clean::ImplItem(ref i) if i.polarity == Some(clean::ImplPolarity::Negative) => {
+                continue
+            }
+            // Adding a public field breaks struct literals and patterns, unless
+            // they were already ruled out by a private field.
+            clean::StructFieldItem(..) => {
+                let parent = new_entry.parent.as_ref().and_then(|p| old.entries.get(p));
+                match parent.map(|p| &p.item.inner) {
+                    Some(&clean::StructItem(ref s)) if !s.fields_stripped => Severity::Major,
+                    _ => Severity::Minor,
+                }
+            }
+            // Adding a variant breaks exhaustive matches.
+            clean::VariantItem(..) => Severity::Major,
+            // Every implementor has to provide the new item.
+            clean::TyMethodItem(..) |
+            clean::AssociatedTypeItem(_, None) |
+            clean::AssociatedConstItem(_, None) => Severity::Major,
+            _ => Severity::Minor,
+        };
+        changes.push(Change {
+            severity,
+            path: path.clone(),
+            message: match new_entry.kind {
+                ItemType::Impl => "impl was added".to_string(),
+                ItemType::TyMethod => "required method was added".to_string(),
+                kind => format!("{} was added", kind_name(kind)),
+            },
+        });
+    }
+
+    changes
+}
+
+/// Whether the addition or removal of `entry` is already covered by the change
+/// to its parent, given the API it is missing from.
+fn is_reported_by_parent(entry: &Entry, other: &Api) -> bool {
+    let parent = match entry.parent {
+        Some(ref parent) => parent,
+        None => return false,
+    };
+    match other.entries.get(parent) {
+        // The parent was removed or added as a whole.
+        None => true,
+        // The contents of a re-export aren't listed, so they can't be compared.
+        Some(other_parent) => other_parent.kind == ItemType::Import,
+    }
+}
+
+fn compare_entries(path: &str, old: &Entry, new: &Entry, changes: &mut Vec<Change>) {
+    let mut change = |severity, message| {
+        changes.push(Change { severity, path: path.to_string(), message });
+    };
+
+    // Re-exports are only documented as such when the item is reachable
+    // through another path, so their presence is all that can be compared.
+    if old.kind == ItemType::Import || new.kind == ItemType::Import {
+        return;
+    }
+
+    match (old.kind, new.kind) {
+        (ItemType::Method, ItemType::TyMethod) => {
+            change(Severity::Major, "default implementation was removed".to_string());
+        }
+        (ItemType::TyMethod, ItemType::Method) => {
+            change(Severity::Minor, "default implementation was added".to_string());
+        }
+        (old_kind, new_kind) if old_kind != new_kind => {
+            change(Severity::Major, format!("changed from {} to {}",
+                                            kind_name(old_kind), kind_name(new_kind)));
+            return;
+        }
+        _ => {}
+    }
+
+    match (&old.item.inner, &new.item.inner) {
+        (&clean::ImplItem(ref old_impl), &clean::ImplItem(ref new_impl)) => {
+            let negative = |i: &clean::Impl| i.polarity == Some(clean::ImplPolarity::Negative);
+            match (negative(old_impl), negative(new_impl)) {
+                (false, true) => change(Severity::Major, "impl was removed".to_string()),
+                (true, false) => change(Severity::Minor, "impl was added".to_string()),
+                _ => {}
+            }
+        }
+        (&clean::StructItem(ref old_struct), &clean::StructItem(ref new_struct)) => {
+            if !old_struct.fields_stripped && new_struct.fields_stripped {
+                change(Severity::Major, "private fields were added".to_string());
+            }
+        }
+        (&clean::AssociatedTypeItem(_, Some(_)), &clean::AssociatedTypeItem(_, None)) |
+        (&clean::AssociatedConstItem(_, Some(_)), &clean::AssociatedConstItem(_, None)) => {
+            change(Severity::Major, "default was removed".to_string());
+        }
+        _ => {}
+    }
+
+    if old.signature != new.signature {
+        change(Severity::Major, format!("signature changed from `{}` to `{}`",
+                                        old.signature.as_ref().map_or("", |s| s),
+                                        new.signature.as_ref().map_or("", |s| s)));
+    }
+    for bound in new.bounds.difference(&old.bounds) {
+        change(Severity::Major, format!("bound `{}` was added", bound));
+    }
+    for bound in old.bounds.difference(&new.bounds) {
+        change(Severity::Minor, format!("bound `{}` was removed", bound));
+    }
+}
+
+fn kind_name(kind: ItemType) -> &'static str {
+    match kind {
+        ItemType::Module => "module",
+        ItemType::ExternCrate => "extern crate",
+        ItemType::Import => "re-export",
+        ItemType::Struct => "struct",
+        ItemType::Union => "union",
+        ItemType::Enum => "enum",
+        ItemType::Function => "function",
+        ItemType::Typedef => "type alias",
+        ItemType::Static => "static",
+        ItemType::Trait => "trait",
+        ItemType::Impl => "impl",
+        ItemType::TyMethod | ItemType::Method => "method",
+        ItemType::StructField => "field",
+        ItemType::Variant => "variant",
+        ItemType::Macro => "macro",
+        ItemType::Primitive => "primitive",
+        ItemType::AssociatedType => "associated type",
+        ItemType::Constant => "constant",
+        ItemType::AssociatedConst => "associated constant",
+        ItemType::ForeignType => "foreign type",
+    }
+}
+
+/// The public API of a crate, flattened into a map from paths to entries.
+struct Api<'a> {
+    krate: CrateNum,
+    /// The paths of the types and traits of the crate, used to name the impls
+    /// of those types.
+    paths: FxHashMap<DefId, String>,
+    entries: BTreeMap<String, Entry<'a>>,
+}
+
+struct Entry<'a> {
+    item: &'a clean::Item,
+    kind: ItemType,
+    /// The path of the entry this one is a part of, e.g. the struct of a field.
+    parent: Option<String>,
+    /// Everything about the item that has to stay the same, except for its
+    /// trait bounds.
+    signature: Option<String>,
+    bounds: BTreeSet<String>,
+}
+
+impl<'a> Api<'a> {
+    fn new(root: &'a clean::Item) -> Api<'a> {
+        let mut api = Api {
+            krate: root.def_id.krate,
+            paths: FxHashMap(),
+            entries: BTreeMap::new(),
+        };
+        let mut impls = Vec::new();
+        if let clean::ModuleItem(ref m) = root.inner {
+            api.add_module_items(None, &m.items, &mut impls);
+        }
+        // Impls are handled last, so that the paths of all types are known.
+        for item in impls {
+            api.add_impl(item);
+        }
+        api
+    }
+
+    fn add(&mut self, path: String, parent: Option<String>, item: &'a clean::Item) {
+        let (signature, bounds) = signature(item);
+        self.entries.insert(path, Entry {
+            item,
+            kind: item.type_(),
+            parent,
+            signature,
+            bounds,
+        });
+    }
+
+    fn add_module_items(&mut self,
+                        module: Option<&str>,
+                        items: &'a [clean::Item],
+                        impls: &mut Vec<&'a clean::Item>) {
+        for item in items {
+            if item.is_stripped() || item.attrs.lists("doc").has_word("hidden") {
+                continue;
+            }
+            let name = match item.inner {
+                clean::ImplItem(..) => {
+                    impls.push(item);
+                    continue;
+                }
+                clean::MacroItem(..) |
+                clean::PrimitiveItem(..) |
+                clean::ExternCrateItem(..) => continue,
+                clean::ImportItem(clean::Import::Glob(ref source)) => {
+                    format!("{:#}::*", source.path)
+                }
+                _ => match item.name {
+                    Some(ref name) => name.clone(),
+                    None => continue,
+                },
+            };
+            let path = match module {
+                Some(module) => format!("{}::{}", module, name),
+                None => name,
+            };
+
+            match item.inner {
+                clean::ModuleItem(ref m) => {
+                    self.add_module_items(Some(&path), &m.items, impls);
+                }
+                clean::StructItem(clean::Struct { ref fields, .. }) |
+                clean::UnionItem(clean::Union { ref fields, .. }) => {
+                    self.add_members(&path, fields);
+                }
+                clean::EnumItem(ref e) => self.add_members(&path, &e.variants),
+                clean::TraitItem(ref t) => self.add_members(&path, &t.items),
+                _ => {}
+            }
+            if item.def_id.krate == self.krate {
+                self.paths.insert(item.def_id, path.clone());
+            }
+            self.add(path, module.map(|m| m.to_string()), item);
+        }
+    }
+
+    fn add_members(&mut self, parent: &str, items: &'a [clean::Item]) {
+        for item in items {
+            if item.is_stripped() || item.attrs.lists("doc").has_word("hidden") {
+                continue;
+            }
+            if let Some(ref name) = item.name {
+                self.add(format!("{}::{}", parent, name), Some(parent.to_string()), item);
+            }
+        }
+    }
+
+    fn add_impl(&mut self, item: &'a clean::Item) {
+        // Impls of foreign crates are picked up when documenting `Deref`
+        // targets, but aren't part of this API.
+        if item.def_id.krate != self.krate {
+            return;
+        }
+        let i = match item.inner {
+            clean::ImplItem(ref i) => i,
+            _ => return,
+        };
+        let type_path = i.for_.def_id().and_then(|did| self.paths.get(&did).cloned());
+
+        match i.trait_ {
+            None => {
+                // Inherent items are listed as members of their type.
+                let type_path = type_path.unwrap_or_else(|| format!("{:#}", i.for_));
+                for member in &i.items {
+                    if member.is_stripped() ||
+                       member.attrs.lists("doc").has_word("hidden") {
+                        continue;
+                    }
+                    if let Some(ref name) = member.name {
+                        let path = format!("{}::{}", type_path, name);
+                        self.add(path, Some(type_path.clone()), member);
+                    }
+                }
+            }
+            Some(ref trait_) => {
+                // Name the type by its path in the crate, in case types with
+                // the same name are defined in several modules.
+                let module = type_path.as_ref().and_then(|p| p.rfind("::").map(|i| &p[..i + 2]));
+                let type_name = format!("{}{:#}", module.unwrap_or(""), i.for_);
+                let path = format!("impl {:#} for {}", trait_, type_name);
+                for member in &i.items {
+                    if let clean::TypedefItem(..) = member.inner {
+                        if let Some(ref name) = member.name {
+                            let member_path = format!("<{} as {:#}>::{}",
+                                                      type_name, trait_, name);
+                            self.add(member_path, Some(path.clone()), member);
+                        }
+                    }
+                }
+                self.add(path, type_path, item);
+            }
+        }
+    }
+}
+
+/// Splits an item into its signature and the set of its trait bounds, so that
+/// added and removed bounds can be told apart from other changes.
+fn signature(item: &clean::Item) -> (Option<String>, BTreeSet<String>) {
+    let mut bounds = BTreeSet::new();
+    let signature = match item.inner {
+        clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+            Some(fn_signature(&f.decl, &f.generics, f.unsafety, f.constness, f.abi,
+                              &mut bounds))
+        }
+        clean::MethodItem(ref m) => {
+            Some(fn_signature(&m.decl, &m.generics, m.unsafety, m.constness, m.abi,
+                              &mut bounds))
+        }
+        clean::TyMethodItem(ref m) => {
+            Some(fn_signature(&m.decl, &m.generics, m.unsafety, hir::Constness::NotConst,
+                              m.abi, &mut bounds))
+        }
+        clean::StructItem(ref s) => {
+            Some(format!("{}{}", struct_type(&s.struct_type), generics(&s.generics, &mut bounds)))
+        }
+        clean::UnionItem(ref u) => Some(format!("union{}", generics(&u.generics, &mut bounds))),
+        clean::EnumItem(ref e) => Some(format!("enum{}", generics(&e.generics, &mut bounds))),
+        clean::VariantItem(ref v) => Some(match v.kind {
+            clean::VariantKind::CLike => String::new(),
+            clean::VariantKind::Tuple(ref types) => {
+                let types = types.iter().map(|t| format!("{:#}", t)).collect::<Vec<_>>();
+                format!("({})", types.join(", "))
+            }
+            clean::VariantKind::Struct(ref s) => {
+                let fields = s.fields.iter().filter_map(|field| match field.inner {
+                    clean::StructFieldItem(ref t) => {
+                        Some(format!("{}: {:#}", field.name.as_ref().map_or("", |s| s), t))
+                    }
+                    _ => None,
+                }).collect::<Vec<_>>();
+                format!("{{ {} }}", fields.join(", "))
+            }
+        }),
+        clean::StructFieldItem(ref t) => Some(format!("{:#}", t)),
+        clean::TypedefItem(ref t, _) => {
+            Some(format!("type{} = {:#}", generics(&t.generics, &mut bounds), t.type_))
+        }
+        clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => {
+            let mutability = if s.mutability == clean::Mutable { "mut " } else { "" };
+            Some(format!("static {}{:#}", mutability, s.type_))
+        }
+        clean::ConstantItem(ref c) => Some(format!("const {:#}", c.type_)),
+        clean::TraitItem(ref t) => {
+            bounds.extend(t.bounds.iter().map(|b| format!("Self: {:#}", b)));
+            Some(format!("{}{}trait{}",
+                         if t.unsafety == hir::Unsafety::Unsafe { "unsafe " } else { "" },
+                         if t.auto { "auto " } else { "" },
+                         generics(&t.generics, &mut bounds)))
+        }
+        clean::ImplItem(ref i) => {
+            Some(format!("{}impl{}",
+                         if i.unsafety == hir::Unsafety::Unsafe { "unsafe " } else { "" },
+                         generics(&i.generics, &mut bounds)))
+        }
+        clean::AssociatedConstItem(ref t, _) => Some(format!("const {:#}", t)),
+        clean::AssociatedTypeItem(ref b, _) => {
+            bounds.extend(b.iter().map(|b| format!("Self::{}: {:#}",
+                                                    item.name.as_ref().map_or("", |s| s), b)));
+            None
+        }
+        _ => None,
+    };
+    (signature, bounds)
+}
+
+fn fn_signature(decl: &clean::FnDecl,
+                g: &clean::Generics,
+                unsafety: hir::Unsafety,
+                constness: hir::Constness,
+                abi: Abi,
+                bounds: &mut BTreeSet<String>) -> String {
+    let mut sig = String::new();
+    if constness == hir::Constness::Const {
+        sig.push_str("const ");
+    }
+    if unsafety == hir::Unsafety::Unsafe {
+        sig.push_str("unsafe ");
+    }
+    if abi != Abi::Rust {
+        sig.push_str(&format!("extern \"{}\" ", abi.name()));
+    }
+    sig.push_str("fn");
+    sig.push_str(&generics(g, bounds));
+
+    let mut inputs = decl.inputs.values.iter()
+        .map(|arg| format!("{:#}", arg.type_))
+        .collect::<Vec<_>>();
+    if decl.variadic {
+        inputs.push("...".to_string());
+    }
+    sig.push_str(&format!("({})", inputs.join(", ")));
+    if let clean::Return(ref ty) = decl.output {
+        sig.push_str(&format!(" -> {:#}", ty));
+    }
+    sig
+}
+
+fn struct_type(struct_type: &::doctree::StructType) -> &'static str {
+    match *struct_type {
+        ::doctree::Plain => "struct",
+        ::doctree::Tuple => "tuple struct",
+        ::doctree::Unit => "unit struct",
+    }
+}
+
+/// Renders the generic parameters of an item, without their bounds, which are
+/// added to `bounds` one by one instead.
+fn generics(generics: &clean::Generics, bounds: &mut BTreeSet<String>) -> String {
+    let mut params = Vec::new();
+    for param in &generics.params {
+        match *param {
+            clean::GenericParamDef::Lifetime(ref lt) => params.push(lt.get_ref().to_string()),
+            clean::GenericParamDef::Type(ref tp) => {
+                // `impl Trait` in argument position is only described by its
+                // bounds.
+                if tp.synthetic.is_none() {
+                    params.push(match tp.default {
+                        Some(ref default) => format!("{} = {:#}", tp.name, default),
+                        None => tp.name.clone(),
+                    });
+                }
+                bounds.extend(tp.bounds.iter().map(|b| format!("{}: {:#}", tp.name, b)));
+            }
+        }
+    }
+
+    for pred in &generics.where_predicates {
+        match *pred {
+            clean::WherePredicate::BoundPredicate { ref ty, bounds: ref b } => {
+                bounds.extend(b.iter().map(|b| format!("{:#}: {:#}", ty, b)));
+            }
+            clean::WherePredicate::RegionPredicate { ref lifetime, bounds: ref b } => {
+                bounds.extend(b.iter().map(|b| format!("{}: {}", lifetime.get_ref(), b.get_ref())));
+            }
+            clean::WherePredicate::EqPredicate { ref lhs, ref rhs } => {
+                bounds.insert(format!("{:#} == {:#}", lhs, rhs));
+            }
+        }
+    }
+
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
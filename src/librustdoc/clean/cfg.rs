@@ -101,6 +101,45 @@ impl Cfg {
         }
     }
 
+    /// Returns the part of the configuration about the options with one of the given names,
+    /// leaving out all other options, or `None` if nothing is left.
+    ///
+    /// The result is always implied by the configuration, so it may be less strict than it, e.g.
+    /// `all(unix, feature = "a")` becomes just `unix` when only `unix` is kept, while
+    /// `any(unix, feature = "a")` is left out entirely.
+    pub fn retain_names(&self, names: &[String]) -> Option<Cfg> {
+        self.retain_names_with_polarity(names, true)
+    }
+
+    /// `positive` is false under an odd number of `not`s, where `all` and `any` swap roles.
+    fn retain_names_with_polarity(&self, names: &[String], positive: bool) -> Option<Cfg> {
+        match *self {
+            Cfg::True | Cfg::False => None,
+            Cfg::Cfg(name, _) => {
+                if names.iter().any(|n| *n == *name.as_str()) {
+                    Some(self.clone())
+                } else {
+                    None
+                }
+            }
+            Cfg::Not(ref cfg) => cfg.retain_names_with_polarity(names, !positive).map(|cfg| !cfg),
+            Cfg::Any(ref sub_cfgs) | Cfg::All(ref sub_cfgs) => {
+                let is_all = if let Cfg::All(..) = *self { true } else { false };
+                let retained = sub_cfgs.iter()
+                    .map(|cfg| cfg.retain_names_with_polarity(names, positive));
+                // Leaving out part of an `all` only makes it less strict, but leaving out part of
+                // an `any` would make it stricter, so the whole `any` has to go then.
+                let mut sub_cfgs = if is_all == positive {
+                    retained.filter_map(|cfg| cfg).collect::<Vec<_>>()
+                } else {
+                    retained.collect::<Option<Vec<_>>>()?
+                }.into_iter();
+                let first = sub_cfgs.next()?;
+                Some(sub_cfgs.fold(first, |x, y| if is_all { x & y } else { x | y }))
+            }
+        }
+    }
+
     /// Checks whether the given configuration can be matched in the current session.
     ///
     /// Equivalent to `attr::cfg_matches`.
@@ -213,16 +252,30 @@ impl ops::BitAndAssign for Cfg {
             (&mut Cfg::False, _) | (_, Cfg::True) => {},
             (s, Cfg::False) => *s = Cfg::False,
             (s @ &mut Cfg::True, b) => *s = b,
-            (&mut Cfg::All(ref mut a), Cfg::All(ref mut b)) => a.append(b),
-            (&mut Cfg::All(ref mut a), ref mut b) => a.push(mem::replace(b, Cfg::True)),
+            (&mut Cfg::All(ref mut a), Cfg::All(ref mut b)) => {
+                for c in b.drain(..) {
+                    if !a.contains(&c) {
+                        a.push(c);
+                    }
+                }
+            },
+            (&mut Cfg::All(ref mut a), ref mut b) => {
+                if !a.contains(b) {
+                    a.push(mem::replace(b, Cfg::True));
+                }
+            },
             (s, Cfg::All(mut a)) => {
                 let b = mem::replace(s, Cfg::True);
-                a.push(b);
+                if !a.contains(&b) {
+                    a.push(b);
+                }
                 *s = Cfg::All(a);
             },
             (s, b) => {
-                let a = mem::replace(s, Cfg::True);
-                *s = Cfg::All(vec![a, b]);
+                if *s != b {
+                    let a = mem::replace(s, Cfg::True);
+                    *s = Cfg::All(vec![a, b]);
+                }
             },
         }
     }
@@ -532,6 +585,65 @@ mod test {
                 word_cfg("a") & word_cfg("b") & word_cfg("c"),
                 Cfg::All(vec![word_cfg("a"), word_cfg("b"), word_cfg("c")])
             );
+
+            assert_eq!(word_cfg("a") & word_cfg("a"), word_cfg("a"));
+            assert_eq!(
+                word_cfg("a") & word_cfg("b") & word_cfg("a"),
+                Cfg::All(vec![word_cfg("a"), word_cfg("b")])
+            );
+            assert_eq!(
+                Cfg::All(vec![word_cfg("a"), word_cfg("b")]) &
+                    Cfg::All(vec![word_cfg("b"), word_cfg("c")]),
+                Cfg::All(vec![word_cfg("a"), word_cfg("b"), word_cfg("c")])
+            );
+        })
+    }
+
+    #[test]
+    fn test_retain_names() {
+        with_globals(|| {
+            let names = vec!["unix".to_string(), "target_os".to_string()];
+
+            assert_eq!(word_cfg("unix").retain_names(&names), Some(word_cfg("unix")));
+            assert_eq!(word_cfg("test").retain_names(&names), None);
+            assert_eq!(
+                name_value_cfg("target_os", "linux").retain_names(&names),
+                Some(name_value_cfg("target_os", "linux"))
+            );
+            assert_eq!(
+                (!word_cfg("unix")).retain_names(&names),
+                Some(!word_cfg("unix"))
+            );
+            assert_eq!((!word_cfg("test")).retain_names(&names), None);
+            assert_eq!(
+                (word_cfg("unix") & name_value_cfg("feature", "a")).retain_names(&names),
+                Some(word_cfg("unix"))
+            );
+            assert_eq!(
+                (word_cfg("unix") | name_value_cfg("target_os", "redox")).retain_names(&names),
+                Some(word_cfg("unix") | name_value_cfg("target_os", "redox"))
+            );
+            assert_eq!(
+                (word_cfg("unix") | name_value_cfg("feature", "a")).retain_names(&names),
+                None
+            );
+            assert_eq!(
+                (word_cfg("test") | name_value_cfg("feature", "a")).retain_names(&names),
+                None
+            );
+            assert_eq!(
+                (!(word_cfg("unix") & name_value_cfg("feature", "a"))).retain_names(&names),
+                None
+            );
+            assert_eq!(
+                (!(word_cfg("unix") | name_value_cfg("feature", "a"))).retain_names(&names),
+                Some(!word_cfg("unix"))
+            );
+            assert_eq!(
+                (word_cfg("unix") & !(name_value_cfg("target_os", "redox") & word_cfg("test")))
+                    .retain_names(&names),
+                Some(word_cfg("unix"))
+            );
         })
     }
 
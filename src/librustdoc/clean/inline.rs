@@ -11,12 +11,13 @@
 //! Support for inlining external documentation into the current AST.
 
 use std::iter::once;
+use std::mem;
 
 use syntax::ast;
 use rustc::hir;
 
 use rustc::hir::def::{Def, CtorKind};
-use rustc::hir::def_id::DefId;
+use rustc::hir::def_id::{DefId, CrateNum, CRATE_DEF_INDEX};
 use rustc::ty;
 use rustc::util::nodemap::FxHashSet;
 
@@ -421,6 +422,40 @@ fn build_module(cx: &DocContext, did: DefId, visited: &mut FxHashSet<DefId>) ->
     }
 }
 
+/// Builds the public API of an external crate as the module item of its root.
+///
+/// This is used to compare the local crate against a previous build of itself.
+/// Unlike inlining a module with `try_inline`, all of the trait impls of the
+/// crate are collected, whether or not they were already inlined elsewhere.
+pub fn build_external_crate(cx: &DocContext, cnum: CrateNum) -> clean::Item {
+    let did = DefId { krate: cnum, index: CRATE_DEF_INDEX };
+
+    // Keep `build_impls` from pulling in the impls of every other crate, and
+    // start from a clean slate so none of this crate's impls are skipped.
+    let populated_all_crate_impls = cx.populated_all_crate_impls.replace(true);
+    let inlined = mem::replace(&mut cx.renderinfo.borrow_mut().inlined, FxHashSet());
+
+    let mut module = build_module(cx, did, &mut FxHashSet());
+    module.is_crate = true;
+    for &impl_did in cx.tcx.all_trait_implementations(cnum).iter() {
+        build_impl(cx, impl_did, &mut module.items);
+    }
+
+    cx.populated_all_crate_impls.set(populated_all_crate_impls);
+    cx.renderinfo.borrow_mut().inlined = inlined;
+
+    clean::Item {
+        source: cx.tcx.def_span(did).clean(cx),
+        name: Some(cx.tcx.crate_name(cnum).to_string()),
+        attrs: load_attrs(cx, did),
+        inner: clean::ModuleItem(module),
+        visibility: Some(clean::Public),
+        stability: cx.tcx.lookup_stability(did).clean(cx),
+        deprecation: cx.tcx.lookup_deprecation(did).clean(cx),
+        def_id: did,
+    }
+}
+
 pub fn print_inlined_const(cx: &DocContext, did: DefId) -> String {
     cx.tcx.rendered_const(did)
 }
@@ -30,6 +30,7 @@ use syntax::symbol::keywords;
 use syntax::symbol::{Symbol, InternedString};
 use syntax_pos::{self, DUMMY_SP, Pos, FileName};
 
+use rustc::lint;
 use rustc::middle::const_val::ConstVal;
 use rustc::middle::privacy::AccessLevels;
 use rustc::middle::resolve_lifetime as rl;
@@ -63,7 +64,7 @@ use doctree;
 use visit_ast;
 use html::render::{cache, ExternalLocation};
 use html::item_type::ItemType;
-use html::markdown::markdown_links;
+use html::markdown::{bare_urls, invalid_codeblock_attributes, markdown_links};
 
 pub mod inline;
 pub mod cfg;
@@ -139,6 +140,8 @@ pub struct Crate {
     // Only here so that they can be filtered through the rustdoc passes.
     pub external_traits: FxHashMap<DefId, Trait>,
     pub masked_crates: FxHashSet<CrateNum>,
+    /// The root module of the crate given with `--api-baseline`, if any.
+    pub api_baseline: Option<Item>,
 }
 
 impl<'a, 'tcx, 'rcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx, 'rcx> {
@@ -196,6 +199,10 @@ impl<'a, 'tcx, 'rcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx, 'rcx>
             }));
         }
 
+        // This needs the doc-reachability of the baseline's items, so it has to
+        // happen before the access levels are moved into the crate.
+        let api_baseline = cx.api_baseline.map(|cnum| inline::build_external_crate(cx, cnum));
+
         let mut access_levels = cx.access_levels.borrow_mut();
         let mut external_traits = cx.external_traits.borrow_mut();
 
@@ -209,6 +216,7 @@ impl<'a, 'tcx, 'rcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx, 'rcx>
             access_levels: Arc::new(mem::replace(&mut access_levels, Default::default())),
             external_traits: mem::replace(&mut external_traits, Default::default()),
             masked_crates,
+            api_baseline,
         }
     }
 }
@@ -516,10 +524,10 @@ impl Clean<Item> for doctree::Module {
                                  .map_or(true, |a| a.style == AttrStyle::Inner) {
             // inner doc comment, use the module's own scope for resolution
             cx.mod_ids.borrow_mut().push(self.id);
-            self.attrs.clean(cx)
+            (&self.attrs[..], self.id).clean(cx)
         } else {
             // outer doc comment, use its parent's scope
-            let attrs = self.attrs.clean(cx);
+            let attrs = (&self.attrs[..], self.id).clean(cx);
             cx.mod_ids.borrow_mut().push(self.id);
             attrs
         };
@@ -1174,145 +1182,245 @@ enum PathKind {
     Type,
 }
 
-fn resolution_failure(cx: &DocContext, path_str: &str) {
-    cx.sess().warn(&format!("[{}] cannot be resolved, ignoring it...", path_str));
+/// Returns the span of the documentation in `attrs`, which the lints about the
+/// documentation are reported at.
+fn span_of_attrs(attrs: &Attributes) -> syntax_pos::Span {
+    match (attrs.doc_strings.first(), attrs.doc_strings.last()) {
+        (Some(first), Some(last)) => first.span().to(last.span()),
+        _ => DUMMY_SP,
+    }
+}
+
+fn resolution_failure(cx: &DocContext, attrs: &Attributes, node: ast::NodeId, path_str: &str) {
+    cx.tcx.lint_node(lint::builtin::INTRA_DOC_LINK_RESOLUTION_FAILURE,
+                     node,
+                     span_of_attrs(attrs),
+                     &format!("`[{}]` cannot be resolved, ignoring it...", path_str));
+}
+
+/// Reports a link from the documentation of the public item `node` to a
+/// private item of the crate, which won't be documented.
+fn check_private_link(cx: &DocContext, attrs: &Attributes, node: ast::NodeId,
+                      link: &str, def: Def) {
+    match def {
+        Def::PrimTy(..) | Def::SelfTy(..) | Def::Macro(..) | Def::Err => return,
+        _ => {}
+    }
+    let target = def.def_id();
+    let access_levels = cx.access_levels.borrow();
+    if target.is_local() && access_levels.is_public(cx.tcx.hir.local_def_id(node)) &&
+       !access_levels.is_public(target) {
+        cx.tcx.lint_node(lint::builtin::PRIVATE_INTRA_DOC_LINKS,
+                         node,
+                         span_of_attrs(attrs),
+                         &format!("public documentation links to the private item `{}`",
+                                  link));
+    }
+}
+
+/// Reports the bare URLs and the misspelled code block attributes in the
+/// documentation of `node`.
+fn check_markdown(cx: &DocContext, attrs: &Attributes, node: ast::NodeId, dox: &str) {
+    for url in bare_urls(dox) {
+        cx.tcx.struct_span_lint_node(lint::builtin::BARE_URLS,
+                                     node,
+                                     span_of_attrs(attrs),
+                                     &format!("the URL `{}` is not a link", url))
+              .help(&format!("write `<{}>` to make it a link", url))
+              .emit();
+    }
+    for (attr, suggestion) in invalid_codeblock_attributes(dox) {
+        cx.tcx.struct_span_lint_node(lint::builtin::INVALID_CODEBLOCK_ATTRIBUTES,
+                                     node,
+                                     span_of_attrs(attrs),
+                                     &format!("unknown code block attribute `{}`", attr))
+              .help(&format!("did you mean `{}`?", suggestion))
+              .emit();
+    }
 }
 
 impl Clean<Attributes> for [ast::Attribute] {
     fn clean(&self, cx: &DocContext) -> Attributes {
-        let mut attrs = Attributes::from_ast(cx.sess().diagnostic(), self);
+        clean_attrs(self, None, cx)
+    }
+}
 
-        if UnstableFeatures::from_environment().is_nightly_build() {
-            let dox = attrs.collapsed_doc_value().unwrap_or_else(String::new);
-            for ori_link in markdown_links(&dox) {
-                // bail early for real links
-                if ori_link.contains('/') {
+/// The attributes of the local item with the given id, which the lints about
+/// its documentation are reported at.
+impl<'a> Clean<Attributes> for (&'a [ast::Attribute], ast::NodeId) {
+    fn clean(&self, cx: &DocContext) -> Attributes {
+        clean_attrs(self.0, Some(self.1), cx)
+    }
+}
+
+/// Cleans the attributes of an item, resolving the intra-doc links of its
+/// documentation. When the item isn't known, unresolved links are reported at
+/// the innermost module, and the other problems of the documentation aren't
+/// reported at all, since it may come from another crate.
+fn clean_attrs(ast_attrs: &[ast::Attribute], item: Option<ast::NodeId>,
+               cx: &DocContext) -> Attributes {
+    let node = item.or_else(|| cx.mod_ids.borrow().last().cloned())
+                   .unwrap_or(ast::CRATE_NODE_ID);
+    let mut attrs = Attributes::from_ast(cx.sess().diagnostic(), ast_attrs);
+
+    // Show the `#[cfg]` requirements on the options given with `--auto-doc-cfg` as if they were
+    // given with `#[doc(cfg)]`.
+    if !cx.auto_doc_cfg.is_empty() {
+        let mut auto_cfg = Cfg::True;
+        for cfg_mi in ast_attrs.lists("cfg") {
+            if let Some(cfg) = cfg_mi.meta_item().and_then(|mi| Cfg::parse(mi).ok())
+                                     .and_then(|cfg| cfg.retain_names(&cx.auto_doc_cfg)) {
+                auto_cfg &= cfg;
+            }
+        }
+        if auto_cfg != Cfg::True {
+            let cfg = match attrs.cfg.take() {
+                Some(cfg) => Cfg::clone(&cfg) & auto_cfg,
+                None => auto_cfg,
+            };
+            attrs.cfg = Some(Arc::new(cfg));
+        }
+    }
+    if let Some(item) = item {
+        if let Some(dox) = attrs.collapsed_doc_value() {
+            check_markdown(cx, &attrs, item, &dox);
+        }
+    }
+
+    if UnstableFeatures::from_environment().is_nightly_build() {
+        let dox = attrs.collapsed_doc_value().unwrap_or_else(String::new);
+        for ori_link in markdown_links(&dox) {
+            // bail early for real links
+            if ori_link.contains('/') {
+                continue;
+            }
+            let link = ori_link.replace("`", "");
+            let (def, fragment) = {
+                let mut kind = PathKind::Unknown;
+                let path_str = if let Some(prefix) =
+                    ["struct@", "enum@", "type@",
+                     "trait@", "union@"].iter()
+                                      .find(|p| link.starts_with(**p)) {
+                    kind = PathKind::Type;
+                    link.trim_left_matches(prefix)
+                } else if let Some(prefix) =
+                    ["const@", "static@",
+                     "value@", "function@", "mod@",
+                     "fn@", "module@", "method@"]
+                        .iter().find(|p| link.starts_with(**p)) {
+                    kind = PathKind::Value;
+                    link.trim_left_matches(prefix)
+                } else if link.ends_with("()") {
+                    kind = PathKind::Value;
+                    link.trim_right_matches("()")
+                } else if link.starts_with("macro@") {
+                    kind = PathKind::Macro;
+                    link.trim_left_matches("macro@")
+                } else if link.ends_with('!') {
+                    kind = PathKind::Macro;
+                    link.trim_right_matches('!')
+                } else {
+                    &link[..]
+                }.trim();
+
+                if path_str.contains(|ch: char| !(ch.is_alphanumeric() ||
+                                                  ch == ':' || ch == '_')) {
                     continue;
                 }
-                let link = ori_link.replace("`", "");
-                let (def, fragment) = {
-                    let mut kind = PathKind::Unknown;
-                    let path_str = if let Some(prefix) =
-                        ["struct@", "enum@", "type@",
-                         "trait@", "union@"].iter()
-                                          .find(|p| link.starts_with(**p)) {
-                        kind = PathKind::Type;
-                        link.trim_left_matches(prefix)
-                    } else if let Some(prefix) =
-                        ["const@", "static@",
-                         "value@", "function@", "mod@",
-                         "fn@", "module@", "method@"]
-                            .iter().find(|p| link.starts_with(**p)) {
-                        kind = PathKind::Value;
-                        link.trim_left_matches(prefix)
-                    } else if link.ends_with("()") {
-                        kind = PathKind::Value;
-                        link.trim_right_matches("()")
-                    } else if link.starts_with("macro@") {
-                        kind = PathKind::Macro;
-                        link.trim_left_matches("macro@")
-                    } else if link.ends_with('!') {
-                        kind = PathKind::Macro;
-                        link.trim_right_matches('!')
-                    } else {
-                        &link[..]
-                    }.trim();
 
-                    if path_str.contains(|ch: char| !(ch.is_alphanumeric() ||
-                                                      ch == ':' || ch == '_')) {
-                        continue;
+                match kind {
+                    PathKind::Value => {
+                        if let Ok(def) = resolve(cx, path_str, true) {
+                            def
+                        } else {
+                            resolution_failure(cx, &attrs, node, path_str);
+                            // this could just be a normal link or a broken link
+                            // we could potentially check if something is
+                            // "intra-doc-link-like" and warn in that case
+                            continue;
+                        }
                     }
-
-                    match kind {
-                        PathKind::Value => {
-                            if let Ok(def) = resolve(cx, path_str, true) {
-                                def
-                            } else {
-                                resolution_failure(cx, path_str);
-                                // this could just be a normal link or a broken link
-                                // we could potentially check if something is
-                                // "intra-doc-link-like" and warn in that case
-                                continue;
-                            }
+                    PathKind::Type => {
+                        if let Ok(def) = resolve(cx, path_str, false) {
+                            def
+                        } else {
+                            resolution_failure(cx, &attrs, node, path_str);
+                            // this could just be a normal link
+                            continue;
                         }
-                        PathKind::Type => {
-                            if let Ok(def) = resolve(cx, path_str, false) {
-                                def
-                            } else {
-                                resolution_failure(cx, path_str);
-                                // this could just be a normal link
+                    }
+                    PathKind::Unknown => {
+                        // try everything!
+                        if let Some(macro_def) = macro_resolve(cx, path_str) {
+                            if let Ok(type_def) = resolve(cx, path_str, false) {
+                                let (type_kind, article, type_disambig)
+                                    = type_ns_kind(type_def.0, path_str);
+                                ambiguity_error(cx, &attrs, path_str,
+                                                article, type_kind, &type_disambig,
+                                                "a", "macro", &format!("macro@{}", path_str));
                                 continue;
+                            } else if let Ok(value_def) = resolve(cx, path_str, true) {
+                                let (value_kind, value_disambig)
+                                    = value_ns_kind(value_def.0, path_str)
+                                        .expect("struct and mod cases should have been \
+                                                 caught in previous branch");
+                                ambiguity_error(cx, &attrs, path_str,
+                                                "a", value_kind, &value_disambig,
+                                                "a", "macro", &format!("macro@{}", path_str));
                             }
-                        }
-                        PathKind::Unknown => {
-                            // try everything!
-                            if let Some(macro_def) = macro_resolve(cx, path_str) {
-                                if let Ok(type_def) = resolve(cx, path_str, false) {
+                            (macro_def, None)
+                        } else if let Ok(type_def) = resolve(cx, path_str, false) {
+                            // It is imperative we search for not-a-value first
+                            // Otherwise we will find struct ctors for when we are looking
+                            // for structs, and the link won't work.
+                            // if there is something in both namespaces
+                            if let Ok(value_def) = resolve(cx, path_str, true) {
+                                let kind = value_ns_kind(value_def.0, path_str);
+                                if let Some((value_kind, value_disambig)) = kind {
                                     let (type_kind, article, type_disambig)
                                         = type_ns_kind(type_def.0, path_str);
                                     ambiguity_error(cx, &attrs, path_str,
                                                     article, type_kind, &type_disambig,
-                                                    "a", "macro", &format!("macro@{}", path_str));
+                                                    "a", value_kind, &value_disambig);
                                     continue;
-                                } else if let Ok(value_def) = resolve(cx, path_str, true) {
-                                    let (value_kind, value_disambig)
-                                        = value_ns_kind(value_def.0, path_str)
-                                            .expect("struct and mod cases should have been \
-                                                     caught in previous branch");
-                                    ambiguity_error(cx, &attrs, path_str,
-                                                    "a", value_kind, &value_disambig,
-                                                    "a", "macro", &format!("macro@{}", path_str));
                                 }
-                                (macro_def, None)
-                            } else if let Ok(type_def) = resolve(cx, path_str, false) {
-                                // It is imperative we search for not-a-value first
-                                // Otherwise we will find struct ctors for when we are looking
-                                // for structs, and the link won't work.
-                                // if there is something in both namespaces
-                                if let Ok(value_def) = resolve(cx, path_str, true) {
-                                    let kind = value_ns_kind(value_def.0, path_str);
-                                    if let Some((value_kind, value_disambig)) = kind {
-                                        let (type_kind, article, type_disambig)
-                                            = type_ns_kind(type_def.0, path_str);
-                                        ambiguity_error(cx, &attrs, path_str,
-                                                        article, type_kind, &type_disambig,
-                                                        "a", value_kind, &value_disambig);
-                                        continue;
-                                    }
-                                }
-                                type_def
-                            } else if let Ok(value_def) = resolve(cx, path_str, true) {
-                                value_def
-                            } else {
-                                resolution_failure(cx, path_str);
-                                // this could just be a normal link
-                                continue;
                             }
+                            type_def
+                        } else if let Ok(value_def) = resolve(cx, path_str, true) {
+                            value_def
+                        } else {
+                            resolution_failure(cx, &attrs, node, path_str);
+                            // this could just be a normal link
+                            continue;
                         }
-                        PathKind::Macro => {
-                            if let Some(def) = macro_resolve(cx, path_str) {
-                                (def, None)
-                            } else {
-                                resolution_failure(cx, path_str);
-                                continue
-                            }
+                    }
+                    PathKind::Macro => {
+                        if let Some(def) = macro_resolve(cx, path_str) {
+                            (def, None)
+                        } else {
+                            resolution_failure(cx, &attrs, node, path_str);
+                            continue
                         }
                     }
-                };
-
-                if let Def::PrimTy(_) = def {
-                    attrs.links.push((ori_link, None, fragment));
-                } else {
-                    let id = register_def(cx, def);
-                    attrs.links.push((ori_link, Some(id), fragment));
                 }
+            };
+
+            if let Some(item) = item {
+                check_private_link(cx, &attrs, item, &link, def);
             }
 
-            cx.sess().abort_if_errors();
+            if let Def::PrimTy(_) = def {
+                attrs.links.push((ori_link, None, fragment));
+            } else {
+                let id = register_def(cx, def);
+                attrs.links.push((ori_link, Some(id), fragment));
+            }
         }
 
-        attrs
+        cx.sess().abort_if_errors();
     }
+
+    attrs
 }
 
 #[derive(Clone, RustcEncodable, RustcDecodable, PartialEq, Eq, Debug, Hash)]
@@ -1946,7 +2054,7 @@ impl Clean<Item> for doctree::Function {
         });
         Item {
             name: Some(self.name.clean(cx)),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.whence.clean(cx),
             visibility: self.vis.clean(cx),
             stability: self.stab.clean(cx),
@@ -2124,7 +2232,7 @@ pub struct Trait {
 
 impl Clean<Item> for doctree::Trait {
     fn clean(&self, cx: &DocContext) -> Item {
-        let attrs = self.attrs.clean(cx);
+        let attrs = (&self.attrs[..], self.id).clean(cx);
         let is_spotlight = attrs.has_doc_flag("spotlight");
         Item {
             name: Some(self.name.clean(cx)),
@@ -2198,7 +2306,7 @@ impl Clean<Item> for hir::TraitItem {
         };
         Item {
             name: Some(self.name.clean(cx)),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.span.clean(cx),
             def_id: cx.tcx.hir.local_def_id(self.id),
             visibility: None,
@@ -2227,7 +2335,7 @@ impl Clean<Item> for hir::ImplItem {
         Item {
             name: Some(self.name.clean(cx)),
             source: self.span.clean(cx),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             def_id: cx.tcx.hir.local_def_id(self.id),
             visibility: self.vis.clean(cx),
             stability: get_stability(cx, cx.tcx.hir.local_def_id(self.id)),
@@ -2991,7 +3099,7 @@ impl Clean<Item> for hir::StructField {
     fn clean(&self, cx: &DocContext) -> Item {
         Item {
             name: Some(self.ident.name).clean(cx),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.span.clean(cx),
             visibility: self.vis.clean(cx),
             stability: get_stability(cx, cx.tcx.hir.local_def_id(self.id)),
@@ -3069,7 +3177,7 @@ impl Clean<Vec<Item>> for doctree::Struct {
 
         ret.push(Item {
             name: Some(name),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.whence.clean(cx),
             def_id: cx.tcx.hir.local_def_id(self.id),
             visibility: self.vis.clean(cx),
@@ -3094,7 +3202,7 @@ impl Clean<Vec<Item>> for doctree::Union {
 
         ret.push(Item {
             name: Some(name),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.whence.clean(cx),
             def_id: cx.tcx.hir.local_def_id(self.id),
             visibility: self.vis.clean(cx),
@@ -3146,7 +3254,7 @@ impl Clean<Vec<Item>> for doctree::Enum {
 
         ret.push(Item {
             name: Some(name),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.whence.clean(cx),
             def_id: cx.tcx.hir.local_def_id(self.id),
             visibility: self.vis.clean(cx),
@@ -3172,7 +3280,7 @@ impl Clean<Item> for doctree::Variant {
     fn clean(&self, cx: &DocContext) -> Item {
         Item {
             name: Some(self.name.clean(cx)),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.def.id()).clean(cx),
             source: self.whence.clean(cx),
             visibility: None,
             stability: self.stab.clean(cx),
@@ -3456,7 +3564,7 @@ impl Clean<Item> for doctree::Typedef {
     fn clean(&self, cx: &DocContext) -> Item {
         Item {
             name: Some(self.name.clean(cx)),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.whence.clean(cx),
             def_id: cx.tcx.hir.local_def_id(self.id.clone()),
             visibility: self.vis.clean(cx),
@@ -3507,7 +3615,7 @@ impl Clean<Item> for doctree::Static {
         debug!("cleaning static {}: {:?}", self.name.clean(cx), self);
         Item {
             name: Some(self.name.clean(cx)),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.whence.clean(cx),
             def_id: cx.tcx.hir.local_def_id(self.id),
             visibility: self.vis.clean(cx),
@@ -3532,7 +3640,7 @@ impl Clean<Item> for doctree::Constant {
     fn clean(&self, cx: &DocContext) -> Item {
         Item {
             name: Some(self.name.clean(cx)),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.whence.clean(cx),
             def_id: cx.tcx.hir.local_def_id(self.id),
             visibility: self.vis.clean(cx),
@@ -3620,7 +3728,7 @@ impl Clean<Vec<Item>> for doctree::Impl {
 
         ret.push(Item {
             name: None,
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.whence.clean(cx),
             def_id: cx.tcx.hir.local_def_id(self.id),
             visibility: self.vis.clean(cx),
@@ -3810,7 +3918,7 @@ impl Clean<Item> for hir::ForeignItem {
         };
         Item {
             name: Some(self.name.clean(cx)),
-            attrs: self.attrs.clean(cx),
+            attrs: (&self.attrs[..], self.id).clean(cx),
             source: self.span.clean(cx),
             def_id: cx.tcx.hir.local_def_id(self.id),
             visibility: self.vis.clean(cx),
@@ -13,7 +13,7 @@ use rustc_driver::{self, driver, target_features, abort_on_err};
 use rustc::session::{self, config};
 use rustc::hir::def_id::{DefId, CrateNum};
 use rustc::hir::def::Def;
-use rustc::middle::cstore::CrateStore;
+use rustc::middle::cstore::{CrateStore, CrateLoader as MiddleCrateLoader};
 use rustc::middle::privacy::AccessLevels;
 use rustc::ty::{self, TyCtxt, AllArenas};
 use rustc::hir::map as hir_map;
@@ -29,6 +29,8 @@ use syntax::ast::NodeId;
 use syntax::codemap;
 use syntax::edition::Edition;
 use syntax::feature_gate::UnstableFeatures;
+use syntax::symbol::Symbol;
+use syntax_pos::DUMMY_SP;
 use syntax::json::JsonEmitter;
 use errors;
 use errors::emitter::{Emitter, EmitterWriter};
@@ -82,7 +84,12 @@ pub struct DocContext<'a, 'tcx: 'a, 'rcx: 'a> {
     pub fake_def_ids: RefCell<FxHashMap<CrateNum, DefId>>,
     pub all_fake_def_ids: RefCell<FxHashSet<DefId>>,
     /// Maps (type_id, trait_id) -> auto trait impl
-    pub generated_synthetics: RefCell<FxHashSet<(DefId, DefId)>>
+    pub generated_synthetics: RefCell<FxHashSet<(DefId, DefId)>>,
+    /// The previous build of this crate given with `--api-baseline`, if any
+    pub api_baseline: Option<CrateNum>,
+    /// The names of the options in `#[cfg]` attributes to show like `#[doc(cfg)]`, given with
+    /// `--auto-doc-cfg`
+    pub auto_doc_cfg: Vec<String>,
 }
 
 impl<'a, 'tcx, 'rcx> DocContext<'a, 'tcx, 'rcx> {
@@ -173,12 +180,13 @@ pub fn run_core(search_paths: SearchPaths,
                 input: Input,
                 triple: Option<TargetTriple>,
                 maybe_sysroot: Option<PathBuf>,
-                allow_warnings: bool,
                 crate_name: Option<String>,
                 force_unstable_if_unmarked: bool,
                 edition: Edition,
                 cg: CodegenOptions,
-                error_format: ErrorOutputType) -> (clean::Crate, RenderInfo)
+                error_format: ErrorOutputType,
+                api_baseline: Option<String>,
+                auto_doc_cfg: Vec<String>) -> (clean::Crate, RenderInfo)
 {
     // Parse, resolve, and typecheck the given crate.
 
@@ -187,16 +195,12 @@ pub fn run_core(search_paths: SearchPaths,
         _ => None
     };
 
-    let warning_lint = lint::builtin::WARNINGS.name_lower();
-
     let host_triple = TargetTriple::from_triple(config::host_triple());
     // plays with error output here!
     let sessopts = config::Options {
         maybe_sysroot,
         search_paths,
         crate_types: vec![config::CrateTypeRlib],
-        lint_opts: if !allow_warnings { vec![(warning_lint, lint::Allow)] } else { vec![] },
-        lint_cap: Some(lint::Allow),
         cg,
         externs,
         target_triple: triple.unwrap_or(host_triple),
@@ -222,6 +226,21 @@ pub fn run_core(search_paths: SearchPaths,
         let cstore = Rc::new(CStore::new(codegen_backend.metadata_loader()));
         rustc_lint::register_builtins(&mut sess.lint_store.borrow_mut(), Some(&sess));
 
+        // The lints of the compiler run on the crate as well, but with the function bodies
+        // removed they'd mostly be wrong, so only the lints about the documentation are reported.
+        // Those warn by default, whether or not `--display-warnings` is passed.
+        let rustdoc_lints = [
+            lint::LintId::of(lint::builtin::INTRA_DOC_LINK_RESOLUTION_FAILURE),
+            lint::LintId::of(lint::builtin::PRIVATE_INTRA_DOC_LINKS),
+            lint::LintId::of(lint::builtin::BARE_URLS),
+            lint::LintId::of(lint::builtin::INVALID_CODEBLOCK_ATTRIBUTES),
+        ];
+        let lints = sess.lint_store.borrow().get_lints().iter()
+            .map(|&(l, _)| lint::LintId::of(l))
+            .filter(|id| !rustdoc_lints.contains(id))
+            .collect::<Vec<_>>();
+        sess.driver_lint_caps.extend(lints.into_iter().map(|id| (id, lint::Allow)));
+
         let mut cfg = config::build_configuration(&sess, config::parse_cfgspecs(cfgs));
         target_features::add_configuration(&mut cfg, &sess, &*codegen_backend);
         sess.parse_sess.config = cfg;
@@ -251,6 +270,15 @@ pub fn run_core(search_paths: SearchPaths,
             ..
         } = abort_on_err(result, &sess);
 
+        // The baseline is never named by the crate itself, so load it up front
+        // to make it part of the crate graph the analysis is run on.
+        let api_baseline = api_baseline.map(|baseline| {
+            let mut crate_loader = CrateLoader::new(&sess, &cstore, &name);
+            MiddleCrateLoader::process_path_extern(&mut crate_loader,
+                                                   Symbol::intern(&baseline),
+                                                   DUMMY_SP)
+        });
+
         // We need to hold on to the complete resolver, so we clone everything
         // for the analysis passes to use. Suboptimal, but necessary in the
         // current architecture.
@@ -327,6 +355,8 @@ pub fn run_core(search_paths: SearchPaths,
                 fake_def_ids: RefCell::new(FxHashMap()),
                 all_fake_def_ids: RefCell::new(FxHashSet()),
                 generated_synthetics: RefCell::new(FxHashSet()),
+                api_baseline,
+                auto_doc_cfg,
             };
             debug!("crate: {:?}", tcx.hir.krate());
 
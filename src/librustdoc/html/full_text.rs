@@ -0,0 +1,222 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The full-text search index.
+//!
+//! With `--full-text-index`, the words of the documentation of every item in
+//! the search index are written to `search-index-full.js`, so that items can
+//! be searched for by what their documentation says and not only by name.
+//! Words are reduced to their stems, so that e.g. a search for "allocating"
+//! finds documentation talking about "allocation".
+//!
+//! The search in `static/main.js` splits and stems queries the same way as
+//! this module does with the documentation, so the two have to be kept in
+//! sync.
+//!
+//! For every crate, the index is an object with the sorted stems in `t`, and
+//! in `p` the items whose documentation contains the stem at the same
+//! position. The items are given by their position in the search index of the
+//! crate, where 0 is the crate itself and `i + 1` the `i`-th item. These are
+//! written as the differences between consecutive positions, each one as a
+//! little-endian sequence of base64 digits where the value 32 is set on all
+//! digits but the last.
+
+use std::collections::BTreeMap;
+use std::iter;
+
+use pulldown_cmark::{Event, Parser, Tag};
+use serialize::json::{as_json, Json, ToJson};
+
+use clean;
+
+/// Words shorter than this aren't indexed.
+const MIN_WORD_LEN: usize = 3;
+
+/// Words too common in documentation to be worth indexing.
+const STOP_WORDS: &[&str] = &[
+    "also", "and", "are", "been", "but", "can", "for", "from", "has", "have", "its", "not",
+    "one", "such", "than", "that", "the", "them", "then", "there", "these", "they", "this",
+    "those", "was", "were", "what", "when", "where", "which", "will", "with", "you", "your",
+];
+
+const BASE64_DIGITS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Returns the sorted stems of the words in the documentation of `item`.
+pub fn item_terms(item: &clean::Item) -> Vec<String> {
+    match item.collapsed_doc_value() {
+        Some(doc) => doc_terms(&doc),
+        None => Vec::new(),
+    }
+}
+
+/// Returns the sorted stems of the words in the Markdown text `md`, leaving
+/// out code blocks, link destinations and the like.
+pub fn doc_terms(md: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut in_code_block = false;
+    for event in Parser::new(md) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            Event::Text(ref text) if !in_code_block => {
+                for word in text.split(|c: char| !c.is_alphanumeric()) {
+                    let word = word.to_lowercase();
+                    if word.chars().count() >= MIN_WORD_LEN &&
+                       !STOP_WORDS.contains(&&word[..]) {
+                        terms.push(stem(&word));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Strips the common inflectional and derivational suffixes of the lowercase
+/// English word `word`.
+///
+/// This is a much simplified version of the Porter stemmer, which only has to
+/// map the forms of a word to the same stem, not to a real word.
+pub fn stem(word: &str) -> String {
+    let mut w = word.to_string();
+    if w.len() <= 3 || !w.is_ascii() {
+        return w;
+    }
+
+    // Plurals and the third person.
+    if w.ends_with("sses") {
+        let len = w.len() - 2;
+        w.truncate(len);
+    } else if w.ends_with("ies") {
+        let len = w.len() - 3;
+        w.truncate(len);
+        w.push('y');
+    } else if w.ends_with('s') && !w.ends_with("ss") && !w.ends_with("us") &&
+              !w.ends_with("is") {
+        w.pop();
+    }
+
+    // Participles and the past tense.
+    let base_len = if w.ends_with("ing") {
+        w.len() - 3
+    } else if w.ends_with("ed") {
+        w.len() - 2
+    } else {
+        w.len()
+    };
+    if base_len < w.len() && base_len >= 3 && w[..base_len].contains(is_vowel) {
+        w.truncate(base_len);
+        let last = w.as_bytes()[base_len - 1];
+        if last == w.as_bytes()[base_len - 2] && !is_vowel(last as char) &&
+           !b"lsz".contains(&last) {
+            w.pop();
+        }
+    }
+
+    // Nouns and adverbs made from other words.
+    for &(suffix, replacement) in &[("ational", "ate"), ("ization", "ize"), ("ation", "ate"),
+                                    ("fulness", "ful"), ("iveness", "ive"), ("ness", ""),
+                                    ("ly", "")] {
+        if w.ends_with(suffix) && w.len() - suffix.len() >= 4 {
+            let len = w.len() - suffix.len();
+            w.truncate(len);
+            w.push_str(replacement);
+            break;
+        }
+    }
+
+    if w.len() > 4 && w.ends_with('e') && !w.ends_with("ee") {
+        w.pop();
+    }
+    w
+}
+
+fn is_vowel(c: char) -> bool {
+    "aeiouy".contains(c)
+}
+
+/// Builds the line of `search-index-full.js` for the crate `krate`, from the
+/// terms of the crate documentation and of the items of its search index, in
+/// the order of the search index.
+pub fn build_index<'a, I>(krate: &str, crate_terms: &'a [String], item_terms: I) -> String
+    where I: IntoIterator<Item = &'a [String]>
+{
+    let mut postings = BTreeMap::<&str, Vec<usize>>::new();
+    for (pos, terms) in iter::once(crate_terms).chain(item_terms).enumerate() {
+        for term in terms {
+            postings.entry(&term[..]).or_insert_with(Vec::new).push(pos);
+        }
+    }
+
+    let mut data = BTreeMap::new();
+    data.insert("t".to_owned(), Json::Array(postings.keys().map(|t| t.to_json()).collect()));
+    data.insert("p".to_owned(),
+                Json::Array(postings.values().map(|p| encode_positions(p).to_json()).collect()));
+    format!("fullTextIndex[{}] = {};", as_json(&krate), Json::Object(data))
+}
+
+fn encode_positions(positions: &[usize]) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for &pos in positions {
+        let mut delta = pos - last;
+        last = pos;
+        loop {
+            let digit = delta & 31;
+            delta >>= 5;
+            if delta == 0 {
+                out.push(BASE64_DIGITS[digit] as char);
+                break;
+            }
+            out.push(BASE64_DIGITS[digit | 32] as char);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{doc_terms, encode_positions, stem};
+
+    #[test]
+    fn test_stem() {
+        fn t(words: &[&str], expected: &str) {
+            for word in words {
+                assert_eq!(stem(word), expected, "stem of {}", word);
+            }
+        }
+        t(&["allocate", "allocates", "allocated", "allocating", "allocation", "allocations"],
+          "allocat");
+        t(&["run", "runs", "running"], "run");
+        t(&["entry", "entries"], "entry");
+        t(&["pass", "passes", "passed", "passing"], "pass");
+        t(&["safe", "safely"], "safe");
+        t(&["initialize", "initialized", "initialization"], "initializ");
+        t(&["only"], "only");
+        t(&["größe"], "größe");
+    }
+
+    #[test]
+    fn test_doc_terms() {
+        assert_eq!(doc_terms("Allocates the [`Vec`](struct.Vec.html) *in place*.\n\n\
+                              ```\nlet v = vec![1];\n```"),
+                   ["allocat", "plac", "vec"]);
+    }
+
+    #[test]
+    fn test_encode_positions() {
+        assert_eq!(encode_positions(&[]), "");
+        assert_eq!(encode_positions(&[0, 1, 5]), "ABE");
+        assert_eq!(encode_positions(&[40]), "oB");
+    }
+}
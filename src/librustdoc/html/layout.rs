@@ -20,6 +20,8 @@ pub struct Layout {
     pub favicon: String,
     pub external_html: ExternalHtml,
     pub krate: String,
+    /// Whether the full-text search index is generated for the crate.
+    pub full_text_index: bool,
 }
 
 pub struct Page<'a> {
@@ -132,7 +134,8 @@ pub fn render<T: fmt::Display, S: fmt::Display>(
                 </p>\
                 <p>\
                     Search functions by type signature (e.g. \
-                    <code>vec -> usize</code> or <code>* -> vec</code>)\
+                    <code>Vec&lt;T&gt; -> usize</code>, <code>&amp;str, usize -> bool</code> \
+                    or <code>* -> vec</code>)\
                 </p>\
                 <p>\
                     Search multiple things at once by splitting your query with comma (e.g. \
@@ -148,6 +151,7 @@ pub fn render<T: fmt::Display, S: fmt::Display>(
     </script>\
     <script src=\"{root_path}aliases.js\"></script>\
     <script src=\"{root_path}main{suffix}.js\"></script>\
+    {full_text_index}\
     <script defer src=\"{root_path}search-index.js\"></script>\
 </body>\
 </html>",
@@ -182,6 +186,11 @@ pub fn render<T: fmt::Display, S: fmt::Display>(
     after_content = layout.external_html.after_content,
     sidebar   = *sidebar,
     krate     = layout.krate,
+    full_text_index = if layout.full_text_index {
+        format!("<script defer src=\"{}search-index-full.js\"></script>", page.root_path)
+    } else {
+        "".to_owned()
+    },
     themes = themes.iter()
                    .filter_map(|t| t.file_stem())
                    .filter_map(|t| t.to_str())
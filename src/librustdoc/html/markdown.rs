@@ -35,6 +35,7 @@ use std::fmt::{self, Write};
 use std::str;
 use syntax::feature_gate::UnstableFeatures;
 use syntax::codemap::Span;
+use syntax_pos::FileName;
 
 use html::render::derive_id;
 use html::toc::TocBuilder;
@@ -443,6 +444,9 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector, position: Sp
     let mut prev_offset = 0;
     let mut nb_lines = 0;
     let mut register_header = None;
+    // A doctest is only added once the next code block is seen, since that block may hold the
+    // expected output of the doctest.
+    let mut pending_test = None;
     'main: while let Some(event) = parser.next() {
         match event {
             Event::Start(Tag::CodeBlock(s)) => {
@@ -451,7 +455,8 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector, position: Sp
                 } else {
                     LangString::parse(&*s)
                 };
-                if !block_info.rust {
+                if !block_info.rust && !block_info.output {
+                    add_pending_test(tests, pending_test.take(), None);
                     continue
                 }
                 let mut test_s = String::new();
@@ -473,17 +478,31 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector, position: Sp
                         break 'main;
                     }
                 }
+                if block_info.output {
+                    let unchecked = match pending_test {
+                        // `compile_fail` implies `no_run`.
+                        Some((_, ref info, ..)) if info.no_run || info.ignore => {
+                            Some("the doctest before this `output` block isn't run, so its \
+                                  output is not checked")
+                        }
+                        Some(_) => None,
+                        None => Some("this `output` block doesn't follow a doctest, so it is \
+                                      not checked"),
+                    };
+                    if let (Some(msg), Some(ref sess)) = (unchecked, sess) {
+                        sess.span_warn(position, msg);
+                    }
+                    add_pending_test(tests, pending_test.take(), Some(test_s));
+                    continue
+                }
+                add_pending_test(tests, pending_test.take(), None);
                 if let Some(offset) = offset {
                     let lines = test_s.lines().map(|l| map_line(l).for_code());
                     let text = lines.collect::<Vec<&str>>().join("\n");
                     nb_lines += doc[prev_offset..offset].lines().count();
                     let line = tests.get_line() + (nb_lines - 1);
                     let filename = tests.get_filename();
-                    tests.add_test(text.to_owned(),
-                                   block_info.should_panic, block_info.no_run,
-                                   block_info.ignore, block_info.test_harness,
-                                   block_info.compile_fail, block_info.error_codes,
-                                   line, filename, block_info.allow_fail);
+                    pending_test = Some((text, block_info, line, filename));
                     prev_offset = offset;
                 } else {
                     if let Some(ref sess) = sess {
@@ -493,6 +512,8 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector, position: Sp
                 }
             }
             Event::Start(Tag::Header(level)) => {
+                // The test names depend on the headers they're under.
+                add_pending_test(tests, pending_test.take(), None);
                 register_header = Some(level as u32);
             }
             Event::Text(ref s) if register_header.is_some() => {
@@ -507,6 +528,19 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector, position: Sp
             _ => {}
         }
     }
+    add_pending_test(tests, pending_test, None);
+}
+
+fn add_pending_test(tests: &mut ::test::Collector,
+                    test: Option<(String, LangString, usize, FileName)>,
+                    expected_output: Option<String>) {
+    if let Some((text, block_info, line, filename)) = test {
+        tests.add_test(text,
+                       block_info.should_panic, block_info.no_run,
+                       block_info.ignore, block_info.test_harness,
+                       block_info.compile_fail, block_info.error_codes,
+                       expected_output, line, filename, block_info.allow_fail);
+    }
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
@@ -520,6 +554,7 @@ struct LangString {
     compile_fail: bool,
     error_codes: Vec<String>,
     allow_fail: bool,
+    output: bool,
 }
 
 impl LangString {
@@ -534,9 +569,20 @@ impl LangString {
             compile_fail: false,
             error_codes: Vec::new(),
             allow_fail: false,
+            output: false,
         }
     }
 
+    /// The attributes which are understood in the language string of a code block.
+    const ATTRIBUTES: &'static [&'static str] = &[
+        "should_panic", "no_run", "ignore", "allow_fail", "rust", "test_harness", "compile_fail",
+        "output",
+    ];
+
+    fn tokens(string: &str) -> impl Iterator<Item = &str> {
+        string.split(|c: char| !(c == '_' || c == '-' || c.is_alphanumeric()))
+    }
+
     fn parse(string: &str) -> LangString {
         let mut seen_rust_tags = false;
         let mut seen_other_tags = false;
@@ -547,11 +593,7 @@ impl LangString {
         }
 
         data.original = string.to_owned();
-        let tokens = string.split(|c: char|
-            !(c == '_' || c == '-' || c.is_alphanumeric())
-        );
-
-        for token in tokens {
+        for token in LangString::tokens(string) {
             match token.trim() {
                 "" => {},
                 "should_panic" => {
@@ -561,6 +603,7 @@ impl LangString {
                 "no_run" => { data.no_run = true; seen_rust_tags = !seen_other_tags; }
                 "ignore" => { data.ignore = true; seen_rust_tags = !seen_other_tags; }
                 "allow_fail" => { data.allow_fail = true; seen_rust_tags = !seen_other_tags; }
+                "output" => { data.output = true; seen_other_tags = true; }
                 "rust" => { data.rust = true; seen_rust_tags = true; }
                 "test_harness" => {
                     data.test_harness = true;
@@ -783,17 +826,97 @@ pub fn markdown_links(md: &str) -> Vec<String> {
     links
 }
 
+/// Returns whether `md` contains a Rust code block, i.e. one that would be run
+/// as a documentation test.
+pub fn has_rust_code_block(md: &str) -> bool {
+    Parser::new(md).any(|event| match event {
+        Event::Start(Tag::CodeBlock(ref lang)) => lang.is_empty() || LangString::parse(lang).rust,
+        _ => false,
+    })
+}
+
+/// Returns the code of a code block with the language string `lang` as shown
+/// in the documentation if it is Rust code, that is without the lines hidden
+/// with a leading `#`, or `None` if it isn't Rust code.
+pub fn visible_rust_code(lang: &str, code: &str) -> Option<String> {
+    if !lang.is_empty() && !LangString::parse(lang).rust {
+        return None;
+    }
+    let lines = code.lines().filter_map(|l| map_line(l).for_html());
+    Some(lines.collect::<Vec<&str>>().join("\n"))
+}
+
+/// Returns the URLs in `md` which aren't links, like `https://example.com`
+/// instead of `<https://example.com>`.
+pub fn bare_urls(md: &str) -> Vec<String> {
+    fn push_urls(text: &str, urls: &mut Vec<String>) {
+        for word in text.split_whitespace() {
+            if let Some(start) = word.find("http://").or_else(|| word.find("https://")) {
+                // Punctuation at the end is more likely part of the sentence.
+                let url = word[start..].trim_right_matches(|c: char| ".,:;!?)\"'".contains(c));
+                urls.push(url.to_owned());
+            }
+        }
+    }
+
+    let mut urls = Vec::new();
+    // The depth of the links and code the parser is in.
+    let mut depth = 0;
+    // The parser may split text at characters like `_`, so the text between
+    // other events is collected first.
+    let mut text = String::new();
+    for event in Parser::new(md) {
+        match event {
+            Event::Text(ref t) => {
+                if depth == 0 {
+                    text.push_str(t);
+                }
+                continue
+            }
+            Event::Start(Tag::Link(..)) | Event::Start(Tag::Image(..)) |
+            Event::Start(Tag::Code) | Event::Start(Tag::CodeBlock(_)) => depth += 1,
+            Event::End(Tag::Link(..)) | Event::End(Tag::Image(..)) |
+            Event::End(Tag::Code) | Event::End(Tag::CodeBlock(_)) => depth -= 1,
+            _ => {}
+        }
+        push_urls(&text, &mut urls);
+        text.clear();
+    }
+    push_urls(&text, &mut urls);
+    urls
+}
+
+/// Returns the attributes of the code blocks in `md` which look like
+/// misspellings of the attributes rustdoc understands, like `should-panic`,
+/// together with the attribute they were likely meant to be.
+pub fn invalid_codeblock_attributes(md: &str) -> Vec<(String, &'static str)> {
+    let mut invalid = Vec::new();
+    for event in Parser::new(md) {
+        if let Event::Start(Tag::CodeBlock(ref lang)) = event {
+            for token in LangString::tokens(lang) {
+                let normalized = token.to_lowercase().replace('-', "_");
+                if let Some(&attr) = LangString::ATTRIBUTES.iter().find(|&&a| a == normalized) {
+                    if attr != token {
+                        invalid.push((token.to_owned(), attr));
+                    }
+                }
+            }
+        }
+    }
+    invalid
+}
+
 #[cfg(test)]
 mod tests {
     use super::{LangString, Markdown, MarkdownHtml};
-    use super::plain_summary_line;
+    use super::{bare_urls, has_rust_code_block, invalid_codeblock_attributes, plain_summary_line};
     use html::render::reset_ids;
 
     #[test]
     fn test_lang_string_parse() {
         fn t(s: &str,
             should_panic: bool, no_run: bool, ignore: bool, rust: bool, test_harness: bool,
-            compile_fail: bool, allow_fail: bool, error_codes: Vec<String>) {
+            compile_fail: bool, allow_fail: bool, error_codes: Vec<String>, output: bool) {
             assert_eq!(LangString::parse(s), LangString {
                 should_panic,
                 no_run,
@@ -804,6 +927,7 @@ mod tests {
                 error_codes,
                 original: s.to_owned(),
                 allow_fail,
+                output,
             })
         }
 
@@ -811,23 +935,25 @@ mod tests {
             Vec::new()
         }
 
-        // marker                | should_panic| no_run| ignore| rust | test_harness| compile_fail
-        //                       | allow_fail | error_codes
-        t("",                      false,        false,  false,  true,  false, false, false, v());
-        t("rust",                  false,        false,  false,  true,  false, false, false, v());
-        t("sh",                    false,        false,  false,  false, false, false, false, v());
-        t("ignore",                false,        false,  true,   true,  false, false, false, v());
-        t("should_panic",          true,         false,  false,  true,  false, false, false, v());
-        t("no_run",                false,        true,   false,  true,  false, false, false, v());
-        t("test_harness",          false,        false,  false,  true,  true,  false, false, v());
-        t("compile_fail",          false,        true,   false,  true,  false, true,  false, v());
-        t("allow_fail",            false,        false,  false,  true,  false, false, true,  v());
-        t("{.no_run .example}",    false,        true,   false,  true,  false, false, false, v());
-        t("{.sh .should_panic}",   true,         false,  false,  false, false, false, false, v());
-        t("{.example .rust}",      false,        false,  false,  true,  false, false, false, v());
-        t("{.test_harness .rust}", false,        false,  false,  true,  true,  false, false, v());
-        t("text, no_run",          false,        true,   false,  false, false, false, false, v());
-        t("text,no_run",           false,        true,   false,  false, false, false, false, v());
+        // marker                | should_panic | no_run | ignore | rust | test_harness
+        //                       | compile_fail | allow_fail | error_codes | output
+        t("",                      false, false, false, true,  false, false, false, v(), false);
+        t("rust",                  false, false, false, true,  false, false, false, v(), false);
+        t("sh",                    false, false, false, false, false, false, false, v(), false);
+        t("ignore",                false, false, true,  true,  false, false, false, v(), false);
+        t("should_panic",          true,  false, false, true,  false, false, false, v(), false);
+        t("no_run",                false, true,  false, true,  false, false, false, v(), false);
+        t("test_harness",          false, false, false, true,  true,  false, false, v(), false);
+        t("compile_fail",          false, true,  false, true,  false, true,  false, v(), false);
+        t("allow_fail",            false, false, false, true,  false, false, true,  v(), false);
+        t("{.no_run .example}",    false, true,  false, true,  false, false, false, v(), false);
+        t("{.sh .should_panic}",   true,  false, false, false, false, false, false, v(), false);
+        t("{.example .rust}",      false, false, false, true,  false, false, false, v(), false);
+        t("{.test_harness .rust}", false, false, false, true,  true,  false, false, v(), false);
+        t("text, no_run",          false, true,  false, false, false, false, false, v(), false);
+        t("text,no_run",           false, true,  false, false, false, false, false, v(), false);
+        t("output",                false, false, false, false, false, false, false, v(), true);
+        t("text,output",           false, false, false, false, false, false, false, v(), true);
     }
 
     #[test]
@@ -911,4 +1037,31 @@ mod tests {
         t("Struct<'a, T>", "<p>Struct&lt;'a, T&gt;</p>\n");
         t("Struct<br>", "<p>Struct&lt;br&gt;</p>\n");
     }
+
+    #[test]
+    fn test_has_rust_code_block() {
+        assert!(has_rust_code_block("```\nfoo();\n```"));
+        assert!(has_rust_code_block("text\n\n```rust,no_run\nfoo();\n```"));
+        assert!(has_rust_code_block("    foo();"));
+        assert!(!has_rust_code_block("```text\nfoo\n```"));
+        assert!(!has_rust_code_block("just `code`"));
+    }
+
+    #[test]
+    fn test_bare_urls() {
+        assert_eq!(bare_urls("See https://example.com/a_b, or <https://example.com>."),
+                   ["https://example.com/a_b"]);
+        assert_eq!(bare_urls("[docs](http://example.com) and `http://example.com`"),
+                   Vec::<String>::new());
+        assert_eq!(bare_urls("```\nhttp://example.com\n```"), Vec::<String>::new());
+        assert_eq!(bare_urls("(at http://example.com/x)"), ["http://example.com/x"]);
+    }
+
+    #[test]
+    fn test_invalid_codeblock_attributes() {
+        assert_eq!(invalid_codeblock_attributes("```should-panic,no_run\n```\n\
+                                                 ```Rust\n```\n```text\n```"),
+                   [("should-panic".to_owned(), "should_panic"), ("Rust".to_owned(), "rust")]);
+        assert_eq!(invalid_codeblock_attributes("```compile_fail,E0123\n```"), Vec::new());
+    }
 }
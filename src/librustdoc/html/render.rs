@@ -74,7 +74,8 @@ use html::format::{VisSpace, Method, UnsafetySpace, MutableSpace};
 use html::format::fmt_impl_for_trait_page;
 use html::item_type::ItemType;
 use html::markdown::{self, Markdown, MarkdownHtml, MarkdownSummaryLine};
-use html::{highlight, layout};
+use html::{full_text, highlight, layout};
+use scrape_examples::ScrapedExamples;
 
 use minifier;
 
@@ -135,6 +136,9 @@ pub struct SharedContext {
     /// Suffix to be added on resource files (if suffix is "-v2" then "light.css" becomes
     /// "light-v2.css").
     pub resource_suffix: String,
+    /// The calls to the functions of the crate found in the files given with
+    /// `--scrape-examples`.
+    pub scraped_examples: ScrapedExamples,
 }
 
 impl SharedContext {
@@ -320,6 +324,7 @@ pub struct Cache {
     parent_stack: Vec<DefId>,
     parent_is_trait_impl: bool,
     search_index: Vec<IndexItem>,
+    full_text_index: bool,
     stripped_mod: bool,
     deref_trait_did: Option<DefId>,
     deref_mut_trait_did: Option<DefId>,
@@ -385,6 +390,8 @@ struct IndexItem {
     parent: Option<DefId>,
     parent_idx: Option<usize>,
     search_type: Option<IndexItemFunctionType>,
+    /// The stems of the words of the documentation, for the full-text index.
+    terms: Vec<String>,
 }
 
 impl ToJson for IndexItem {
@@ -516,7 +523,9 @@ pub fn run(mut krate: clean::Crate,
            renderinfo: RenderInfo,
            sort_modules_alphabetically: bool,
            themes: Vec<PathBuf>,
-           enable_minification: bool) -> Result<(), Error> {
+           enable_minification: bool,
+           scraped_examples: ScrapedExamples,
+           full_text_index: bool) -> Result<(), Error> {
     let src_root = match krate.src {
         FileName::Real(ref p) => match p.parent() {
             Some(p) => p.to_path_buf(),
@@ -535,12 +544,14 @@ pub fn run(mut krate: clean::Crate,
             favicon: "".to_string(),
             external_html: external_html.clone(),
             krate: krate.name.clone(),
+            full_text_index,
         },
         css_file_extension: css_file_extension.clone(),
         created_dirs: RefCell::new(FxHashSet()),
         sort_modules_alphabetically,
         themes,
         resource_suffix,
+        scraped_examples,
     };
 
     // If user passed in `--playground-url` arg, we fill in crate name here
@@ -611,6 +622,7 @@ pub fn run(mut krate: clean::Crate,
         stack: Vec::new(),
         parent_stack: Vec::new(),
         search_index: Vec::new(),
+        full_text_index,
         parent_is_trait_impl: false,
         extern_locations: FxHashMap(),
         primitive_locations: FxHashMap(),
@@ -661,6 +673,11 @@ pub fn run(mut krate: clean::Crate,
 
     // Build our search index
     let index = build_index(&krate, &mut cache);
+    let full_text_index = if full_text_index {
+        Some(build_full_text_index(&krate, &cache))
+    } else {
+        None
+    };
 
     // Freeze the cache now that the index has been built. Put an Arc into TLS
     // for future parallelization opportunities
@@ -668,7 +685,7 @@ pub fn run(mut krate: clean::Crate,
     CACHE_KEY.with(|v| *v.borrow_mut() = cache.clone());
     CURRENT_LOCATION_KEY.with(|s| s.borrow_mut().clear());
 
-    write_shared(&cx, &krate, &*cache, index, enable_minification)?;
+    write_shared(&cx, &krate, &*cache, index, full_text_index, enable_minification)?;
 
     // And finally render the whole crate's documentation
     cx.krate(krate)
@@ -682,7 +699,8 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
 
     let Cache { ref mut search_index,
                 ref orphan_impl_items,
-                ref mut paths, .. } = *cache;
+                ref mut paths,
+                full_text_index, .. } = *cache;
 
     // Attach all orphan items to the type's definition if the type
     // has since been learned.
@@ -696,6 +714,7 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
                 parent: Some(did),
                 parent_idx: None,
                 search_type: get_index_search_type(&item),
+                terms: if full_text_index { full_text::item_terms(item) } else { Vec::new() },
             });
         }
     }
@@ -744,10 +763,21 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
             Json::Object(crate_data))
 }
 
+/// Build the full-text search index, in the order of the search index built by
+/// `build_index`.
+fn build_full_text_index(krate: &clean::Crate, cache: &Cache) -> String {
+    let crate_terms = krate.module.as_ref().map(|module| {
+        full_text::item_terms(module)
+    }).unwrap_or(Vec::new());
+    full_text::build_index(&krate.name, &crate_terms,
+                           cache.search_index.iter().map(|item| &item.terms[..]))
+}
+
 fn write_shared(cx: &Context,
                 krate: &clean::Crate,
                 cache: &Cache,
                 search_index: String,
+                full_text_index: Option<String>,
                 enable_minification: bool) -> Result<(), Error> {
     // Write out the shared files. Note that these are shared among all rustdoc
     // docs placed in the output directory, so this needs to be a synchronized
@@ -956,6 +986,20 @@ themePicker.onblur = handleThemeButtonsBlur;
     }
     try_err!(writeln!(&mut w, "initSearch(searchIndex);"), &dst);
 
+    // Update the full-text search index. It's loaded before the search index,
+    // so there's nothing to call here.
+    if let Some(full_text_index) = full_text_index {
+        let dst = cx.dst.join("search-index-full.js");
+        let mut all_indexes = try_err!(collect(&dst, &krate.name, "fullTextIndex"), &dst);
+        all_indexes.push(full_text_index);
+        all_indexes.sort();
+        let mut w = try_err!(File::create(&dst), &dst);
+        try_err!(writeln!(&mut w, "var fullTextIndex = {{}};"), &dst);
+        for index in &all_indexes {
+            try_err!(writeln!(&mut w, "{}", *index), &dst);
+        }
+    }
+
     // Update the list of all implementors for traits
     let dst = cx.dst.join("implementors");
     for (&did, imps) in &cache.implementors {
@@ -1301,6 +1345,11 @@ impl DocFolder for Cache {
                             parent,
                             parent_idx: None,
                             search_type: get_index_search_type(&item),
+                            terms: if self.full_text_index {
+                                full_text::item_terms(&item)
+                            } else {
+                                Vec::new()
+                            },
                         });
                     }
                 }
@@ -1487,6 +1536,7 @@ impl<'a> Cache {
                                 parent: None,
                                 parent_idx: None,
                                 search_type: get_index_search_type(&item),
+                                terms: Vec::new(),
                             });
             }
         }
@@ -2616,7 +2666,35 @@ fn item_function(w: &mut fmt::Formatter, cx: &Context, it: &clean::Item,
               name_len,
               indent: 0,
            })?;
-    document(w, cx, it)
+    document(w, cx, it)?;
+    render_scraped_examples(w, cx, it)
+}
+
+/// The number of calls shown on the page of an item scraped with
+/// `--scrape-examples`.
+const MAX_SCRAPED_EXAMPLES: usize = 3;
+
+fn render_scraped_examples(w: &mut fmt::Formatter, cx: &Context,
+                           it: &clean::Item) -> fmt::Result {
+    let calls = match cx.shared.scraped_examples.get(&it.def_id) {
+        Some(calls) if !calls.is_empty() => calls,
+        _ => return Ok(()),
+    };
+    write!(w, "<div class='docblock scraped-examples'>\
+               <h5>Examples found in repository</h5>")?;
+    for call in calls.iter().take(MAX_SCRAPED_EXAMPLES) {
+        write!(w, "<div class='scraped-example'>\
+                   <div class='scraped-example-title'>{} (line {})</div>{}</div>",
+               Escape(&call.file),
+               call.line,
+               highlight::render_with_highlighting(&call.snippet, Some("rust"),
+                                                   None, None, None))?;
+    }
+    if calls.len() > MAX_SCRAPED_EXAMPLES {
+        let more = calls.len() - MAX_SCRAPED_EXAMPLES;
+        write!(w, "<p>{} more use{} not shown</p>", more, if more == 1 { "" } else { "s" })?;
+    }
+    write!(w, "</div>")
 }
 
 fn render_implementor(cx: &Context, implementor: &Impl, w: &mut fmt::Formatter,
@@ -2779,6 +2857,7 @@ fn item_trait(w: &mut fmt::Formatter, cx: &Context, it: &clean::Item,
         render_stability_since(w, m, t)?;
         write!(w, "</span></h3>")?;
         document(w, cx, m)?;
+        render_scraped_examples(w, cx, m)?;
         Ok(())
     }
 
@@ -3823,6 +3902,7 @@ fn render_impl(w: &mut fmt::Formatter, cx: &Context, i: &Impl, link: AssocItemLi
                     document_stability(w, cx, item)?;
                     if show_def_docs {
                         document_full(w, item, cx, &prefix)?;
+                        render_scraped_examples(w, cx, item)?;
                     }
                 }
             } else {
@@ -47,6 +47,7 @@
     // 0 for "In Names"
     // 1 for "In Parameters"
     // 2 for "In Return Types"
+    // 3 for "In Documentation", if the full-text index was generated
     var currentTab = 0;
 
     var themesWidth = null;
@@ -413,8 +414,155 @@
         var currentResults, index, searchIndex;
         var MAX_LEV_DISTANCE = 3;
         var MAX_RESULTS = 200;
+        // The position of every crate in `searchIndex`, followed by its items.
+        var crateStarts = {};
+        // The positions in `searchIndex` of the items of the full-text index,
+        // decoded when they are first searched for.
+        var fullTextPositions = {};
         var params = getQueryStringParams();
 
+        // Words shorter than this and the stop words are not in the full-text
+        // index, see `html/full_text.rs`.
+        var FULL_TEXT_MIN_WORD_LEN = 3;
+        var FULL_TEXT_STOP_WORDS = [
+            "also", "and", "are", "been", "but", "can", "for", "from", "has", "have", "its",
+            "not", "one", "such", "than", "that", "the", "them", "then", "there", "these",
+            "they", "this", "those", "was", "were", "what", "when", "where", "which", "will",
+            "with", "you", "your",
+        ];
+        var BASE64_DIGITS = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        /**
+         * Strips the common suffixes of a lowercase English word, which has to
+         * be done exactly like `stem` in `html/full_text.rs` does it.
+         * @param  {[string]} w [The word]
+         * @return {[string]}   [The stem of the word]
+         */
+        function stemWord(w) {
+            if (w.length <= 3 || /[^\x00-\x7f]/.test(w)) {
+                return w;
+            }
+
+            // Plurals and the third person.
+            if (w.endsWith("sses")) {
+                w = w.slice(0, -2);
+            } else if (w.endsWith("ies")) {
+                w = w.slice(0, -3) + "y";
+            } else if (w.endsWith("s") && !w.endsWith("ss") && !w.endsWith("us") &&
+                       !w.endsWith("is")) {
+                w = w.slice(0, -1);
+            }
+
+            // Participles and the past tense.
+            var baseLen = w.length;
+            if (w.endsWith("ing")) {
+                baseLen -= 3;
+            } else if (w.endsWith("ed")) {
+                baseLen -= 2;
+            }
+            if (baseLen < w.length && baseLen >= 3 && /[aeiouy]/.test(w.slice(0, baseLen))) {
+                w = w.slice(0, baseLen);
+                var last = w.charAt(baseLen - 1);
+                if (last === w.charAt(baseLen - 2) && "aeiouylsz".indexOf(last) === -1) {
+                    w = w.slice(0, -1);
+                }
+            }
+
+            // Nouns and adverbs made from other words.
+            var suffixes = [["ational", "ate"], ["ization", "ize"], ["ation", "ate"],
+                            ["fulness", "ful"], ["iveness", "ive"], ["ness", ""], ["ly", ""]];
+            for (var i = 0; i < suffixes.length; ++i) {
+                var suffix = suffixes[i][0];
+                if (w.endsWith(suffix) && w.length - suffix.length >= 4) {
+                    w = w.slice(0, w.length - suffix.length) + suffixes[i][1];
+                    break;
+                }
+            }
+
+            if (w.length > 4 && w.endsWith("e") && !w.endsWith("ee")) {
+                w = w.slice(0, -1);
+            }
+            return w;
+        }
+
+        /**
+         * Splits a query into words and returns their stems, in order, leaving
+         * out the words that are not in the full-text index.
+         * @param  {[string]} query [The user query]
+         * @return {[string]}       [The stems of the words of the query]
+         */
+        function fullTextTerms(query) {
+            var terms = [];
+            var words = query.toLowerCase().split(/[^0-9a-z\u0080-\uffff]+/);
+            for (var i = 0; i < words.length; ++i) {
+                if (words[i].length >= FULL_TEXT_MIN_WORD_LEN &&
+                    FULL_TEXT_STOP_WORDS.indexOf(words[i]) === -1) {
+                    terms.push(stemWord(words[i]));
+                }
+            }
+            return terms;
+        }
+
+        /**
+         * Returns the indices of the stems of the full-text index of a crate equal
+         * to `stem`, or starting with it if `isPrefix` is true.
+         * @param  {[string]} stems    [The sorted stems of the index]
+         * @param  {[string]} stem     [The stem to look for]
+         * @param  {[boolean]} isPrefix [Whether longer stems match too]
+         * @return {[Number]}          [The indices of the matching stems]
+         */
+        function findStems(stems, stem, isPrefix) {
+            var lo = 0, hi = stems.length;
+            while (lo < hi) {
+                var mid = (lo + hi) >> 1;
+                if (stems[mid] < stem) {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+            var found = [];
+            for (; lo < stems.length; ++lo) {
+                if (stems[lo] !== stem && (isPrefix !== true || !stems[lo].startsWith(stem))) {
+                    break;
+                }
+                found.push(lo);
+            }
+            return found;
+        }
+
+        /**
+         * Returns the positions in `searchIndex` of the items whose
+         * documentation contains a stem of the full-text index of a crate. These
+         * are written as the differences between consecutive positions in
+         * the crate, in variable-length base64, see `html/full_text.rs`.
+         * @param  {[string]} crate [The name of the crate]
+         * @param  {[Number]} i     [The index of the stem]
+         * @return {[Number]}       [The positions of the items]
+         */
+        function fullTextItems(crate, i) {
+            var key = crate + "/" + i;
+            if (fullTextPositions[key] === undefined) {
+                var encoded = fullTextIndex[crate].p[i];
+                var positions = [];
+                var pos = crateStarts[crate], value = 0, shift = 0;
+                for (var j = 0; j < encoded.length; ++j) {
+                    var digit = BASE64_DIGITS.indexOf(encoded.charAt(j));
+                    value += (digit & 31) << shift;
+                    if (digit & 32) {
+                        shift += 5;
+                    } else {
+                        pos += value;
+                        positions.push(pos);
+                        value = 0;
+                        shift = 0;
+                    }
+                }
+                fullTextPositions[key] = positions;
+            }
+            return fullTextPositions[key];
+        }
+
         // Populate search bar with query string search term when provided,
         // but only if the input bar is empty. This avoid the obnoxious issue
         // where you start trying to do a search, and the index loads, and
@@ -444,6 +592,7 @@
                 val = valLower,
                 typeFilter = itemTypeFromName(query.type),
                 results = {}, results_in_args = {}, results_returned = {},
+                results_in_docs = {},
                 split = valLower.split("::");
 
             for (var z = 0; z < split.length; ++z) {
@@ -592,6 +741,83 @@
                 };
             }
 
+            // Splits a list of types on the commas which are not between angle
+            // brackets, so that `HashMap<K, V>, K` gives two types.
+            function splitTypes(val) {
+                var types = [];
+                var depth = 0;
+                var start = 0;
+                for (var i = 0; i < val.length; ++i) {
+                    var c = val.charAt(i);
+                    if (c === "<") {
+                        depth += 1;
+                    } else if (c === ">") {
+                        depth -= 1;
+                    } else if (c === "," && depth === 0) {
+                        types.push(val.substring(start, i));
+                        start = i + 1;
+                    }
+                }
+                types.push(val.substring(start));
+                return types;
+            }
+
+            // Looks for the items whose documentation contains all the words of
+            // the query. The last word may not be typed completely yet, so the
+            // stems starting with it match, as well as the stems it starts with
+            // which are only missing a few letters to it.
+            function searchFullText(query, filter) {
+                var results = {};
+                var terms = fullTextTerms(query);
+                if (terms.length === 0) {
+                    return results;
+                }
+                for (var crate in fullTextIndex) {
+                    if (!fullTextIndex.hasOwnProperty(crate) ||
+                        crateStarts[crate] === undefined) {
+                        continue;
+                    }
+                    var stems = fullTextIndex[crate].t;
+                    // the number of terms each item matches, from the first one
+                    var counts = {};
+                    for (var i = 0; i < terms.length; ++i) {
+                        var found;
+                        if (i === terms.length - 1) {
+                            found = findStems(stems, terms[i], true);
+                            var minLen = Math.max(FULL_TEXT_MIN_WORD_LEN, terms[i].length - 3);
+                            for (var len = terms[i].length - 1; len >= minLen; --len) {
+                                found = found.concat(findStems(stems, terms[i].substr(0, len)));
+                            }
+                        } else {
+                            found = findStems(stems, terms[i]);
+                        }
+                        for (var j = 0; j < found.length; ++j) {
+                            var positions = fullTextItems(crate, found[j]);
+                            for (var k = 0; k < positions.length; ++k) {
+                                if ((counts[positions[k]] || 0) === i) {
+                                    counts[positions[k]] = i + 1;
+                                }
+                            }
+                        }
+                    }
+                    for (var pos in counts) {
+                        if (!counts.hasOwnProperty(pos) || counts[pos] !== terms.length) {
+                            continue;
+                        }
+                        var ty = searchIndex[pos];
+                        if (typePassesFilter(filter, ty.ty)) {
+                            results[generateId(ty)] = {
+                                id: parseInt(pos, 10),
+                                index: -1,
+                                lev: 0,
+                                dontValidate: true,
+                            };
+                        }
+                    }
+                }
+                return results;
+            }
+
             function checkGenerics(obj, val) {
                 // The names match, but we need to be sure that all generics kinda
                 // match as well.
@@ -632,8 +858,10 @@
                 var lev_distance = MAX_LEV_DISTANCE + 1;
                 if (obj.n === val.name) {
                     if (literalSearch === true) {
-                        if (val.generics && val.generics.length !== 0) {
-                            if (obj.g && obj.length >= val.generics.length) {
+                        // The index leaves out type parameters, so e.g. `Vec<T>` is
+                        // recorded without generics and matches any `Vec`.
+                        if (val.generics && val.generics.length !== 0 && obj.g) {
+                            if (obj.g.length >= val.generics.length) {
                                 var elems = obj.g.slice(0);
                                 var allFound = true;
                                 var x;
@@ -698,9 +926,17 @@
 
                 if (obj && obj.type && obj.type.i && obj.type.i.length > 0) {
                     for (var i = 0; i < obj.type.i.length; i++) {
-                        var tmp = checkType(obj.type.i[i], val, literalSearch);
-                        if (literalSearch === true && tmp === true) {
-                            return true;
+                        var input = obj.type.i[i];
+                        // `self` is the type or trait the method belongs to.
+                        if (input.n === "self" && obj.parent) {
+                            input = {n: obj.parent.name.toLowerCase()};
+                        }
+                        var tmp = checkType(input, val, literalSearch);
+                        if (literalSearch === true) {
+                            if (tmp === true) {
+                                return true;
+                            }
+                            continue;
                         }
                         lev_distance = Math.min(tmp, lev_distance);
                         if (lev_distance === 0) {
@@ -841,15 +1077,17 @@
                 query.search = val;
             // searching by type
             } else if (val.search("->") > -1) {
-                var trimmer = function(s) { return s.trim(); };
+                // references are indexed as the type they refer to
+                var trimmer = function(s) { return s.trim().replace(/^&\s*(mut\s+)?/, ""); };
                 var parts = val.split("->").map(trimmer);
                 var input = parts[0];
                 // sort inputs so that order does not matter
-                var inputs = input.split(",").map(trimmer).sort();
+                var inputs = splitTypes(input).map(trimmer).sort();
                 for (var i = 0; i < inputs.length; ++i) {
                     inputs[i] = extractGenerics(inputs[i]);
                 }
-                var output = extractGenerics(parts[1]);
+                // `A ->` is the same as `A -> *`
+                var output = extractGenerics(parts[1] || "*");
 
                 for (var i = 0; i < nSearchWords; ++i) {
                     var type = searchIndex[i].type;
@@ -866,14 +1104,17 @@
                         var in_args = false;
                         var module = false;
 
-                        if (input === "*") {
+                        if (input === "*" || input === "") {
                             module = true;
                         } else {
                             var allFound = true;
                             for (var it = 0; allFound === true && it < inputs.length; it++) {
-                                allFound = checkType(type, inputs[it], true);
+                                allFound = findArg(ty, inputs[it], true);
                             }
                             in_args = allFound;
+                            // the whole signature matches
+                            module = in_args === true &&
+                                (output.name === "*" || returned === true);
                         }
                         if (in_args === true) {
                             results_in_args[fullId] = {
@@ -903,6 +1144,9 @@
                 });
                 query.output = output.name;
             } else {
+                if (typeof fullTextIndex !== "undefined") {
+                    results_in_docs = searchFullText(val, typeFilter);
+                }
                 query.inputs = [val];
                 query.output = val;
                 query.search = val;
@@ -1021,6 +1265,7 @@
             var ret = {
                 'in_args': sortResults(results_in_args, true),
                 'returned': sortResults(results_returned, true),
+                'in_docs': sortResults(results_in_docs),
                 'others': sortResults(results),
             };
             if (ALIASES && ALIASES[window.currentCrate] &&
@@ -1141,7 +1386,7 @@
             search_input.onkeydown = function(e) {
                 // "actives" references the currently highlighted item in each search tab.
                 // Each array in "actives" represents a tab.
-                var actives = [[], [], []];
+                var actives = [[], [], [], []];
                 // "current" is used to know which tab we're looking into.
                 var current = 0;
                 onEach(document.getElementsByClassName('search-results'), function(e) {
@@ -1178,10 +1423,11 @@
                             actives[currentTab][0].getElementsByTagName('a')[0].href;
                     }
                 } else if (e.which === 9) { // tab
+                    var nbTabs = document.getElementById('titles').childNodes.length;
                     if (e.shiftKey) {
-                        printTab(currentTab > 0 ? currentTab - 1 : 2);
+                        printTab(currentTab > 0 ? currentTab - 1 : nbTabs - 1);
                     } else {
-                        printTab(currentTab > 1 ? 0 : currentTab + 1);
+                        printTab(currentTab < nbTabs - 1 ? currentTab + 1 : 0);
                     }
                     e.preventDefault();
                 } else if (e.which === 16) { // shift
@@ -1320,6 +1566,12 @@
             var ret_others = addTab(results['others'], query);
             var ret_in_args = addTab(results['in_args'], query, false);
             var ret_returned = addTab(results['returned'], query, false);
+            var ret_in_docs = ["", 0];
+            var in_docs_header = "";
+            if (typeof fullTextIndex !== "undefined") {
+                ret_in_docs = addTab(results['in_docs'], query, false);
+                in_docs_header = makeTabHeader(3, "In Documentation", ret_in_docs[1]);
+            }
 
             var output = '<h1>Results for ' + escape(query.query) +
                 (query.type ? ' (type: ' + escape(query.type) + ')' : '') + '</h1>' +
@@ -1327,8 +1579,9 @@
                 makeTabHeader(0, "In Names", ret_others[1]) +
                 makeTabHeader(1, "In Parameters", ret_in_args[1]) +
                 makeTabHeader(2, "In Return Types", ret_returned[1]) +
+                in_docs_header +
                 '</div><div id="results">' +
-                ret_others[0] + ret_in_args[0] + ret_returned[0] + '</div>';
+                ret_others[0] + ret_in_args[0] + ret_returned[0] + ret_in_docs[0] + '</div>';
 
             addClass(document.getElementById('main'), 'hidden');
             var search = document.getElementById('search');
@@ -1345,17 +1598,21 @@
             });
             initSearchNav();
             var elems = document.getElementById('titles').childNodes;
-            elems[0].onclick = function() { printTab(0); };
-            elems[1].onclick = function() { printTab(1); };
-            elems[2].onclick = function() { printTab(2); };
-            printTab(currentTab);
+            for (var i = 0; i < elems.length; ++i) {
+                elems[i].onclick = (function(nb) {
+                    return function() { printTab(nb); };
+                })(i);
+            }
+            printTab(currentTab < elems.length ? currentTab : 0);
         }
 
         function execSearch(query, searchWords) {
-            var queries = query.raw.split(",");
+            // commas separate the types of the arguments in type searches
+            var queries = query.raw.indexOf("->") > -1 ? [query.raw] : query.raw.split(",");
             var results = {
                 'in_args': [],
                 'returned': [],
+                'in_docs': [],
                 'others': [],
             };
 
@@ -1366,6 +1623,7 @@
 
                     results['in_args'].push(tmp['in_args']);
                     results['returned'].push(tmp['returned']);
+                    results['in_docs'].push(tmp['in_docs']);
                     results['others'].push(tmp['others']);
                 }
             }
@@ -1413,12 +1671,14 @@
                 return {
                     'in_args': mergeArrays(results['in_args']),
                     'returned': mergeArrays(results['returned']),
+                    'in_docs': mergeArrays(results['in_docs']),
                     'others': mergeArrays(results['others']),
                 };
             } else {
                 return {
                     'in_args': results['in_args'][0],
                     'returned': results['returned'][0],
+                    'in_docs': results['in_docs'][0],
                     'others': results['others'][0],
                 };
             }
@@ -1461,6 +1721,7 @@
             for (var crate in rawSearchIndex) {
                 if (!rawSearchIndex.hasOwnProperty(crate)) { continue; }
 
+                crateStarts[crate] = searchIndex.length;
                 searchWords.push(crate);
                 searchIndex.push({
                     crate: crate,
@@ -2056,7 +2317,7 @@
 
     // In the search display, allows to switch between tabs.
     function printTab(nb) {
-        if (nb === 0 || nb === 1 || nb === 2) {
+        if (nb >= 0 && nb < document.getElementById('titles').childNodes.length) {
             currentTab = nb;
         }
         var nb_copy = nb;
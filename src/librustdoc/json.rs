@@ -0,0 +1,387 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The JSON output format of rustdoc.
+//!
+//! This serializes the cleaned crate into a single `<crate>.json` file, for
+//! tools that need rustdoc's resolved view of an API rather than its HTML.
+//! The schema is versioned with `FORMAT_VERSION`, which is bumped whenever it
+//! changes in an incompatible way.
+//!
+//! The crate is written as a tree of items starting at the crate root, with
+//! every item carrying its `id`, `name`, `kind` (named like the HTML item
+//! types, e.g. `struct` or `tymethod`), `visibility`, `docs`, `attrs`, `cfg`,
+//! `stability`, `deprecation` and `span`, plus the kind-specific fields in
+//! `inner`. Ids are of the form `"crate:index"`, where the crate numbers are
+//! listed in `external_crates`. Types, trait bounds and where-predicates are
+//! written as Rust source, and resolved paths also carry the id they refer
+//! to. Impls, including the auto-trait impls synthesized by rustdoc (marked
+//! as `synthetic`), are listed among the items of the module containing
+//! their type. Stripped items are left out.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::middle::stability;
+use serialize::json::{Json, ToJson, as_json};
+
+use clean::{self, GetDefId};
+use clean::cfg::Cfg;
+use html::item_type::ItemType;
+use html::render::Error;
+
+/// The version of the schema, bumped on every incompatible change.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Writes the JSON documentation of `krate` to `dst/<crate>.json`.
+pub fn run(krate: clean::Crate, dst: PathBuf) -> Result<(), Error> {
+    let mut external_crates = BTreeMap::new();
+    for &(cnum, ref ext) in &krate.externs {
+        let mut obj = BTreeMap::new();
+        obj.insert("name".to_string(), ext.name.to_json());
+        external_crates.insert(cnum.as_u32().to_string(), Json::Object(obj));
+    }
+
+    let mut obj = BTreeMap::new();
+    obj.insert("format_version".to_string(), FORMAT_VERSION.to_json());
+    obj.insert("name".to_string(), krate.name.to_json());
+    obj.insert("version".to_string(), krate.version.to_json());
+    obj.insert("root".to_string(), krate.module.as_ref().and_then(item).to_json());
+    obj.insert("external_crates".to_string(), Json::Object(external_crates));
+
+    fs::create_dir_all(&dst).map_err(|e| Error::new(e, &dst))?;
+    let path = dst.join(format!("{}.json", krate.name));
+    let write = || -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(&path)?);
+        write!(w, "{}", as_json(&Json::Object(obj)))?;
+        w.flush()
+    };
+    write().map_err(|e| Error::new(e, &path))
+}
+
+fn id(did: DefId) -> Json {
+    format!("{}:{}", did.krate.as_u32(), did.index.as_raw_u32()).to_json()
+}
+
+fn ty(ty: &clean::Type) -> Json {
+    format!("{:#}", ty).to_json()
+}
+
+/// A type together with the id of the item it refers to, if any.
+fn resolved_ty(t: &clean::Type) -> Json {
+    let mut obj = BTreeMap::new();
+    obj.insert("type".to_string(), ty(t));
+    obj.insert("id".to_string(), t.def_id().map(id).to_json());
+    Json::Object(obj)
+}
+
+fn bounds(bounds: &[clean::TyParamBound]) -> Json {
+    Json::Array(bounds.iter().map(|b| format!("{:#}", b).to_json()).collect())
+}
+
+fn list(items: &[clean::Item]) -> Json {
+    Json::Array(items.iter().filter_map(item).collect())
+}
+
+fn item(item: &clean::Item) -> Option<Json> {
+    if item.is_stripped() {
+        return None;
+    }
+
+    let mut obj = BTreeMap::new();
+    obj.insert("id".to_string(), id(item.def_id));
+    obj.insert("name".to_string(), item.name.to_json());
+    obj.insert("kind".to_string(), ItemType::from(item).css_class().to_json());
+    obj.insert("visibility".to_string(), visibility(&item.visibility));
+    obj.insert("docs".to_string(), item.collapsed_doc_value().to_json());
+    obj.insert("attrs".to_string(), Json::Array(
+        item.attrs.other_attrs.iter()
+            .filter(|attr| !attr.is_sugared_doc)
+            .map(|attr| ::syntax::print::pprust::attr_to_string(attr).to_json())
+            .collect()));
+    obj.insert("cfg".to_string(), item.attrs.cfg.as_ref().map(|c| cfg(c)).to_json());
+    obj.insert("stability".to_string(), item.stability.as_ref().map(stability).to_json());
+    obj.insert("deprecation".to_string(), item.deprecation.as_ref().map(|d| {
+        let mut obj = BTreeMap::new();
+        obj.insert("since".to_string(), d.since.to_json());
+        obj.insert("note".to_string(), d.note.to_json());
+        Json::Object(obj)
+    }).to_json());
+    obj.insert("span".to_string(), span(&item.source));
+    obj.insert("inner".to_string(), inner(&item.inner));
+    Some(Json::Object(obj))
+}
+
+fn visibility(vis: &Option<clean::Visibility>) -> Json {
+    match *vis {
+        None => Json::Null,
+        Some(clean::Public) => "public".to_json(),
+        Some(clean::Inherited) => "default".to_json(),
+        Some(clean::Crate) => "crate".to_json(),
+        Some(clean::Visibility::Restricted(did, ref path)) => {
+            let mut restricted = BTreeMap::new();
+            restricted.insert("parent".to_string(), id(did));
+            restricted.insert("path".to_string(), format!("{:#}", path).to_json());
+            let mut obj = BTreeMap::new();
+            obj.insert("restricted".to_string(), Json::Object(restricted));
+            Json::Object(obj)
+        }
+    }
+}
+
+fn cfg(cfg: &Cfg) -> Json {
+    let mut obj = BTreeMap::new();
+    match *cfg {
+        Cfg::True => return Json::Boolean(true),
+        Cfg::False => return Json::Boolean(false),
+        Cfg::Cfg(name, value) => {
+            obj.insert("name".to_string(), name.to_string().to_json());
+            obj.insert("value".to_string(), value.map(|v| v.to_string()).to_json());
+        }
+        Cfg::Not(ref child) => {
+            obj.insert("not".to_string(), self::cfg(child));
+        }
+        Cfg::All(ref cfgs) => {
+            obj.insert("all".to_string(), Json::Array(cfgs.iter().map(self::cfg).collect()));
+        }
+        Cfg::Any(ref cfgs) => {
+            obj.insert("any".to_string(), Json::Array(cfgs.iter().map(self::cfg).collect()));
+        }
+    }
+    Json::Object(obj)
+}
+
+fn stability(stab: &clean::Stability) -> Json {
+    let mut obj = BTreeMap::new();
+    obj.insert("stable".to_string(), (stab.level == stability::Stable).to_json());
+    obj.insert("feature".to_string(), stab.feature.to_json());
+    obj.insert("since".to_string(), stab.since.to_json());
+    obj.insert("unstable_reason".to_string(), stab.unstable_reason.to_json());
+    obj.insert("issue".to_string(), stab.issue.to_json());
+    obj.insert("deprecated_since".to_string(), stab.deprecated_since.to_json());
+    obj.insert("deprecated_reason".to_string(), stab.deprecated_reason.to_json());
+    Json::Object(obj)
+}
+
+fn span(span: &clean::Span) -> Json {
+    let mut obj = BTreeMap::new();
+    obj.insert("filename".to_string(), span.filename.to_string().to_json());
+    obj.insert("begin".to_string(), vec![span.loline, span.locol].to_json());
+    obj.insert("end".to_string(), vec![span.hiline, span.hicol].to_json());
+    Json::Object(obj)
+}
+
+fn generics(generics: &clean::Generics) -> Json {
+    let params = generics.params.iter().map(|param| {
+        let mut obj = BTreeMap::new();
+        match *param {
+            clean::GenericParamDef::Lifetime(ref lt) => {
+                obj.insert("kind".to_string(), "lifetime".to_json());
+                obj.insert("name".to_string(), lt.get_ref().to_json());
+            }
+            clean::GenericParamDef::Type(ref tp) => {
+                obj.insert("kind".to_string(), "type".to_json());
+                obj.insert("name".to_string(), tp.name.to_json());
+                obj.insert("bounds".to_string(), bounds(&tp.bounds));
+                obj.insert("default".to_string(), tp.default.as_ref().map(ty).to_json());
+                obj.insert("synthetic".to_string(), tp.synthetic.is_some().to_json());
+            }
+        }
+        Json::Object(obj)
+    }).collect();
+
+    let where_predicates = generics.where_predicates.iter().map(|pred| {
+        match *pred {
+            clean::WherePredicate::BoundPredicate { ty: ref t, bounds: ref b } => {
+                let b = b.iter().map(|b| format!("{:#}", b)).collect::<Vec<_>>();
+                format!("{:#}: {}", t, b.join(" + "))
+            }
+            clean::WherePredicate::RegionPredicate { ref lifetime, bounds: ref b } => {
+                let b = b.iter().map(|lt| lt.get_ref()).collect::<Vec<_>>();
+                format!("{}: {}", lifetime.get_ref(), b.join(" + "))
+            }
+            clean::WherePredicate::EqPredicate { ref lhs, ref rhs } => {
+                format!("{:#} == {:#}", lhs, rhs)
+            }
+        }.to_json()
+    }).collect();
+
+    let mut obj = BTreeMap::new();
+    obj.insert("params".to_string(), Json::Array(params));
+    obj.insert("where_predicates".to_string(), Json::Array(where_predicates));
+    Json::Object(obj)
+}
+
+fn decl(decl: &clean::FnDecl) -> Json {
+    let inputs = decl.inputs.values.iter().map(|arg| {
+        let mut obj = BTreeMap::new();
+        obj.insert("name".to_string(), arg.name.to_json());
+        obj.insert("type".to_string(), ty(&arg.type_));
+        Json::Object(obj)
+    }).collect();
+
+    let mut obj = BTreeMap::new();
+    obj.insert("inputs".to_string(), Json::Array(inputs));
+    obj.insert("output".to_string(), match decl.output {
+        clean::Return(ref t) => ty(t),
+        clean::DefaultReturn => Json::Null,
+    });
+    obj.insert("variadic".to_string(), decl.variadic.to_json());
+    Json::Object(obj)
+}
+
+fn function(obj: &mut BTreeMap<String, Json>,
+            decl: &clean::FnDecl,
+            generics: &clean::Generics,
+            unsafety: hir::Unsafety,
+            constness: hir::Constness,
+            abi: ::rustc_target::spec::abi::Abi) {
+    obj.insert("decl".to_string(), self::decl(decl));
+    obj.insert("generics".to_string(), self::generics(generics));
+    obj.insert("unsafe".to_string(), (unsafety == hir::Unsafety::Unsafe).to_json());
+    obj.insert("const".to_string(), (constness == hir::Constness::Const).to_json());
+    obj.insert("abi".to_string(), abi.name().to_json());
+}
+
+fn struct_type(struct_type: &::doctree::StructType) -> Json {
+    match *struct_type {
+        ::doctree::Plain => "plain",
+        ::doctree::Tuple => "tuple",
+        ::doctree::Unit => "unit",
+    }.to_json()
+}
+
+fn inner(inner: &clean::ItemEnum) -> Json {
+    let mut obj = BTreeMap::new();
+    match *inner {
+        clean::ModuleItem(ref m) => {
+            obj.insert("is_crate".to_string(), m.is_crate.to_json());
+            obj.insert("items".to_string(), list(&m.items));
+        }
+        clean::ExternCrateItem(ref name, ref rename) => {
+            obj.insert("name".to_string(), name.to_json());
+            obj.insert("rename".to_string(), rename.to_json());
+        }
+        clean::ImportItem(ref import) => {
+            let (name, source, glob) = match *import {
+                clean::Import::Simple(ref name, ref source) => (Some(name), source, false),
+                clean::Import::Glob(ref source) => (None, source, true),
+            };
+            obj.insert("name".to_string(), name.to_json());
+            obj.insert("source".to_string(), format!("{:#}", source.path).to_json());
+            obj.insert("id".to_string(), source.did.map(id).to_json());
+            obj.insert("glob".to_string(), glob.to_json());
+        }
+        clean::StructItem(clean::Struct {
+            ref struct_type, ref generics, ref fields, fields_stripped
+        }) |
+        clean::UnionItem(clean::Union {
+            ref struct_type, ref generics, ref fields, fields_stripped
+        }) => {
+            obj.insert("struct_type".to_string(), self::struct_type(struct_type));
+            obj.insert("generics".to_string(), self::generics(generics));
+            obj.insert("fields".to_string(), list(fields));
+            obj.insert("fields_stripped".to_string(), fields_stripped.to_json());
+        }
+        clean::EnumItem(ref e) => {
+            obj.insert("generics".to_string(), generics(&e.generics));
+            obj.insert("variants".to_string(), list(&e.variants));
+            obj.insert("variants_stripped".to_string(), e.variants_stripped.to_json());
+        }
+        clean::VariantItem(ref v) => {
+            match v.kind {
+                clean::VariantKind::CLike => {
+                    obj.insert("variant_kind".to_string(), "plain".to_json());
+                }
+                clean::VariantKind::Tuple(ref types) => {
+                    obj.insert("variant_kind".to_string(), "tuple".to_json());
+                    obj.insert("fields".to_string(),
+                               Json::Array(types.iter().map(ty).collect()));
+                }
+                clean::VariantKind::Struct(ref s) => {
+                    obj.insert("variant_kind".to_string(), "struct".to_json());
+                    obj.insert("fields".to_string(), list(&s.fields));
+                    obj.insert("fields_stripped".to_string(), s.fields_stripped.to_json());
+                }
+            }
+        }
+        clean::StructFieldItem(ref t) => {
+            obj.insert("type".to_string(), resolved_ty(t));
+        }
+        clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+            function(&mut obj, &f.decl, &f.generics, f.unsafety, f.constness, f.abi);
+        }
+        clean::MethodItem(ref m) => {
+            function(&mut obj, &m.decl, &m.generics, m.unsafety, m.constness, m.abi);
+            obj.insert("has_body".to_string(), true.to_json());
+        }
+        clean::TyMethodItem(ref m) => {
+            function(&mut obj, &m.decl, &m.generics, m.unsafety, hir::Constness::NotConst,
+                     m.abi);
+            obj.insert("has_body".to_string(), false.to_json());
+        }
+        clean::TypedefItem(ref t, is_associated) => {
+            obj.insert("type".to_string(), resolved_ty(&t.type_));
+            obj.insert("generics".to_string(), generics(&t.generics));
+            obj.insert("is_associated".to_string(), is_associated.to_json());
+        }
+        clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => {
+            obj.insert("type".to_string(), resolved_ty(&s.type_));
+            obj.insert("mutable".to_string(), (s.mutability == clean::Mutable).to_json());
+            obj.insert("expr".to_string(), s.expr.to_json());
+        }
+        clean::ConstantItem(ref c) => {
+            obj.insert("type".to_string(), resolved_ty(&c.type_));
+            obj.insert("expr".to_string(), c.expr.to_json());
+        }
+        clean::TraitItem(ref t) => {
+            obj.insert("auto".to_string(), t.auto.to_json());
+            obj.insert("unsafe".to_string(), (t.unsafety == hir::Unsafety::Unsafe).to_json());
+            obj.insert("generics".to_string(), generics(&t.generics));
+            obj.insert("bounds".to_string(), bounds(&t.bounds));
+            obj.insert("items".to_string(), list(&t.items));
+        }
+        clean::ImplItem(ref i) => {
+            let mut provided = i.provided_trait_methods.iter().collect::<Vec<_>>();
+            provided.sort();
+            obj.insert("unsafe".to_string(), (i.unsafety == hir::Unsafety::Unsafe).to_json());
+            obj.insert("generics".to_string(), generics(&i.generics));
+            obj.insert("trait".to_string(), i.trait_.as_ref().map(resolved_ty).to_json());
+            obj.insert("for".to_string(), resolved_ty(&i.for_));
+            obj.insert("negative".to_string(),
+                       (i.polarity == Some(clean::ImplPolarity::Negative)).to_json());
+            obj.insert("synthetic".to_string(), i.synthetic.to_json());
+            obj.insert("provided_trait_methods".to_string(), provided.to_json());
+            obj.insert("items".to_string(), list(&i.items));
+        }
+        clean::AssociatedConstItem(ref t, ref default) => {
+            obj.insert("type".to_string(), resolved_ty(t));
+            obj.insert("default".to_string(), default.to_json());
+        }
+        clean::AssociatedTypeItem(ref b, ref default) => {
+            obj.insert("bounds".to_string(), bounds(b));
+            obj.insert("default".to_string(), default.as_ref().map(resolved_ty).to_json());
+        }
+        clean::MacroItem(ref m) => {
+            obj.insert("source".to_string(), m.source.to_json());
+        }
+        clean::PrimitiveItem(ref p) => {
+            obj.insert("name".to_string(), p.as_str().to_json());
+        }
+        clean::ForeignTypeItem => {}
+        clean::StrippedItem(..) => unreachable!(),
+    }
+    Json::Object(obj)
+}
@@ -59,6 +59,7 @@ use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::mpsc::channel;
 
+use serialize::json::ToJson;
 use syntax::edition::Edition;
 use externalfiles::ExternalHtml;
 use rustc::session::{early_warn, early_error};
@@ -67,6 +68,7 @@ use rustc::session::config::{ErrorOutputType, RustcOptGroup, Externs, CodegenOpt
 use rustc::session::config::{nightly_options, build_codegen_options};
 use rustc_target::spec::TargetTriple;
 
+pub mod api_diff;
 #[macro_use]
 pub mod externalfiles;
 
@@ -79,17 +81,21 @@ pub mod html {
     pub mod escape;
     pub mod item_type;
     pub mod format;
+    pub mod full_text;
     pub mod layout;
     pub mod markdown;
     pub mod render;
     pub mod toc;
 }
+pub mod json;
 pub mod markdown;
 pub mod passes;
 pub mod plugins;
+pub mod scrape_examples;
 pub mod visit_ast;
 pub mod visit_lib;
 pub mod test;
+pub mod text;
 pub mod theme;
 
 use clean::AttributesExt;
@@ -143,7 +149,8 @@ pub fn opts() -> Vec<RustcOptGroup> {
                      "[rust]")
         }),
         stable("w", |o| {
-            o.optopt("w", "output-format", "the output type to write", "[html]")
+            o.optopt("w", "output-format", "the output type to write",
+                     "[html|json|markdown|man]")
         }),
         stable("o", |o| o.optopt("o", "output", "where to place the output", "PATH")),
         stable("crate-name", |o| {
@@ -302,6 +309,46 @@ pub fn opts() -> Vec<RustcOptGroup> {
                        "disable-minification",
                        "Disable minification applied on JS files")
         }),
+        unstable("scrape-examples", |o| {
+            o.optmulti("",
+                       "scrape-examples",
+                       "show calls to the crate's functions found in the Rust files at PATH, \
+                        or in a directory of them, on the pages of those functions",
+                       "PATH")
+        }),
+        unstable("merge-doctests", |o| {
+            o.optflag("",
+                      "merge-doctests",
+                      "compile the doctests without crate attributes into a single crate, \
+                       instead of compiling every doctest on its own")
+        }),
+        unstable("full-text-index", |o| {
+            o.optflag("",
+                      "full-text-index",
+                      "also index the words of the documentation for the search, in \
+                       search-index-full.js")
+        }),
+        unstable("show-coverage", |o| {
+            o.optflag("",
+                      "show-coverage",
+                      "instead of documenting the crate, report how many of its items are \
+                       documented and have examples, as a table or as JSON with \
+                       `--output-format json`")
+        }),
+        unstable("api-baseline", |o| {
+            o.optopt("",
+                     "api-baseline",
+                     "instead of documenting the crate, report the changes to its public API \
+                      since the previous build of it passed with `--extern NAME=PATH`",
+                     "NAME")
+        }),
+        unstable("auto-doc-cfg", |o| {
+            o.optmulti("",
+                       "auto-doc-cfg",
+                       "show the `#[cfg]` requirements of items on the given options, like \
+                        `unix` or `target_os`, as if they were given with `#[doc(cfg)]`",
+                       "NAMES")
+        }),
     ]
 }
 
@@ -479,6 +526,7 @@ pub fn main_args(args: &[String]) -> isize {
     let playground_url = matches.opt_str("playground-url");
     let maybe_sysroot = matches.opt_str("sysroot").map(PathBuf::from);
     let display_warnings = matches.opt_present("display-warnings");
+    let merge_doctests = matches.opt_present("merge-doctests");
     let linker = matches.opt_str("linker").map(PathBuf::from);
     let sort_modules_alphabetically = !matches.opt_present("sort-modules-by-appearance");
     let resource_suffix = matches.opt_str("resource-suffix");
@@ -498,11 +546,12 @@ pub fn main_args(args: &[String]) -> isize {
     match (should_test, markdown_input) {
         (true, true) => {
             return markdown::test(input, cfgs, libs, externs, test_args, maybe_sysroot,
-                                  display_warnings, linker, edition, cg, &diag)
+                                  display_warnings, merge_doctests, linker, edition, cg, &diag)
         }
         (true, false) => {
             return test::run(Path::new(input), cfgs, libs, externs, test_args, crate_name,
-                             maybe_sysroot, display_warnings, linker, edition, cg)
+                             maybe_sysroot, display_warnings, merge_doctests, linker, edition,
+                             cg)
         }
         (false, true) => return markdown::render(Path::new(input),
                                                  output.unwrap_or(PathBuf::from("doc")),
@@ -512,14 +561,46 @@ pub fn main_args(args: &[String]) -> isize {
     }
 
     let output_format = matches.opt_str("w");
+    let show_coverage = matches.opt_present("show-coverage");
+    let full_text_index = matches.opt_present("full-text-index");
+    let scrape_examples = matches.opt_strs("scrape-examples").into_iter()
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    let unstable_format = match output_format.as_ref().map(|s| &**s) {
+        Some("json") => Some("JSON"),
+        Some("markdown") => Some("Markdown"),
+        Some("man") => Some("man page"),
+        _ => None,
+    };
+    if let Some(name) = unstable_format {
+        if !nightly_options::is_unstable_enabled(&matches) {
+            diag.struct_err(&format!("the {} output format is unstable", name))
+                .note(&format!("pass `-Z unstable-options` to use `--output-format {}`",
+                               output_format.as_ref().unwrap()))
+                .emit();
+            return 1;
+        }
+    }
 
     let res = acquire_input(PathBuf::from(input), externs, edition, cg, &matches, error_format,
                             move |out| {
         let Output { krate, passes, renderinfo } = out;
         let diag = core::new_handler(error_format, None);
+        if show_coverage {
+            // The table has already been printed by the coverage pass.
+            if output_format.as_ref().map(|s| &**s) == Some("json") {
+                println!("{}", passes::Coverage::new(&krate).to_json());
+            }
+            return 0;
+        }
+        if krate.api_baseline.is_some() {
+            info!("going to compare the public API");
+            return api_diff::run(&krate);
+        }
         info!("going to format");
         match output_format.as_ref().map(|s| &**s) {
             Some("html") | None => {
+                let scraped_examples = scrape_examples::scrape(&krate, &scrape_examples, &diag);
                 html::render::run(krate, &external_html, playground_url,
                                   output.unwrap_or(PathBuf::from("doc")),
                                   resource_suffix.unwrap_or(String::new()),
@@ -528,10 +609,33 @@ pub fn main_args(args: &[String]) -> isize {
                                   renderinfo,
                                   sort_modules_alphabetically,
                                   themes,
-                                  enable_minification)
+                                  enable_minification,
+                                  scraped_examples,
+                                  full_text_index)
                     .expect("failed to generate documentation");
                 0
             }
+            Some("json") => {
+                json::run(krate, output.unwrap_or(PathBuf::from("doc")))
+                    .expect("failed to generate documentation");
+                0
+            }
+            Some(format @ "markdown") | Some(format @ "man") => {
+                let dst = output.unwrap_or(PathBuf::from("doc"));
+                let res = if format == "man" {
+                    text::man::run(krate, dst)
+                } else {
+                    text::markdown::run(krate, dst)
+                };
+                match res {
+                    Ok(()) => 0,
+                    Err(e) => {
+                        diag.struct_err(&format!("couldn't generate documentation: {}", e))
+                            .emit();
+                        1
+                    }
+                }
+            }
             Some(s) => {
                 diag.struct_err(&format!("unknown output format: {}", s)).emit();
                 1
@@ -626,9 +730,15 @@ where R: 'static + Send,
     let crate_name = matches.opt_str("crate-name");
     let crate_version = matches.opt_str("crate-version");
     let plugin_path = matches.opt_str("plugin-path");
+    let api_baseline = matches.opt_str("api-baseline");
+    let auto_doc_cfg = matches.opt_strs("auto-doc-cfg").iter()
+        .flat_map(|names| names.split(',').map(|name| name.trim().to_string()))
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    let show_coverage_table = matches.opt_present("show-coverage") &&
+        matches.opt_str("w").as_ref().map(|s| &**s) != Some("json");
 
     info!("starting to run rustc");
-    let display_warnings = matches.opt_present("display-warnings");
 
     let force_unstable_if_unmarked = matches.opt_strs("Z").iter().any(|x| {
         *x == "force-unstable-if-unmarked"
@@ -641,8 +751,9 @@ where R: 'static + Send,
 
         let (mut krate, renderinfo) =
             core::run_core(paths, cfgs, externs, Input::File(cratefile), triple, maybe_sysroot,
-                           display_warnings, crate_name.clone(),
-                           force_unstable_if_unmarked, edition, cg, error_format);
+                           crate_name.clone(),
+                           force_unstable_if_unmarked, edition, cg, error_format,
+                           api_baseline, auto_doc_cfg);
 
         info!("finished with rustc");
 
@@ -709,6 +820,10 @@ where R: 'static + Send,
             }
         }
 
+        if show_coverage_table {
+            passes.push("calculate-doc-coverage".to_string());
+        }
+
         // Load all plugins/passes into a PluginManager
         let path = plugin_path.unwrap_or("/tmp/rustdoc/plugins".to_string());
         let mut pm = plugins::PluginManager::new(PathBuf::from(path));
@@ -752,6 +867,15 @@ fn check_deprecated_options(matches: &getopts::Matches, diag: &errors::Handler)
 
     for flag in deprecated_flags.into_iter() {
         if matches.opt_present(flag) {
+            // `--output-format` is how the unstable JSON, Markdown and man
+            // page backends are selected.
+            if *flag == "output-format" {
+                match matches.opt_str("output-format").as_ref().map(|s| &**s) {
+                    Some("json") | Some("markdown") | Some("man") => continue,
+                    _ => {}
+                }
+            }
+
             let mut err = diag.struct_warn(&format!("the '{}' flag is considered deprecated",
                                                     flag));
             err.warn("please see https://github.com/rust-lang/rust/issues/44136");
@@ -0,0 +1,228 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::BTreeMap;
+use std::ops;
+
+use serialize::json::{Json, ToJson};
+
+use clean::{self, Item};
+use html::markdown::has_rust_code_block;
+use plugins;
+
+/// Counts the documented items and the items with examples in the crate, and
+/// prints a table of them per module and per item kind.
+pub fn calculate_doc_coverage(krate: clean::Crate) -> plugins::PluginResult {
+    Coverage::new(&krate).print_table();
+    krate
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct ItemCount {
+    pub total: u64,
+    pub with_docs: u64,
+    pub with_examples: u64,
+}
+
+impl ItemCount {
+    fn count_item(&mut self, has_docs: bool, has_examples: bool) {
+        self.total += 1;
+        if has_docs {
+            self.with_docs += 1;
+        }
+        if has_examples {
+            self.with_examples += 1;
+        }
+    }
+
+    fn percentage(&self, count: u64) -> f64 {
+        if self.total > 0 {
+            (count as f64 * 100.0) / self.total as f64
+        } else {
+            100.0
+        }
+    }
+}
+
+impl ops::AddAssign for ItemCount {
+    fn add_assign(&mut self, rhs: ItemCount) {
+        self.total += rhs.total;
+        self.with_docs += rhs.with_docs;
+        self.with_examples += rhs.with_examples;
+    }
+}
+
+impl ToJson for ItemCount {
+    fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("total".to_string(), self.total.to_json());
+        obj.insert("with_docs".to_string(), self.with_docs.to_json());
+        obj.insert("with_examples".to_string(), self.with_examples.to_json());
+        Json::Object(obj)
+    }
+}
+
+/// The documentation coverage of a crate.
+///
+/// Only the items that are left after the other passes are counted, so with
+/// the default passes these are the public items. The items of trait impls
+/// aren't counted, since they are documented by the trait, and neither are
+/// re-exports.
+#[derive(Default, Debug)]
+pub struct Coverage {
+    /// The counts of the items of every module, by module path.
+    pub modules: BTreeMap<String, ItemCount>,
+    /// The counts of every kind of item, named like the HTML item types.
+    pub kinds: BTreeMap<&'static str, ItemCount>,
+}
+
+impl Coverage {
+    pub fn new(krate: &clean::Crate) -> Coverage {
+        let mut coverage = Coverage::default();
+        if let Some(ref module) = krate.module {
+            coverage.visit_module(krate.name.clone(), module);
+        }
+        coverage
+    }
+
+    pub fn total(&self) -> ItemCount {
+        let mut total = ItemCount::default();
+        for count in self.modules.values() {
+            total += *count;
+        }
+        total
+    }
+
+    fn count(&mut self, module: &str, item: &Item) {
+        let docs = item.collapsed_doc_value().unwrap_or_default();
+        let has_docs = !docs.trim().is_empty();
+        let has_examples = has_docs && has_rust_code_block(&docs);
+        self.modules.entry(module.to_string()).or_insert_with(ItemCount::default)
+            .count_item(has_docs, has_examples);
+        self.kinds.entry(item.type_().css_class()).or_insert_with(ItemCount::default)
+            .count_item(has_docs, has_examples);
+    }
+
+    fn visit_module(&mut self, path: String, module: &Item) {
+        self.count(&path, module);
+        if let clean::ModuleItem(ref m) = module.inner {
+            for item in &m.items {
+                self.visit(&path, item);
+            }
+        }
+    }
+
+    fn visit(&mut self, module: &str, item: &Item) {
+        match item.inner {
+            clean::StrippedItem(..) |
+            clean::ImportItem(..) |
+            clean::ExternCrateItem(..) => {}
+            clean::ModuleItem(..) => {
+                let path = format!("{}::{}", module, item.name.as_ref().unwrap());
+                self.visit_module(path, item);
+            }
+            clean::ImplItem(ref i) => {
+                // Only inherent impls add to the API of the crate, and the impl
+                // blocks themselves are rarely documented.
+                if i.trait_.is_none() {
+                    for item in &i.items {
+                        self.visit(module, item);
+                    }
+                }
+            }
+            clean::StructItem(clean::Struct { ref fields, .. }) |
+            clean::UnionItem(clean::Union { ref fields, .. }) => {
+                self.count(module, item);
+                for field in fields {
+                    self.visit(module, field);
+                }
+            }
+            clean::VariantItem(clean::Variant {
+                kind: clean::VariantKind::Struct(ref s)
+            }) => {
+                self.count(module, item);
+                for field in &s.fields {
+                    self.visit(module, field);
+                }
+            }
+            clean::EnumItem(ref e) => {
+                self.count(module, item);
+                for variant in &e.variants {
+                    self.visit(module, variant);
+                }
+            }
+            clean::TraitItem(ref t) => {
+                self.count(module, item);
+                for item in &t.items {
+                    self.visit(module, item);
+                }
+            }
+            _ => self.count(module, item),
+        }
+    }
+
+    pub fn print_table(&self) {
+        fn print_table_line() {
+            println!("+-{0:->35}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+", "");
+        }
+
+        fn print_table_record(name: &str, count: &ItemCount) {
+            // Keep the end of long module paths, which tells them apart.
+            let name = match name.char_indices().rev().nth(31) {
+                Some((start, _)) if name.chars().count() > 35 => {
+                    format!("...{}", &name[start..])
+                }
+                _ => name.to_string(),
+            };
+            println!("| {:<35} | {:>10} | {:>10} | {:>9.1}% | {:>10} | {:>9.1}% |",
+                     name,
+                     count.total,
+                     count.with_docs,
+                     count.percentage(count.with_docs),
+                     count.with_examples,
+                     count.percentage(count.with_examples));
+        }
+
+        fn print_table_header(title: &str) {
+            print_table_line();
+            println!("| {:<35} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} |",
+                     title, "Items", "Documented", "Percentage", "Examples", "Percentage");
+            print_table_line();
+        }
+
+        print_table_header("Module");
+        for (module, count) in &self.modules {
+            print_table_record(module, count);
+        }
+        print_table_line();
+        print_table_record("Total", &self.total());
+        print_table_line();
+
+        println!();
+
+        print_table_header("Item kind");
+        for (kind, count) in &self.kinds {
+            print_table_record(kind, count);
+        }
+        print_table_line();
+    }
+}
+
+impl ToJson for Coverage {
+    fn to_json(&self) -> Json {
+        let modules = self.modules.iter().map(|(m, c)| (m.clone(), c.to_json())).collect();
+        let kinds = self.kinds.iter().map(|(k, c)| (k.to_string(), c.to_json())).collect();
+        let mut obj = BTreeMap::new();
+        obj.insert("modules".to_string(), Json::Object(modules));
+        obj.insert("kinds".to_string(), Json::Object(kinds));
+        obj.insert("total".to_string(), self.total().to_json());
+        Json::Object(obj)
+    }
+}
@@ -36,6 +36,9 @@ pub use self::unindent_comments::unindent_comments;
 mod propagate_doc_cfg;
 pub use self::propagate_doc_cfg::propagate_doc_cfg;
 
+mod calculate_doc_coverage;
+pub use self::calculate_doc_coverage::{calculate_doc_coverage, Coverage};
+
 type Pass = (&'static str,                                      // name
              fn(clean::Crate) -> plugins::PluginResult,         // fn
              &'static str);                                     // description
@@ -54,6 +57,8 @@ pub const PASSES: &'static [Pass] = &[
      "strips all private import statements (`use`, `extern crate`) from a crate"),
     ("propagate-doc-cfg", propagate_doc_cfg,
      "propagates `#[doc(cfg(...))]` to child items"),
+    ("calculate-doc-coverage", calculate_doc_coverage,
+     "counts the number of items with and without documentation and examples"),
 ];
 
 pub const DEFAULT_PASSES: &'static [&'static str] = &[
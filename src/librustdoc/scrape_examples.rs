@@ -0,0 +1,313 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Finding uses of the crate's functions in example and test sources.
+//!
+//! With `--scrape-examples`, rustdoc parses the given Rust files and looks for
+//! calls to the documented functions and methods of the crate, which are then
+//! shown on the pages of those items. The files are only parsed, not resolved,
+//! so calls are matched by name:
+//!
+//! * `f(..)` and `m::f(..)` match the function `f` (of the module `m`),
+//! * `T::f(..)` matches the associated function or method `f` of the type or
+//!   trait `T`,
+//! * `x.f(..)` matches the method `f`, if it is the only method of the crate
+//!   with that name.
+//!
+//! The arguments of macro invocations like `assert_eq!` are searched as well,
+//! when they parse as a list of expressions.
+
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use errors;
+use errors::emitter::Emitter;
+use rustc::hir::def_id::DefId;
+use rustc::util::nodemap::FxHashMap;
+use rustc_data_structures::sync::Lrc;
+use syntax::ast;
+use syntax::codemap::{CodeMap, FilePathMapping};
+use syntax::parse::{self, token, ParseSess};
+use syntax::visit::{self, Visitor};
+use syntax_pos::{FileName, Span};
+
+use clean;
+
+/// The number of lines of context around a call when its enclosing function
+/// is too long to be shown as a whole.
+const CONTEXT_LINES: usize = 3;
+/// The longest function that is shown as a whole around a call.
+const MAX_FUNCTION_LINES: usize = 20;
+
+/// A call to an item of the crate in one of the scraped files.
+#[derive(Clone, Debug)]
+pub struct CallSite {
+    /// The path of the file, as given on the command line.
+    pub file: String,
+    /// The line of the call, starting at 1.
+    pub line: usize,
+    /// The source around the call.
+    pub snippet: String,
+}
+
+/// The calls found for every item, in the order of the files they are in.
+pub type ScrapedExamples = FxHashMap<DefId, Vec<CallSite>>;
+
+/// Looks for calls to the items of `krate` in the Rust files at `paths`, which
+/// may be files or directories to search recursively.
+pub fn scrape(krate: &clean::Crate, paths: &[PathBuf], diag: &errors::Handler)
+              -> ScrapedExamples {
+    let mut index = Index::default();
+    if let Some(clean::Item { inner: clean::ModuleItem(ref m), ref name, .. }) = krate.module {
+        index.add_module(name.as_ref().map_or("", |s| s), &m.items);
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        collect_files(path, &mut files, diag);
+    }
+
+    let mut examples = FxHashMap();
+    for file in &files {
+        let src = match fs::read_to_string(file) {
+            Ok(src) => src,
+            Err(e) => {
+                diag.warn(&format!("failed to read {}: {}", file.display(), e));
+                continue;
+            }
+        };
+        // Files that fail to parse are reported with a single warning below,
+        // rather than with the parser's own errors.
+        let codemap = Lrc::new(CodeMap::new(FilePathMapping::empty()));
+        let handler = errors::Handler::with_emitter(false, false, box SilentEmitter);
+        let sess = ParseSess::with_span_handler(handler, codemap);
+        let krate = match parse::parse_crate_from_source_str(FileName::Real(file.clone()),
+                                                             src.clone(), &sess) {
+            Ok(krate) => krate,
+            Err(mut e) => {
+                e.cancel();
+                diag.warn(&format!("failed to parse {}, skipping it", file.display()));
+                continue;
+            }
+        };
+
+        let mut finder = Finder {
+            index: &index,
+            sess: &sess,
+            enclosing_fn: None,
+            calls: Vec::new(),
+        };
+        visit::walk_crate(&mut finder, &krate);
+
+        let lines = src.lines().collect::<Vec<_>>();
+        for (did, call, enclosing_fn) in finder.calls {
+            let (line, snippet) = snippet(sess.codemap(), &lines, call, enclosing_fn);
+            examples.entry(did).or_insert_with(Vec::new).push(CallSite {
+                file: file.display().to_string(),
+                line,
+                snippet,
+            });
+        }
+    }
+    examples
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>, diag: &errors::Handler) {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return;
+    }
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            diag.warn(&format!("failed to read {}: {}", path.display(), e));
+            return;
+        }
+    };
+    let mut paths = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect::<Vec<_>>();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() || path.extension().map_or(false, |e| e == "rs") {
+            collect_files(&path, files, diag);
+        }
+    }
+}
+
+/// Returns the line of `call`, and the source of the snippet showing it: the
+/// whole enclosing function if it's short enough, or a few lines around the
+/// call otherwise.
+fn snippet(codemap: &CodeMap, lines: &[&str], call: Span, enclosing_fn: Option<Span>)
+           -> (usize, String) {
+    let line_of = |pos| codemap.lookup_char_pos(pos).line;
+    let (call_lo, call_hi) = (line_of(call.lo()), line_of(call.hi()));
+    let (lo, hi) = match enclosing_fn {
+        Some(span) if line_of(span.hi()) - line_of(span.lo()) < MAX_FUNCTION_LINES => {
+            (line_of(span.lo()), line_of(span.hi()))
+        }
+        _ => (call_lo.saturating_sub(CONTEXT_LINES).max(1),
+              (call_hi + CONTEXT_LINES).min(lines.len())),
+    };
+    (call_lo, lines[lo - 1..hi].join("\n"))
+}
+
+/// Discards the diagnostics of the parser.
+struct SilentEmitter;
+
+impl Emitter for SilentEmitter {
+    fn emit(&mut self, _: &errors::DiagnosticBuilder) {}
+}
+
+/// The functions and methods of the crate that calls can be matched against.
+#[derive(Default)]
+struct Index {
+    /// The functions and methods by name, with the name of the module, type or
+    /// trait they belong to.
+    functions: FxHashMap<String, Vec<(String, DefId)>>,
+    /// The methods taking `self` by name.
+    methods: FxHashMap<String, Vec<DefId>>,
+}
+
+impl Index {
+    fn add_module(&mut self, module: &str, items: &[clean::Item]) {
+        for item in items {
+            match item.inner {
+                clean::ModuleItem(ref m) => {
+                    self.add_module(item.name.as_ref().unwrap(), &m.items);
+                }
+                clean::FunctionItem(..) => self.add_function(module, item),
+                clean::ImplItem(clean::Impl { trait_: None, ref for_, ref items, .. }) => {
+                    if let clean::ResolvedPath { ref path, .. } = *for_ {
+                        for item in items {
+                            self.add_function(path.last_name(), item);
+                        }
+                    }
+                }
+                clean::TraitItem(ref t) => {
+                    for trait_item in &t.items {
+                        self.add_function(item.name.as_ref().unwrap(), trait_item);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn add_function(&mut self, parent: &str, item: &clean::Item) {
+        let name = match item.name {
+            Some(ref name) => name.clone(),
+            None => return,
+        };
+        let decl = match item.inner {
+            clean::FunctionItem(ref f) => &f.decl,
+            clean::MethodItem(ref m) => &m.decl,
+            clean::TyMethodItem(ref m) => &m.decl,
+            _ => return,
+        };
+        if decl.inputs.values.first().map_or(false, |arg| arg.name == "self") {
+            self.methods.entry(name.clone()).or_insert_with(Vec::new).push(item.def_id);
+        }
+        self.functions.entry(name).or_insert_with(Vec::new)
+            .push((parent.to_string(), item.def_id));
+    }
+
+    fn resolve_path(&self, path: &ast::Path) -> Option<DefId> {
+        let mut segments = path.segments.iter().rev().map(|s| s.ident.name.as_str());
+        let name = segments.next()?;
+        let parent = segments.next();
+        let candidates = self.functions.get(&*name)?;
+        match parent {
+            Some(parent) => {
+                candidates.iter().find(|&&(ref p, _)| *p == *parent).map(|&(_, did)| did)
+            }
+            // Assume that a bare name was imported from the crate, unless it
+            // is ambiguous.
+            None if candidates.len() == 1 => Some(candidates[0].1),
+            None => None,
+        }
+    }
+
+    fn resolve_method(&self, name: &str) -> Option<DefId> {
+        match self.methods.get(name) {
+            Some(candidates) if candidates.len() == 1 => Some(candidates[0]),
+            _ => None,
+        }
+    }
+}
+
+struct Finder<'a> {
+    index: &'a Index,
+    sess: &'a ParseSess,
+    enclosing_fn: Option<Span>,
+    /// The calls found, with the item they call and the enclosing function.
+    calls: Vec<(DefId, Span, Option<Span>)>,
+}
+
+impl<'a> Finder<'a> {
+    fn with_enclosing_fn<F: FnOnce(&mut Self)>(&mut self, span: Span, f: F) {
+        let old = mem::replace(&mut self.enclosing_fn, Some(span));
+        f(self);
+        self.enclosing_fn = old;
+    }
+}
+
+impl<'a, 'ast> Visitor<'ast> for Finder<'a> {
+    fn visit_item(&mut self, item: &'ast ast::Item) {
+        match item.node {
+            ast::ItemKind::Fn(..) => {
+                self.with_enclosing_fn(item.span, |this| visit::walk_item(this, item))
+            }
+            _ => visit::walk_item(self, item),
+        }
+    }
+
+    fn visit_impl_item(&mut self, item: &'ast ast::ImplItem) {
+        self.with_enclosing_fn(item.span, |this| visit::walk_impl_item(this, item))
+    }
+
+    fn visit_trait_item(&mut self, item: &'ast ast::TraitItem) {
+        self.with_enclosing_fn(item.span, |this| visit::walk_trait_item(this, item))
+    }
+
+    fn visit_expr(&mut self, expr: &'ast ast::Expr) {
+        let did = match expr.node {
+            ast::ExprKind::Call(ref callee, _) => match callee.node {
+                ast::ExprKind::Path(None, ref path) => self.index.resolve_path(path),
+                _ => None,
+            },
+            ast::ExprKind::MethodCall(ref segment, _) => {
+                self.index.resolve_method(&segment.ident.name.as_str())
+            }
+            _ => None,
+        };
+        if let Some(did) = did {
+            self.calls.push((did, expr.span, self.enclosing_fn));
+        }
+        visit::walk_expr(self, expr)
+    }
+
+    fn visit_mac(&mut self, mac: &'ast ast::Mac) {
+        // Macros aren't expanded, but most macros in examples and tests, like
+        // `assert_eq!` or `println!`, take a list of expressions.
+        let mut parser = parse::stream_to_parser(self.sess, mac.node.tts.clone().into());
+        while parser.token != token::Eof {
+            match parser.parse_expr() {
+                Ok(expr) => Visitor::visit_expr(self, &expr),
+                Err(mut e) => {
+                    e.cancel();
+                    break;
+                }
+            }
+            if !parser.eat(&token::Comma) {
+                break;
+            }
+        }
+    }
+}
@@ -53,6 +53,9 @@ pub struct TestOptions {
     pub display_warnings: bool,
     /// Additional crate-level attributes to add to doctests.
     pub attrs: Vec<String>,
+    /// Whether to compile the doctests which allow it into a single crate, instead of compiling
+    /// every doctest on its own.
+    pub merge_doctests: bool,
 }
 
 pub fn run(input_path: &Path,
@@ -63,6 +66,7 @@ pub fn run(input_path: &Path,
            crate_name: Option<String>,
            maybe_sysroot: Option<PathBuf>,
            display_warnings: bool,
+           merge_doctests: bool,
            linker: Option<PathBuf>,
            edition: Edition,
            cg: CodegenOptions)
@@ -124,6 +128,7 @@ pub fn run(input_path: &Path,
         });
         let mut opts = scrape_test_config(hir_forest.krate());
         opts.display_warnings |= display_warnings;
+        opts.merge_doctests = merge_doctests;
         let mut collector = Collector::new(
             crate_name,
             cfgs,
@@ -169,6 +174,7 @@ fn scrape_test_config(krate: &::rustc::hir::Crate) -> TestOptions {
         no_crate_inject: false,
         display_warnings: false,
         attrs: Vec::new(),
+        merge_doctests: false,
     };
 
     let test_attrs: Vec<_> = krate.attrs.iter()
@@ -198,11 +204,58 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, line: usize,
             cfgs: Vec<String>, libs: SearchPaths,
             cg: CodegenOptions, externs: Externs,
             should_panic: bool, no_run: bool, as_test_harness: bool,
-            compile_fail: bool, mut error_codes: Vec<String>, opts: &TestOptions,
-            maybe_sysroot: Option<PathBuf>, linker: Option<PathBuf>, edition: Edition) {
+            compile_fail: bool, mut error_codes: Vec<String>, expected_output: Option<&str>,
+            opts: &TestOptions, maybe_sysroot: Option<PathBuf>, linker: Option<PathBuf>,
+            edition: Edition) {
     // the test harness wants its own `main` & top level functions, so
     // never wrap the test in `fn main() { ... }`
     let (test, line_offset) = make_test(test, Some(cratename), as_test_harness, opts);
+    let line = line as isize - line_offset as isize;
+
+    let (libdir, outdir) = compile_test(&test, filename, line, cfgs, libs, cg, externs,
+                                        no_run, as_test_harness, maybe_sysroot, linker, edition,
+                                        true, |compile_result, output| {
+        match (compile_result, compile_fail) {
+            (Ok(()), true) => {
+                panic!("test compiled while it wasn't supposed to")
+            }
+            (Ok(()), false) => {}
+            (Err(()), true) => {
+                if error_codes.len() > 0 {
+                    error_codes.retain(|err| !output.contains(err));
+                }
+            }
+            (Err(()), false) => {
+                panic!("couldn't compile the test")
+            }
+        }
+
+        if error_codes.len() > 0 {
+            panic!("Some expected error codes were not found: {:?}", error_codes);
+        }
+    });
+
+    if no_run { return }
+
+    run_binary(Command::new(outdir.path().join("rust_out")), &libdir, should_panic,
+               expected_output);
+}
+
+/// Compiles a doctest crate into `rust_out` in a new temporary directory, or only checks it if
+/// `no_run` is set, and returns the directory of the target libraries and the temporary
+/// directory.
+///
+/// Whether the crate compiled and the messages of the compiler are passed to `check`, which
+/// panics to fail the test. The messages are then written to the output of the test, unless
+/// `show_output` is false.
+fn compile_test<F>(test: &str, filename: &FileName, line: isize,
+                   cfgs: Vec<String>, libs: SearchPaths,
+                   cg: CodegenOptions, externs: Externs,
+                   no_run: bool, as_test_harness: bool,
+                   maybe_sysroot: Option<PathBuf>, linker: Option<PathBuf>, edition: Edition,
+                   show_output: bool, check: F) -> (PathBuf, TempDir)
+    where F: FnOnce(Result<(), ()>, &str) + Send
+{
     // FIXME(#44940): if doctests ever support path remapping, then this filename
     // needs to be the result of CodeMap::span_to_unmapped_path
     let input = config::Input::Str {
@@ -258,14 +311,19 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, line: usize,
         }
         let data = Arc::new(Mutex::new(Vec::new()));
         let codemap = Lrc::new(CodeMap::new_doctest(
-            sessopts.file_path_mapping(), filename.clone(), line as isize - line_offset as isize
+            sessopts.file_path_mapping(), filename.clone(), line
         ));
         let emitter = errors::emitter::EmitterWriter::new(box Sink(data.clone()),
                                                         Some(codemap.clone()),
                                                         false,
                                                         false);
         let old = io::set_panic(Some(box Sink(data.clone())));
-        let _bomb = Bomb(data.clone(), old.unwrap_or(box io::stdout()));
+        let old: Box<Write+Send> = if show_output {
+            old.unwrap_or(box io::stdout())
+        } else {
+            box io::sink()
+        };
+        let _bomb = Bomb(data.clone(), old);
 
         // Compile the code
         let diagnostic_handler = errors::Handler::with_emitter(true, false, box emitter);
@@ -310,43 +368,30 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, line: usize,
             Err(_) | Ok(Err(CompileIncomplete::Errored(_))) => Err(())
         };
 
-        match (compile_result, compile_fail) {
-            (Ok(()), true) => {
-                panic!("test compiled while it wasn't supposed to")
-            }
-            (Ok(()), false) => {}
-            (Err(()), true) => {
-                if error_codes.len() > 0 {
-                    let out = String::from_utf8(data.lock().unwrap().to_vec()).unwrap();
-                    error_codes.retain(|err| !out.contains(err));
-                }
-            }
-            (Err(()), false) => {
-                panic!("couldn't compile the test")
-            }
-        }
-
-        if error_codes.len() > 0 {
-            panic!("Some expected error codes were not found: {:?}", error_codes);
-        }
+        let output = String::from_utf8_lossy(&data.lock().unwrap()).into_owned();
+        check(compile_result, &output);
 
         (libdir, outdir)
     });
 
-    if no_run { return }
+    (libdir, outdir.into_inner().unwrap())
+}
 
+/// Runs a compiled doctest, and checks that it succeeds, or that it fails if `should_panic` is
+/// set, and that it prints `expected_output` if given.
+fn run_binary(mut cmd: Command, libdir: &Path, should_panic: bool,
+              expected_output: Option<&str>) {
     // Run the code!
     //
     // We're careful to prepend the *target* dylib search path to the child's
     // environment to ensure that the target loads the right libraries at
     // runtime. It would be a sad day if the *host* libraries were loaded as a
     // mistake.
-    let mut cmd = Command::new(&outdir.lock().unwrap().path().join("rust_out"));
     let var = DynamicLibrary::envvar();
     let newpath = {
         let path = env::var_os(var).unwrap_or(OsString::new());
         let mut path = env::split_paths(&path).collect::<Vec<_>>();
-        path.insert(0, libdir.clone());
+        path.insert(0, libdir.to_path_buf());
         env::join_paths(path).unwrap()
     };
     cmd.env(var, &newpath);
@@ -364,10 +409,24 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, line: usize,
                        str::from_utf8(&out.stdout).unwrap_or(""),
                        str::from_utf8(&out.stderr).unwrap_or(""));
             }
+            if let Some(expected) = expected_output {
+                let stdout = String::from_utf8_lossy(&out.stdout);
+                if normalize_output(&stdout) != normalize_output(expected) {
+                    panic!("test executable printed:\n{}\nbut was expected to print:\n{}\n",
+                           stdout, expected);
+                }
+            }
         }
     }
 }
 
+/// Normalizes the output of a doctest for comparing it to the expected output, so that
+/// whitespace at the end of the lines and of the output doesn't matter.
+fn normalize_output(s: &str) -> String {
+    let lines = s.lines().map(|line| line.trim_right()).collect::<Vec<_>>();
+    lines.join("\n").trim_right().to_string()
+}
+
 /// Makes the test file. Also returns the number of lines before the code begins
 pub fn make_test(s: &str,
                  cratename: Option<&str>,
@@ -409,9 +468,24 @@ pub fn make_test(s: &str,
         }
     }
 
+    if dont_insert_main || has_main(s) {
+        prog.push_str(everything_else);
+    } else {
+        prog.push_str("fn main() {\n");
+        line_offset += 1;
+        prog.push_str(everything_else);
+        prog.push_str("\n}");
+    }
+
+    info!("final test program: {}", prog);
+
+    (prog, line_offset)
+}
+
+fn has_main(s: &str) -> bool {
     // FIXME (#21299): prefer libsyntax or some other actual parser over this
     // best-effort ad hoc approach
-    let already_has_main = s.lines()
+    s.lines()
         .map(|line| {
             let comment = line.find("//");
             if let Some(comment_begins) = comment {
@@ -420,20 +494,112 @@ pub fn make_test(s: &str,
                 line
             }
         })
-        .any(|code| code.contains("fn main"));
+        .any(|code| code.contains("fn main"))
+}
 
-    if dont_insert_main || already_has_main {
-        prog.push_str(everything_else);
-    } else {
-        prog.push_str("fn main() {\n");
-        line_offset += 1;
-        prog.push_str(everything_else);
-        prog.push_str("\n}");
+/// Whether a doctest can be compiled together with other doctests, which requires that it is
+/// expected to compile and link, and that it has neither crate attributes nor its own `main`.
+/// `no_run` doctests are left out, since they needn't link, and one that doesn't would keep all
+/// the others from being merged.
+fn can_merge(s: &str, no_run: bool, should_ignore: bool, as_test_harness: bool,
+             compile_fail: bool) -> bool {
+    !no_run && !should_ignore && !as_test_harness && !compile_fail &&
+        !partition_source(s).0.contains("#![") && !has_main(s)
+}
+
+/// Makes the crate of merged doctests, with the code of each doctest in the `main` function of
+/// its own module, and a `main` function calling the one of the doctest whose index is given in
+/// the `RUSTDOC_MERGED_DOCTEST` environment variable.
+fn make_merged_test(tests: &[String], cratename: &str, opts: &TestOptions) -> String {
+    let mut prog = String::new();
+
+    // Allow the same lints and add the same crate attributes as in `make_test`.
+    if opts.attrs.is_empty() && !opts.display_warnings {
+        prog.push_str("#![allow(unused)]\n");
+    }
+    for attr in &opts.attrs {
+        prog.push_str(&format!("#![{}]\n", attr));
     }
 
-    info!("final test program: {}", prog);
+    // The `extern crate` items of the doctests are moved to the crate root, where they can load
+    // macros, and where `use` declarations look for them in the 2015 edition.
+    let mut extern_crates = Vec::new();
+    if !opts.no_crate_inject && cratename != "std" &&
+       tests.iter().any(|test| test.contains(cratename)) {
+        extern_crates.push(format!("extern crate {};", cratename));
+    }
+    let mut bodies = Vec::with_capacity(tests.len());
+    for test in tests {
+        let (crate_attrs, everything_else) = partition_source(test);
+        for line in crate_attrs.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            if !extern_crates.iter().any(|c| c == line) {
+                extern_crates.push(line.to_string());
+            }
+        }
+        bodies.push(everything_else);
+    }
+    for extern_crate in &extern_crates {
+        prog.push_str(extern_crate);
+        prog.push_str("\n");
+    }
 
-    (prog, line_offset)
+    for (i, body) in bodies.iter().enumerate() {
+        prog.push_str(&format!("mod __doctest_{} {{\n#[allow(unused_imports)]\nuse super::*;\n\
+                                pub fn main() {{\n{}\n}}\n}}\n",
+                               i, body.trim()));
+    }
+    prog.push_str("fn main() {\n");
+    prog.push_str("    match &*::std::env::var(\"RUSTDOC_MERGED_DOCTEST\").unwrap() {\n");
+    for i in 0..tests.len() {
+        prog.push_str(&format!("        \"{0}\" => __doctest_{0}::main(),\n", i));
+    }
+    prog.push_str("        _ => unreachable!(),\n");
+    prog.push_str("    }\n");
+    prog.push_str("}\n");
+
+    info!("merged test program: {}", prog);
+
+    prog
+}
+
+/// The doctests compiled together into a single crate with `--merge-doctests`.
+///
+/// The crate is compiled by the first of these tests to run, after all of them have been
+/// collected, and each test then runs the resulting binary. If the crate doesn't compile, e.g.
+/// because of clashing `extern crate` items, every test is compiled on its own instead.
+#[derive(Default)]
+struct MergedDoctests {
+    tests: Vec<String>,
+    /// Once compiled, the directory of the target libraries and the directory of the binary, if
+    /// the crate compiled.
+    compiled: Option<Option<(PathBuf, TempDir)>>,
+}
+
+/// Returns the binary of the merged doctests and the directory of the target libraries,
+/// compiling them first if no other test did already, or `None` if they don't compile.
+fn compile_merged(merged: &Mutex<MergedDoctests>, cratename: &str, opts: &TestOptions,
+                  cfgs: Vec<String>, libs: SearchPaths,
+                  cg: CodegenOptions, externs: Externs,
+                  maybe_sysroot: Option<PathBuf>, linker: Option<PathBuf>, edition: Edition)
+                  -> Option<(PathBuf, PathBuf)> {
+    let mut merged = merged.lock().unwrap();
+    if merged.compiled.is_none() {
+        let test = make_merged_test(&merged.tests, cratename, opts);
+        let filename = FileName::Custom("merged doctests".to_owned());
+        let mut compiled = false;
+        let (libdir, outdir) = compile_test(&test, &filename, 0, cfgs, libs, cg, externs,
+                                            false, false, maybe_sysroot, linker, edition,
+                                            false, |compile_result, _| {
+            compiled = compile_result.is_ok();
+        });
+        merged.compiled = Some(if compiled { Some((libdir, outdir)) } else { None });
+    }
+    match merged.compiled {
+        Some(Some((ref libdir, ref outdir))) => {
+            Some((outdir.path().join("rust_out"), libdir.clone()))
+        }
+        _ => None,
+    }
 }
 
 // FIXME(aburka): use a real parser to deal with multiline attributes
@@ -499,6 +665,7 @@ pub struct Collector {
     filename: Option<PathBuf>,
     linker: Option<PathBuf>,
     edition: Edition,
+    merged: Arc<Mutex<MergedDoctests>>,
 }
 
 impl Collector {
@@ -522,6 +689,7 @@ impl Collector {
             filename,
             linker,
             edition,
+            merged: Arc::new(Mutex::new(MergedDoctests::default())),
         }
     }
 
@@ -532,6 +700,7 @@ impl Collector {
     pub fn add_test(&mut self, test: String,
                     should_panic: bool, no_run: bool, should_ignore: bool,
                     as_test_harness: bool, compile_fail: bool, error_codes: Vec<String>,
+                    expected_output: Option<String>,
                     line: usize, filename: FileName, allow_fail: bool) {
         let name = self.generate_name(line, &filename);
         let cfgs = self.cfgs.clone();
@@ -543,6 +712,14 @@ impl Collector {
         let maybe_sysroot = self.maybe_sysroot.clone();
         let linker = self.linker.clone();
         let edition = self.edition;
+        let merged = if opts.merge_doctests &&
+                        can_merge(&test, no_run, should_ignore, as_test_harness, compile_fail) {
+            let mut merged = self.merged.lock().unwrap();
+            merged.tests.push(test.clone());
+            Some((self.merged.clone(), merged.tests.len() - 1))
+        } else {
+            None
+        };
         debug!("Creating test {}: {}", name, test);
         self.tests.push(testing::TestDescAndFn {
             desc: testing::TestDesc {
@@ -559,6 +736,19 @@ impl Collector {
                     rustc_driver::in_rustc_thread(move || with_globals(move || {
                         io::set_panic(panic);
                         io::set_print(print);
+                        if let Some((merged, index)) = merged {
+                            let binary = compile_merged(&merged, &cratename, &opts,
+                                                        cfgs.clone(), libs.clone(), cg.clone(),
+                                                        externs.clone(), maybe_sysroot.clone(),
+                                                        linker.clone(), edition);
+                            if let Some((binary, libdir)) = binary {
+                                let mut cmd = Command::new(binary);
+                                cmd.env("RUSTDOC_MERGED_DOCTEST", index.to_string());
+                                run_binary(cmd, &libdir, should_panic,
+                                           expected_output.as_ref().map(|s| &s[..]));
+                                return;
+                            }
+                        }
                         run_test(&test,
                                  &cratename,
                                  &filename,
@@ -572,6 +762,7 @@ impl Collector {
                                  as_test_harness,
                                  compile_fail,
                                  error_codes,
+                                 expected_output.as_ref().map(|s| &s[..]),
                                  &opts,
                                  maybe_sysroot,
                                  linker,
@@ -755,7 +946,7 @@ impl<'a, 'hir> intravisit::Visitor<'hir> for HirCollector<'a, 'hir> {
 
 #[cfg(test)]
 mod tests {
-    use super::{TestOptions, make_test};
+    use super::{TestOptions, can_merge, make_merged_test, make_test, normalize_output};
 
     #[test]
     fn make_test_basic() {
@@ -999,4 +1190,59 @@ assert_eq!(2+2, 4);
         let output = make_test(input, None, false, &opts);
         assert_eq!(output, (expected.clone(), 1));
     }
+
+    #[test]
+    fn can_merge_plain_tests() {
+        assert!(can_merge("assert_eq!(2+2, 4);", false, false, false, false));
+        assert!(can_merge("extern crate asdf;\nassert_eq!(2+2, 4);", false, false, false, false));
+        assert!(!can_merge("#![feature(asdf)]\nassert_eq!(2+2, 4);", false, false, false, false));
+        assert!(!can_merge("fn main() {\nassert_eq!(2+2, 4);\n}", false, false, false, false));
+        assert!(!can_merge("assert_eq!(2+2, 4);", false, true, false, false));
+        assert!(!can_merge("assert_eq!(2+2, 4);", false, false, true, false));
+        assert!(!can_merge("assert_eq!(2+2, 4);", false, false, false, true));
+        assert!(!can_merge("assert_eq!(2+2, 4);", true, false, false, false));
+    }
+
+    #[test]
+    fn make_merged_test_basic() {
+        //each test gets its own module, and `main` picks the one to run
+        let opts = TestOptions::default();
+        let tests = vec!["use asdf::qwop;\nassert_eq!(2+2, 4);".to_string(),
+                         "extern crate other;\nlet x = 5;".to_string()];
+        let expected =
+"#![allow(unused)]
+extern crate asdf;
+extern crate other;
+mod __doctest_0 {
+#[allow(unused_imports)]
+use super::*;
+pub fn main() {
+use asdf::qwop;
+assert_eq!(2+2, 4);
+}
+}
+mod __doctest_1 {
+#[allow(unused_imports)]
+use super::*;
+pub fn main() {
+let x = 5;
+}
+}
+fn main() {
+    match &*::std::env::var(\"RUSTDOC_MERGED_DOCTEST\").unwrap() {
+        \"0\" => __doctest_0::main(),
+        \"1\" => __doctest_1::main(),
+        _ => unreachable!(),
+    }
+}
+".to_string();
+        assert_eq!(make_merged_test(&tests, "asdf", &opts), expected);
+    }
+
+    #[test]
+    fn normalize_output_whitespace() {
+        assert_eq!(normalize_output("a \r\nb\n\n"), "a\nb");
+        assert_eq!(normalize_output("  a\n\n b"), "  a\n\n b");
+        assert_eq!(normalize_output(""), "");
+    }
 }
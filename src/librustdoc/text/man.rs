@@ -0,0 +1,296 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The man page output format of rustdoc.
+//!
+//! This writes the documentation of every module as a page of section 3 of
+//! the manual, named after the path of the module like `std.fs.3`, so that
+//! it can be read with `man` where there is no browser. The pages are
+//! converted to roff, using the `man` macros, from the pages of the Markdown
+//! output format. Links are left out, keeping only their text.
+
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+use pulldown_cmark::{Event, Parser, Tag};
+use pulldown_cmark::{Options, OPTION_ENABLE_FOOTNOTES, OPTION_ENABLE_TABLES};
+
+use clean;
+use html::render::Error;
+
+/// Writes the man pages of `krate` to `dst/man3`.
+pub fn run(krate: clean::Crate, dst: PathBuf) -> Result<(), Error> {
+    let dir = dst.join("man3");
+    fs::create_dir_all(&dir).map_err(|e| Error::new(e, &dir))?;
+    let source = match krate.version {
+        Some(ref version) => format!("{} {}", krate.name, version),
+        None => krate.name.clone(),
+    };
+    for page in super::pages(&krate) {
+        let name = page.path.join("::");
+        // `:` can't be used in file names on Windows.
+        let path = dir.join(format!("{}.3", page.path.join(".")));
+        let write = || -> io::Result<()> {
+            let mut w = BufWriter::new(File::create(&path)?);
+            write!(w, ".TH \"{}\" 3 \"\" \"{}\" \"Rust Library Documentation\"\n",
+                   escape(&name), escape(&source))?;
+            write!(w, ".SH NAME\n{}", escape(&name))?;
+            if !page.summary.is_empty() {
+                write!(w, " \\- {}", escape(&page.summary))?;
+            }
+            w.write_all(b"\n")?;
+            if !page.docs.is_empty() {
+                // The documentation is put in a section of its own unless it
+                // starts with a heading.
+                if !page.docs.starts_with("## ") {
+                    w.write_all(b".SH DESCRIPTION\n")?;
+                }
+                w.write_all(roff(&page.docs).as_bytes())?;
+            }
+            w.write_all(roff(&page.items).as_bytes())?;
+            w.flush()
+        };
+        write().map_err(|e| Error::new(e, &path))?;
+    }
+    Ok(())
+}
+
+/// Escapes the characters of `text` which have a meaning in roff.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-").replace('"', "\\(dq")
+}
+
+/// Converts the Markdown `md` to roff, for the body of a man page. Level 1 and
+/// 2 headings start sections of the page, and level 3 headings subsections.
+pub fn roff(md: &str) -> String {
+    let mut opts = Options::empty();
+    opts.insert(OPTION_ENABLE_TABLES);
+    opts.insert(OPTION_ENABLE_FOOTNOTES);
+
+    let mut w = Writer {
+        out: String::new(),
+        fonts: vec![],
+        lists: vec![],
+        item_start: false,
+        in_code_block: false,
+        upper_case: false,
+        cells: 0,
+    };
+    for event in Parser::new_ext(md, opts) {
+        w.event(event);
+    }
+    if !w.out.is_empty() && !w.out.ends_with('\n') {
+        w.out.push('\n');
+    }
+    w.out
+}
+
+struct Writer {
+    out: String,
+    /// The fonts of the open emphasis, strong emphasis, code spans and bold
+    /// headings.
+    fonts: Vec<&'static str>,
+    /// The number of the next item of every open list, or `None` for bullet
+    /// lists.
+    lists: Vec<Option<usize>>,
+    /// Whether a list item or footnote has just been started, so that its
+    /// first paragraph goes after its tag.
+    item_start: bool,
+    in_code_block: bool,
+    /// Whether the text is written in upper case, as in section headings.
+    upper_case: bool,
+    /// The number of cells of the open table row.
+    cells: usize,
+}
+
+impl Writer {
+    fn start_line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    /// Writes the request `request` on a line of its own.
+    fn request(&mut self, request: &str) {
+        self.start_line();
+        self.out.push_str(request);
+        self.out.push('\n');
+    }
+
+    /// Starts a paragraph, which is indented within list items.
+    fn paragraph(&mut self) {
+        if self.item_start {
+            self.item_start = false;
+        } else if self.lists.is_empty() {
+            self.request(".PP");
+        } else {
+            self.request(".IP");
+        }
+    }
+
+    fn push_font(&mut self, font: &'static str) {
+        self.fonts.push(font);
+        self.out.push_str("\\f");
+        self.out.push_str(font);
+    }
+
+    fn pop_font(&mut self) {
+        self.fonts.pop();
+        self.out.push_str("\\f");
+        self.out.push_str(self.fonts.last().cloned().unwrap_or("R"));
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.text(&text),
+            Event::Html(..) | Event::InlineHtml(..) => {}
+            Event::FootnoteReference(name) => self.text(&format!("[{}]", name)),
+            Event::SoftBreak => self.out.push('\n'),
+            Event::HardBreak => self.request(".br"),
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.paragraph(),
+            Tag::Header(1) | Tag::Header(2) => {
+                self.start_line();
+                self.out.push_str(".SH ");
+                self.upper_case = true;
+            }
+            Tag::Header(3) => {
+                self.start_line();
+                self.out.push_str(".SS ");
+            }
+            Tag::Header(_) => {
+                self.paragraph();
+                self.push_font("B");
+            }
+            Tag::BlockQuote => self.request(".RS 4"),
+            Tag::CodeBlock(..) => {
+                self.paragraph();
+                self.request(".RS 4");
+                self.request(".nf");
+                self.in_code_block = true;
+            }
+            Tag::List(start) => {
+                if !self.lists.is_empty() {
+                    self.request(".RS");
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                let tag = match self.lists.last_mut() {
+                    Some(&mut Some(ref mut n)) => {
+                        *n += 1;
+                        format!("{}.", *n - 1)
+                    }
+                    _ => "\\(bu".to_string(),
+                };
+                self.request(&format!(".IP {} 4", tag));
+                self.item_start = true;
+            }
+            Tag::FootnoteDefinition(name) => {
+                self.request(&format!(".IP [{}] 4", escape(&name)));
+                self.item_start = true;
+            }
+            Tag::Table(..) => self.paragraph(),
+            Tag::TableHead => {
+                self.cells = 0;
+                self.push_font("B");
+            }
+            Tag::TableRow => {
+                self.request(".br");
+                self.cells = 0;
+            }
+            Tag::TableCell => {
+                if self.cells > 0 {
+                    self.out.push_str(" | ");
+                }
+                self.cells += 1;
+            }
+            Tag::Emphasis => self.push_font("I"),
+            Tag::Strong | Tag::Code => self.push_font("B"),
+            Tag::Rule | Tag::Link(..) | Tag::Image(..) => {}
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Header(1) | Tag::Header(2) => {
+                self.upper_case = false;
+                self.out.push('\n');
+            }
+            Tag::Header(3) => self.out.push('\n'),
+            Tag::Header(_) => self.pop_font(),
+            Tag::BlockQuote => self.request(".RE"),
+            Tag::CodeBlock(..) => {
+                self.in_code_block = false;
+                self.request(".fi");
+                self.request(".RE");
+            }
+            Tag::List(..) => {
+                self.lists.pop();
+                if !self.lists.is_empty() {
+                    self.request(".RE");
+                }
+            }
+            Tag::TableHead | Tag::Emphasis | Tag::Strong | Tag::Code => self.pop_font(),
+            Tag::Paragraph | Tag::Rule | Tag::Item | Tag::FootnoteDefinition(..) |
+            Tag::Table(..) | Tag::TableRow | Tag::TableCell | Tag::Link(..) |
+            Tag::Image(..) => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        self.item_start = false;
+        for c in text.chars() {
+            if self.out.is_empty() || self.out.ends_with('\n') {
+                // Leading spaces break the line outside of code blocks, and
+                // lines starting with a dot or a quote are requests.
+                if c == ' ' && !self.in_code_block {
+                    continue;
+                }
+                if c == '.' || c == '\'' {
+                    self.out.push_str("\\&");
+                }
+            }
+            match c {
+                '\\' => self.out.push_str("\\e"),
+                '-' => self.out.push_str("\\-"),
+                c if self.upper_case => self.out.extend(c.to_uppercase()),
+                c => self.out.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::roff;
+
+    #[test]
+    fn test_roff() {
+        fn t(md: &str, expected: &str) {
+            assert_eq!(roff(md), expected, "{:?}", md);
+        }
+
+        t("Some *text* with `code`.", ".PP\nSome \\fItext\\fR with \\fBcode\\fR.\n");
+        t("## Examples\n\nText", ".SH EXAMPLES\n.PP\nText\n");
+        t("### Struct `Foo`", ".SS Struct \\fBFoo\\fR\n");
+        t("```rust\nlet x = -1;\n.5\n```", ".PP\n.RS 4\n.nf\nlet x = \\-1;\n\\&.5\n.fi\n.RE\n");
+        t("- a\n- b", ".IP \\(bu 4\na\n.IP \\(bu 4\nb\n");
+        t("[link](https://example.com) \\\\", ".PP\nlink \\e\n");
+    }
+}
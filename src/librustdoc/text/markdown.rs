@@ -0,0 +1,439 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The Markdown output format of rustdoc.
+//!
+//! This writes the documentation of every module to `<path>/index.md`,
+//! following the layout of the HTML output, for publishing it with tools that
+//! take Markdown, like wikis. The documentation comments are parsed and
+//! written out again, so that their headings are nested under the heading of
+//! their item, the hidden lines of Rust examples are left out, and intra-doc
+//! links point to the Markdown pages.
+
+use std::cmp;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+use pulldown_cmark::{Alignment, Event, Parser, Tag};
+use pulldown_cmark::{Options, OPTION_ENABLE_FOOTNOTES, OPTION_ENABLE_TABLES};
+
+use clean;
+use html::markdown::visible_rust_code;
+use html::render::Error;
+
+/// Writes the Markdown documentation of `krate` to `dst`.
+pub fn run(krate: clean::Crate, dst: PathBuf) -> Result<(), Error> {
+    for page in super::pages(&krate) {
+        let dir = page.path.iter().fold(dst.clone(), |dir, name| dir.join(name));
+        fs::create_dir_all(&dir).map_err(|e| Error::new(e, &dir))?;
+        let path = dir.join("index.md");
+        let write = || -> io::Result<()> {
+            let mut w = BufWriter::new(File::create(&path)?);
+            write!(w, "# {} {}\n",
+                   if page.is_crate { "Crate" } else { "Module" }, page.path.join("::"))?;
+            if !page.docs.is_empty() {
+                write!(w, "\n{}\n", page.docs)?;
+            }
+            if !page.items.is_empty() {
+                write!(w, "\n{}\n", page.items)?;
+            }
+            w.flush()
+        };
+        write().map_err(|e| Error::new(e, &path))?;
+    }
+    Ok(())
+}
+
+/// Writes `code` as a code span, using enough backticks to hold the ones in
+/// it.
+pub fn code_span(code: &str) -> String {
+    let ticks = "`".repeat(longest_run(code, '`') + 1);
+    let pad = if code.starts_with('`') || code.ends_with('`') { " " } else { "" };
+    format!("{0}{1}{2}{1}{0}", ticks, pad, code)
+}
+
+fn longest_run(s: &str, c: char) -> usize {
+    s.split(|x| x != c).map(|run| run.len()).max().unwrap_or(0)
+}
+
+/// Rewrites the Markdown documentation `md` with its headings moved down by
+/// `level` and the hidden lines of its Rust code blocks left out. The
+/// destinations of its links are replaced as given in `links`, where an empty
+/// replacement leaves out the link but keeps its text.
+pub fn docs(md: &str, level: i32, links: &[(String, String)]) -> String {
+    let mut opts = Options::empty();
+    opts.insert(OPTION_ENABLE_TABLES);
+    opts.insert(OPTION_ENABLE_FOOTNOTES);
+
+    let replacer = |_: &str, s: &str| {
+        links.iter().find(|link| link.0 == s).map(|&(_, ref replace)| {
+            (replace.clone(), String::new())
+        })
+    };
+    let p = Parser::new_with_broken_link_callback(md, opts, Some(&replacer));
+
+    let mut w = Writer {
+        out: String::new(),
+        prefix: String::new(),
+        prefixes: vec![],
+        lists: vec![],
+        first_item: false,
+        item_start: false,
+        in_html: false,
+        level,
+        links,
+        open_links: vec![],
+        code: None,
+        table: None,
+    };
+    for event in p {
+        w.event(event);
+    }
+    w.out.trim_right().to_string()
+}
+
+struct Writer<'a> {
+    out: String,
+    /// What every line starts with, for block quotes, list items and footnotes.
+    prefix: String,
+    /// The length of `prefix` before every open block quote, list item and
+    /// footnote.
+    prefixes: Vec<usize>,
+    /// The number of the next item of every open list, or `None` for bullet
+    /// lists.
+    lists: Vec<Option<usize>>,
+    /// Whether the next list item is the first of its list.
+    first_item: bool,
+    /// Whether a list item, block quote or footnote has just been started, so
+    /// that its first block goes on the same line.
+    item_start: bool,
+    /// Whether the last event was a line of an HTML block.
+    in_html: bool,
+    level: i32,
+    links: &'a [(String, String)],
+    /// Whether every open link is kept, or left out in favour of its text.
+    open_links: Vec<bool>,
+    /// The text of the open code span or code block.
+    code: Option<String>,
+    /// The alignment of the columns of the open table.
+    table: Option<Vec<Alignment>>,
+}
+
+impl<'a> Writer<'a> {
+    fn newline(&mut self) {
+        self.out.push('\n');
+        self.out.push_str(&self.prefix);
+    }
+
+    /// Starts a new line, which is empty if `empty` is set.
+    fn line(&mut self, empty: bool) {
+        if empty {
+            self.out.push('\n');
+            self.out.push_str(self.prefix.trim_right());
+        } else {
+            self.newline();
+        }
+    }
+
+    /// Separates a new block from the previous one with an empty line.
+    fn start_block(&mut self) {
+        if self.item_start {
+            self.item_start = false;
+        } else if !self.out.is_empty() {
+            self.line(true);
+            self.newline();
+        }
+    }
+
+    /// Starts a block quote, list item or footnote with `marker`, whose
+    /// following lines start with `indent`.
+    fn open(&mut self, marker: &str, indent: &str) {
+        self.out.push_str(marker);
+        self.prefixes.push(self.prefix.len());
+        self.prefix.push_str(indent);
+        self.item_start = true;
+    }
+
+    fn close(&mut self) {
+        let len = self.prefixes.pop().unwrap_or(0);
+        self.prefix.truncate(len);
+    }
+
+    fn link_dest(&self, dest: &str) -> String {
+        match self.links.iter().find(|link| link.0 == dest) {
+            Some(&(_, ref replace)) => replace.clone(),
+            None => dest.to_string(),
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        let is_html = match event {
+            Event::Html(..) => true,
+            _ => false,
+        };
+        if !is_html {
+            self.in_html = false;
+        }
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.text(&text),
+            Event::Html(html) => {
+                if self.in_html {
+                    self.newline();
+                } else {
+                    self.start_block();
+                    self.in_html = true;
+                }
+                let html = html.trim_right_matches('\n');
+                for (i, line) in html.split('\n').enumerate() {
+                    if i > 0 {
+                        self.line(line.is_empty());
+                    }
+                    self.out.push_str(line);
+                }
+            }
+            Event::InlineHtml(html) => self.out.push_str(&html),
+            Event::FootnoteReference(name) => {
+                self.out.push_str("[^");
+                self.out.push_str(&name);
+                self.out.push(']');
+            }
+            Event::SoftBreak => self.newline(),
+            Event::HardBreak => {
+                self.out.push('\\');
+                self.newline();
+            }
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.start_block(),
+            Tag::Rule => {
+                self.start_block();
+                self.out.push_str("---");
+            }
+            Tag::Header(level) => {
+                self.start_block();
+                let level = cmp::min(level + self.level, 6) as usize;
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            Tag::BlockQuote => {
+                self.start_block();
+                self.open("> ", "> ");
+            }
+            Tag::CodeBlock(..) => {
+                self.start_block();
+                self.code = Some(String::new());
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.start_block();
+                } else if self.item_start {
+                    self.item_start = false;
+                } else {
+                    self.newline();
+                }
+                self.lists.push(start);
+                self.first_item = true;
+            }
+            Tag::Item => {
+                if !self.first_item {
+                    self.newline();
+                }
+                self.first_item = false;
+                let marker = match self.lists.last_mut() {
+                    Some(&mut Some(ref mut n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.open(&marker, &" ".repeat(marker.len()));
+            }
+            Tag::FootnoteDefinition(name) => {
+                self.start_block();
+                self.open(&format!("[^{}]: ", name), "    ");
+            }
+            Tag::Table(alignments) => {
+                self.start_block();
+                self.table = Some(alignments);
+            }
+            Tag::TableHead => self.out.push('|'),
+            Tag::TableRow => {
+                self.newline();
+                self.out.push('|');
+            }
+            Tag::TableCell => self.out.push(' '),
+            Tag::Emphasis => self.out.push('*'),
+            Tag::Strong => self.out.push_str("**"),
+            Tag::Code => self.code = Some(String::new()),
+            Tag::Link(dest, _) => {
+                let keep = !self.link_dest(&dest).is_empty();
+                if keep {
+                    self.out.push('[');
+                }
+                self.open_links.push(keep);
+            }
+            Tag::Image(..) => self.out.push_str("!["),
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph | Tag::Rule | Tag::Header(..) | Tag::TableRow => {}
+            Tag::BlockQuote | Tag::Item | Tag::FootnoteDefinition(..) => self.close(),
+            Tag::List(..) => {
+                self.lists.pop();
+            }
+            Tag::CodeBlock(lang) => {
+                let code = self.code.take().unwrap_or(String::new());
+                let (info, code) = match visible_rust_code(&lang, &code) {
+                    Some(code) => ("rust".to_string(), code),
+                    None => (lang.into_owned(), code),
+                };
+                let fence = "`".repeat(cmp::max(longest_run(&code, '`') + 1, 3));
+                self.out.push_str(&fence);
+                self.out.push_str(&info);
+                for line in code.trim_right_matches('\n').split('\n') {
+                    self.line(line.is_empty());
+                    self.out.push_str(line);
+                }
+                self.newline();
+                self.out.push_str(&fence);
+            }
+            Tag::Table(..) => self.table = None,
+            Tag::TableHead => {
+                self.newline();
+                let row = self.table.as_ref().map(|alignments| {
+                    alignments.iter().map(|alignment| match *alignment {
+                        Alignment::None => " --- |",
+                        Alignment::Left => " :-- |",
+                        Alignment::Center => " :-: |",
+                        Alignment::Right => " --: |",
+                    }).collect::<String>()
+                }).unwrap_or_default();
+                self.out.push('|');
+                self.out.push_str(&row);
+            }
+            Tag::TableCell => self.out.push_str(" |"),
+            Tag::Emphasis => self.out.push('*'),
+            Tag::Strong => self.out.push_str("**"),
+            Tag::Code => {
+                let code = self.code.take().unwrap_or(String::new());
+                self.out.push_str(&code_span(&code));
+            }
+            Tag::Link(dest, title) => {
+                if self.open_links.pop().unwrap_or(false) {
+                    let dest = self.link_dest(&dest);
+                    self.link_end(&dest, &title);
+                }
+            }
+            Tag::Image(dest, title) => self.link_end(&dest, &title),
+        }
+    }
+
+    fn link_end(&mut self, dest: &str, title: &str) {
+        self.out.push_str("](");
+        if dest.contains(' ') || dest.contains(')') {
+            self.out.push('<');
+            self.out.push_str(dest);
+            self.out.push('>');
+        } else {
+            self.out.push_str(dest);
+        }
+        if !title.is_empty() {
+            self.out.push_str(" \"");
+            self.out.push_str(&title.replace('"', "\\\""));
+            self.out.push('"');
+        }
+        self.out.push(')');
+    }
+
+    fn text(&mut self, text: &str) {
+        self.item_start = false;
+        if let Some(ref mut code) = self.code {
+            code.push_str(text);
+            return;
+        }
+        // Whether nothing but the markers of block quotes and list items has
+        // been written on the current line.
+        let mut line_start = {
+            let line = &self.out[self.out.rfind('\n').map_or(0, |i| i + 1)..];
+            line.chars().all(|c| " >-+*.0123456789".contains(c))
+        };
+        let mut prev = self.out.chars().next_back();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            let escape = match c {
+                '\\' | '`' | '*' | '[' | ']' | '<' => true,
+                // An underscore within a word doesn't start emphasis.
+                '_' => {
+                    !prev.map_or(false, char::is_alphanumeric) ||
+                        !chars.peek().map_or(false, |&c| c.is_alphanumeric())
+                }
+                '|' => self.table.is_some(),
+                '#' => line_start,
+                _ => false,
+            };
+            if escape {
+                self.out.push('\\');
+            }
+            self.out.push(c);
+            prev = Some(c);
+            line_start = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{code_span, docs};
+
+    #[test]
+    fn test_code_span() {
+        assert_eq!(code_span("foo"), "`foo`");
+        assert_eq!(code_span("a`b"), "``a`b``");
+        assert_eq!(code_span("`a"), "`` `a ``");
+    }
+
+    #[test]
+    fn test_docs() {
+        fn t(md: &str, level: i32, expected: &str) {
+            assert_eq!(docs(md, level, &[]), expected, "{:?}", md);
+        }
+
+        t("Some *text* with `code`.", 0, "Some *text* with `code`.");
+        t("# Examples\n\nText", 3, "#### Examples\n\nText");
+        t("###### Deep", 3, "###### Deep");
+        t("_a_ 2 * 3", 0, "*a* 2 \\* 3");
+        t("\\# Not a heading", 0, "\\# Not a heading");
+        t("```\n# fn main() {\nlet x = 1;\n# }\n```", 0, "```rust\nlet x = 1;\n```");
+        t("```text\n# not hidden\n```", 0, "```text\n# not hidden\n```");
+        t("- a\n- b\n\n1. c\n2. d", 0, "- a\n- b\n\n1. c\n2. d");
+        t("- a\n  - b\n- c", 0, "- a\n  - b\n- c");
+        t("> quoted\n>\n> twice", 0, "> quoted\n>\n> twice");
+        t("| a | b |\n|---|:-:|\n| c | d |", 0, "| a | b |\n| --- | :-: |\n| c | d |");
+    }
+
+    #[test]
+    fn test_docs_links() {
+        let links = vec![("Foo".to_string(), "index.md#struct-foo".to_string()),
+                         ("Bar".to_string(), String::new())];
+        assert_eq!(docs("See [Foo] and [the bar](Bar).", 0, &links),
+                   "See [Foo](index.md#struct-foo) and the bar.");
+        assert_eq!(docs("See [Baz](https://example.com).", 0, &links),
+                   "See [Baz](https://example.com).");
+    }
+}
@@ -0,0 +1,537 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The Markdown and man page output formats of rustdoc.
+//!
+//! Both formats are built from the same pages: the documentation of every
+//! module is first written as Markdown, with the items of the module listed
+//! under a heading per kind of item like on the HTML page of the module, and
+//! the fields, variants, methods and trait implementations of a type listed
+//! under the type. The Markdown output format writes these pages as they
+//! are, while the man page output format converts them to roff.
+
+use std::fmt::Write;
+
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc_data_structures::fx::FxHashMap;
+
+use clean::{self, GetDefId};
+use html::format::{AbiSpace, ConstnessSpace, Method, TyParamBounds, UnsafetySpace, VisSpace};
+use html::format::WhereClause;
+use html::item_type::ItemType;
+use html::markdown::plain_summary_line;
+
+pub mod man;
+pub mod markdown;
+
+/// The documentation of a module, written as Markdown.
+pub struct Page {
+    /// The path of the module, starting with the name of the crate.
+    pub path: Vec<String>,
+    /// Whether this is the crate root.
+    pub is_crate: bool,
+    /// The first line of the documentation of the module.
+    pub summary: String,
+    /// The documentation of the module, with its headings starting at level 2.
+    pub docs: String,
+    /// The items of the module, with a level 2 heading for every kind of item.
+    pub items: String,
+}
+
+/// The kinds of items listed on the page of a module, in order.
+const SECTIONS: &[(&[ItemType], &str)] = &[
+    (&[ItemType::ExternCrate, ItemType::Import], "Re-exports"),
+    (&[ItemType::Primitive], "Primitive Types"),
+    (&[ItemType::Module], "Modules"),
+    (&[ItemType::Macro], "Macros"),
+    (&[ItemType::Struct], "Structs"),
+    (&[ItemType::Enum], "Enums"),
+    (&[ItemType::Constant], "Constants"),
+    (&[ItemType::Static], "Statics"),
+    (&[ItemType::Trait], "Traits"),
+    (&[ItemType::Function], "Functions"),
+    (&[ItemType::Typedef], "Type Definitions"),
+    (&[ItemType::Union], "Unions"),
+    (&[ItemType::ForeignType], "Foreign Types"),
+];
+
+/// Builds the pages of all the modules of `krate`, starting with the crate
+/// root.
+pub fn pages(krate: &clean::Crate) -> Vec<Page> {
+    let mut pages = vec![];
+    if let Some(ref module) = krate.module {
+        let mut cx = Context {
+            locations: FxHashMap(),
+            impls: FxHashMap(),
+        };
+        let path = vec![krate.name.clone()];
+        cx.collect(module, &path);
+        cx.collect_impls(module);
+        cx.module(module, path, &mut pages);
+    }
+    pages
+}
+
+struct Context<'a> {
+    /// The page every item is documented on, along with the anchor of its
+    /// heading unless it is a module. Fields, variants, methods and the like
+    /// are linked to the heading of their parent.
+    locations: FxHashMap<DefId, (Vec<String>, Option<String>)>,
+    /// The impls of every type of the crate.
+    impls: FxHashMap<DefId, Vec<&'a clean::Item>>,
+}
+
+impl<'a> Context<'a> {
+    fn collect(&mut self, module: &clean::Item, path: &[String]) {
+        self.locations.insert(module.def_id, (path.to_vec(), None));
+        for item in items(module) {
+            match item.inner {
+                clean::ModuleItem(..) => {
+                    let mut path = path.to_vec();
+                    path.push(item.name.clone().unwrap());
+                    self.collect(item, &path);
+                }
+                clean::ImplItem(..) | clean::ImportItem(..) | clean::ExternCrateItem(..) => {}
+                _ => {
+                    let location = (path.to_vec(), Some(anchor(&heading(item))));
+                    for child in children(item) {
+                        self.locations.insert(child.def_id, location.clone());
+                    }
+                    self.locations.insert(item.def_id, location);
+                }
+            }
+        }
+    }
+
+    fn collect_impls(&mut self, module: &'a clean::Item) {
+        for item in items(module) {
+            match item.inner {
+                clean::ModuleItem(..) => self.collect_impls(item),
+                clean::ImplItem(ref i) => {
+                    let did = match i.for_.def_id() {
+                        Some(did) => did,
+                        None => continue,
+                    };
+                    if let Some(location) = self.locations.get(&did).cloned() {
+                        for child in &i.items {
+                            self.locations.insert(child.def_id, location.clone());
+                        }
+                    }
+                    self.impls.entry(did).or_insert(vec![]).push(item);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn module(&self, module: &clean::Item, path: Vec<String>, pages: &mut Vec<Page>) {
+        let mut out = String::new();
+        for &(types, title) in SECTIONS {
+            let mut section = items(module)
+                .filter(|item| types.contains(&item.type_()))
+                .collect::<Vec<_>>();
+            if section.is_empty() {
+                continue;
+            }
+            section.sort_by_key(|item| item.name.clone());
+
+            write!(out, "## {}\n\n", title).unwrap();
+            for item in section {
+                self.item(&mut out, item, &path);
+            }
+        }
+
+        let docs = module.collapsed_doc_value().unwrap_or(String::new());
+        pages.push(Page {
+            path: path.clone(),
+            is_crate: module.is_crate(),
+            summary: plain_summary_line(&docs),
+            docs: self.docs(module, &path, 1),
+            items: out.trim_right().to_string(),
+        });
+
+        for item in items(module).filter(|item| item.is_mod()) {
+            let mut path = path.clone();
+            path.push(item.name.clone().unwrap());
+            self.module(item, path, pages);
+        }
+    }
+
+    fn item(&self, out: &mut String, item: &clean::Item, page: &[String]) {
+        let name = item.name.as_ref().map(|s| &**s).unwrap_or("");
+        match item.inner {
+            clean::ExternCrateItem(ref krate, ref rename) => {
+                let code = match *rename {
+                    Some(ref rename) => format!("extern crate {} as {};", krate, rename),
+                    None => format!("extern crate {};", krate),
+                };
+                list_item(out, &format!("{:#}{}", VisSpace(&item.visibility), code), "");
+                return;
+            }
+            clean::ImportItem(ref import) => {
+                let code = match *import {
+                    clean::Import::Simple(ref name, ref source) => {
+                        if *name == source.path.last_name() {
+                            format!("use {:#};", source.path)
+                        } else {
+                            format!("use {:#} as {};", source.path, name)
+                        }
+                    }
+                    clean::Import::Glob(ref source) => format!("use {:#}::*;", source.path),
+                };
+                list_item(out, &format!("{:#}{}", VisSpace(&item.visibility), code), "");
+                return;
+            }
+            clean::ModuleItem(..) => {
+                let mut target = page.to_vec();
+                target.push(name.to_string());
+                let summary = plain_summary_line(&item.collapsed_doc_value().unwrap_or_default());
+                write!(out, "- [{}]({})", markdown::code_span(name), relative(page, &target))
+                    .unwrap();
+                if !summary.is_empty() {
+                    write!(out, ": {}", summary).unwrap();
+                }
+                out.push_str("\n\n");
+                return;
+            }
+            _ => {}
+        }
+
+        write!(out, "### {}\n\n", heading(item)).unwrap();
+        if let Some(signature) = signature(item) {
+            write!(out, "```rust\n{}\n```\n\n", signature).unwrap();
+        }
+        if let Some(ref deprecation) = item.deprecation {
+            out.push_str("**Deprecated**");
+            if !deprecation.since.is_empty() {
+                write!(out, " since {}", deprecation.since).unwrap();
+            }
+            if !deprecation.note.is_empty() {
+                write!(out, ": {}", deprecation.note).unwrap();
+            }
+            out.push_str("\n\n");
+        }
+        let docs = self.docs(item, page, 3);
+        if !docs.is_empty() {
+            write!(out, "{}\n\n", docs).unwrap();
+        }
+
+        match item.inner {
+            clean::StructItem(clean::Struct { ref fields, .. }) |
+            clean::UnionItem(clean::Union { ref fields, .. }) => {
+                self.list(out, "Fields", fields.iter(), page);
+            }
+            clean::EnumItem(ref e) => self.list(out, "Variants", e.variants.iter(), page),
+            clean::TraitItem(ref t) => {
+                let items = t.items.iter().filter(|item| !item.is_stripped());
+                let of_type = |ty| items.clone().filter(move |item| item.type_() == ty);
+                self.list(out, "Associated Types", of_type(ItemType::AssociatedType), page);
+                self.list(out, "Associated Constants", of_type(ItemType::AssociatedConst), page);
+                self.list(out, "Required Methods", of_type(ItemType::TyMethod), page);
+                self.list(out, "Provided Methods", of_type(ItemType::Method), page);
+            }
+            _ => {}
+        }
+
+        let impls = match self.impls.get(&item.def_id) {
+            Some(impls) => impls,
+            None => return,
+        };
+        for impl_item in impls {
+            let i = match impl_item.inner {
+                clean::ImplItem(ref i) if i.trait_.is_none() => i,
+                _ => continue,
+            };
+            if i.items.iter().all(|item| item.is_stripped()) {
+                continue;
+            }
+            write!(out, "#### Methods\n\n```rust\n{}\n```\n\n", impl_header(i)).unwrap();
+            self.list(out, "", i.items.iter(), page);
+        }
+        for &(synthetic, title) in &[(false, "Trait Implementations"),
+                                     (true, "Auto Trait Implementations")] {
+            let mut headers = impls.iter().filter_map(|impl_item| match impl_item.inner {
+                clean::ImplItem(ref i) if i.trait_.is_some() && i.synthetic == synthetic => {
+                    Some(impl_header(i))
+                }
+                _ => None,
+            }).collect::<Vec<_>>();
+            if headers.is_empty() {
+                continue;
+            }
+            headers.sort();
+            write!(out, "#### {}\n\n", title).unwrap();
+            for header in headers {
+                list_item(out, &header, "");
+            }
+        }
+    }
+
+    /// Lists the fields, variants or associated items `items` with their
+    /// documentation, under a level 4 heading with the given title.
+    fn list<'b, I>(&self, out: &mut String, title: &str, items: I, page: &[String])
+        where I: Iterator<Item = &'b clean::Item>
+    {
+        let mut items = items.filter(|item| !item.is_stripped()).peekable();
+        if items.peek().is_none() {
+            return;
+        }
+        if !title.is_empty() {
+            write!(out, "#### {}\n\n", title).unwrap();
+        }
+        for item in items {
+            let code = signature(item).unwrap_or(item.name.clone().unwrap_or_default());
+            list_item(out, &code, &self.docs(item, page, 4));
+        }
+    }
+
+    /// Returns the documentation of `item`, with its headings moved down by
+    /// `level` and its intra-doc links pointing to the pages of the crate.
+    fn docs(&self, item: &clean::Item, page: &[String], level: i32) -> String {
+        let md = match item.collapsed_doc_value() {
+            Some(md) => md,
+            None => return String::new(),
+        };
+        let links = item.attrs.links.iter().map(|&(ref s, did, _)| {
+            // Links to items which aren't documented on these pages, such as
+            // primitives or the items of other crates, are left out.
+            let location = did.and_then(|did| self.locations.get(&did));
+            let target = location.map(|&(ref path, ref anchor)| {
+                let mut target = relative(page, path);
+                if let Some(ref anchor) = *anchor {
+                    target.push('#');
+                    target.push_str(anchor);
+                }
+                target
+            });
+            (s.clone(), target.unwrap_or(String::new()))
+        }).collect::<Vec<_>>();
+        markdown::docs(&md, level, &links)
+    }
+}
+
+/// The items of `module` which aren't stripped.
+fn items<'a>(module: &'a clean::Item) -> impl Iterator<Item = &'a clean::Item> + 'a {
+    let items: &[clean::Item] = match module.inner {
+        clean::ModuleItem(ref m) => &m.items,
+        _ => &[],
+    };
+    items.iter().filter(|item| !item.is_stripped())
+}
+
+/// The fields, variants and associated items of `item`.
+fn children(item: &clean::Item) -> &[clean::Item] {
+    match item.inner {
+        clean::StructItem(clean::Struct { ref fields, .. }) |
+        clean::UnionItem(clean::Union { ref fields, .. }) => fields,
+        clean::EnumItem(ref e) => &e.variants,
+        clean::TraitItem(ref t) => &t.items,
+        _ => &[],
+    }
+}
+
+/// The heading of an item on the page of its module, like ``Struct `Foo` ``.
+fn heading(item: &clean::Item) -> String {
+    let kind = match item.inner {
+        clean::FunctionItem(..) | clean::ForeignFunctionItem(..) => "Function",
+        clean::TraitItem(..) => "Trait",
+        clean::StructItem(..) => "Struct",
+        clean::UnionItem(..) => "Union",
+        clean::EnumItem(..) => "Enum",
+        clean::TypedefItem(..) => "Type Definition",
+        clean::MacroItem(..) => "Macro",
+        clean::PrimitiveItem(..) => "Primitive Type",
+        clean::StaticItem(..) | clean::ForeignStaticItem(..) => "Static",
+        clean::ConstantItem(..) => "Constant",
+        clean::ForeignTypeItem => "Foreign Type",
+        _ => "Item",
+    };
+    format!("{} {}", kind, markdown::code_span(item.name.as_ref().unwrap()))
+}
+
+/// The anchor of a heading, as generated for Markdown by most tools: the text
+/// in lower case, with spaces replaced by hyphens and punctuation left out.
+fn anchor(heading: &str) -> String {
+    heading.chars().filter_map(|c| match c {
+        ' ' => Some('-'),
+        c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c.to_ascii_lowercase()),
+        _ => None,
+    }).collect()
+}
+
+/// The path of the page of the module `to` relative to the page of `from`.
+fn relative(from: &[String], to: &[String]) -> String {
+    let common = from.iter().zip(to).take_while(|&(a, b)| a == b).count();
+    let mut path = "../".repeat(from.len() - common);
+    for name in &to[common..] {
+        path.push_str(name);
+        path.push('/');
+    }
+    path.push_str("index.md");
+    path
+}
+
+/// Writes a list item with the code `code`, followed by the Markdown `docs`.
+fn list_item(out: &mut String, code: &str, docs: &str) {
+    if code.contains('\n') {
+        out.push_str("- ```rust\n");
+        for line in code.lines() {
+            write!(out, "  {}\n", line).unwrap();
+        }
+        out.push_str("  ```\n");
+    } else {
+        write!(out, "- {}\n", markdown::code_span(code)).unwrap();
+    }
+    if !docs.is_empty() {
+        out.push('\n');
+        for line in docs.lines() {
+            if !line.is_empty() {
+                out.push_str("  ");
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out.push('\n');
+}
+
+fn impl_header(i: &clean::Impl) -> String {
+    let mut header = format!("{}impl{:#} ", UnsafetySpace(i.unsafety), i.generics);
+    if let Some(ref trait_) = i.trait_ {
+        if i.polarity == Some(clean::ImplPolarity::Negative) {
+            header.push('!');
+        }
+        write!(header, "{:#} for ", trait_).unwrap();
+    }
+    write!(header, "{:#}{:#}",
+           i.for_, WhereClause { gens: &i.generics, indent: 0, end_newline: false }).unwrap();
+    header
+}
+
+fn function(item: &clean::Item,
+            decl: &clean::FnDecl,
+            generics: &clean::Generics,
+            unsafety: hir::Unsafety,
+            constness: hir::Constness,
+            abi: ::rustc_target::spec::abi::Abi) -> String {
+    let head = format!("{:#}{:#}{:#}{:#}fn {}{:#}",
+                       VisSpace(&item.visibility),
+                       ConstnessSpace(constness),
+                       UnsafetySpace(unsafety),
+                       AbiSpace(abi),
+                       item.name.as_ref().unwrap(),
+                       generics);
+    format!("{}{:#}{:#}",
+            head,
+            Method { decl, name_len: head.len(), indent: 0 },
+            WhereClause { gens: generics, indent: 0, end_newline: false })
+}
+
+/// The declaration of `item`, as written in Rust but without the bodies of
+/// functions and the fields of types.
+fn signature(item: &clean::Item) -> Option<String> {
+    let vis = VisSpace(&item.visibility);
+    let name = item.name.as_ref().map(|s| &**s).unwrap_or("");
+    let signature = match item.inner {
+        clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+            function(item, &f.decl, &f.generics, f.unsafety, f.constness, f.abi)
+        }
+        clean::MethodItem(ref m) => {
+            function(item, &m.decl, &m.generics, m.unsafety, m.constness, m.abi)
+        }
+        clean::TyMethodItem(ref m) => {
+            function(item, &m.decl, &m.generics, m.unsafety, hir::Constness::NotConst, m.abi)
+        }
+        clean::StructItem(clean::Struct { ref generics, .. }) => {
+            format!("{:#}struct {}{:#}", vis, name, generics)
+        }
+        clean::UnionItem(clean::Union { ref generics, .. }) => {
+            format!("{:#}union {}{:#}", vis, name, generics)
+        }
+        clean::EnumItem(ref e) => format!("{:#}enum {}{:#}", vis, name, e.generics),
+        clean::TraitItem(ref t) => {
+            let mut signature = format!("{:#}{:#}{}trait {}{:#}",
+                                        vis, UnsafetySpace(t.unsafety),
+                                        if t.is_auto { "auto " } else { "" },
+                                        name, t.generics);
+            if !t.bounds.is_empty() {
+                write!(signature, ": {:#}", TyParamBounds(&t.bounds)).unwrap();
+            }
+            signature
+        }
+        clean::TypedefItem(ref t, _) => {
+            format!("{:#}type {}{:#} = {:#};", vis, name, t.generics, t.type_)
+        }
+        clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => {
+            format!("{:#}static {}{}: {:#}", vis,
+                    if s.mutability == clean::Mutable { "mut " } else { "" }, name, s.type_)
+        }
+        clean::ConstantItem(ref c) => format!("{:#}const {}: {:#}", vis, name, c.type_),
+        clean::ForeignTypeItem => format!("{:#}type {};", vis, name),
+        clean::MacroItem(ref m) => m.source.trim().to_string(),
+        clean::StructFieldItem(ref ty) => format!("{:#}{}: {:#}", vis, name, ty),
+        clean::VariantItem(ref v) => match v.kind {
+            clean::VariantKind::CLike => name.to_string(),
+            clean::VariantKind::Tuple(ref types) => {
+                let types = types.iter().map(|ty| format!("{:#}", ty)).collect::<Vec<_>>();
+                format!("{}({})", name, types.join(", "))
+            }
+            clean::VariantKind::Struct(ref s) => {
+                let fields = s.fields.iter().filter_map(|field| match field.inner {
+                    clean::StructFieldItem(ref ty) => {
+                        Some(format!("{}: {:#}", field.name.as_ref().unwrap(), ty))
+                    }
+                    _ => None,
+                }).collect::<Vec<_>>();
+                format!("{} {{ {} }}", name, fields.join(", "))
+            }
+        },
+        clean::AssociatedConstItem(ref ty, ref default) => match *default {
+            Some(ref default) => format!("const {}: {:#} = {};", name, ty, default),
+            None => format!("const {}: {:#};", name, ty),
+        },
+        clean::AssociatedTypeItem(ref bounds, ref default) => {
+            let mut signature = format!("type {}", name);
+            if !bounds.is_empty() {
+                write!(signature, ": {:#}", TyParamBounds(bounds)).unwrap();
+            }
+            if let Some(ref default) = *default {
+                write!(signature, " = {:#}", default).unwrap();
+            }
+            signature.push(';');
+            signature
+        }
+        clean::PrimitiveItem(..) | clean::ModuleItem(..) | clean::ExternCrateItem(..) |
+        clean::ImportItem(..) | clean::ImplItem(..) | clean::StrippedItem(..) => return None,
+    };
+    Some(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{anchor, relative};
+
+    #[test]
+    fn test_anchor() {
+        assert_eq!(anchor("Struct `Foo`"), "struct-foo");
+        assert_eq!(anchor("Type Definition `my_type`"), "type-definition-my_type");
+    }
+
+    #[test]
+    fn test_relative() {
+        let krate = vec!["foo".to_string()];
+        let module = vec!["foo".to_string(), "bar".to_string()];
+        assert_eq!(relative(&krate, &krate), "index.md");
+        assert_eq!(relative(&krate, &module), "bar/index.md");
+        assert_eq!(relative(&module, &krate), "../index.md");
+        assert_eq!(relative(&module, &["baz".to_string()]), "../../baz/index.md");
+    }
+}
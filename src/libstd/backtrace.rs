@@ -0,0 +1,426 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Support for capturing a stack backtrace of an OS thread
+//!
+//! This module contains the support necessary to capture a stack backtrace of
+//! a running OS thread from the OS thread itself. The `Backtrace` type
+//! supports capturing a stack trace via the `Backtrace::capture` and
+//! `Backtrace::force_capture` functions.
+//!
+//! A backtrace is typically quite handy to attach to errors (e.g. types
+//! implementing `std::error::Error`) to get a causal chain of where an error
+//! was generated.
+//!
+//! ## Accuracy
+//!
+//! Backtraces are attempted to be as accurate as possible, but no guarantees
+//! are provided about the exact accuracy of a backtrace. Instruction pointers,
+//! symbol names, filenames, line numbers, etc, may all be incorrect when
+//! reported. Accuracy is attempted on a best-effort basis, however, and bugs
+//! are always welcome to indicate areas of improvement!
+//!
+//! For most platforms a backtrace with a filename/line number requires that
+//! programs be compiled with debug information. Without debug information
+//! filenames/line numbers will not be reported.
+//!
+//! ## Platform support
+//!
+//! Not all platforms that libstd compiles for support capturing backtraces.
+//! Some platforms simply do nothing when capturing a backtrace. To check
+//! whether the platform supports capturing backtraces you can consult the
+//! `BacktraceStatus` enum as a result of `Backtrace::status`.
+//!
+//! Like above with accuracy platform support is done on a best effort basis.
+//! Sometimes libraries may not be available at runtime or something may go
+//! wrong which would cause a backtrace to not be captured. Please feel free to
+//! report issues with platforms where a backtrace cannot be captured though!
+//!
+//! ## Environment Variables
+//!
+//! The `Backtrace::capture` function may not actually capture a backtrace by
+//! default. Its behavior is governed by two environment variables:
+//!
+//! * `RUST_LIB_BACKTRACE` - if this is set to `0` then `Backtrace::capture`
+//!   will never capture a backtrace. Any other value this is set to will
+//!   enable `Backtrace::capture`.
+//!
+//! * `RUST_BACKTRACE` - if `RUST_LIB_BACKTRACE` is not set, then this variable
+//!   is consulted with the same rules of `RUST_LIB_BACKTRACE`.
+//!
+//! * If neither of the above env vars are set, then `Backtrace::capture` will
+//!   be disabled.
+//!
+//! Capturing a backtrace can be a quite expensive runtime operation, so the
+//! environment variables allow either forcibly disabling this runtime
+//! performance hit or allow selectively enabling it in some programs.
+//!
+//! Note that the `Backtrace::force_capture` function can be used to ignore
+//! these environment variables. Also note that the state of environment
+//! variables is cached once the first backtrace is created, so altering
+//! `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE` at runtime may not actually change
+//! how backtraces are captured.
+
+#![unstable(feature = "backtrace", issue = "0")]
+
+use env;
+use fmt;
+use sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "backtrace")]
+use io::prelude::*;
+#[cfg(feature = "backtrace")]
+use io;
+#[cfg(feature = "backtrace")]
+use ptr;
+#[cfg(feature = "backtrace")]
+use sync::Mutex;
+#[cfg(feature = "backtrace")]
+use sys_common::backtrace::{self, Frame, PrintFormat, MAX_NB_FRAMES};
+
+/// A captured OS thread stack backtrace.
+///
+/// This type represents a stack backtrace for an OS thread captured at a
+/// previous point in time. In some instances the `Backtrace` type may
+/// internally be empty due to configuration. For more information see
+/// `Backtrace::capture`.
+///
+/// Capturing only walks the stack; symbol names, filenames and line numbers
+/// are resolved the first time the backtrace is formatted.
+pub struct Backtrace {
+    inner: Inner,
+}
+
+/// The current status of a backtrace, indicating whether it was captured or
+/// whether it is empty for some other reason.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BacktraceStatus {
+    /// Capturing a backtrace is not supported, likely because it's not
+    /// implemented for the current platform.
+    Unsupported,
+    /// Capturing a backtrace has been disabled through either the
+    /// `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE` environment variables.
+    Disabled,
+    /// A backtrace has been captured and the `Backtrace` should print
+    /// reasonable information when rendered.
+    Captured,
+    #[doc(hidden)]
+    __Nonexhaustive,
+}
+
+enum Inner {
+    Unsupported,
+    Disabled,
+    #[cfg(feature = "backtrace")]
+    Captured(Mutex<Capture>),
+}
+
+#[cfg(feature = "backtrace")]
+struct Capture {
+    actual_start: usize,
+    frames: Vec<BacktraceFrame>,
+    resolved: bool,
+}
+
+#[cfg(feature = "backtrace")]
+struct BacktraceFrame {
+    frame: Frame,
+    name: Option<String>,
+    filelines: Vec<(Vec<u8>, u32)>,
+}
+
+// Frames are plain code addresses.
+#[cfg(feature = "backtrace")]
+unsafe impl Send for Capture {}
+
+fn _assert_send_sync() {
+    fn _assert<T: Send + Sync>() {}
+    _assert::<Backtrace>();
+}
+
+impl Backtrace {
+    /// Returns whether backtrace captures are enabled through environment
+    /// variables.
+    fn enabled() -> bool {
+        // Cache the result of reading the environment variables to make
+        // backtrace captures speedy, because otherwise reading environment
+        // variables every time can be somewhat slow.
+        static ENABLED: AtomicUsize = AtomicUsize::new(0);
+        match ENABLED.load(Ordering::SeqCst) {
+            0 => {}
+            1 => return false,
+            _ => return true,
+        }
+        let enabled = match env::var_os("RUST_LIB_BACKTRACE") {
+            Some(s) => &s != "0",
+            None => match env::var_os("RUST_BACKTRACE") {
+                Some(s) => &s != "0",
+                None => false,
+            },
+        };
+        ENABLED.store(enabled as usize + 1, Ordering::SeqCst);
+        enabled
+    }
+
+    /// Capture a stack backtrace of the current thread.
+    ///
+    /// This function will capture a stack backtrace of the current OS thread
+    /// of execution, returning a `Backtrace` type which can be later used to
+    /// display the stack trace or inspect it.
+    ///
+    /// This function is governed by the `RUST_LIB_BACKTRACE` and
+    /// `RUST_BACKTRACE` environment variables described in the [module
+    /// documentation](index.html#environment-variables); when they leave
+    /// captures disabled this returns a `Backtrace` whose status is
+    /// `Disabled`. Use `force_capture` to capture regardless.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(backtrace)]
+    ///
+    /// use std::backtrace::Backtrace;
+    ///
+    /// let backtrace = Backtrace::capture();
+    /// println!("{}", backtrace);
+    /// ```
+    #[inline(never)] // want to make sure there's a frame here to remove
+    pub fn capture() -> Backtrace {
+        if !Backtrace::enabled() {
+            return Backtrace { inner: Inner::Disabled };
+        }
+        Backtrace::create(Backtrace::capture as usize)
+    }
+
+    /// Forcibly captures a full backtrace, regardless of environment variable
+    /// configuration.
+    ///
+    /// This function behaves the same as `capture` except that it ignores the
+    /// values of the `RUST_BACKTRACE` and `RUST_LIB_BACKTRACE` environment
+    /// variables, always capturing a backtrace.
+    ///
+    /// Note that capturing a backtrace can be an expensive operation on some
+    /// platforms, so this should be used with caution in performance-sensitive
+    /// parts of code.
+    #[inline(never)] // want to make sure there's a frame here to remove
+    pub fn force_capture() -> Backtrace {
+        Backtrace::create(Backtrace::force_capture as usize)
+    }
+
+    // Capture a backtrace which starts just before the function addressed by
+    // `ip`.
+    #[cfg(feature = "backtrace")]
+    fn create(ip: usize) -> Backtrace {
+        let mut raw = vec![Frame {
+            exact_position: ptr::null(),
+            symbol_addr: ptr::null(),
+            inline_context: 0,
+        }; MAX_NB_FRAMES];
+        // Walking the stack sets up state that isn't thread-safe on some
+        // platforms, like dbghelp's on Windows, which is torn down again when
+        // the context is dropped. Neither may happen without the lock, and the
+        // context isn't kept, so that other captures can set up their own.
+        let nb_frames = {
+            let _lock = backtrace::lock();
+            match backtrace::unwind_backtrace(&mut raw) {
+                Ok((nb_frames, _context)) => nb_frames,
+                Err(_) => return Backtrace { inner: Inner::Unsupported },
+            }
+        };
+
+        // If no frames came out assume that this is an unsupported platform
+        // since `unwind_backtrace` doesn't provide a way of learning this
+        // right now, and this should be a good enough approximation.
+        if nb_frames == 0 {
+            return Backtrace { inner: Inner::Unsupported };
+        }
+
+        let frames = raw[..nb_frames].iter().map(|&frame| {
+            BacktraceFrame {
+                frame,
+                name: None,
+                filelines: Vec::new(),
+            }
+        }).collect::<Vec<_>>();
+
+        // Skip the frames of the capture itself, which end with the one for
+        // `capture` or `force_capture`. Not every platform reports the
+        // enclosing function of a frame, in which case nothing is skipped.
+        let actual_start = frames.iter()
+            .position(|f| f.frame.symbol_addr as usize == ip)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        Backtrace {
+            inner: Inner::Captured(Mutex::new(Capture {
+                actual_start,
+                frames,
+                resolved: false,
+            })),
+        }
+    }
+
+    #[cfg(not(feature = "backtrace"))]
+    fn create(_ip: usize) -> Backtrace {
+        Backtrace { inner: Inner::Unsupported }
+    }
+
+    /// Returns the status of this backtrace, indicating whether this
+    /// backtrace request was unsupported, disabled, or a stack trace was
+    /// actually captured.
+    pub fn status(&self) -> BacktraceStatus {
+        match self.inner {
+            Inner::Unsupported => BacktraceStatus::Unsupported,
+            Inner::Disabled => BacktraceStatus::Disabled,
+            #[cfg(feature = "backtrace")]
+            Inner::Captured(_) => BacktraceStatus::Captured,
+        }
+    }
+}
+
+impl fmt::Display for Backtrace {
+    /// Formats the backtrace like the panic handler does.
+    ///
+    /// The alternate flag (`{:#}`) prints every frame along with its address,
+    /// like `RUST_BACKTRACE=full` does for panics.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.inner {
+            Inner::Unsupported => fmt.write_str("unsupported backtrace"),
+            Inner::Disabled => fmt.write_str("disabled backtrace"),
+            #[cfg(feature = "backtrace")]
+            Inner::Captured(ref capture) => {
+                let format = if fmt.alternate() {
+                    PrintFormat::Full
+                } else {
+                    PrintFormat::Short
+                };
+                let mut capture = capture.lock().unwrap_or_else(|e| e.into_inner());
+                capture.resolve();
+                let mut buf = Vec::new();
+                capture.print(&mut buf, format).map_err(|_| fmt::Error)?;
+                fmt.write_str(&String::from_utf8_lossy(&buf))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Backtrace {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.inner {
+            Inner::Unsupported => fmt.write_str("<unsupported>"),
+            Inner::Disabled => fmt.write_str("<disabled>"),
+            #[cfg(feature = "backtrace")]
+            Inner::Captured(ref capture) => {
+                let mut capture = capture.lock().unwrap_or_else(|e| e.into_inner());
+                capture.resolve();
+                fmt.debug_list()
+                    .entries(capture.frames[capture.actual_start..].iter())
+                    .finish()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "backtrace")]
+impl Capture {
+    fn resolve(&mut self) {
+        if self.resolved {
+            return
+        }
+
+        // A fresh context, which goes away again before the lock does.
+        let _lock = backtrace::lock();
+        let context = match backtrace::symbol_context() {
+            Ok(context) => context,
+            Err(_) => return,
+        };
+        self.resolved = true;
+        for frame in self.frames.iter_mut() {
+            let name = &mut frame.name;
+            let filelines = &mut frame.filelines;
+            let _ = backtrace::resolve_symname(frame.frame, |symname| {
+                *name = symname.map(|s| s.to_string());
+                Ok(())
+            }, &context);
+            let _ = backtrace::foreach_symbol_fileline(frame.frame, |file, line| {
+                filelines.push((file.to_vec(), line));
+                Ok(())
+            }, &context);
+        }
+    }
+
+    fn print(&self, w: &mut Write, format: PrintFormat) -> io::Result<()> {
+        let mut frames = &self.frames[self.actual_start..];
+
+        // Like the panic handler, cut the short format off at the frame that
+        // entered the thread's main function.
+        if format == PrintFormat::Short {
+            let end = frames.iter().position(|frame| {
+                frame.name.as_ref().map_or(false, |name| {
+                    name.contains("__rust_begin_short_backtrace")
+                })
+            });
+            if let Some(end) = end {
+                if end > 0 {
+                    frames = &frames[..end];
+                }
+            }
+        }
+
+        writeln!(w, "stack backtrace:")?;
+        for (index, frame) in frames.iter().enumerate() {
+            let name = frame.name.as_ref().map(|s| &s[..]);
+            backtrace::output(w, index, frame.frame, name, format)?;
+            for &(ref file, line) in &frame.filelines {
+                backtrace::output_fileline(w, file, line, format)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "backtrace")]
+impl fmt::Debug for BacktraceFrame {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut dbg = fmt.debug_struct("BacktraceFrame");
+        match self.name {
+            Some(ref name) => {
+                let mut demangled = Vec::new();
+                let _ = backtrace::demangle(&mut demangled, name, PrintFormat::Short);
+                dbg.field("fn", &String::from_utf8_lossy(&demangled));
+            }
+            None => {
+                dbg.field("fn", &"<unknown>");
+            }
+        }
+        if let Some(&(ref file, line)) = self.filelines.first() {
+            dbg.field("file", &String::from_utf8_lossy(file));
+            dbg.field("line", &line);
+        }
+        dbg.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backtrace, BacktraceStatus};
+
+    #[test]
+    fn force_capture_is_not_disabled() {
+        let bt = Backtrace::force_capture();
+        assert!(bt.status() != BacktraceStatus::Disabled);
+        let printed = bt.to_string();
+        match bt.status() {
+            BacktraceStatus::Captured => assert!(printed.starts_with("stack backtrace:")),
+            _ => assert_eq!(printed, "unsupported backtrace"),
+        }
+        // Formatting again reuses the resolved frames.
+        assert_eq!(bt.to_string(), printed);
+    }
+}
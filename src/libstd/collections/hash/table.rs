@@ -1,4 +1,4 @@
-// Copyright 2014-2015 The Rust Project Developers. See the COPYRIGHT
+// Copyright 2014-2018 The Rust Project Developers. See the COPYRIGHT
 // file at the top-level directory of this distribution and at
 // http://rust-lang.org/COPYRIGHT.
 //
@@ -9,895 +9,1351 @@
 // except according to those terms.
 
 use alloc::{Global, Alloc, Layout, LayoutErr, CollectionAllocErr, oom};
-use hash::{BuildHasher, Hash, Hasher};
+use cmp;
+use isize;
 use marker;
-use mem::{size_of, needs_drop};
-use mem;
-use ops::{Deref, DerefMut};
-use ptr::{self, Unique, NonNull};
+use mem::{self, needs_drop, ManuallyDrop};
+use ptr::{self, NonNull};
 
-use self::BucketState::*;
-
-/// Integer type used for stored hash values.
-///
-/// No more than bit_width(usize) bits are needed to select a bucket.
-///
-/// The most significant bit is ours to use for tagging `SafeHash`.
-///
-/// (Even if we could have usize::MAX bytes allocated for buckets,
-/// each bucket stores at least a `HashUint`, so there can be no more than
-/// usize::MAX / size_of(usize) buckets.)
-type HashUint = usize;
-
-const EMPTY_BUCKET: HashUint = 0;
-const EMPTY: usize = 1;
+use self::Fallibility::*;
+use self::imp::{BitMaskWord, Group, BITMASK_MASK, BITMASK_STRIDE, GROUP_WIDTH};
 
-/// Special `Unique<HashUint>` that uses the lower bit of the pointer
-/// to expose a boolean tag.
-/// Note: when the pointer is initialized to EMPTY `.ptr()` will return
-/// null and the tag functions shouldn't be used.
-struct TaggedHashUintPtr(Unique<HashUint>);
+/// Control byte value for an empty bucket.
+const EMPTY: u8 = 0b1111_1111;
 
-impl TaggedHashUintPtr {
-    #[inline]
-    unsafe fn new(ptr: *mut HashUint) -> Self {
-        debug_assert!(ptr as usize & 1 == 0 || ptr as usize == EMPTY as usize);
-        TaggedHashUintPtr(Unique::new_unchecked(ptr))
-    }
-
-    #[inline]
-    fn set_tag(&mut self, value: bool) {
-        let mut usize_ptr = self.0.as_ptr() as usize;
-        unsafe {
-            if value {
-                usize_ptr |= 1;
-            } else {
-                usize_ptr &= !1;
-            }
-            self.0 = Unique::new_unchecked(usize_ptr as *mut HashUint)
-        }
-    }
+/// Control byte value for a deleted bucket.
+const DELETED: u8 = 0b1000_0000;
 
-    #[inline]
-    fn tag(&self) -> bool {
-        (self.0.as_ptr() as usize) & 1 == 1
-    }
+/// Checks whether a control byte represents a full bucket (top bit is clear).
+#[inline]
+fn is_full(ctrl: u8) -> bool {
+    ctrl & 0x80 == 0
+}
 
-    #[inline]
-    fn ptr(&self) -> *mut HashUint {
-        (self.0.as_ptr() as usize & !1) as *mut HashUint
-    }
+/// Checks whether a special control value is EMPTY (just check 1 bit).
+#[inline]
+fn special_is_empty(ctrl: u8) -> bool {
+    debug_assert!(!is_full(ctrl));
+    ctrl & 0x01 != 0
 }
 
-/// The raw hashtable, providing safe-ish access to the unzipped and highly
-/// optimized arrays of hashes, and key-value pairs.
-///
-/// This design is a lot faster than the naive
-/// `Vec<Option<(u64, K, V)>>`, because we don't pay for the overhead of an
-/// option on every element, and we get a generally more cache-aware design.
-///
-/// Essential invariants of this structure:
-///
-///   - if `t.hashes[i] == EMPTY_BUCKET`, then `Bucket::at_index(&t, i).raw`
-///     points to 'undefined' contents. Don't read from it. This invariant is
-///     enforced outside this module with the `EmptyBucket`, `FullBucket`,
-///     and `SafeHash` types.
-///
-///   - An `EmptyBucket` is only constructed at an index with
-///     a hash of EMPTY_BUCKET.
-///
-///   - A `FullBucket` is only constructed at an index with a
-///     non-EMPTY_BUCKET hash.
-///
-///   - A `SafeHash` is only constructed for non-`EMPTY_BUCKET` hash. We get
-///     around hashes of zero by changing them to 0x8000_0000_0000_0000,
-///     which will likely map to the same bucket, while not being confused
-///     with "empty".
-///
-///   - Both "arrays represented by pointers" are the same length:
-///     `capacity`. This is set at creation and never changes. The arrays
-///     are unzipped and are more cache aware (scanning through 8 hashes
-///     brings in at most 2 cache lines, since they're all right beside each
-///     other). This layout may waste space in padding such as in a map from
-///     u64 to u8, but is a more cache conscious layout as the key-value pairs
-///     are only very shortly probed and the desired value will be in the same
-///     or next cache line.
-///
-/// You can kind of think of this module/data structure as a safe wrapper
-/// around just the "table" part of the hashtable. It enforces some
-/// invariants at the type level and employs some performance trickery,
-/// but in general is just a tricked out `Vec<Option<(u64, K, V)>>`.
-///
-/// The hashtable also exposes a special boolean tag. The tag defaults to false
-/// when the RawTable is created and is accessible with the `tag` and `set_tag`
-/// functions.
-pub struct RawTable<K, V> {
-    capacity_mask: usize,
-    size: usize,
-    hashes: TaggedHashUintPtr,
+/// Primary hash function, used to select the initial bucket to probe from.
+#[inline]
+fn h1(hash: u64) -> usize {
+    hash as usize
+}
 
-    // Because K/V do not appear directly in any of the types in the struct,
-    // inform rustc that in fact instances of K and V are reachable from here.
-    marker: marker::PhantomData<(K, V)>,
+/// Secondary hash function, saved in the low 7 bits of the control byte.
+#[inline]
+fn h2(hash: u64) -> u8 {
+    // Grab the top 7 bits of the hash. Only the low `usize` bits of the hash
+    // are guaranteed to be well mixed on 32-bit platforms, so take the top
+    // bits of those rather than of the full 64-bit value.
+    let hash_len = mem::size_of::<usize>();
+    let top7 = hash >> (hash_len * 8 - 7);
+    (top7 & 0x7f) as u8
 }
 
-// An unsafe view of a RawTable bucket
-// Valid indexes are within [0..table_capacity)
-pub struct RawBucket<K, V> {
-    hash_start: *mut HashUint,
-    // We use *const to ensure covariance with respect to K and V
-    pair_start: *const (K, V),
-    idx: usize,
-    _marker: marker::PhantomData<(K, V)>,
+/// Returns the number of buckets needed to hold the given number of items,
+/// taking the maximum load factor into account.
+///
+/// Returns `None` if an overflow occurs.
+#[inline]
+fn capacity_to_buckets(cap: usize) -> Option<usize> {
+    let adjusted_cap = if cap < 8 {
+        // Need at least 1 free bucket on small tables
+        cap + 1
+    } else {
+        // Otherwise require 1/8 buckets to be empty (87.5% load)
+        cap.checked_mul(8)? / 7
+    };
+
+    // Any overflows will have been caught by the checked_mul.
+    adjusted_cap.checked_next_power_of_two()
 }
 
-impl<K, V> Copy for RawBucket<K, V> {}
-impl<K, V> Clone for RawBucket<K, V> {
-    fn clone(&self) -> RawBucket<K, V> {
-        *self
+/// Returns the maximum effective capacity for the given bucket mask, taking
+/// the maximum load factor into account.
+#[inline]
+fn bucket_mask_to_capacity(bucket_mask: usize) -> usize {
+    if bucket_mask < 8 {
+        bucket_mask
+    } else {
+        ((bucket_mask + 1) / 8) * 7
     }
 }
 
-pub struct Bucket<K, V, M> {
-    raw: RawBucket<K, V>,
-    table: M,
-}
+// SSE2 lets us compare a whole group of 16 control bytes against a value with
+// a handful of instructions.
+#[cfg(all(not(stage0),
+          target_feature = "sse2",
+          any(target_arch = "x86", target_arch = "x86_64")))]
+mod imp {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64 as x86;
+
+    use super::{BitMask, EMPTY};
+
+    pub type BitMaskWord = u16;
+    pub const BITMASK_STRIDE: usize = 1;
+    pub const BITMASK_MASK: BitMaskWord = 0xffff;
+
+    /// Number of control bytes in a `Group`.
+    pub const GROUP_WIDTH: usize = 16;
+
+    /// A group of control bytes which can be scanned for a particular value
+    /// in parallel, backed by a single SSE2 register.
+    #[derive(Copy, Clone)]
+    pub struct Group(x86::__m128i);
+
+    impl Group {
+        /// Loads a group of bytes starting at the given address.
+        #[inline]
+        pub unsafe fn load(ptr: *const u8) -> Group {
+            Group(x86::_mm_loadu_si128(ptr as *const _))
+        }
 
-impl<K, V, M: Copy> Copy for Bucket<K, V, M> {}
-impl<K, V, M: Copy> Clone for Bucket<K, V, M> {
-    fn clone(&self) -> Bucket<K, V, M> {
-        *self
+        /// Loads a group of bytes starting at the given address, which must
+        /// be aligned to `GROUP_WIDTH`.
+        #[inline]
+        pub unsafe fn load_aligned(ptr: *const u8) -> Group {
+            debug_assert_eq!(ptr as usize & (GROUP_WIDTH - 1), 0);
+            Group(x86::_mm_load_si128(ptr as *const _))
+        }
+
+        /// Stores the group of bytes to the given address, which must be
+        /// aligned to `GROUP_WIDTH`.
+        #[inline]
+        pub unsafe fn store_aligned(self, ptr: *mut u8) {
+            debug_assert_eq!(ptr as usize & (GROUP_WIDTH - 1), 0);
+            x86::_mm_store_si128(ptr as *mut _, self.0);
+        }
+
+        /// Returns a `BitMask` indicating all bytes in the group which have
+        /// the given value.
+        #[inline]
+        pub fn match_byte(self, byte: u8) -> BitMask {
+            unsafe {
+                let cmp = x86::_mm_cmpeq_epi8(self.0, x86::_mm_set1_epi8(byte as i8));
+                BitMask(x86::_mm_movemask_epi8(cmp) as u16)
+            }
+        }
+
+        /// Returns a `BitMask` indicating all bytes in the group which are
+        /// `EMPTY`.
+        #[inline]
+        pub fn match_empty(self) -> BitMask {
+            self.match_byte(EMPTY)
+        }
+
+        /// Returns a `BitMask` indicating all bytes in the group which are
+        /// `EMPTY` or `DELETED`.
+        #[inline]
+        pub fn match_empty_or_deleted(self) -> BitMask {
+            // A byte is EMPTY or DELETED iff the high bit is set, which is
+            // exactly what movemask extracts.
+            unsafe { BitMask(x86::_mm_movemask_epi8(self.0) as u16) }
+        }
+
+        /// Returns a `BitMask` indicating all bytes in the group which are
+        /// full.
+        #[inline]
+        pub fn match_full(self) -> BitMask {
+            self.match_empty_or_deleted().invert()
+        }
+
+        /// Performs the following transformation on all bytes in the group:
+        /// - `EMPTY => EMPTY`
+        /// - `DELETED => EMPTY`
+        /// - `FULL => DELETED`
+        #[inline]
+        pub fn convert_special_to_empty_and_full_to_deleted(self) -> Group {
+            // Map high_bit = 1 (EMPTY or DELETED) to 1111_1111
+            // and high_bit = 0 (FULL) to 1000_0000
+            //
+            // Here's this logic expanded to concrete values:
+            //   let special = 0 > byte = 1111_1111 (true) or 0000_0000 (false)
+            //   1111_1111 | 1000_0000 = 1111_1111
+            //   0000_0000 | 1000_0000 = 1000_0000
+            unsafe {
+                let zero = x86::_mm_setzero_si128();
+                let special = x86::_mm_cmpgt_epi8(zero, self.0);
+                Group(x86::_mm_or_si128(special, x86::_mm_set1_epi8(0x80u8 as i8)))
+            }
+        }
     }
 }
 
-pub struct EmptyBucket<K, V, M> {
-    raw: RawBucket<K, V>,
-    table: M,
-}
+// Everywhere else a group is a `u64` and the comparisons are done with
+// ordinary integer bit twiddling, processing 8 control bytes at once.
+#[cfg(not(all(not(stage0),
+              target_feature = "sse2",
+              any(target_arch = "x86", target_arch = "x86_64"))))]
+mod imp {
+    use ptr;
 
-pub struct FullBucket<K, V, M> {
-    raw: RawBucket<K, V>,
-    table: M,
-}
+    use super::{portable, BitMask};
 
-pub type FullBucketMut<'table, K, V> = FullBucket<K, V, &'table mut RawTable<K, V>>;
+    pub type BitMaskWord = u64;
+    pub const BITMASK_STRIDE: usize = 8;
+    pub const BITMASK_MASK: BitMaskWord = 0x8080_8080_8080_8080;
 
-pub enum BucketState<K, V, M> {
-    Empty(EmptyBucket<K, V, M>),
-    Full(FullBucket<K, V, M>),
-}
+    /// Number of control bytes in a `Group`.
+    pub const GROUP_WIDTH: usize = 8;
 
-// A GapThenFull encapsulates the state of two consecutive buckets at once.
-// The first bucket, called the gap, is known to be empty.
-// The second bucket is full.
-pub struct GapThenFull<K, V, M> {
-    gap: EmptyBucket<K, V, ()>,
-    full: FullBucket<K, V, M>,
-}
+    /// A group of control bytes which can be scanned for a particular value
+    /// in parallel, packed into a single `u64`.
+    ///
+    /// The bytes are kept in memory order, so the bit masks are converted to
+    /// little-endian to make bit positions match bucket order on all targets.
+    #[derive(Copy, Clone)]
+    pub struct Group(u64);
+
+    impl Group {
+        /// Loads a group of bytes starting at the given address.
+        #[inline]
+        pub unsafe fn load(ptr: *const u8) -> Group {
+            Group(ptr::read_unaligned(ptr as *const u64))
+        }
 
-/// A hash that is not zero, since we use a hash of zero to represent empty
-/// buckets.
-#[derive(PartialEq, Copy, Clone)]
-pub struct SafeHash {
-    hash: HashUint,
-}
+        /// Loads a group of bytes starting at the given address, which must
+        /// be aligned to `GROUP_WIDTH`.
+        #[inline]
+        pub unsafe fn load_aligned(ptr: *const u8) -> Group {
+            debug_assert_eq!(ptr as usize & (GROUP_WIDTH - 1), 0);
+            Group(ptr::read(ptr as *const u64))
+        }
 
-impl SafeHash {
-    /// Peek at the hash value, which is guaranteed to be non-zero.
-    #[inline(always)]
-    pub fn inspect(&self) -> HashUint {
-        self.hash
-    }
+        /// Stores the group of bytes to the given address, which must be
+        /// aligned to `GROUP_WIDTH`.
+        #[inline]
+        pub unsafe fn store_aligned(self, ptr: *mut u8) {
+            debug_assert_eq!(ptr as usize & (GROUP_WIDTH - 1), 0);
+            ptr::write(ptr as *mut u64, self.0);
+        }
 
-    #[inline(always)]
-    pub fn new(hash: u64) -> Self {
-        // We need to avoid 0 in order to prevent collisions with
-        // EMPTY_HASH. We can maintain our precious uniform distribution
-        // of initial indexes by unconditionally setting the MSB,
-        // effectively reducing the hashes by one bit.
-        //
-        // Truncate hash to fit in `HashUint`.
-        let hash_bits = size_of::<HashUint>() * 8;
-        SafeHash { hash: (1 << (hash_bits - 1)) | (hash as HashUint) }
+        /// Returns a `BitMask` indicating all bytes in the group which
+        /// *may* have the given value.
+        ///
+        /// This can report false positives, see `portable::match_byte`.
+        /// Every match is confirmed against the key anyway, so this does not
+        /// affect correctness.
+        #[inline]
+        pub fn match_byte(self, byte: u8) -> BitMask {
+            BitMask(portable::match_byte(self.0, byte).to_le())
+        }
+
+        /// Returns a `BitMask` indicating all bytes in the group which are
+        /// `EMPTY`.
+        #[inline]
+        pub fn match_empty(self) -> BitMask {
+            BitMask(portable::match_empty(self.0).to_le())
+        }
+
+        /// Returns a `BitMask` indicating all bytes in the group which are
+        /// `EMPTY` or `DELETED`.
+        #[inline]
+        pub fn match_empty_or_deleted(self) -> BitMask {
+            BitMask(portable::match_empty_or_deleted(self.0).to_le())
+        }
+
+        /// Returns a `BitMask` indicating all bytes in the group which are
+        /// full.
+        #[inline]
+        pub fn match_full(self) -> BitMask {
+            self.match_empty_or_deleted().invert()
+        }
+
+        /// Performs the following transformation on all bytes in the group:
+        /// - `EMPTY => EMPTY`
+        /// - `DELETED => EMPTY`
+        /// - `FULL => DELETED`
+        #[inline]
+        pub fn convert_special_to_empty_and_full_to_deleted(self) -> Group {
+            Group(portable::convert_special_to_empty_and_full_to_deleted(self.0))
+        }
     }
 }
 
-/// We need to remove hashes of 0. That's reserved for empty buckets.
-/// This function wraps up `hash_keyed` to be the only way outside this
-/// module to generate a SafeHash.
-pub fn make_hash<T: ?Sized, S>(hash_state: &S, t: &T) -> SafeHash
-    where T: Hash,
-          S: BuildHasher
-{
-    let mut state = hash_state.build_hasher();
-    t.hash(&mut state);
-    SafeHash::new(state.finish())
-}
+/// The bit twiddling behind the portable `Group`, on groups of 8 control
+/// bytes packed into a `u64`. The matching functions set the top bit of every
+/// matching byte. It is kept apart from `Group` so that it is tested on SSE2
+/// targets as well.
+#[cfg(any(test,
+          not(all(not(stage0),
+                  target_feature = "sse2",
+                  any(target_arch = "x86", target_arch = "x86_64")))))]
+mod portable {
+    /// Helper function to replicate a byte across a `u64`.
+    #[inline]
+    fn repeat(byte: u8) -> u64 {
+        (byte as u64) * 0x0101_0101_0101_0101
+    }
 
-// `replace` casts a `*HashUint` to a `*SafeHash`. Since we statically
-// ensure that a `FullBucket` points to an index with a non-zero hash,
-// and a `SafeHash` is just a `HashUint` with a different name, this is
-// safe.
-//
-// This test ensures that a `SafeHash` really IS the same size as a
-// `HashUint`. If you need to change the size of `SafeHash` (and
-// consequently made this test fail), `replace` needs to be
-// modified to no longer assume this.
-#[test]
-fn can_alias_safehash_as_hash() {
-    assert_eq!(size_of::<SafeHash>(), size_of::<HashUint>())
-}
+    /// Matches the bytes of the group which *may* have the given value.
+    ///
+    /// This can report false positives, but only for full buckets that
+    /// directly follow another match.
+    #[inline]
+    pub fn match_byte(group: u64, byte: u8) -> u64 {
+        // This algorithm is derived from
+        // http://graphics.stanford.edu/~seander/bithacks.html##ValueInWord
+        let cmp = group ^ repeat(byte);
+        cmp.wrapping_sub(repeat(0x01)) & !cmp & repeat(0x80)
+    }
 
-// RawBucket methods are unsafe as it's possible to
-// make a RawBucket point to invalid memory using safe code.
-impl<K, V> RawBucket<K, V> {
-    unsafe fn hash(&self) -> *mut HashUint {
-        self.hash_start.offset(self.idx as isize)
+    /// Matches the bytes of the group which are `EMPTY`.
+    #[inline]
+    pub fn match_empty(group: u64) -> u64 {
+        // If the high bit is set, then the byte must be either
+        // 1111_1111 (EMPTY) or 1000_0000 (DELETED), so we only need to
+        // check whether the next bit is set too.
+        group & (group << 1) & repeat(0x80)
     }
-    unsafe fn pair(&self) -> *mut (K, V) {
-        self.pair_start.offset(self.idx as isize) as *mut (K, V)
+
+    /// Matches the bytes of the group which are `EMPTY` or `DELETED`.
+    #[inline]
+    pub fn match_empty_or_deleted(group: u64) -> u64 {
+        // A byte is EMPTY or DELETED iff the high bit is set
+        group & repeat(0x80)
     }
-    unsafe fn hash_pair(&self) -> (*mut HashUint, *mut (K, V)) {
-        (self.hash(), self.pair())
+
+    /// Maps `EMPTY` and `DELETED` bytes to `EMPTY`, and full ones to
+    /// `DELETED`.
+    #[inline]
+    pub fn convert_special_to_empty_and_full_to_deleted(group: u64) -> u64 {
+        // Map high_bit = 1 (EMPTY or DELETED) to 1111_1111
+        // and high_bit = 0 (FULL) to 1000_0000
+        //
+        // Here's this logic expanded to concrete values:
+        //   let full = 1000_0000 (true) or 0000_0000 (false)
+        //   !1000_0000 + 1 = 0111_1111 + 1 = 1000_0000 (no carry)
+        //   !0000_0000 + 0 = 1111_1111 + 0 = 1111_1111 (no carry)
+        let full = !group & repeat(0x80);
+        !full + (full >> 7)
     }
 }
 
-// Buckets hold references to the table.
-impl<K, V, M> FullBucket<K, V, M> {
-    /// Borrow a reference to the table.
-    pub fn table(&self) -> &M {
-        &self.table
-    }
-    /// Borrow a mutable reference to the table.
-    pub fn table_mut(&mut self) -> &mut M {
-        &mut self.table
+/// A bit mask with one bit set for each matching control byte of a `Group`.
+///
+/// With the portable implementation each control byte maps to a whole byte
+/// of the mask, of which only the top bit can be set; `BITMASK_STRIDE`
+/// accounts for that when turning bit positions into bucket offsets.
+#[derive(Copy, Clone)]
+pub struct BitMask(BitMaskWord);
+
+impl BitMask {
+    /// Returns a new `BitMask` with all bits inverted.
+    #[inline]
+    fn invert(self) -> BitMask {
+        BitMask(self.0 ^ BITMASK_MASK)
     }
-    /// Move out the reference to the table.
-    pub fn into_table(self) -> M {
-        self.table
+
+    /// Returns a new `BitMask` with the lowest bit removed.
+    #[inline]
+    fn remove_lowest_bit(self) -> BitMask {
+        BitMask(self.0 & (self.0 - 1))
     }
-    /// Get the raw index.
-    pub fn index(&self) -> usize {
-        self.raw.idx
+
+    /// Returns whether the `BitMask` has at least one set bit.
+    #[inline]
+    fn any_bit_set(self) -> bool {
+        self.0 != 0
     }
-    /// Get the raw bucket.
-    pub fn raw(&self) -> RawBucket<K, V> {
-        self.raw
+
+    /// Returns the first set bit in the `BitMask`, if there is one.
+    #[inline]
+    fn lowest_set_bit(self) -> Option<usize> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.trailing_zeros())
+        }
     }
-}
 
-impl<K, V, M> EmptyBucket<K, V, M> {
-    /// Borrow a reference to the table.
-    pub fn table(&self) -> &M {
-        &self.table
+    /// Returns the number of trailing zeroes in the `BitMask`.
+    #[inline]
+    fn trailing_zeros(self) -> usize {
+        self.0.trailing_zeros() as usize / BITMASK_STRIDE
     }
-    /// Borrow a mutable reference to the table.
-    pub fn table_mut(&mut self) -> &mut M {
-        &mut self.table
+
+    /// Returns the number of leading zeroes in the `BitMask`.
+    #[inline]
+    fn leading_zeros(self) -> usize {
+        self.0.leading_zeros() as usize / BITMASK_STRIDE
     }
 }
 
-impl<K, V, M> Bucket<K, V, M> {
-    /// Get the raw index.
-    pub fn index(&self) -> usize {
-        self.raw.idx
-    }
-    /// get the table.
-    pub fn into_table(self) -> M {
-        self.table
+impl Iterator for BitMask {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        let bit = self.lowest_set_bit()?;
+        *self = self.remove_lowest_bit();
+        Some(bit)
     }
 }
 
-impl<K, V, M> Deref for FullBucket<K, V, M>
-    where M: Deref<Target = RawTable<K, V>>
-{
-    type Target = RawTable<K, V>;
-    fn deref(&self) -> &RawTable<K, V> {
-        &self.table
-    }
+/// The control bytes of the empty singleton table, which is what every table
+/// starts out as before it allocates. They are all `EMPTY`, so a lookup in an
+/// unallocated table needs no special casing, and are never written to.
+#[repr(C)]
+struct EmptyGroup {
+    _align: [Group; 0],
+    bytes: [u8; GROUP_WIDTH],
 }
 
-/// `Put` is implemented for types which provide access to a table and cannot be invalidated
-///  by filling a bucket. A similar implementation for `Take` is possible.
-pub trait Put<K, V> {
-    unsafe fn borrow_table_mut(&mut self) -> &mut RawTable<K, V>;
+static EMPTY_GROUP: EmptyGroup = EmptyGroup {
+    _align: [],
+    bytes: [EMPTY; GROUP_WIDTH],
+};
+
+/// Probe sequence based on triangular numbers, which is guaranteed (since our
+/// table size is a power of two) to visit every group of elements exactly
+/// once.
+///
+/// A triangular probe has us jump by 1 more group every time. So first we
+/// jump by 1 group (meaning we just continue our linear scan), then 2 groups
+/// (skipping over 1 group), then 3 groups (skipping over 2 groups), and so on.
+struct ProbeSeq {
+    bucket_mask: usize,
+    pos: usize,
+    stride: usize,
 }
 
+impl Iterator for ProbeSeq {
+    type Item = usize;
 
-impl<'t, K, V> Put<K, V> for &'t mut RawTable<K, V> {
-    unsafe fn borrow_table_mut(&mut self) -> &mut RawTable<K, V> {
-        *self
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        // We should have found an empty bucket by now and ended the probe.
+        debug_assert!(self.stride <= self.bucket_mask, "Went past end of probe sequence");
+
+        let result = self.pos;
+        self.stride += GROUP_WIDTH;
+        self.pos += self.stride;
+        self.pos &= self.bucket_mask;
+        Some(result)
     }
 }
 
-impl<K, V, M> Put<K, V> for Bucket<K, V, M>
-    where M: Put<K, V>
-{
-    unsafe fn borrow_table_mut(&mut self) -> &mut RawTable<K, V> {
-        self.table.borrow_table_mut()
-    }
+/// The raw hashtable, an open-addressing table of `(K, V)` pairs which is
+/// probed a whole group of buckets at a time.
+///
+/// Alongside the buckets, the table keeps one control byte per bucket. A
+/// control byte is either `EMPTY`, `DELETED` (a tombstone left behind by a
+/// removal), or, for full buckets, the top 7 bits of the element's hash (its
+/// "h2"). A lookup starts at the bucket selected by the low bits of the hash
+/// (its "h1") and loads `GROUP_WIDTH` control bytes at once, which on x86 is
+/// a single SSE2 register. Comparing all of them against the h2 of the hash
+/// yields a small set of candidate buckets, so the keys are only compared for
+/// elements whose hash very likely matches. The probe ends at the first group
+/// containing an `EMPTY` byte, and otherwise moves on to the next group along
+/// a triangular probe sequence.
+///
+/// Essential invariants of this structure:
+///
+///   - The number of buckets is a power of two, or zero for the unallocated
+///     empty singleton, which shares a static group of `EMPTY` control bytes.
+///
+///   - The control bytes are followed by `GROUP_WIDTH` trailing bytes
+///     mirroring the first `GROUP_WIDTH` ones, so that a group can be loaded
+///     at any bucket without wrapping around the end of the table. Tables
+///     smaller than a group leave the bytes between the end of the table and
+///     the mirror `EMPTY`.
+///
+///   - Only buckets with a full control byte hold an initialized element.
+///
+///   - At most 7/8 of the buckets are ever full or deleted, which guarantees
+///     that every probe sequence ends. `growth_left` counts how many more
+///     `EMPTY` buckets may be filled before the table has to be rehashed.
+///
+/// The table does not know how to hash its keys: the callers pass the hash of
+/// every element they look up or insert, and a hashing closure whenever the
+/// table may need to be rehashed.
+pub struct RawTable<K, V> {
+    // Mask to get an index from a hash value. The value is one less than the
+    // number of buckets in the table.
+    bucket_mask: usize,
+
+    // Pointer to the array of control bytes
+    ctrl: NonNull<u8>,
+
+    // Pointer to the array of buckets
+    data: NonNull<(K, V)>,
+
+    // Number of elements that can be inserted before we need to grow the table
+    growth_left: usize,
+
+    // Number of elements in the table
+    items: usize,
+
+    // Tell dropck that we own instances of K and V.
+    marker: marker::PhantomData<(K, V)>,
 }
 
-impl<K, V, M> Put<K, V> for FullBucket<K, V, M>
-    where M: Put<K, V>
-{
-    unsafe fn borrow_table_mut(&mut self) -> &mut RawTable<K, V> {
-        self.table.borrow_table_mut()
-    }
+unsafe impl<K: Send, V: Send> Send for RawTable<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for RawTable<K, V> {}
+
+/// A reference to a full bucket of a `RawTable`.
+///
+/// A `Bucket` does not borrow the table it points into, and remembers its
+/// index so that it can be erased again. It is up to the user to make sure it
+/// is not used after the table is rehashed or the element is removed.
+pub struct Bucket<K, V> {
+    ptr: NonNull<(K, V)>,
+    index: usize,
 }
 
-impl<K, V, M: Deref<Target = RawTable<K, V>>> Bucket<K, V, M> {
-    pub fn new(table: M, hash: SafeHash) -> Bucket<K, V, M> {
-        Bucket::at_index(table, hash.inspect() as usize)
+impl<K, V> Copy for Bucket<K, V> {}
+impl<K, V> Clone for Bucket<K, V> {
+    fn clone(&self) -> Bucket<K, V> {
+        *self
     }
+}
 
-    pub fn new_from(r: RawBucket<K, V>, t: M)
-        -> Bucket<K, V, M>
-    {
+impl<K, V> Bucket<K, V> {
+    #[inline]
+    unsafe fn from_base_index(base: NonNull<(K, V)>, index: usize) -> Bucket<K, V> {
         Bucket {
-            raw: r,
-            table: t,
+            ptr: NonNull::new_unchecked(base.as_ptr().add(index)),
+            index,
         }
     }
 
-    pub fn at_index(table: M, ib_index: usize) -> Bucket<K, V, M> {
-        // if capacity is 0, then the RawBucket will be populated with bogus pointers.
-        // This is an uncommon case though, so avoid it in release builds.
-        debug_assert!(table.capacity() > 0,
-                      "Table should have capacity at this point");
-        let ib_index = ib_index & table.capacity_mask;
-        Bucket {
-            raw: table.raw_bucket_at(ib_index),
-            table,
-        }
+    #[inline]
+    unsafe fn write(&self, pair: (K, V)) {
+        ptr::write(self.ptr.as_ptr(), pair)
     }
 
-    pub fn first(table: M) -> Bucket<K, V, M> {
-        Bucket {
-            raw: table.raw_bucket_at(0),
-            table,
-        }
-    }
-
-    // "So a few of the first shall be last: for many be called,
-    // but few chosen."
-    //
-    // We'll most likely encounter a few buckets at the beginning that
-    // have their initial buckets near the end of the table. They were
-    // placed at the beginning as the probe wrapped around the table
-    // during insertion. We must skip forward to a bucket that won't
-    // get reinserted too early and won't unfairly steal others spot.
-    // This eliminates the need for robin hood.
-    pub fn head_bucket(table: M) -> Bucket<K, V, M> {
-        let mut bucket = Bucket::first(table);
-
-        loop {
-            bucket = match bucket.peek() {
-                Full(full) => {
-                    if full.displacement() == 0 {
-                        // This bucket occupies its ideal spot.
-                        // It indicates the start of another "cluster".
-                        bucket = full.into_bucket();
-                        break;
-                    }
-                    // Leaving this bucket in the last cluster for later.
-                    full.into_bucket()
-                }
-                Empty(b) => {
-                    // Encountered a hole between clusters.
-                    b.into_bucket()
-                }
-            };
-            bucket.next();
-        }
-        bucket
+    #[inline]
+    unsafe fn read(&self) -> (K, V) {
+        ptr::read(self.ptr.as_ptr())
     }
 
-    /// Reads a bucket at a given index, returning an enum indicating whether
-    /// it's initialized or not. You need to match on this enum to get
-    /// the appropriate types to call most of the other functions in
-    /// this module.
-    pub fn peek(self) -> BucketState<K, V, M> {
-        match unsafe { *self.raw.hash() } {
-            EMPTY_BUCKET => {
-                Empty(EmptyBucket {
-                    raw: self.raw,
-                    table: self.table,
-                })
-            }
-            _ => {
-                Full(FullBucket {
-                    raw: self.raw,
-                    table: self.table,
-                })
-            }
-        }
+    #[inline]
+    unsafe fn drop(&self) {
+        ptr::drop_in_place(self.ptr.as_ptr())
     }
 
-    /// Modifies the bucket in place to make it point to the next slot.
-    pub fn next(&mut self) {
-        self.raw.idx = self.raw.idx.wrapping_add(1) & self.table.capacity_mask;
+    #[inline]
+    unsafe fn copy_from_nonoverlapping(&self, other: &Bucket<K, V>) {
+        ptr::copy_nonoverlapping(other.ptr.as_ptr(), self.ptr.as_ptr(), 1)
     }
 
-    /// Modifies the bucket in place to make it point to the previous slot.
-    pub fn prev(&mut self) {
-        self.raw.idx = self.raw.idx.wrapping_sub(1) & self.table.capacity_mask;
+    /// Borrows the key and value in this bucket. The caller picks the
+    /// lifetime of the reference and is responsible for it not outliving the
+    /// element.
+    #[inline]
+    pub unsafe fn as_ref<'a>(&self) -> &'a (K, V) {
+        &*self.ptr.as_ptr()
     }
-}
 
-impl<K, V, M: Deref<Target = RawTable<K, V>>> EmptyBucket<K, V, M> {
+    /// Mutably borrows the key and value in this bucket. The caller picks the
+    /// lifetime of the reference and is responsible for it neither outliving
+    /// the element nor aliasing another reference to it.
     #[inline]
-    pub fn next(self) -> Bucket<K, V, M> {
-        let mut bucket = self.into_bucket();
-        bucket.next();
-        bucket
+    pub unsafe fn as_mut<'a>(&self) -> &'a mut (K, V) {
+        &mut *self.ptr.as_ptr()
     }
+}
 
+/// Whether a failed allocation should abort the process or be reported to
+/// the caller.
+pub(crate) enum Fallibility {
+    Fallible,
+    Infallible,
+}
+
+// Returns a Layout which describes the allocation required for a hash table,
+// and the offset of the buckets in the allocation.
+//
+// The control bytes (including the trailing group) come first, aligned to the
+// group width so that groups can be loaded and stored aligned.
+#[inline]
+fn calculate_layout<K, V>(buckets: usize) -> Result<(Layout, usize), LayoutErr> {
+    debug_assert!(buckets.is_power_of_two());
+    let ctrl = Layout::from_size_align(buckets + GROUP_WIDTH, GROUP_WIDTH)?;
+    let data = Layout::array::<(K, V)>(buckets)?;
+    ctrl.extend(data)
+}
+
+// Allocations larger than `isize::MAX` bytes would break pointer offsets on
+// 32-bit platforms, where they might actually succeed.
+#[inline]
+fn alloc_guard(alloc_size: usize) -> Result<(), CollectionAllocErr> {
+    if mem::size_of::<usize>() < 8 && alloc_size > isize::MAX as usize {
+        Err(CollectionAllocErr::CapacityOverflow)
+    } else {
+        Ok(())
+    }
+}
+
+impl<K, V> RawTable<K, V> {
+    /// Creates a new empty table without allocating any memory.
     #[inline]
-    pub fn into_bucket(self) -> Bucket<K, V, M> {
-        Bucket {
-            raw: self.raw,
-            table: self.table,
+    pub fn new() -> RawTable<K, V> {
+        RawTable {
+            bucket_mask: 0,
+            ctrl: unsafe {
+                NonNull::new_unchecked(&EMPTY_GROUP.bytes as *const _ as *mut u8)
+            },
+            data: NonNull::dangling(),
+            growth_left: 0,
+            items: 0,
+            marker: marker::PhantomData,
         }
     }
 
-    pub fn gap_peek(self) -> Result<GapThenFull<K, V, M>, Bucket<K, V, M>> {
-        let gap = EmptyBucket {
-            raw: self.raw,
-            table: (),
-        };
+    /// Allocates a table with the given number of buckets, which must be a
+    /// power of two. The control bytes are left uninitialized.
+    unsafe fn new_uninitialized(
+        buckets: usize,
+        fallibility: Fallibility,
+    ) -> Result<RawTable<K, V>, CollectionAllocErr> {
+        let (layout, data_offset) = calculate_layout::<K, V>(buckets)?;
+        alloc_guard(layout.size())?;
+        let buffer = Global.alloc(layout).map_err(|e| match fallibility {
+            Infallible => oom(layout),
+            Fallible => e,
+        })?;
 
-        match self.next().peek() {
-            Full(bucket) => {
-                Ok(GapThenFull {
-                    gap,
-                    full: bucket,
-                })
-            }
-            Empty(e) => Err(e.into_bucket()),
-        }
+        let ctrl = buffer.cast::<u8>();
+        Ok(RawTable {
+            bucket_mask: buckets - 1,
+            ctrl,
+            data: NonNull::new_unchecked(ctrl.as_ptr().add(data_offset) as *mut (K, V)),
+            growth_left: bucket_mask_to_capacity(buckets - 1),
+            items: 0,
+            marker: marker::PhantomData,
+        })
     }
-}
 
-impl<K, V, M> EmptyBucket<K, V, M>
-    where M: Put<K, V>
-{
-    /// Puts given key and value pair, along with the key's hash,
-    /// into this bucket in the hashtable. Note how `self` is 'moved' into
-    /// this function, because this slot will no longer be empty when
-    /// we return! A `FullBucket` is returned for later use, pointing to
-    /// the newly-filled slot in the hashtable.
-    ///
-    /// Use `make_hash` to construct a `SafeHash` to pass to this function.
-    pub fn put(mut self, hash: SafeHash, key: K, value: V) -> FullBucket<K, V, M> {
-        unsafe {
-            *self.raw.hash() = hash.inspect();
-            ptr::write(self.raw.pair(), (key, value));
+    /// Allocates a table able to hold at least `capacity` elements without
+    /// reallocating, with all buckets empty.
+    fn try_with_capacity(
+        capacity: usize,
+        fallibility: Fallibility,
+    ) -> Result<RawTable<K, V>, CollectionAllocErr> {
+        if capacity == 0 {
+            return Ok(RawTable::new());
+        }
 
-            self.table.borrow_table_mut().size += 1;
+        let buckets = capacity_to_buckets(capacity)
+            .ok_or(CollectionAllocErr::CapacityOverflow)?;
+        unsafe {
+            let table = RawTable::new_uninitialized(buckets, fallibility)?;
+            ptr::write_bytes(table.ctrl.as_ptr(), EMPTY, table.num_ctrl_bytes());
+            Ok(table)
         }
+    }
 
-        FullBucket {
-            raw: self.raw,
-            table: self.table,
+    /// Allocates a table able to hold at least `capacity` elements without
+    /// reallocating, with all buckets empty.
+    pub fn with_capacity(capacity: usize) -> RawTable<K, V> {
+        match RawTable::try_with_capacity(capacity, Infallible) {
+            Err(CollectionAllocErr::CapacityOverflow) => panic!("capacity overflow"),
+            Err(CollectionAllocErr::AllocErr) => unreachable!(),
+            Ok(table) => table,
         }
     }
-}
 
-impl<K, V, M: Deref<Target = RawTable<K, V>>> FullBucket<K, V, M> {
-    #[inline]
-    pub fn next(self) -> Bucket<K, V, M> {
-        let mut bucket = self.into_bucket();
-        bucket.next();
-        bucket
+    /// Deallocates the table without dropping any elements.
+    unsafe fn free_buckets(&mut self) {
+        let (layout, _) = calculate_layout::<K, V>(self.buckets()).unwrap();
+        Global.dealloc(self.ctrl.as_opaque(), layout);
     }
 
+    /// Returns whether this is the unallocated empty singleton.
     #[inline]
-    pub fn into_bucket(self) -> Bucket<K, V, M> {
-        Bucket {
-            raw: self.raw,
-            table: self.table,
-        }
+    fn is_empty_singleton(&self) -> bool {
+        self.bucket_mask == 0
     }
 
-    /// Duplicates the current position. This can be useful for operations
-    /// on two or more buckets.
-    pub fn stash(self) -> FullBucket<K, V, Self> {
-        FullBucket {
-            raw: self.raw,
-            table: self,
-        }
+    /// Returns the bucket at the given index.
+    #[inline]
+    unsafe fn bucket(&self, index: usize) -> Bucket<K, V> {
+        debug_assert!(!self.is_empty_singleton());
+        debug_assert!(index < self.buckets());
+        Bucket::from_base_index(self.data, index)
     }
 
-    /// Get the distance between this bucket and the 'ideal' location
-    /// as determined by the key's hash stored in it.
-    ///
-    /// In the cited blog posts above, this is called the "distance to
-    /// initial bucket", or DIB. Also known as "probe count".
-    pub fn displacement(&self) -> usize {
-        // Calculates the distance one has to travel when going from
-        // `hash mod capacity` onwards to `idx mod capacity`, wrapping around
-        // if the destination is not reached before the end of the table.
-        (self.raw.idx.wrapping_sub(self.hash().inspect() as usize)) & self.table.capacity_mask
+    /// Returns a pointer to a control byte.
+    #[inline]
+    unsafe fn ctrl(&self, index: usize) -> *mut u8 {
+        debug_assert!(index < self.num_ctrl_bytes());
+        self.ctrl.as_ptr().add(index)
     }
 
+    /// Sets a control byte, and possibly also the replicated control byte at
+    /// the end of the array.
     #[inline]
-    pub fn hash(&self) -> SafeHash {
-        unsafe { SafeHash { hash: *self.raw.hash() } }
-    }
+    unsafe fn set_ctrl(&self, index: usize, ctrl: u8) {
+        // Replicate the first GROUP_WIDTH control bytes at the end of the
+        // array without using a branch:
+        // - If index >= GROUP_WIDTH then index == index2.
+        // - Otherwise index2 == self.bucket_mask + 1 + index.
+        //
+        // The very last replicated control byte is never actually read
+        // because we mask the initial index for unaligned loads, but we need
+        // to write it anyways because it overlaps the first group of tables
+        // that are smaller than a group.
+        //
+        // For tables smaller than a group, the replicated bytes land after
+        // the `EMPTY` bytes that pad the real control bytes to a full group.
+        let index2 = ((index.wrapping_sub(GROUP_WIDTH)) & self.bucket_mask) + GROUP_WIDTH;
 
-    /// Gets references to the key and value at a given index.
-    pub fn read(&self) -> (&K, &V) {
-        unsafe {
-            let pair_ptr = self.raw.pair();
-            (&(*pair_ptr).0, &(*pair_ptr).1)
-        }
+        *self.ctrl(index) = ctrl;
+        *self.ctrl(index2) = ctrl;
     }
-}
 
-// We take a mutable reference to the table instead of accepting anything that
-// implements `DerefMut` to prevent fn `take` from being called on `stash`ed
-// buckets.
-impl<'t, K, V> FullBucket<K, V, &'t mut RawTable<K, V>> {
-    /// Removes this bucket's key and value from the hashtable.
+    /// Returns an iterator for a probe sequence on the table.
     ///
-    /// This works similarly to `put`, building an `EmptyBucket` out of the
-    /// taken bucket.
-    pub fn take(self) -> (EmptyBucket<K, V, &'t mut RawTable<K, V>>, K, V) {
-        self.table.size -= 1;
-
-        unsafe {
-            *self.raw.hash() = EMPTY_BUCKET;
-            let (k, v) = ptr::read(self.raw.pair());
-            (EmptyBucket {
-                 raw: self.raw,
-                 table: self.table,
-             },
-            k,
-            v)
+    /// This iterator never terminates, but is guaranteed to visit each
+    /// bucket group exactly once.
+    #[inline]
+    fn probe_seq(&self, hash: u64) -> ProbeSeq {
+        ProbeSeq {
+            bucket_mask: self.bucket_mask,
+            pos: h1(hash) & self.bucket_mask,
+            stride: 0,
         }
     }
-}
-
-// This use of `Put` is misleading and restrictive, but safe and sufficient for our use cases
-// where `M` is a full bucket or table reference type with mutable access to the table.
-impl<K, V, M> FullBucket<K, V, M>
-    where M: Put<K, V>
-{
-    pub fn replace(&mut self, h: SafeHash, k: K, v: V) -> (SafeHash, K, V) {
-        unsafe {
-            let old_hash = ptr::replace(self.raw.hash() as *mut SafeHash, h);
-            let (old_key, old_val) = ptr::replace(self.raw.pair(), (k, v));
 
-            (old_hash, old_key, old_val)
+    /// Searches for an empty or deleted bucket which is suitable for
+    /// inserting a new element.
+    ///
+    /// There must be at least 1 empty bucket in the table.
+    #[inline]
+    fn find_insert_slot(&self, hash: u64) -> usize {
+        for pos in self.probe_seq(hash) {
+            unsafe {
+                let group = Group::load(self.ctrl(pos));
+                if let Some(bit) = group.match_empty_or_deleted().lowest_set_bit() {
+                    let result = (pos + bit) & self.bucket_mask;
+
+                    // In tables smaller than the group width, trailing
+                    // control bytes outside the range of the table are
+                    // filled with EMPTY entries. These will unfortunately
+                    // trigger a match, but once masked may point to a full
+                    // bucket that is already occupied. We detect this
+                    // situation here and perform a second scan starting at
+                    // the beginning of the table. This second scan is
+                    // guaranteed to find an empty slot (due to the load
+                    // factor) before hitting the trailing control bytes
+                    // (containing EMPTY).
+                    if is_full(*self.ctrl(result)) {
+                        debug_assert!(self.bucket_mask < GROUP_WIDTH);
+                        debug_assert_ne!(pos, 0);
+                        return Group::load_aligned(self.ctrl(0))
+                            .match_empty_or_deleted()
+                            .trailing_zeros();
+                    }
+                    return result;
+                }
+            }
         }
+
+        // probe_seq never returns.
+        unreachable!();
     }
-}
 
-impl<K, V, M> FullBucket<K, V, M>
-    where M: Deref<Target = RawTable<K, V>> + DerefMut
-{
-    /// Gets mutable references to the key and value at a given index.
-    pub fn read_mut(&mut self) -> (&mut K, &mut V) {
+    /// Searches for an element in the table, given its hash and a function
+    /// that recognizes it.
+    #[inline]
+    pub fn find<F>(&self, hash: u64, mut eq: F) -> Option<Bucket<K, V>>
+        where F: FnMut(&(K, V)) -> bool
+    {
         unsafe {
-            let pair_ptr = self.raw.pair();
-            (&mut (*pair_ptr).0, &mut (*pair_ptr).1)
+            for pos in self.probe_seq(hash) {
+                let group = Group::load(self.ctrl(pos));
+                for bit in group.match_byte(h2(hash)) {
+                    let index = (pos + bit) & self.bucket_mask;
+                    let bucket = self.bucket(index);
+                    if eq(bucket.as_ref()) {
+                        return Some(bucket);
+                    }
+                }
+                if group.match_empty().any_bit_set() {
+                    return None;
+                }
+            }
         }
+
+        // probe_seq never returns.
+        unreachable!();
     }
-}
 
-impl<'t, K, V, M> FullBucket<K, V, M>
-    where M: Deref<Target = RawTable<K, V>> + 't
-{
-    /// Exchange a bucket state for immutable references into the table.
-    /// Because the underlying reference to the table is also consumed,
-    /// no further changes to the structure of the table are possible;
-    /// in exchange for this, the returned references have a longer lifetime
-    /// than the references returned by `read()`.
-    pub fn into_refs(self) -> (&'t K, &'t V) {
+    /// Inserts a new element into the table, growing it first if needed.
+    ///
+    /// This does not check if the given element already exists in the table.
+    /// `hasher` is used to recompute the hashes of the elements if the table
+    /// has to be rehashed.
+    #[inline]
+    pub fn insert<H>(&mut self, hash: u64, pair: (K, V), hasher: H) -> Bucket<K, V>
+        where H: Fn(&K) -> u64
+    {
         unsafe {
-            let pair_ptr = self.raw.pair();
-            (&(*pair_ptr).0, &(*pair_ptr).1)
+            let mut index = self.find_insert_slot(hash);
+
+            // We can avoid growing the table once we have reached our load
+            // factor if we are replacing a tombstone.
+            let old_ctrl = *self.ctrl(index);
+            if self.growth_left == 0 && special_is_empty(old_ctrl) {
+                self.reserve(1, hasher);
+                index = self.find_insert_slot(hash);
+            }
+
+            self.insert_in_slot(index, hash, pair)
         }
     }
-}
 
-impl<'t, K, V, M> FullBucket<K, V, M>
-    where M: Deref<Target = RawTable<K, V>> + DerefMut + 't
-{
-    /// This works similarly to `into_refs`, exchanging a bucket state
-    /// for mutable references into the table.
-    pub fn into_mut_refs(self) -> (&'t mut K, &'t mut V) {
+    /// Inserts a new element into the table, without growing the table.
+    ///
+    /// There must be enough space in the table to insert the new element,
+    /// which callers ensure by calling `reserve` first.
+    ///
+    /// This does not check if the given element already exists in the table.
+    #[inline]
+    pub fn insert_no_grow(&mut self, hash: u64, pair: (K, V)) -> Bucket<K, V> {
         unsafe {
-            let pair_ptr = self.raw.pair();
-            (&mut (*pair_ptr).0, &mut (*pair_ptr).1)
+            let index = self.find_insert_slot(hash);
+            self.insert_in_slot(index, hash, pair)
         }
     }
-}
 
-impl<K, V, M> GapThenFull<K, V, M>
-    where M: Deref<Target = RawTable<K, V>>
-{
+    /// Writes a new element into a slot returned by `find_insert_slot`.
     #[inline]
-    pub fn full(&self) -> &FullBucket<K, V, M> {
-        &self.full
-    }
+    unsafe fn insert_in_slot(&mut self, index: usize, hash: u64, pair: (K, V)) -> Bucket<K, V> {
+        let bucket = self.bucket(index);
 
-    pub fn into_table(self) -> M {
-        self.full.into_table()
-    }
+        // If we are replacing a DELETED entry then we don't need to update
+        // the load counter.
+        let old_ctrl = *self.ctrl(index);
+        self.growth_left -= special_is_empty(old_ctrl) as usize;
 
-    pub fn shift(mut self) -> Result<GapThenFull<K, V, M>, Bucket<K, V, M>> {
-        unsafe {
-            let (gap_hash, gap_pair) = self.gap.raw.hash_pair();
-            let (full_hash, full_pair) = self.full.raw.hash_pair();
-            *gap_hash = mem::replace(&mut *full_hash, EMPTY_BUCKET);
-            ptr::copy_nonoverlapping(full_pair, gap_pair, 1);
-        }
+        self.set_ctrl(index, h2(hash));
+        bucket.write(pair);
+        self.items += 1;
+        bucket
+    }
 
-        let FullBucket { raw: prev_raw, .. } = self.full;
+    /// Erases an element from the table without dropping it.
+    ///
+    /// This leaves the bucket `DELETED` unless no probe sequence can have
+    /// passed over it, in which case it can simply become `EMPTY` again.
+    /// It is fine to call this on a bucket yielded by `raw_iter` while the
+    /// iteration is still going on.
+    pub unsafe fn erase_no_drop(&mut self, item: &Bucket<K, V>) {
+        let index = item.index;
+        let index_before = index.wrapping_sub(GROUP_WIDTH) & self.bucket_mask;
+        let empty_before = Group::load(self.ctrl(index_before)).match_empty();
+        let empty_after = Group::load(self.ctrl(index)).match_empty();
+
+        // If we are inside a continuous block of GROUP_WIDTH full or deleted
+        // cells then a probe window may have seen a full block when trying to
+        // insert. We therefore need to keep that block non-empty so that
+        // lookups will continue searching to the next probe window.
+        let ctrl = if empty_before.leading_zeros() + empty_after.trailing_zeros()
+            >= GROUP_WIDTH {
+            DELETED
+        } else {
+            self.growth_left += 1;
+            EMPTY
+        };
+        self.set_ctrl(index, ctrl);
+        self.items -= 1;
+    }
 
-        match self.full.next().peek() {
-            Full(bucket) => {
-                self.gap.raw = prev_raw;
+    /// Erases an element from the table, dropping it in place.
+    pub unsafe fn erase(&mut self, item: Bucket<K, V>) {
+        // Erase the element from the table first since drop might panic.
+        self.erase_no_drop(&item);
+        item.drop();
+    }
 
-                self.full = bucket;
+    /// Removes an element from the table, returning it.
+    pub unsafe fn remove(&mut self, item: Bucket<K, V>) -> (K, V) {
+        self.erase_no_drop(&item);
+        item.read()
+    }
 
-                Ok(self)
+    /// Marks all buckets as empty without dropping their contents.
+    pub fn clear_no_drop(&mut self) {
+        if !self.is_empty_singleton() {
+            unsafe {
+                ptr::write_bytes(self.ctrl.as_ptr(), EMPTY, self.num_ctrl_bytes());
             }
-            Empty(b) => Err(b.into_bucket()),
         }
+        self.items = 0;
+        self.growth_left = bucket_mask_to_capacity(self.bucket_mask);
     }
-}
 
-// Returns a Layout which describes the allocation required for a hash table,
-// and the offset of the array of (key, value) pairs in the allocation.
-fn calculate_layout<K, V>(capacity: usize) -> Result<(Layout, usize), LayoutErr> {
-    let hashes = Layout::array::<HashUint>(capacity)?;
-    let pairs = Layout::array::<(K, V)>(capacity)?;
-    hashes.extend(pairs)
-}
-
-pub(crate) enum Fallibility {
-    Fallible,
-    Infallible,
-}
+    /// Ensures that at least `additional` items can be inserted into the
+    /// table without reallocation.
+    #[inline]
+    pub fn reserve<H>(&mut self, additional: usize, hasher: H)
+        where H: Fn(&K) -> u64
+    {
+        if additional > self.growth_left {
+            match self.reserve_rehash(additional, &hasher, Infallible) {
+                Err(CollectionAllocErr::CapacityOverflow) => panic!("capacity overflow"),
+                Err(CollectionAllocErr::AllocErr) => unreachable!(),
+                Ok(()) => { /* yay */ }
+            }
+        }
+    }
 
-use self::Fallibility::*;
+    /// Tries to ensure that at least `additional` items can be inserted into
+    /// the table without reallocation.
+    #[inline]
+    pub fn try_reserve<H>(&mut self, additional: usize, hasher: H)
+        -> Result<(), CollectionAllocErr>
+        where H: Fn(&K) -> u64
+    {
+        if additional > self.growth_left {
+            self.reserve_rehash(additional, &hasher, Fallible)
+        } else {
+            Ok(())
+        }
+    }
 
-impl<K, V> RawTable<K, V> {
-    /// Does not initialize the buckets. The caller should ensure they,
-    /// at the very least, set every hash to EMPTY_BUCKET.
-    /// Returns an error if it cannot allocate or capacity overflows.
-    unsafe fn new_uninitialized_internal(
-        capacity: usize,
+    /// Out-of-line slow path for `reserve` and `try_reserve`.
+    #[inline(never)]
+    #[cold]
+    fn reserve_rehash<H>(
+        &mut self,
+        additional: usize,
+        hasher: &H,
         fallibility: Fallibility,
-    ) -> Result<RawTable<K, V>, CollectionAllocErr> {
-        if capacity == 0 {
-            return Ok(RawTable {
-                size: 0,
-                capacity_mask: capacity.wrapping_sub(1),
-                hashes: TaggedHashUintPtr::new(EMPTY as *mut HashUint),
-                marker: marker::PhantomData,
-            });
+    ) -> Result<(), CollectionAllocErr>
+        where H: Fn(&K) -> u64
+    {
+        let new_items = self.items
+            .checked_add(additional)
+            .ok_or(CollectionAllocErr::CapacityOverflow)?;
+
+        // Rehash in-place without re-allocating if we have plenty of spare
+        // capacity that is locked up due to DELETED entries.
+        let full_capacity = bucket_mask_to_capacity(self.bucket_mask);
+        if new_items <= full_capacity / 2 {
+            unsafe {
+                self.rehash_in_place(hasher);
+            }
+            Ok(())
+        } else {
+            // Otherwise, conservatively resize to at least the next size up
+            // to avoid churning deletes into frequent rehashes.
+            self.resize(cmp::max(new_items, full_capacity + 1), hasher, fallibility)
         }
-
-        // Allocating hashmaps is a little tricky. We need to allocate two
-        // arrays, but since we know their sizes and alignments up front,
-        // we just allocate a single array, and then have the subarrays
-        // point into it.
-        let (layout, _) = calculate_layout::<K, V>(capacity)?;
-        let buffer = Global.alloc(layout).map_err(|e| match fallibility {
-            Infallible => oom(layout),
-            Fallible => e,
-        })?;
-
-        Ok(RawTable {
-            capacity_mask: capacity.wrapping_sub(1),
-            size: 0,
-            hashes: TaggedHashUintPtr::new(buffer.cast().as_ptr()),
-            marker: marker::PhantomData,
-        })
     }
 
-    /// Does not initialize the buckets. The caller should ensure they,
-    /// at the very least, set every hash to EMPTY_BUCKET.
-    unsafe fn new_uninitialized(capacity: usize) -> RawTable<K, V> {
-        match Self::new_uninitialized_internal(capacity, Infallible) {
-            Err(CollectionAllocErr::CapacityOverflow) => panic!("capacity overflow"),
-            Err(CollectionAllocErr::AllocErr) => unreachable!(),
-            Ok(table) => { table }
+    /// Rehashes the contents of the table in place (i.e. without changing the
+    /// allocation size), turning all `DELETED` buckets back into `EMPTY` ones.
+    unsafe fn rehash_in_place<H>(&mut self, hasher: &H)
+        where H: Fn(&K) -> u64
+    {
+        // Bulk convert all full control bytes to DELETED, and all DELETED
+        // control bytes to EMPTY. This effectively frees up all buckets
+        // containing a DELETED entry.
+        let mut i = 0;
+        while i < self.buckets() {
+            let group = Group::load_aligned(self.ctrl(i));
+            let group = group.convert_special_to_empty_and_full_to_deleted();
+            group.store_aligned(self.ctrl(i));
+            i += GROUP_WIDTH;
         }
-    }
 
-    fn raw_bucket_at(&self, index: usize) -> RawBucket<K, V> {
-        let (_, pairs_offset) = calculate_layout::<K, V>(self.capacity()).unwrap();
-        let buffer = self.hashes.ptr() as *mut u8;
-        unsafe {
-            RawBucket {
-                hash_start: buffer as *mut HashUint,
-                pair_start: buffer.add(pairs_offset) as *const (K, V),
-                idx: index,
-                _marker: marker::PhantomData,
+        // Fix up the trailing control bytes. See the comments in set_ctrl
+        // for the handling of tables smaller than the group width.
+        if self.buckets() < GROUP_WIDTH {
+            ptr::copy_nonoverlapping(self.ctrl(0), self.ctrl(GROUP_WIDTH), self.buckets());
+        } else {
+            ptr::copy_nonoverlapping(self.ctrl(0), self.ctrl(self.buckets()), GROUP_WIDTH);
+        }
+
+        // If the hasher panics then some elements are left in DELETED
+        // buckets, where nothing would ever find or drop them again. The
+        // guard drops them and fixes up the counters so the table stays
+        // consistent.
+        let guard = RehashGuard { table: self as *mut RawTable<K, V> };
+
+        // At this point, DELETED elements are elements that we haven't
+        // rehashed yet. Find them and re-insert them at their ideal
+        // position.
+        'outer: for i in 0..self.buckets() {
+            if *self.ctrl(i) != DELETED {
+                continue;
+            }
+
+            'inner: loop {
+                // Hash the current item
+                let item = self.bucket(i);
+                let hash = hasher(&item.as_ref().0);
+
+                // Search for a suitable place to put it
+                let new_i = self.find_insert_slot(hash);
+
+                // Probing works by scanning through all of the control
+                // bytes in groups, which may not be aligned to the group
+                // size. If both the new and old position fall within the
+                // same unaligned group, then there is no benefit in moving
+                // it and we can just continue to the next item.
+                let probe_index = |pos: usize| {
+                    (pos.wrapping_sub(self.probe_seq(hash).pos) & self.bucket_mask)
+                        / GROUP_WIDTH
+                };
+                if probe_index(i) == probe_index(new_i) {
+                    self.set_ctrl(i, h2(hash));
+                    continue 'outer;
+                }
+
+                // We are moving the current item to a new position. Write
+                // our H2 to the control byte of the new position.
+                let prev_ctrl = *self.ctrl(new_i);
+                self.set_ctrl(new_i, h2(hash));
+
+                if prev_ctrl == EMPTY {
+                    // If the target slot is empty, simply move the current
+                    // element into the new slot and clear the old control
+                    // byte.
+                    self.set_ctrl(i, EMPTY);
+                    self.bucket(new_i).copy_from_nonoverlapping(&item);
+                    continue 'outer;
+                } else {
+                    // If the target slot is occupied, swap the two elements
+                    // and then continue processing the element that we just
+                    // swapped into the old slot.
+                    debug_assert_eq!(prev_ctrl, DELETED);
+                    mem::swap(self.bucket(new_i).as_mut(), item.as_mut());
+                    continue 'inner;
+                }
             }
         }
+
+        mem::forget(guard);
+        self.growth_left = bucket_mask_to_capacity(self.bucket_mask) - self.items;
     }
 
-    fn new_internal(
+    /// Allocates a new table of a different size and moves the contents of
+    /// the current table into it.
+    fn resize<H>(
+        &mut self,
         capacity: usize,
+        hasher: &H,
         fallibility: Fallibility,
-    ) -> Result<RawTable<K, V>, CollectionAllocErr> {
+    ) -> Result<(), CollectionAllocErr>
+        where H: Fn(&K) -> u64
+    {
         unsafe {
-            let ret = RawTable::new_uninitialized_internal(capacity, fallibility)?;
-            ptr::write_bytes(ret.hashes.ptr(), 0, capacity);
-            Ok(ret)
+            debug_assert!(self.items <= capacity);
+
+            // Allocate and initialize the new table. Until the elements are
+            // all moved, it must only be deallocated, not dropped, since the
+            // elements are still owned by the old table.
+            let new_table = RawTable::try_with_capacity(capacity, fallibility)?;
+            let mut guard = ResizeGuard { table: ManuallyDrop::new(new_table) };
+            guard.table.growth_left -= self.items;
+            guard.table.items = self.items;
+
+            // Copy all elements to the new table. The hasher may panic, in
+            // which case the old table still owns everything.
+            for item in self.raw_iter() {
+                // This may panic.
+                let hash = hasher(&item.as_ref().0);
+
+                // We can use a simpler version of insert() here since:
+                // - there are no DELETED entries.
+                // - we know there is enough space in the table.
+                // - all elements are unique.
+                let index = guard.table.find_insert_slot(hash);
+                guard.table.set_ctrl(index, h2(hash));
+                guard.table.bucket(index).copy_from_nonoverlapping(&item);
+            }
+
+            // We successfully copied all elements without panicking. Now
+            // replace self with the new table. The guard is left holding the
+            // old table, whose allocation it frees without dropping the
+            // elements that were moved out of it.
+            mem::swap(self, &mut *guard.table);
+            Ok(())
         }
     }
 
-    /// Tries to create a new raw table from a given capacity. If it cannot allocate,
-    /// it returns with AllocErr.
-    pub fn try_new(capacity: usize) -> Result<RawTable<K, V>, CollectionAllocErr> {
-        Self::new_internal(capacity, Fallible)
-    }
+    /// Shrinks the table to fit `max(self.len(), min_size)` elements.
+    pub fn shrink_to<H>(&mut self, min_size: usize, hasher: H)
+        where H: Fn(&K) -> u64
+    {
+        let min_size = cmp::max(self.items, min_size);
+
+        // Calculate the minimal number of buckets that we need to hold this
+        // many elements. If that does not even fit in a `usize` the table
+        // certainly can't shrink.
+        let min_buckets = match capacity_to_buckets(min_size) {
+            Some(buckets) => buckets,
+            None => return,
+        };
 
-    /// Creates a new raw table from a given capacity. All buckets are
-    /// initially empty.
-    pub fn new(capacity: usize) -> RawTable<K, V> {
-        match Self::new_internal(capacity, Infallible) {
-            Err(CollectionAllocErr::CapacityOverflow) => panic!("capacity overflow"),
-            Err(CollectionAllocErr::AllocErr) => unreachable!(),
-            Ok(table) => { table }
+        // If we have more buckets than we need, shrink the table.
+        if min_size == 0 {
+            *self = RawTable::new();
+        } else if min_buckets < self.buckets() {
+            if self.items == 0 {
+                *self = RawTable::with_capacity(min_size);
+            } else {
+                match self.resize(min_size, &hasher, Infallible) {
+                    Err(CollectionAllocErr::CapacityOverflow) => panic!("capacity overflow"),
+                    Err(CollectionAllocErr::AllocErr) => unreachable!(),
+                    Ok(()) => { /* yay */ }
+                }
+            }
         }
     }
 
-    /// The hashtable's capacity, similar to a vector's.
+    /// Returns the number of elements the table can hold without reallocating.
+    #[inline]
     pub fn capacity(&self) -> usize {
-        self.capacity_mask.wrapping_add(1)
+        self.items + self.growth_left
     }
 
-    /// The number of elements ever `put` in the hashtable, minus the number
-    /// of elements ever `take`n.
-    pub fn size(&self) -> usize {
-        self.size
+    /// Returns the number of elements in the table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items
     }
 
-    fn raw_buckets(&self) -> RawBuckets<K, V> {
-        RawBuckets {
-            raw: self.raw_bucket_at(0),
-            elems_left: self.size,
-            marker: marker::PhantomData,
+    /// Returns the number of buckets in the table.
+    #[inline]
+    pub fn buckets(&self) -> usize {
+        self.bucket_mask + 1
+    }
+
+    /// Returns the number of control bytes in the table.
+    #[inline]
+    fn num_ctrl_bytes(&self) -> usize {
+        self.bucket_mask + 1 + GROUP_WIDTH
+    }
+
+    /// Returns an iterator over every element in the table. It is up to the
+    /// caller to ensure that the `RawTable` outlives the `RawIter`, and that
+    /// the table is not changed during the iteration other than by erasing
+    /// buckets the iterator has already yielded.
+    #[inline]
+    pub unsafe fn raw_iter(&self) -> RawIter<K, V> {
+        RawIter {
+            data: self.data,
+            ctrl: self.ctrl.as_ptr(),
+            current_group: Group::load_aligned(self.ctrl.as_ptr()).match_full(),
+            group_index: 0,
+            items: self.items,
         }
     }
 
     pub fn iter(&self) -> Iter<K, V> {
         Iter {
-            iter: self.raw_buckets(),
+            iter: unsafe { self.raw_iter() },
+            marker: marker::PhantomData,
         }
     }
 
     pub fn iter_mut(&mut self) -> IterMut<K, V> {
         IterMut {
-            iter: self.raw_buckets(),
-            _marker: marker::PhantomData,
+            iter: unsafe { self.raw_iter() },
+            marker: marker::PhantomData,
         }
     }
 
     pub fn into_iter(self) -> IntoIter<K, V> {
-        let RawBuckets { raw, elems_left, .. } = self.raw_buckets();
-        // Replace the marker regardless of lifetime bounds on parameters.
-        IntoIter {
-            iter: RawBuckets {
-                raw,
-                elems_left,
+        unsafe {
+            let iter = self.raw_iter();
+            let alloc = if self.is_empty_singleton() {
+                None
+            } else {
+                let (layout, _) = calculate_layout::<K, V>(self.buckets()).unwrap();
+                Some((self.ctrl, layout))
+            };
+            mem::forget(self);
+            IntoIter {
+                iter,
+                alloc,
                 marker: marker::PhantomData,
-            },
-            table: self,
+            }
         }
     }
 
     pub fn drain(&mut self) -> Drain<K, V> {
-        let RawBuckets { raw, elems_left, .. } = self.raw_buckets();
-        // Replace the marker regardless of lifetime bounds on parameters.
         Drain {
-            iter: RawBuckets {
-                raw,
-                elems_left,
-                marker: marker::PhantomData,
-            },
+            iter: unsafe { self.raw_iter() },
             table: NonNull::from(self),
             marker: marker::PhantomData,
         }
     }
+}
 
-    /// Drops buckets in reverse order. It leaves the table in an inconsistent
-    /// state and should only be used for dropping the table's remaining
-    /// entries. It's used in the implementation of Drop.
-    unsafe fn rev_drop_buckets(&mut self) {
-        // initialize the raw bucket past the end of the table
-        let mut raw = self.raw_bucket_at(self.capacity());
-        let mut elems_left = self.size;
+/// Drops the elements left in `DELETED` buckets by a rehash that was
+/// interrupted by a panic.
+struct RehashGuard<K, V> {
+    table: *mut RawTable<K, V>,
+}
 
-        while elems_left != 0 {
-            raw.idx -= 1;
+impl<K, V> Drop for RehashGuard<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let table = &mut *self.table;
+            for i in 0..table.buckets() {
+                if *table.ctrl(i) == DELETED {
+                    table.set_ctrl(i, EMPTY);
+                    table.bucket(i).drop();
+                    table.items -= 1;
+                }
+            }
+            table.growth_left = bucket_mask_to_capacity(table.bucket_mask) - table.items;
+        }
+    }
+}
+
+/// Frees the allocation of a table whose elements have been moved elsewhere
+/// (or have not been moved in yet), without dropping them.
+struct ResizeGuard<K, V> {
+    table: ManuallyDrop<RawTable<K, V>>,
+}
 
-            if *raw.hash() != EMPTY_BUCKET {
-                elems_left -= 1;
-                ptr::drop_in_place(raw.pair());
+impl<K, V> Drop for ResizeGuard<K, V> {
+    fn drop(&mut self) {
+        if !self.table.is_empty_singleton() {
+            unsafe {
+                self.table.free_buckets();
             }
         }
     }
+}
+
+impl<K: Clone, V: Clone> Clone for RawTable<K, V> {
+    fn clone(&self) -> RawTable<K, V> {
+        if self.is_empty_singleton() {
+            return RawTable::new();
+        }
+
+        unsafe {
+            // Start from an empty table with the same number of buckets and
+            // mark each bucket full as soon as its clone has been written, so
+            // that if `clone` panics only the finished clones get dropped.
+            let mut new_table = match RawTable::new_uninitialized(self.buckets(), Infallible) {
+                Err(CollectionAllocErr::CapacityOverflow) => panic!("capacity overflow"),
+                Err(CollectionAllocErr::AllocErr) => unreachable!(),
+                Ok(table) => table,
+            };
+            ptr::write_bytes(new_table.ctrl.as_ptr(), EMPTY, new_table.num_ctrl_bytes());
+
+            for from in self.raw_iter() {
+                let to = new_table.bucket(from.index);
+                let (ref k, ref v) = *from.as_ref();
+                to.write((k.clone(), v.clone()));
+                new_table.set_ctrl(from.index, *self.ctrl(from.index));
+                new_table.items += 1;
+            }
 
-    /// Set the table tag
-    pub fn set_tag(&mut self, value: bool) {
-        self.hashes.set_tag(value)
+            // Deleted buckets were not copied over, so the clone may have
+            // more room left than the original.
+            new_table.growth_left = bucket_mask_to_capacity(new_table.bucket_mask) - self.items;
+            new_table
+        }
     }
+}
 
-    /// Get the table tag
-    pub fn tag(&self) -> bool {
-        self.hashes.tag()
+unsafe impl<#[may_dangle] K, #[may_dangle] V> Drop for RawTable<K, V> {
+    fn drop(&mut self) {
+        if self.is_empty_singleton() {
+            return;
+        }
+
+        unsafe {
+            if needs_drop::<(K, V)>() {
+                // avoid linear runtime for types that don't need drop
+                for item in self.raw_iter() {
+                    item.drop();
+                }
+            }
+            self.free_buckets();
+        }
     }
 }
 
-/// A raw iterator. The basis for some other iterators in this module. Although
-/// this interface is safe, it's not used outside this module.
-struct RawBuckets<'a, K, V> {
-    raw: RawBucket<K, V>,
-    elems_left: usize,
-
-    // Strictly speaking, this should be &'a (K,V), but that would
-    // require that K:'a, and we often use RawBuckets<'static...> for
-    // move iterations, so that messes up a lot of other things. So
-    // just use `&'a (K,V)` as this is not a publicly exposed type
-    // anyway.
-    marker: marker::PhantomData<&'a ()>,
+/// A raw iterator over the full buckets of a table. The basis for the other
+/// iterators in this module.
+///
+/// It does not borrow the table, see `RawTable::raw_iter` for the rules
+/// that apply when using it.
+pub struct RawIter<K, V> {
+    // Pointers to the start of the buckets and control bytes of the table.
+    data: NonNull<(K, V)>,
+    ctrl: *const u8,
+
+    // The full buckets of the current group that have not been yielded yet.
+    current_group: BitMask,
+
+    // Index of the first bucket of the current group.
+    group_index: usize,
+
+    // Number of elements left to yield.
+    items: usize,
 }
 
 // FIXME(#26925) Remove in favor of `#[derive(Clone)]`
-impl<'a, K, V> Clone for RawBuckets<'a, K, V> {
-    fn clone(&self) -> RawBuckets<'a, K, V> {
-        RawBuckets {
-            raw: self.raw,
-            elems_left: self.elems_left,
-            marker: marker::PhantomData,
+impl<K, V> Clone for RawIter<K, V> {
+    fn clone(&self) -> RawIter<K, V> {
+        RawIter {
+            data: self.data,
+            ctrl: self.ctrl,
+            current_group: self.current_group,
+            group_index: self.group_index,
+            items: self.items,
         }
     }
 }
 
+impl<K, V> Iterator for RawIter<K, V> {
+    type Item = Bucket<K, V>;
 
-impl<'a, K, V> Iterator for RawBuckets<'a, K, V> {
-    type Item = RawBucket<K, V>;
-
-    fn next(&mut self) -> Option<RawBucket<K, V>> {
-        if self.elems_left == 0 {
+    #[inline]
+    fn next(&mut self) -> Option<Bucket<K, V>> {
+        // Counting the remaining items both ends the iteration as soon as the
+        // last element has been found and keeps it from running past the
+        // end of the table.
+        if self.items == 0 {
             return None;
         }
 
-        loop {
-            unsafe {
-                let item = self.raw;
-                self.raw.idx += 1;
-                if *item.hash() != EMPTY_BUCKET {
-                    self.elems_left -= 1;
-                    return Some(item);
+        unsafe {
+            loop {
+                if let Some(bit) = self.current_group.lowest_set_bit() {
+                    self.current_group = self.current_group.remove_lowest_bit();
+                    self.items -= 1;
+                    return Some(Bucket::from_base_index(self.data, self.group_index + bit));
                 }
+
+                self.group_index += GROUP_WIDTH;
+                self.current_group =
+                    Group::load_aligned(self.ctrl.add(self.group_index)).match_full();
             }
         }
     }
 
+    #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.elems_left, Some(self.elems_left))
+        (self.items, Some(self.items))
     }
 }
 
-impl<'a, K, V> ExactSizeIterator for RawBuckets<'a, K, V> {
-    fn len(&self) -> usize {
-        self.elems_left
-    }
-}
+impl<K, V> ExactSizeIterator for RawIter<K, V> {}
 
 /// Iterator over shared references to entries in a table.
 pub struct Iter<'a, K: 'a, V: 'a> {
-    iter: RawBuckets<'a, K, V>,
+    iter: RawIter<K, V>,
+    marker: marker::PhantomData<&'a (K, V)>,
 }
 
 unsafe impl<'a, K: Sync, V: Sync> Sync for Iter<'a, K, V> {}
@@ -908,15 +1364,16 @@ impl<'a, K, V> Clone for Iter<'a, K, V> {
     fn clone(&self) -> Iter<'a, K, V> {
         Iter {
             iter: self.iter.clone(),
+            marker: marker::PhantomData,
         }
     }
 }
 
 /// Iterator over mutable references to entries in a table.
 pub struct IterMut<'a, K: 'a, V: 'a> {
-    iter: RawBuckets<'a, K, V>,
+    iter: RawIter<K, V>,
     // To ensure invariance with respect to V
-    _marker: marker::PhantomData<&'a mut V>,
+    marker: marker::PhantomData<(&'a K, &'a mut V)>,
 }
 
 unsafe impl<'a, K: Sync, V: Sync> Sync for IterMut<'a, K, V> {}
@@ -928,14 +1385,17 @@ impl<'a, K: 'a, V: 'a> IterMut<'a, K, V> {
     pub fn iter(&self) -> Iter<K, V> {
         Iter {
             iter: self.iter.clone(),
+            marker: marker::PhantomData,
         }
     }
 }
 
 /// Iterator over the entries in a table, consuming the table.
 pub struct IntoIter<K, V> {
-    table: RawTable<K, V>,
-    iter: RawBuckets<'static, K, V>,
+    iter: RawIter<K, V>,
+    // The table's allocation, freed once the remaining elements are dropped.
+    alloc: Option<(NonNull<u8>, Layout)>,
+    marker: marker::PhantomData<(K, V)>,
 }
 
 unsafe impl<K: Sync, V: Sync> Sync for IntoIter<K, V> {}
@@ -945,6 +1405,25 @@ impl<K, V> IntoIter<K, V> {
     pub fn iter(&self) -> Iter<K, V> {
         Iter {
             iter: self.iter.clone(),
+            marker: marker::PhantomData,
+        }
+    }
+}
+
+unsafe impl<#[may_dangle] K, #[may_dangle] V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            // Drop all remaining elements
+            if needs_drop::<(K, V)>() {
+                while let Some(item) = self.iter.next() {
+                    item.drop();
+                }
+            }
+
+            // Free the table
+            if let Some((ptr, layout)) = self.alloc {
+                Global.dealloc(ptr.as_opaque(), layout);
+            }
         }
     }
 }
@@ -952,7 +1431,7 @@ impl<K, V> IntoIter<K, V> {
 /// Iterator over the entries in a table, clearing the table.
 pub struct Drain<'a, K: 'a, V: 'a> {
     table: NonNull<RawTable<K, V>>,
-    iter: RawBuckets<'static, K, V>,
+    iter: RawIter<K, V>,
     marker: marker::PhantomData<&'a RawTable<K, V>>,
 }
 
@@ -963,6 +1442,7 @@ impl<'a, K, V> Drain<'a, K, V> {
     pub fn iter(&self) -> Iter<K, V> {
         Iter {
             iter: self.iter.clone(),
+            marker: marker::PhantomData,
         }
     }
 }
@@ -971,9 +1451,9 @@ impl<'a, K, V> Iterator for Iter<'a, K, V> {
     type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<(&'a K, &'a V)> {
-        self.iter.next().map(|raw| unsafe {
-            let pair_ptr = raw.pair();
-            (&(*pair_ptr).0, &(*pair_ptr).1)
+        self.iter.next().map(|item| unsafe {
+            let &(ref k, ref v) = item.as_ref();
+            (k, v)
         })
     }
 
@@ -992,9 +1472,9 @@ impl<'a, K, V> Iterator for IterMut<'a, K, V> {
     type Item = (&'a K, &'a mut V);
 
     fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
-        self.iter.next().map(|raw| unsafe {
-            let pair_ptr = raw.pair();
-            (&(*pair_ptr).0, &mut (*pair_ptr).1)
+        self.iter.next().map(|item| unsafe {
+            let &mut (ref k, ref mut v) = item.as_mut();
+            (k, v)
         })
     }
 
@@ -1010,16 +1490,10 @@ impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {
 }
 
 impl<K, V> Iterator for IntoIter<K, V> {
-    type Item = (SafeHash, K, V);
+    type Item = (K, V);
 
-    fn next(&mut self) -> Option<(SafeHash, K, V)> {
-        self.iter.next().map(|raw| {
-            self.table.size -= 1;
-            unsafe {
-                let (k, v) = ptr::read(raw.pair());
-                (SafeHash { hash: *raw.hash() }, k, v)
-            }
-        })
+    fn next(&mut self) -> Option<(K, V)> {
+        self.iter.next().map(|item| unsafe { item.read() })
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -1029,21 +1503,19 @@ impl<K, V> Iterator for IntoIter<K, V> {
 
 impl<K, V> ExactSizeIterator for IntoIter<K, V> {
     fn len(&self) -> usize {
-        self.iter().len()
+        self.iter.len()
     }
 }
 
 impl<'a, K, V> Iterator for Drain<'a, K, V> {
-    type Item = (SafeHash, K, V);
+    type Item = (K, V);
 
     #[inline]
-    fn next(&mut self) -> Option<(SafeHash, K, V)> {
-        self.iter.next().map(|raw| {
-            unsafe {
-                self.table.as_mut().size -= 1;
-                let (k, v) = ptr::read(raw.pair());
-                (SafeHash { hash: ptr::replace(&mut *raw.hash(), EMPTY_BUCKET) }, k, v)
-            }
+    fn next(&mut self) -> Option<(K, V)> {
+        self.iter.next().map(|item| unsafe {
+            // Take each element out of the table as we go, so that it is
+            // still consistent if dropping one of the elements panics.
+            self.table.as_mut().remove(item)
         })
     }
 
@@ -1061,59 +1533,305 @@ impl<'a, K, V> ExactSizeIterator for Drain<'a, K, V> {
 impl<'a, K: 'a, V: 'a> Drop for Drain<'a, K, V> {
     fn drop(&mut self) {
         self.for_each(drop);
+        // Erasing left tombstones behind, reset all buckets to EMPTY.
+        unsafe {
+            self.table.as_mut().clear_no_drop();
+        }
     }
 }
 
-impl<K: Clone, V: Clone> Clone for RawTable<K, V> {
-    fn clone(&self) -> RawTable<K, V> {
-        unsafe {
-            let cap = self.capacity();
-            let mut new_ht = RawTable::new_uninitialized(cap);
-
-            let mut new_buckets = new_ht.raw_bucket_at(0);
-            let mut buckets = self.raw_bucket_at(0);
-            while buckets.idx < cap {
-                *new_buckets.hash() = *buckets.hash();
-                if *new_buckets.hash() != EMPTY_BUCKET {
-                    let pair_ptr = buckets.pair();
-                    let kv = ((*pair_ptr).0.clone(), (*pair_ptr).1.clone());
-                    ptr::write(new_buckets.pair(), kv);
-                }
-                buckets.idx += 1;
-                new_buckets.idx += 1;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cell::Cell;
+    use collections::hash_map::DefaultHasher;
+    use hash::{Hash, Hasher};
+    use panic::{self, AssertUnwindSafe};
+    use rc::Rc;
+    use usize;
+
+    thread_local! {
+        /// The number of times a `Key` can be hashed before hashing panics.
+        static HASHES_LEFT: Cell<usize> = Cell::new(usize::MAX)
+    }
 
-            new_ht.size = self.size();
-            new_ht.set_tag(self.tag());
+    /// A key that counts how often keys are dropped, and whose `Hash` impl
+    /// panics once `HASHES_LEFT` runs out.
+    struct Key {
+        id: u64,
+        drops: Rc<Cell<usize>>,
+    }
 
-            new_ht
+    impl Hash for Key {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            HASHES_LEFT.with(|left| {
+                if left.get() == 0 {
+                    panic!("hashing a key panicked");
+                }
+                left.set(left.get() - 1);
+            });
+            self.id.hash(state);
         }
     }
-}
 
-unsafe impl<#[may_dangle] K, #[may_dangle] V> Drop for RawTable<K, V> {
-    fn drop(&mut self) {
-        if self.capacity() == 0 {
-            return;
+    impl Drop for Key {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
         }
+    }
+
+    fn hash_id(id: u64) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        hasher.finish()
+    }
 
-        // This is done in reverse because we've likely partially taken
-        // some elements out with `.into_iter()` from the front.
-        // Check if the size is 0, so we don't do a useless scan when
-        // dropping empty tables such as on resize.
-        // Also avoid double drop of elements that have been already moved out.
+    fn hash_key(key: &Key) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn insert_key(table: &mut RawTable<Key, ()>, id: u64, drops: &Rc<Cell<usize>>) {
+        let key = Key { id, drops: drops.clone() };
+        table.insert(hash_id(id), (key, ()), hash_key);
+    }
+
+    fn contains_key(table: &RawTable<Key, ()>, id: u64) -> bool {
+        table.find(hash_id(id), |x| x.0.id == id).is_some()
+    }
+
+    fn remove_key(table: &mut RawTable<Key, ()>, id: u64) {
+        let bucket = table.find(hash_id(id), |x| x.0.id == id).unwrap();
         unsafe {
-            if needs_drop::<(K, V)>() {
-                // avoid linear runtime for types that don't need drop
-                self.rev_drop_buckets();
+            table.erase(bucket);
+        }
+    }
+
+    /// Checks the control bytes of `table` against its counters, returning
+    /// the number of `DELETED` buckets.
+    fn check_ctrl<K, V>(table: &RawTable<K, V>) -> usize {
+        if table.is_empty_singleton() {
+            assert_eq!(table.items, 0);
+            return 0;
+        }
+        let buckets = table.buckets();
+        let (mut full, mut deleted) = (0, 0);
+        unsafe {
+            for i in 0..buckets {
+                let ctrl = *table.ctrl(i);
+                if is_full(ctrl) {
+                    full += 1;
+                } else if ctrl == DELETED {
+                    deleted += 1;
+                } else {
+                    assert_eq!(ctrl, EMPTY);
+                }
+                // The first group is mirrored after the end of the table, or
+                // after a whole group for tables smaller than that.
+                if i < GROUP_WIDTH {
+                    assert_eq!(*table.ctrl(i + cmp::max(buckets, GROUP_WIDTH)), ctrl);
+                }
+            }
+            for i in buckets..GROUP_WIDTH {
+                assert_eq!(*table.ctrl(i), EMPTY);
             }
         }
+        assert_eq!(full, table.items);
+        assert_eq!(table.growth_left, bucket_mask_to_capacity(table.bucket_mask) - full - deleted);
+        assert_eq!(unsafe { table.raw_iter() }.count(), full);
+        deleted
+    }
+
+    /// Fills a table to capacity and then removes most of the elements
+    /// again, which leaves `DELETED` buckets behind. Returns the ids of the
+    /// remaining elements.
+    fn table_with_tombstones(table: &mut RawTable<Key, ()>, drops: &Rc<Cell<usize>>)
+        -> Vec<u64>
+    {
+        let capacity = table.capacity() as u64;
+        for id in 0..capacity {
+            insert_key(table, id, drops);
+        }
+        for id in (0..capacity).filter(|id| id % 4 != 0) {
+            remove_key(table, id);
+        }
+        assert!(check_ctrl(table) > 0);
+        (0..capacity).filter(|id| id % 4 == 0).collect()
+    }
+
+    #[test]
+    fn test_small_tables() {
+        let drops = Rc::new(Cell::new(0));
+        let mut inserted = 0;
+        for capacity in 1..GROUP_WIDTH {
+            let mut table = RawTable::with_capacity(capacity);
+            if table.buckets() >= GROUP_WIDTH {
+                break;
+            }
+            let capacity = table.capacity() as u64;
+            // Different ids land in different buckets, so a few rounds of
+            // filling and emptying the table cover the probes that wrap
+            // around into the padding after the table.
+            for round in 0..64 {
+                let ids = round * capacity..(round + 1) * capacity;
+                for id in ids.clone() {
+                    insert_key(&mut table, id, &drops);
+                    inserted += 1;
+                    check_ctrl(&table);
+                }
+                assert_eq!(table.buckets() as u64, capacity + 1);
+                for id in ids.clone() {
+                    assert!(contains_key(&table, id));
+                }
+                assert!(!contains_key(&table, (round + 1) * capacity));
+                for id in ids {
+                    remove_key(&mut table, id);
+                    check_ctrl(&table);
+                }
+            }
+            assert_eq!(table.len(), 0);
+        }
+        assert_eq!(drops.get(), inserted);
+    }
+
+    #[test]
+    fn test_rehash_in_place() {
+        let drops = Rc::new(Cell::new(0));
+        let mut table = RawTable::with_capacity(448);
+        let buckets = table.buckets();
+        let ids = table_with_tombstones(&mut table, &drops);
 
-        let (layout, _) = calculate_layout::<K, V>(self.capacity()).unwrap();
         unsafe {
-            Global.dealloc(NonNull::new_unchecked(self.hashes.ptr()).as_opaque(), layout);
-            // Remember how everything was allocated out of one buffer
-            // during initialization? We only need one call to free here.
+            table.rehash_in_place(&hash_key);
+        }
+        assert_eq!(check_ctrl(&table), 0);
+        assert_eq!(table.buckets(), buckets);
+        assert_eq!(table.len(), ids.len());
+        for id in 0..448 {
+            assert_eq!(contains_key(&table, id), id % 4 == 0);
+        }
+
+        drop(table);
+        assert_eq!(drops.get(), 448);
+    }
+
+    #[test]
+    fn test_rehash_in_place_panic() {
+        let drops = Rc::new(Cell::new(0));
+        let mut table = RawTable::with_capacity(448);
+        table_with_tombstones(&mut table, &drops);
+
+        HASHES_LEFT.with(|left| left.set(10));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+            table.rehash_in_place(&hash_key);
+        }));
+        HASHES_LEFT.with(|left| left.set(usize::MAX));
+        assert!(result.is_err());
+
+        // The elements that weren't rehashed yet are dropped, and the ones
+        // left behind can still be found.
+        assert_eq!(check_ctrl(&table), 0);
+        assert!(table.len() > 0);
+        assert_eq!(drops.get() + table.len(), 448);
+        for (key, _) in table.iter() {
+            assert!(contains_key(&table, key.id));
+        }
+
+        drop(table);
+        assert_eq!(drops.get(), 448);
+    }
+
+    #[test]
+    fn test_resize_panic() {
+        let drops = Rc::new(Cell::new(0));
+        let mut table = RawTable::with_capacity(100);
+        for id in 0..100 {
+            insert_key(&mut table, id, &drops);
+        }
+        let buckets = table.buckets();
+
+        HASHES_LEFT.with(|left| left.set(50));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            table.resize(200, &hash_key, Infallible)
+        }));
+        HASHES_LEFT.with(|left| left.set(usize::MAX));
+        assert!(result.is_err());
+
+        // The old table still owns all the elements, none of which were
+        // dropped.
+        check_ctrl(&table);
+        assert_eq!(table.buckets(), buckets);
+        assert_eq!(drops.get(), 0);
+        for id in 0..100 {
+            assert!(contains_key(&table, id));
+        }
+
+        drop(table);
+        assert_eq!(drops.get(), 100);
+    }
+
+    /// Packs 8 control bytes into a `u64` the way a little-endian target
+    /// loads them.
+    fn group_word(bytes: [u8; 8]) -> u64 {
+        bytes.iter().rev().fold(0, |word, &byte| word << 8 | byte as u64)
+    }
+
+    /// The positions of the bytes matched by a portable bit mask.
+    fn matches(mask: u64) -> Vec<usize> {
+        assert_eq!(mask & !0x8080_8080_8080_8080, 0);
+        (0..8).filter(|i| mask >> (i * 8) & 0x80 != 0).collect()
+    }
+
+    #[test]
+    fn test_portable_group() {
+        let group = group_word([EMPTY, 0x12, DELETED, 0x34, 0x12, EMPTY, 0x00, 0x7f]);
+        assert_eq!(matches(portable::match_byte(group, 0x12)), [1, 4]);
+        assert_eq!(matches(portable::match_byte(group, 0x00)), [6]);
+        assert_eq!(matches(portable::match_byte(group, 0x7f)), [7]);
+        assert!(matches(portable::match_byte(group, 0x56)).is_empty());
+        assert_eq!(matches(portable::match_empty(group)), [0, 5]);
+        assert_eq!(matches(portable::match_empty_or_deleted(group)), [0, 2, 5]);
+        assert_eq!(
+            portable::convert_special_to_empty_and_full_to_deleted(group),
+            group_word([EMPTY, DELETED, EMPTY, DELETED, DELETED, EMPTY, DELETED, DELETED])
+        );
+    }
+
+    #[test]
+    fn test_portable_match_byte_false_positives() {
+        // xorshift, to go through a lot of groups without depending on rand.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..10_000 {
+            // Small h2 values, so that matches and near misses are common.
+            let mut bytes = [0; 8];
+            for byte in &mut bytes {
+                *byte = match next() % 4 {
+                    0 => EMPTY,
+                    1 => DELETED,
+                    _ => (next() % 4) as u8,
+                };
+            }
+            let h2 = (next() % 4) as u8;
+            let found = matches(portable::match_byte(group_word(bytes), h2));
+
+            // Every true match is found, and every false positive is a full
+            // byte right after another match.
+            for i in 0..8 {
+                if bytes[i] == h2 {
+                    assert!(found.contains(&i));
+                } else if found.contains(&i) {
+                    assert!(is_full(bytes[i]));
+                    assert!(i > 0 && found.contains(&(i - 1)));
+                }
+            }
         }
     }
 }
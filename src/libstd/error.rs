@@ -24,6 +24,7 @@
 // reconsider what crate these items belong in.
 
 use any::TypeId;
+use backtrace::Backtrace;
 use borrow::Cow;
 use cell;
 use char;
@@ -80,6 +81,12 @@ pub trait Error: Debug + Display {
 
     /// The lower-level cause of this error, if any.
     ///
+    /// New code should implement and call [`source`] instead, whose result
+    /// can be downcast. The default implementation of `cause` forwards to
+    /// `source`, so implementing only `source` serves callers of either.
+    ///
+    /// [`source`]: trait.Error.html#method.source
+    ///
     /// # Examples
     ///
     /// ```
@@ -137,7 +144,128 @@ pub trait Error: Debug + Display {
     /// }
     /// ```
     #[stable(feature = "rust1", since = "1.0.0")]
-    fn cause(&self) -> Option<&Error> { None }
+    fn cause(&self) -> Option<&Error> {
+        self.source()
+    }
+
+    /// The lower-level source of this error, if any.
+    ///
+    /// Unlike [`cause`], the returned error is `'static`, so it can be
+    /// downcast to inspect the underlying error, and a whole chain of them
+    /// can be walked with [`chain`].
+    ///
+    /// [`cause`]: trait.Error.html#method.cause
+    /// [`chain`]: trait.Error.html#method.chain
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(error_source)]
+    ///
+    /// use std::error::Error;
+    /// use std::fmt;
+    ///
+    /// #[derive(Debug)]
+    /// struct SuperError {
+    ///     side: SuperErrorSideKick,
+    /// }
+    ///
+    /// impl fmt::Display for SuperError {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         write!(f, "SuperError is here!")
+    ///     }
+    /// }
+    ///
+    /// impl Error for SuperError {
+    ///     fn source(&self) -> Option<&(Error + 'static)> {
+    ///         Some(&self.side)
+    ///     }
+    /// }
+    ///
+    /// #[derive(Debug)]
+    /// struct SuperErrorSideKick;
+    ///
+    /// impl fmt::Display for SuperErrorSideKick {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         write!(f, "SuperErrorSideKick is here!")
+    ///     }
+    /// }
+    ///
+    /// impl Error for SuperErrorSideKick {}
+    ///
+    /// fn get_super_error() -> Result<(), SuperError> {
+    ///     Err(SuperError { side: SuperErrorSideKick })
+    /// }
+    ///
+    /// fn main() {
+    ///     match get_super_error() {
+    ///         Err(e) => {
+    ///             println!("Error: {}", e);
+    ///             let source = e.source().unwrap();
+    ///             println!("Caused by: {}", source);
+    ///             assert!(source.is::<SuperErrorSideKick>());
+    ///         }
+    ///         _ => println!("No error"),
+    ///     }
+    /// }
+    /// ```
+    #[unstable(feature = "error_source", issue = "0")]
+    fn source(&self) -> Option<&(Error + 'static)> {
+        None
+    }
+
+    /// Returns a stack backtrace, if available, of where this error occurred.
+    ///
+    /// This function allows inspecting the location, in code, of where an
+    /// error happened. The returned `Backtrace` contains information about the
+    /// stack trace of the OS thread of execution of where the error
+    /// originated from.
+    ///
+    /// Note that not all errors contain a `Backtrace`. Also note that a
+    /// `Backtrace` may actually be empty. For more information consult the
+    /// `Backtrace` type itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(backtrace)]
+    ///
+    /// use std::backtrace::Backtrace;
+    /// use std::error::Error;
+    /// use std::fmt;
+    ///
+    /// #[derive(Debug)]
+    /// struct ParseError {
+    ///     backtrace: Backtrace,
+    /// }
+    ///
+    /// impl ParseError {
+    ///     fn new() -> ParseError {
+    ///         ParseError { backtrace: Backtrace::capture() }
+    ///     }
+    /// }
+    ///
+    /// impl fmt::Display for ParseError {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         write!(f, "failed to parse")
+    ///     }
+    /// }
+    ///
+    /// impl Error for ParseError {
+    ///     fn backtrace(&self) -> Option<&Backtrace> {
+    ///         Some(&self.backtrace)
+    ///     }
+    /// }
+    ///
+    /// let err = ParseError::new();
+    /// if let Some(backtrace) = err.backtrace() {
+    ///     println!("{}\n{}", err, backtrace);
+    /// }
+    /// ```
+    #[unstable(feature = "backtrace", issue = "0")]
+    fn backtrace(&self) -> Option<&Backtrace> {
+        None
+    }
 
     /// Get the `TypeId` of `self`
     #[doc(hidden)]
@@ -329,6 +457,14 @@ impl<T: Error> Error for Box<T> {
     fn cause(&self) -> Option<&Error> {
         Error::cause(&**self)
     }
+
+    fn source(&self) -> Option<&(Error + 'static)> {
+        Error::source(&**self)
+    }
+
+    fn backtrace(&self) -> Option<&Backtrace> {
+        Error::backtrace(&**self)
+    }
 }
 
 #[stable(feature = "fmt_error", since = "1.11.0")]
@@ -409,6 +545,87 @@ impl Error + 'static {
             None
         }
     }
+
+    /// Returns an iterator starting with this error and continuing with
+    /// recursively calling [`source`].
+    ///
+    /// [`source`]: trait.Error.html#method.source
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(error_source)]
+    ///
+    /// use std::error::Error;
+    /// use std::fmt;
+    ///
+    /// #[derive(Debug)]
+    /// struct A;
+    ///
+    /// #[derive(Debug)]
+    /// struct B(Option<Box<Error + 'static>>);
+    ///
+    /// impl fmt::Display for A {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         write!(f, "A")
+    ///     }
+    /// }
+    ///
+    /// impl fmt::Display for B {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         write!(f, "B")
+    ///     }
+    /// }
+    ///
+    /// impl Error for A {}
+    ///
+    /// impl Error for B {
+    ///     fn source(&self) -> Option<&(Error + 'static)> {
+    ///         self.0.as_ref().map(|e| e.as_ref())
+    ///     }
+    /// }
+    ///
+    /// let b = B(Some(Box::new(A)));
+    /// let err: &(Error + 'static) = &b;
+    ///
+    /// let mut iter = err.chain();
+    ///
+    /// assert_eq!("B".to_string(), iter.next().unwrap().to_string());
+    /// assert_eq!("A".to_string(), iter.next().unwrap().to_string());
+    /// assert!(iter.next().is_none());
+    /// assert!(iter.next().is_none());
+    /// ```
+    #[unstable(feature = "error_source", issue = "0")]
+    #[inline]
+    pub fn chain(&self) -> Chain {
+        Chain {
+            current: Some(self),
+        }
+    }
+}
+
+/// An iterator over an [`Error`] and its sources.
+///
+/// This `struct` is created by the [`chain`] method on [`Error`]. See its
+/// documentation for more.
+///
+/// [`Error`]: trait.Error.html
+/// [`chain`]: trait.Error.html#method.chain
+#[unstable(feature = "error_source", issue = "0")]
+#[derive(Clone, Debug)]
+pub struct Chain<'a> {
+    current: Option<&'a (Error + 'static)>,
+}
+
+#[unstable(feature = "error_source", issue = "0")]
+impl<'a> Iterator for Chain<'a> {
+    type Item = &'a (Error + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.current;
+        self.current = self.current.and_then(|e| e.source());
+        current
+    }
 }
 
 impl Error + 'static + Send {
@@ -505,6 +722,7 @@ impl Error + Send + Sync {
 mod tests {
     use super::Error;
     use fmt;
+    use io;
 
     #[derive(Debug, PartialEq)]
     struct A;
@@ -544,4 +762,38 @@ mod tests {
             Err(e) => assert_eq!(*e.downcast::<A>().unwrap(), A),
         }
     }
+
+    #[derive(Debug)]
+    struct C(Box<Error + Send + Sync>);
+
+    impl fmt::Display for C {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "C")
+        }
+    }
+
+    impl Error for C {
+        fn source(&self) -> Option<&(Error + 'static)> {
+            Some(&*self.0)
+        }
+    }
+
+    #[test]
+    fn source_chain() {
+        let c = C(Box::new(A));
+        assert!(c.source().unwrap().is::<A>());
+        assert_eq!(c.cause().unwrap().to_string(), "A");
+
+        let nested = C(Box::new(C(Box::new(B))));
+        let chain: Vec<String> = (&nested as &(Error + 'static)).chain()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(chain, ["C", "C", "B"]);
+
+        let io = io::Error::new(io::ErrorKind::Other, nested);
+        let sources: Vec<String> = (&io as &(Error + 'static)).chain()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(sources, ["C", "C", "B"]);
+    }
 }
@@ -19,7 +19,7 @@
 
 use fmt;
 use ffi::OsString;
-use io::{self, SeekFrom, Seek, Read, Initializer, Write};
+use io::{self, SeekFrom, Seek, Read, Initializer, Write, IoSlice, IoSliceMut};
 use path::{Path, PathBuf};
 use sys::fs as fs_imp;
 use sys_common::{AsInnerMut, FromInner, AsInner, IntoInner};
@@ -220,6 +220,49 @@ pub struct DirBuilder {
     recursive: bool,
 }
 
+/// An open handle to a directory.
+///
+/// Paths passed to the methods of a `Dir` are resolved relative to the
+/// directory itself rather than to the current working directory. Once a
+/// directory has been opened, renaming or replacing it, or any directory
+/// above it, has no effect on what its methods operate on. This makes it
+/// possible to walk and modify a directory tree without racing against
+/// other processes swapping parts of it for symbolic links.
+///
+/// # Platform-specific behavior
+///
+/// On Unix this holds a file descriptor for the directory, and its methods
+/// correspond to `openat`, `mkdirat`, `unlinkat`, `fstatat`, `fdopendir` and
+/// `renameat`. Those are missing before macOS 10.10 and iOS 8, where every
+/// method fails with `ENOSYS`. On other platforms it currently only remembers
+/// the path it was opened with, and so provides none of the guarantees above.
+/// Note that, this [may change in the future][changes].
+///
+/// [changes]: ../io/index.html#platform-specific-behavior
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(fs_dir)]
+/// use std::fs::Dir;
+///
+/// fn main() -> std::io::Result<()> {
+///     let dir = Dir::open("/var/spool/jobs")?;
+///     for entry in dir.read_dir()? {
+///         let name = entry?.file_name();
+///         if dir.metadata_at(&name)?.is_file() {
+///             dir.remove_at(&name)?;
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "fs_dir", issue = "0")]
+#[derive(Debug)]
+pub struct Dir {
+    inner: fs_imp::Dir,
+}
+
 /// How large a buffer to pre-allocate before reading the entire file.
 fn initial_buffer_size(file: &File) -> usize {
     // Allocate one extra byte so the buffer doesn't need to grow before the
@@ -608,6 +651,10 @@ impl Read for File {
         self.inner.read(buf)
     }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+
     #[inline]
     unsafe fn initializer(&self) -> Initializer {
         Initializer::nop()
@@ -618,6 +665,11 @@ impl Write for File {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.inner.write(buf)
     }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> { self.inner.flush() }
 }
 #[stable(feature = "rust1", since = "1.0.0")]
@@ -632,6 +684,10 @@ impl<'a> Read for &'a File {
         self.inner.read(buf)
     }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+
     #[inline]
     unsafe fn initializer(&self) -> Initializer {
         Initializer::nop()
@@ -642,6 +698,11 @@ impl<'a> Write for &'a File {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.inner.write(buf)
     }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> { self.inner.flush() }
 }
 #[stable(feature = "rust1", since = "1.0.0")]
@@ -1056,12 +1117,15 @@ impl Metadata {
     /// Returns the creation time listed in this metadata.
     ///
     /// The returned value corresponds to the `birthtime` field of `stat` on
-    /// Unix platforms and the `ftCreationTime` field on Windows platforms.
+    /// Unix platforms, the `stx_btime` field of `statx` on Linux, and the
+    /// `ftCreationTime` field on Windows platforms.
     ///
     /// # Errors
     ///
     /// This field may not be available on all platforms, and will return an
-    /// `Err` on platforms where it is not available.
+    /// `Err` on platforms where it is not available. On Linux it is only
+    /// available with kernels that support `statx`, and on filesystems that
+    /// record it.
     ///
     /// # Examples
     ///
@@ -1855,12 +1919,15 @@ pub fn remove_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
 ///
 /// # Platform-specific behavior
 ///
-/// This function currently corresponds to `opendir`, `lstat`, `rm` and `rmdir` functions on Unix
-/// and the `FindFirstFile`, `GetFileAttributesEx`, `DeleteFile`, and `RemoveDirectory` functions
-/// on Windows.
+/// This function currently corresponds to `openat`, `fdopendir`, `unlinkat` and `rmdir` functions
+/// on Unix and the `FindFirstFile`, `GetFileAttributesEx`, `DeleteFile`, and `RemoveDirectory`
+/// functions on Windows. On Unix the contents are only ever accessed relative to directories
+/// that were opened without following symbolic links, see [`Dir`], except on macOS before 10.10
+/// and iOS before 8, which lack those functions and fall back to `lstat`, `unlink` and `rmdir`.
 /// Note that, this [may change in the future][changes].
 ///
 /// [changes]: ../io/index.html#platform-specific-behavior
+/// [`Dir`]: struct.Dir.html
 ///
 /// # Errors
 ///
@@ -2069,11 +2136,136 @@ impl AsInnerMut<fs_imp::DirBuilder> for DirBuilder {
     }
 }
 
+impl Dir {
+    /// Opens the directory at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `path` does not exist or is not
+    /// a directory, or if the process lacks permission to open it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(fs_dir)]
+    /// use std::fs::Dir;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("/tmp")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "fs_dir", issue = "0")]
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Dir> {
+        fs_imp::Dir::open(path.as_ref()).map(|inner| Dir { inner })
+    }
+
+    /// Opens the directory at `path` relative to this one.
+    ///
+    /// Unlike [`Dir::open`], this fails if the last component of `path` is a
+    /// symbolic link rather than following it.
+    ///
+    /// [`Dir::open`]: #method.open
+    #[unstable(feature = "fs_dir", issue = "0")]
+    pub fn open_dir_at<P: AsRef<Path>>(&self, path: P) -> io::Result<Dir> {
+        self.inner.open_dir_at(path.as_ref()).map(|inner| Dir { inner })
+    }
+
+    /// Opens the file at `path` relative to this directory with the options
+    /// in `opts`.
+    ///
+    /// This is the equivalent of [`OpenOptions::open`]; a symbolic link in the
+    /// last component of `path` is followed unless the platform-specific
+    /// options say otherwise (`O_NOFOLLOW` on Unix).
+    ///
+    /// [`OpenOptions::open`]: struct.OpenOptions.html#method.open
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(fs_dir)]
+    /// use std::fs::{Dir, OpenOptions};
+    /// use std::io::Write;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let dir = Dir::open("/tmp")?;
+    ///     let mut file = dir.open_file_at("foo.txt",
+    ///                                     OpenOptions::new().write(true).create(true))?;
+    ///     file.write_all(b"hello")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "fs_dir", issue = "0")]
+    pub fn open_file_at<P: AsRef<Path>>(&self, path: P, opts: &OpenOptions) -> io::Result<File> {
+        self.inner.open_file_at(path.as_ref(), &opts.0).map(|inner| File { inner })
+    }
+
+    /// Creates a new, empty directory at `path` relative to this directory.
+    ///
+    /// See [`create_dir`] for the errors this can return.
+    ///
+    /// [`create_dir`]: fn.create_dir.html
+    #[unstable(feature = "fs_dir", issue = "0")]
+    pub fn create_dir_at<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.inner.create_dir_at(path.as_ref())
+    }
+
+    /// Removes the file, symbolic link or empty directory at `path` relative
+    /// to this directory.
+    ///
+    /// A symbolic link is removed itself; its target is left alone.
+    #[unstable(feature = "fs_dir", issue = "0")]
+    pub fn remove_at<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.inner.remove_at(path.as_ref())
+    }
+
+    /// Queries the metadata of `path` relative to this directory.
+    ///
+    /// Like [`symlink_metadata`], this does not follow a symbolic link in the
+    /// last component of `path`.
+    ///
+    /// [`symlink_metadata`]: fn.symlink_metadata.html
+    #[unstable(feature = "fs_dir", issue = "0")]
+    pub fn metadata_at<P: AsRef<Path>>(&self, path: P) -> io::Result<Metadata> {
+        self.inner.metadata_at(path.as_ref()).map(Metadata)
+    }
+
+    /// Returns an iterator over the entries within this directory.
+    ///
+    /// The paths of the entries are built from the path the directory was
+    /// opened with, and are only meant for display; use
+    /// [`DirEntry::file_name`] with the `*_at` methods to operate on them.
+    ///
+    /// [`DirEntry::file_name`]: struct.DirEntry.html#method.file_name
+    #[unstable(feature = "fs_dir", issue = "0")]
+    pub fn read_dir(&self) -> io::Result<ReadDir> {
+        self.inner.read_dir().map(ReadDir)
+    }
+
+    /// Renames `from` relative to this directory to `to` relative to `to_dir`,
+    /// replacing the original if it exists.
+    ///
+    /// See [`rename`] for the errors this can return.
+    ///
+    /// [`rename`]: fn.rename.html
+    #[unstable(feature = "fs_dir", issue = "0")]
+    pub fn rename_at<P, Q>(&self, from: P, to_dir: &Dir, to: Q) -> io::Result<()>
+        where P: AsRef<Path>, Q: AsRef<Path>
+    {
+        self.inner.rename_at(from.as_ref(), &to_dir.inner, to.as_ref())
+    }
+}
+
+impl AsInner<fs_imp::Dir> for Dir {
+    fn as_inner(&self) -> &fs_imp::Dir { &self.inner }
+}
+
 #[cfg(all(test, not(any(target_os = "cloudabi", target_os = "emscripten"))))]
 mod tests {
     use io::prelude::*;
 
-    use fs::{self, File, OpenOptions};
+    use ffi::OsString;
+    use fs::{self, Dir, File, OpenOptions};
     use io::{ErrorKind, SeekFrom};
     use path::Path;
     use rand::{StdRng, Rng};
@@ -2658,6 +2850,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dir_at_operations() {
+        let tmpdir = tmpdir();
+        let dir = check!(Dir::open(tmpdir.path()));
+
+        check!(dir.create_dir_at("sub"));
+        let sub = check!(dir.open_dir_at("sub"));
+        let mut opts = OpenOptions::new();
+        opts.write(true).create_new(true);
+        check!(check!(sub.open_file_at("foo", &opts)).write(b"foo"));
+        assert_eq!(check!(sub.metadata_at("foo")).len(), 3);
+        assert!(check!(dir.metadata_at("sub")).is_dir());
+
+        let names = check!(sub.read_dir())
+            .map(|e| check!(e).file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![OsString::from("foo")]);
+
+        check!(sub.rename_at("foo", &dir, "bar"));
+        assert!(tmpdir.join("bar").is_file());
+        assert!(!tmpdir.join("sub/foo").exists());
+
+        check!(dir.remove_at("bar"));
+        check!(dir.remove_at("sub"));
+        assert!(!tmpdir.join("bar").exists());
+        assert!(!tmpdir.join("sub").exists());
+    }
+
+    #[test]
+    fn dir_open_dir_at_does_not_follow_symlinks() {
+        let tmpdir = tmpdir();
+        if !got_symlink_permission(&tmpdir) { return };
+
+        check!(fs::create_dir(tmpdir.join("target")));
+        check!(symlink_dir(tmpdir.join("target"), tmpdir.join("link")));
+        let dir = check!(Dir::open(tmpdir.path()));
+        assert!(dir.open_dir_at("link").is_err());
+        assert!(check!(dir.metadata_at("link")).file_type().is_symlink());
+        check!(dir.remove_at("link"));
+        assert!(tmpdir.join("target").is_dir());
+    }
+
     #[test]
     fn unicode_path_is_dir() {
         assert!(Path::new(".").is_dir());
@@ -3331,4 +3565,26 @@ mod tests {
             check!(b.created());
         }
     }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn metadata_statx_birth_time() {
+        use os::linux::fs::MetadataExt;
+        use time::UNIX_EPOCH;
+
+        let tmpdir = tmpdir();
+        let path = tmpdir.join("b");
+        check!(File::create(&path));
+
+        for meta in &[check!(fs::metadata(&path)), check!(check!(File::open(&path)).metadata())] {
+            match meta.st_btime() {
+                Some(secs) => {
+                    let created = check!(check!(meta.created()).duration_since(UNIX_EPOCH));
+                    assert_eq!(created.as_secs() as i64, secs);
+                    assert_eq!(created.subsec_nanos() as i64, meta.st_btime_nsec().unwrap());
+                }
+                None => assert!(meta.created().is_err()),
+            }
+        }
+    }
 }
@@ -15,7 +15,8 @@ use io::prelude::*;
 use cmp;
 use error;
 use fmt;
-use io::{self, Initializer, DEFAULT_BUF_SIZE, Error, ErrorKind, SeekFrom};
+use io::{self, Initializer, DEFAULT_BUF_SIZE, Error, ErrorKind, SeekFrom, IoSlice,
+         IoSliceMut};
 use memchr;
 
 /// The `BufReader` struct adds buffering to any reader.
@@ -271,6 +272,19 @@ impl<R: Read> Read for BufReader<R> {
         Ok(nread)
     }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        let total_len = bufs.iter().map(|b| b.len()).sum::<usize>();
+        if self.pos == self.cap && total_len >= self.buf.len() {
+            return self.inner.read_vectored(bufs);
+        }
+        let nread = {
+            let mut rem = self.fill_buf()?;
+            rem.read_vectored(bufs)?
+        };
+        self.consume(nread);
+        Ok(nread)
+    }
+
     // we can't skip unconditionally because of the large buffer case in read.
     unsafe fn initializer(&self) -> Initializer {
         self.inner.initializer()
@@ -595,6 +609,22 @@ impl<W: Write> Write for BufWriter<W> {
             Write::write(&mut self.buf, buf)
         }
     }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        let total_len = bufs.iter().map(|b| b.len()).sum::<usize>();
+        if self.buf.len() + total_len > self.buf.capacity() {
+            self.flush_buf()?;
+        }
+        if total_len >= self.buf.capacity() {
+            self.panicked = true;
+            let r = self.inner.as_mut().unwrap().write_vectored(bufs);
+            self.panicked = false;
+            r
+        } else {
+            Write::write_vectored(&mut self.buf, bufs)
+        }
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         self.flush_buf().and_then(|()| self.get_mut().flush())
     }
@@ -951,7 +981,7 @@ impl<W: Write> fmt::Debug for LineWriter<W> where W: fmt::Debug {
 #[cfg(test)]
 mod tests {
     use io::prelude::*;
-    use io::{self, BufReader, BufWriter, LineWriter, SeekFrom};
+    use io::{self, BufReader, BufWriter, LineWriter, SeekFrom, IoSlice, IoSliceMut};
     use sync::atomic::{AtomicUsize, Ordering};
     use thread;
     use test;
@@ -1120,6 +1150,48 @@ mod tests {
         assert_eq!(*writer.get_ref(), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
     }
 
+    #[test]
+    fn test_buffered_writer_vectored() {
+        let mut writer = BufWriter::with_capacity(4, Vec::new());
+
+        let n = writer.write_vectored(&[IoSlice::new(&[0]), IoSlice::new(&[1, 2])]).unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(*writer.get_ref(), []);
+
+        let n = writer.write_vectored(&[IoSlice::new(&[3, 4])]).unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(*writer.get_ref(), [0, 1, 2]);
+
+        let n = writer.write_vectored(&[IoSlice::new(&[5, 6, 7]),
+                                        IoSlice::new(&[8, 9])]).unwrap();
+        assert_eq!(n, 5);
+        assert_eq!(*writer.get_ref(), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        writer.flush().unwrap();
+        assert_eq!(*writer.get_ref(), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_buffered_reader_vectored() {
+        let inner: &[u8] = &[5, 6, 7, 0, 1, 2, 3, 4];
+        let mut reader = BufReader::with_capacity(2, inner);
+
+        let mut buf1 = [0; 1];
+        let mut buf2 = [0; 2];
+        let n = reader.read_vectored(&mut [IoSliceMut::new(&mut buf1),
+                                           IoSliceMut::new(&mut buf2)]).unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(buf1, [5]);
+        assert_eq!(buf2, [6, 7]);
+        assert!(reader.buffer().is_empty());
+
+        let mut buf1 = [0; 1];
+        let n = reader.read_vectored(&mut [IoSliceMut::new(&mut buf1)]).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(buf1, [0]);
+        assert_eq!(reader.buffer(), [1]);
+    }
+
     #[test]
     fn test_buffered_writer_inner_flushes() {
         let mut w = BufWriter::with_capacity(3, Vec::new());
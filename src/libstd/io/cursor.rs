@@ -11,7 +11,7 @@
 use io::prelude::*;
 
 use cmp;
-use io::{self, Initializer, SeekFrom, Error, ErrorKind};
+use io::{self, Initializer, SeekFrom, Error, ErrorKind, IoSlice, IoSliceMut};
 
 /// A `Cursor` wraps another type and provides it with a
 /// [`Seek`] implementation.
@@ -229,6 +229,18 @@ impl<T> Read for Cursor<T> where T: AsRef<[u8]> {
         Ok(n)
     }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        let mut nread = 0;
+        for buf in bufs {
+            let n = self.read(buf)?;
+            nread += n;
+            if n < buf.len() {
+                break;
+            }
+        }
+        Ok(nread)
+    }
+
     fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
         let n = buf.len();
         Read::read_exact(&mut self.fill_buf()?, buf)?;
@@ -259,6 +271,23 @@ fn slice_write(pos_mut: &mut u64, slice: &mut [u8], buf: &[u8]) -> io::Result<us
     Ok(amt)
 }
 
+fn slice_write_vectored(
+    pos_mut: &mut u64,
+    slice: &mut [u8],
+    bufs: &[IoSlice],
+) -> io::Result<usize>
+{
+    let mut nwritten = 0;
+    for buf in bufs {
+        let n = slice_write(pos_mut, slice, buf)?;
+        nwritten += n;
+        if n < buf.len() {
+            break;
+        }
+    }
+    Ok(nwritten)
+}
+
 /// Compensate removal of some impls per
 /// https://github.com/rust-lang/rust/pull/49305#issuecomment-376293243
 #[cfg(any(target_pointer_width = "16",
@@ -303,12 +332,31 @@ fn vec_write(pos_mut: &mut u64, vec: &mut Vec<u8>, buf: &[u8]) -> io::Result<usi
     Ok(buf.len())
 }
 
+fn vec_write_vectored(
+    pos_mut: &mut u64,
+    vec: &mut Vec<u8>,
+    bufs: &[IoSlice],
+) -> io::Result<usize>
+{
+    let mut nwritten = 0;
+    for buf in bufs {
+        nwritten += vec_write(pos_mut, vec, buf)?;
+    }
+    Ok(nwritten)
+}
+
 #[stable(feature = "rust1", since = "1.0.0")]
 impl<'a> Write for Cursor<&'a mut [u8]> {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         slice_write(&mut self.pos, self.inner, buf)
     }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        slice_write_vectored(&mut self.pos, self.inner, bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 }
 
@@ -317,6 +365,11 @@ impl<'a> Write for Cursor<&'a mut Vec<u8>> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         vec_write(&mut self.pos, self.inner, buf)
     }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        vec_write_vectored(&mut self.pos, self.inner, bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 }
 
@@ -325,6 +378,11 @@ impl Write for Cursor<Vec<u8>> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         vec_write(&mut self.pos, &mut self.inner, buf)
     }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        vec_write_vectored(&mut self.pos, &mut self.inner, bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 }
 
@@ -334,13 +392,19 @@ impl Write for Cursor<Box<[u8]>> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         slice_write(&mut self.pos, &mut self.inner, buf)
     }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        slice_write_vectored(&mut self.pos, &mut self.inner, bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 }
 
 #[cfg(test)]
 mod tests {
     use io::prelude::*;
-    use io::{Cursor, SeekFrom};
+    use io::{Cursor, SeekFrom, IoSlice, IoSliceMut};
 
     #[test]
     fn test_vec_writer() {
@@ -362,6 +426,18 @@ mod tests {
         assert_eq!(&writer.get_ref()[..], b);
     }
 
+    #[test]
+    fn test_mem_writer_vectored() {
+        let mut writer = Cursor::new(Vec::new());
+        assert_eq!(writer.write_vectored(&[IoSlice::new(&[0])]).unwrap(), 1);
+        assert_eq!(writer.write_vectored(&[IoSlice::new(&[1, 2]),
+                                           IoSlice::new(&[]),
+                                           IoSlice::new(&[3])]).unwrap(), 3);
+        assert_eq!(writer.position(), 4);
+        let b: &[_] = &[0, 1, 2, 3];
+        assert_eq!(&writer.get_ref()[..], b);
+    }
+
     #[test]
     fn test_mem_mut_writer() {
         let mut vec = Vec::new();
@@ -450,6 +526,22 @@ mod tests {
         assert_eq!(writer.write(&[0, 0]).unwrap(), 0);
     }
 
+    #[test]
+    fn test_buf_writer_vectored() {
+        let mut buf = [0 as u8; 5];
+        {
+            let mut writer = Cursor::new(&mut buf[..]);
+            assert_eq!(writer.write_vectored(&[IoSlice::new(&[1, 2]),
+                                               IoSlice::new(&[3])]).unwrap(), 3);
+            assert_eq!(writer.write_vectored(&[IoSlice::new(&[4, 5, 6]),
+                                               IoSlice::new(&[7])]).unwrap(), 2);
+            assert_eq!(writer.position(), 5);
+            assert_eq!(writer.write_vectored(&[IoSlice::new(&[8])]).unwrap(), 0);
+        }
+        let b: &[_] = &[1, 2, 3, 4, 5];
+        assert_eq!(buf, b);
+    }
+
     #[test]
     fn test_mem_reader() {
         let mut reader = Cursor::new(vec![0, 1, 2, 3, 4, 5, 6, 7]);
@@ -472,6 +564,22 @@ mod tests {
         assert_eq!(reader.read(&mut buf).unwrap(), 0);
     }
 
+    #[test]
+    fn test_mem_reader_vectored() {
+        let mut reader = Cursor::new(vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        let mut buf1 = [0; 2];
+        let mut buf2 = [0; 4];
+        assert_eq!(reader.read_vectored(&mut [IoSliceMut::new(&mut buf1),
+                                              IoSliceMut::new(&mut buf2)]).unwrap(), 6);
+        assert_eq!(reader.position(), 6);
+        assert_eq!(buf1, [0, 1]);
+        assert_eq!(buf2, [2, 3, 4, 5]);
+        assert_eq!(reader.read_vectored(&mut [IoSliceMut::new(&mut buf1),
+                                              IoSliceMut::new(&mut buf2)]).unwrap(), 2);
+        assert_eq!(buf1, [6, 7]);
+        assert_eq!(reader.read_vectored(&mut [IoSliceMut::new(&mut buf1)]).unwrap(), 0);
+    }
+
     #[test]
     fn test_boxed_slice_reader() {
         let mut reader = Cursor::new(vec![0, 1, 2, 3, 4, 5, 6, 7].into_boxed_slice());
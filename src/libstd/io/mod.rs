@@ -273,10 +273,12 @@ use cmp;
 use core::str as core_str;
 use error as std_error;
 use fmt;
+use ops::{Deref, DerefMut};
 use result;
 use str;
 use memchr;
 use ptr;
+use sys;
 
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::buffered::{BufReader, BufWriter, LineWriter};
@@ -393,6 +395,28 @@ fn read_to_end<R: Read + ?Sized>(r: &mut R, buf: &mut Vec<u8>) -> Result<usize>
     ret
 }
 
+pub(crate) fn default_read_vectored<F>(read: F, bufs: &mut [IoSliceMut]) -> Result<usize>
+where
+    F: FnOnce(&mut [u8]) -> Result<usize>
+{
+    let buf = bufs
+        .iter_mut()
+        .find(|b| !b.is_empty())
+        .map_or(&mut [][..], |b| &mut **b);
+    read(buf)
+}
+
+pub(crate) fn default_write_vectored<F>(write: F, bufs: &[IoSlice]) -> Result<usize>
+where
+    F: FnOnce(&[u8]) -> Result<usize>
+{
+    let buf = bufs
+        .iter()
+        .find(|b| !b.is_empty())
+        .map_or(&[][..], |b| &**b);
+    write(buf)
+}
+
 /// The `Read` trait allows for reading bytes from a source.
 ///
 /// Implementors of the `Read` trait are called 'readers'.
@@ -525,6 +549,19 @@ pub trait Read {
     #[stable(feature = "rust1", since = "1.0.0")]
     fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
 
+    /// Like `read`, except that it reads into a slice of buffers.
+    ///
+    /// Data is copied to fill each buffer in order, with the final buffer
+    /// written to possibly being only partially filled. This method must behave
+    /// as a single call to `read` with the buffers concatenated would.
+    ///
+    /// The default implementation calls `read` with either the first nonempty
+    /// buffer provided, or an empty one if none exists.
+    #[unstable(feature = "iovec", issue = "0")]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> Result<usize> {
+        default_read_vectored(|b| self.read(b), bufs)
+    }
+
     /// Determines if this `Read`er can work with buffers of uninitialized
     /// memory.
     ///
@@ -919,6 +956,104 @@ pub trait Read {
     }
 }
 
+/// A buffer type used with `Read::read_vectored`.
+///
+/// It is semantically a wrapper around an `&mut [u8]`, but is guaranteed to be
+/// ABI compatible with the `iovec` type on Unix platforms and `WSABUF` on
+/// Windows.
+#[unstable(feature = "iovec", issue = "0")]
+#[repr(transparent)]
+pub struct IoSliceMut<'a>(sys::io::IoSliceMut<'a>);
+
+#[unstable(feature = "iovec", issue = "0")]
+unsafe impl<'a> Send for IoSliceMut<'a> {}
+
+#[unstable(feature = "iovec", issue = "0")]
+unsafe impl<'a> Sync for IoSliceMut<'a> {}
+
+#[unstable(feature = "iovec", issue = "0")]
+impl<'a> fmt::Debug for IoSliceMut<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.0.as_slice(), fmt)
+    }
+}
+
+impl<'a> IoSliceMut<'a> {
+    /// Creates a new `IoSliceMut` wrapping a byte slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics on Windows if the slice is larger than 4GB.
+    #[unstable(feature = "iovec", issue = "0")]
+    #[inline]
+    pub fn new(buf: &'a mut [u8]) -> IoSliceMut<'a> {
+        IoSliceMut(sys::io::IoSliceMut::new(buf))
+    }
+}
+
+#[unstable(feature = "iovec", issue = "0")]
+impl<'a> Deref for IoSliceMut<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+#[unstable(feature = "iovec", issue = "0")]
+impl<'a> DerefMut for IoSliceMut<'a> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.0.as_mut_slice()
+    }
+}
+
+/// A buffer type used with `Write::write_vectored`.
+///
+/// It is semantically a wrapper around an `&[u8]`, but is guaranteed to be
+/// ABI compatible with the `iovec` type on Unix platforms and `WSABUF` on
+/// Windows.
+#[unstable(feature = "iovec", issue = "0")]
+#[repr(transparent)]
+pub struct IoSlice<'a>(sys::io::IoSlice<'a>);
+
+#[unstable(feature = "iovec", issue = "0")]
+unsafe impl<'a> Send for IoSlice<'a> {}
+
+#[unstable(feature = "iovec", issue = "0")]
+unsafe impl<'a> Sync for IoSlice<'a> {}
+
+#[unstable(feature = "iovec", issue = "0")]
+impl<'a> fmt::Debug for IoSlice<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.0.as_slice(), fmt)
+    }
+}
+
+impl<'a> IoSlice<'a> {
+    /// Creates a new `IoSlice` wrapping a byte slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics on Windows if the slice is larger than 4GB.
+    #[unstable(feature = "iovec", issue = "0")]
+    #[inline]
+    pub fn new(buf: &'a [u8]) -> IoSlice<'a> {
+        IoSlice(sys::io::IoSlice::new(buf))
+    }
+}
+
+#[unstable(feature = "iovec", issue = "0")]
+impl<'a> Deref for IoSlice<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
 /// A type used to conditionally initialize buffers passed to `Read` methods.
 #[unstable(feature = "read_initializer", issue = "42788")]
 #[derive(Debug)]
@@ -1049,6 +1184,19 @@ pub trait Write {
     #[stable(feature = "rust1", since = "1.0.0")]
     fn write(&mut self, buf: &[u8]) -> Result<usize>;
 
+    /// Like `write`, except that it writes from a slice of buffers.
+    ///
+    /// Data is copied from each buffer in order, with the final buffer
+    /// read from possibly being only partially consumed. This method must
+    /// behave as a call to `write` with the buffers concatenated would.
+    ///
+    /// The default implementation calls `write` with either the first nonempty
+    /// buffer provided, or an empty one if none exists.
+    #[unstable(feature = "iovec", issue = "0")]
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> Result<usize> {
+        default_write_vectored(|b| self.write(b), bufs)
+    }
+
     /// Flush this output stream, ensuring that all intermediately buffered
     /// contents reach their destination.
     ///
@@ -1749,6 +1897,16 @@ impl<T: Read, U: Read> Read for Chain<T, U> {
         self.second.read(buf)
     }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> Result<usize> {
+        if !self.done_first {
+            match self.first.read_vectored(bufs)? {
+                0 if bufs.iter().any(|b| !b.is_empty()) => self.done_first = true,
+                n => return Ok(n),
+            }
+        }
+        self.second.read_vectored(bufs)
+    }
+
     unsafe fn initializer(&self) -> Initializer {
         let initializer = self.first.initializer();
         if initializer.should_initialize() {
@@ -2087,6 +2245,12 @@ impl std_error::Error for CharsError {
             CharsError::Other(ref e) => e.cause(),
         }
     }
+    fn source(&self) -> Option<&(std_error::Error + 'static)> {
+        match *self {
+            CharsError::NotUtf8 => None,
+            CharsError::Other(ref e) => e.source(),
+        }
+    }
 }
 
 #[unstable(feature = "io", reason = "awaiting stability of Read::chars",
@@ -2174,7 +2338,7 @@ impl<B: BufRead> Iterator for Lines<B> {
 mod tests {
     use io::prelude::*;
     use io;
-    use super::Cursor;
+    use super::{Cursor, IoSlice, IoSliceMut};
     use test;
     use super::repeat;
 
@@ -2389,6 +2553,63 @@ mod tests {
         assert_eq!("AB", s);
     }
 
+    #[test]
+    fn chain_read_vectored() {
+        let mut chain = (&b"ABC"[..]).chain(&b"DEF"[..]);
+        let mut a = [0; 2];
+        let mut b = [0; 2];
+        let n = chain.read_vectored(&mut [IoSliceMut::new(&mut []),
+                                          IoSliceMut::new(&mut a),
+                                          IoSliceMut::new(&mut b)]).unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(&a, b"AB");
+        assert_eq!(&b[..1], b"C");
+        let n = chain.read_vectored(&mut [IoSliceMut::new(&mut a),
+                                          IoSliceMut::new(&mut b)]).unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(&a, b"DE");
+        assert_eq!(&b[..1], b"F");
+        assert_eq!(chain.read_vectored(&mut [IoSliceMut::new(&mut a)]).unwrap(), 0);
+    }
+
+    #[test]
+    fn default_read_vectored() {
+        // `Take` only implements `read`, so this goes through the default
+        // implementation, which reads into the first nonempty buffer.
+        let mut r = io::repeat(7).take(4);
+        let mut a = [0; 3];
+        let mut b = [0; 3];
+        let n = r.read_vectored(&mut [IoSliceMut::new(&mut []),
+                                      IoSliceMut::new(&mut a),
+                                      IoSliceMut::new(&mut b)]).unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(a, [7; 3]);
+        assert_eq!(b, [0; 3]);
+        assert_eq!(r.read_vectored(&mut []).unwrap(), 0);
+    }
+
+    #[test]
+    fn default_write_vectored() {
+        struct OneByOne(Vec<u8>);
+
+        impl Write for OneByOne {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.extend(buf.iter().take(1));
+                Ok(buf.len().min(1))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut w = OneByOne(Vec::new());
+        let n = w.write_vectored(&[IoSlice::new(&[]), IoSlice::new(b"ab")]).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(w.0, b"a");
+        assert_eq!(w.write_vectored(&[]).unwrap(), 0);
+    }
+
     #[bench]
     #[cfg_attr(target_os = "emscripten", ignore)]
     fn bench_read_to_end(b: &mut test::Bencher) {
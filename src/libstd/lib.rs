@@ -287,6 +287,7 @@
 #![feature(ptr_internals)]
 #![feature(rand)]
 #![feature(raw)]
+#![feature(repr_transparent)]
 #![feature(rustc_attrs)]
 #![feature(std_internals)]
 #![feature(stdsimd)]
@@ -463,6 +464,7 @@ pub mod f64;
 #[macro_use]
 pub mod thread;
 pub mod ascii;
+pub mod backtrace;
 pub mod collections;
 pub mod env;
 pub mod error;
@@ -327,6 +327,193 @@ pub trait MetadataExt {
     /// ```
     #[stable(feature = "metadata_ext2", since = "1.8.0")]
     fn st_blocks(&self) -> u64;
+    /// Returns the creation ("birth") time of the file in seconds since the
+    /// Unix epoch.
+    ///
+    /// This is only available if the kernel supports `statx` (Linux 4.11 and
+    /// later) and the filesystem records creation times; otherwise `None` is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(metadata_statx)]
+    /// use std::fs;
+    /// use std::io;
+    /// use std::os::linux::fs::MetadataExt;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("some_file")?;
+    ///     if let Some(btime) = meta.st_btime() {
+    ///         println!("created at {}", btime);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "metadata_statx", issue = "0")]
+    fn st_btime(&self) -> Option<i64> {
+        None
+    }
+    /// Returns the nanosecond part of the creation time, when
+    /// [`st_btime`] returns `Some`.
+    ///
+    /// [`st_btime`]: #method.st_btime
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(metadata_statx)]
+    /// use std::fs;
+    /// use std::io;
+    /// use std::os::linux::fs::MetadataExt;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("some_file")?;
+    ///     println!("{:?}", meta.st_btime_nsec());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "metadata_statx", issue = "0")]
+    fn st_btime_nsec(&self) -> Option<i64> {
+        None
+    }
+    /// Returns the file's `STATX_ATTR_*` attribute flags, such as
+    /// `STATX_ATTR_IMMUTABLE`.
+    ///
+    /// Only the flags in [`st_attributes_mask`] are meaningful. `None` is
+    /// returned if the kernel doesn't support `statx`.
+    ///
+    /// [`st_attributes_mask`]: #method.st_attributes_mask
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(metadata_statx)]
+    /// use std::fs;
+    /// use std::io;
+    /// use std::os::linux::fs::MetadataExt;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("some_file")?;
+    ///     println!("{:?}", meta.st_attributes());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "metadata_statx", issue = "0")]
+    fn st_attributes(&self) -> Option<u64> {
+        None
+    }
+    /// Returns which of the `STATX_ATTR_*` flags the filesystem supports, and
+    /// so which bits of [`st_attributes`] are meaningful.
+    ///
+    /// [`st_attributes`]: #method.st_attributes
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(metadata_statx)]
+    /// use std::fs;
+    /// use std::io;
+    /// use std::os::linux::fs::MetadataExt;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("some_file")?;
+    ///     println!("{:?}", meta.st_attributes_mask());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "metadata_statx", issue = "0")]
+    fn st_attributes_mask(&self) -> Option<u64> {
+        None
+    }
+    /// Returns whether the file is marked immutable (`chattr +i`), or `None`
+    /// if that isn't known.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(metadata_statx)]
+    /// use std::fs;
+    /// use std::io;
+    /// use std::os::linux::fs::MetadataExt;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("some_file")?;
+    ///     if meta.is_immutable() == Some(true) {
+    ///         println!("can't touch this");
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "metadata_statx", issue = "0")]
+    fn is_immutable(&self) -> Option<bool> {
+        None
+    }
+    /// Returns whether the file is marked append-only (`chattr +a`), or
+    /// `None` if that isn't known.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(metadata_statx)]
+    /// use std::fs;
+    /// use std::io;
+    /// use std::os::linux::fs::MetadataExt;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("some_file")?;
+    ///     println!("{:?}", meta.is_append_only());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "metadata_statx", issue = "0")]
+    fn is_append_only(&self) -> Option<bool> {
+        None
+    }
+    /// Returns whether the filesystem stores the file compressed, or `None`
+    /// if that isn't known.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(metadata_statx)]
+    /// use std::fs;
+    /// use std::io;
+    /// use std::os::linux::fs::MetadataExt;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("some_file")?;
+    ///     println!("{:?}", meta.is_compressed());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "metadata_statx", issue = "0")]
+    fn is_compressed(&self) -> Option<bool> {
+        None
+    }
+    /// Returns the ID of the mount containing the file, as found in
+    /// `/proc/self/mountinfo`.
+    ///
+    /// This needs Linux 5.8 or later; otherwise `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(metadata_statx)]
+    /// use std::fs;
+    /// use std::io;
+    /// use std::os::linux::fs::MetadataExt;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("some_file")?;
+    ///     println!("{:?}", meta.st_mnt_id());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[unstable(feature = "metadata_statx", issue = "0")]
+    fn st_mnt_id(&self) -> Option<u64> {
+        None
+    }
 }
 
 #[stable(feature = "metadata_ext", since = "1.1.0")]
@@ -386,4 +573,48 @@ impl MetadataExt for Metadata {
     fn st_blocks(&self) -> u64 {
         self.as_inner().as_inner().st_blocks as u64
     }
+    fn st_btime(&self) -> Option<i64> {
+        self.as_inner().statx_extra_fields()
+            .filter(|ext| ext.stx_mask & STATX_BTIME != 0)
+            .map(|ext| ext.stx_btime_sec)
+    }
+    fn st_btime_nsec(&self) -> Option<i64> {
+        self.as_inner().statx_extra_fields()
+            .filter(|ext| ext.stx_mask & STATX_BTIME != 0)
+            .map(|ext| ext.stx_btime_nsec as i64)
+    }
+    fn st_attributes(&self) -> Option<u64> {
+        self.as_inner().statx_extra_fields().map(|ext| ext.stx_attributes)
+    }
+    fn st_attributes_mask(&self) -> Option<u64> {
+        self.as_inner().statx_extra_fields().map(|ext| ext.stx_attributes_mask)
+    }
+    fn is_immutable(&self) -> Option<bool> {
+        attribute(self, STATX_ATTR_IMMUTABLE)
+    }
+    fn is_append_only(&self) -> Option<bool> {
+        attribute(self, STATX_ATTR_APPEND)
+    }
+    fn is_compressed(&self) -> Option<bool> {
+        attribute(self, STATX_ATTR_COMPRESSED)
+    }
+    fn st_mnt_id(&self) -> Option<u64> {
+        self.as_inner().statx_extra_fields()
+            .filter(|ext| ext.stx_mask & STATX_MNT_ID != 0)
+            .map(|ext| ext.stx_mnt_id)
+    }
+}
+
+// Defined here rather than taken from `sys`, which only has them when
+// building for Linux, while these docs are built everywhere.
+const STATX_BTIME: u32 = 0x800;
+const STATX_MNT_ID: u32 = 0x1000;
+const STATX_ATTR_COMPRESSED: u64 = 0x4;
+const STATX_ATTR_IMMUTABLE: u64 = 0x10;
+const STATX_ATTR_APPEND: u64 = 0x20;
+
+fn attribute(meta: &Metadata, attr: u64) -> Option<bool> {
+    meta.as_inner().statx_extra_fields()
+        .filter(|ext| ext.stx_attributes_mask & attr != 0)
+        .map(|ext| ext.stx_attributes & attr != 0)
 }
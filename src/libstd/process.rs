@@ -121,12 +121,13 @@ use io::prelude::*;
 use ffi::OsStr;
 use fmt;
 use fs;
-use io::{self, Initializer};
+use io::{self, Initializer, IoSlice, IoSliceMut};
 use path::Path;
 use str;
 use sys::pipe::{read2, AnonPipe};
 use sys::process as imp;
 use sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
+use time::Duration;
 
 /// Representation of a running or exited child process.
 ///
@@ -235,6 +236,10 @@ impl Write for ChildStdin {
         self.inner.write(buf)
     }
 
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         Ok(())
     }
@@ -281,6 +286,9 @@ impl Read for ChildStdout {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.inner.read(buf)
     }
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
     #[inline]
     unsafe fn initializer(&self) -> Initializer {
         Initializer::nop()
@@ -328,6 +336,9 @@ impl Read for ChildStderr {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.inner.read(buf)
     }
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
     #[inline]
     unsafe fn initializer(&self) -> Initializer {
         Initializer::nop()
@@ -1242,6 +1253,47 @@ impl Child {
         Ok(self.handle.try_wait()?.map(ExitStatus))
     }
 
+    /// Waits for the child to exit, giving up after `dur` has elapsed.
+    ///
+    /// If the child exits within the timeout, then `Ok(Some(status))` is
+    /// returned and, on Unix, the process id is reaped. If the timeout
+    /// elapses first, `Ok(None)` is returned and the child is left running.
+    /// If an error occurs, then that error is returned.
+    ///
+    /// The calling thread blocks until the child exits or the timeout
+    /// elapses. No threads are started and no signal handlers are installed.
+    /// On Linux this waits on a process file descriptor, and on macOS and the
+    /// BSDs on a kqueue. On older Linux kernels, other Unix platforms and
+    /// Redox the child is polled instead.
+    ///
+    /// Note that like `try_wait`, and unlike `wait`, this function will not
+    /// attempt to drop stdin.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(process_wait_timeout)]
+    ///
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+    ///
+    /// match child.wait_timeout(Duration::from_secs(1)) {
+    ///     Ok(Some(status)) => println!("exited with: {}", status),
+    ///     Ok(None) => {
+    ///         println!("still running after a second, killing it");
+    ///         child.kill().unwrap();
+    ///         child.wait().unwrap();
+    ///     }
+    ///     Err(e) => println!("error attempting to wait: {}", e),
+    /// }
+    /// ```
+    #[unstable(feature = "process_wait_timeout", issue = "0")]
+    pub fn wait_timeout(&mut self, dur: Duration) -> io::Result<Option<ExitStatus>> {
+        Ok(self.handle.wait_timeout(dur)?.map(ExitStatus))
+    }
+
     /// Simultaneously waits for the child to exit and collect all remaining
     /// output on the stdout/stderr handles, returning an `Output`
     /// instance.
@@ -1720,6 +1772,30 @@ mod tests {
         assert!(prog.wait().unwrap().code() == Some(1));
     }
 
+    #[test]
+    #[cfg_attr(target_os = "android", ignore)]
+    fn test_wait_timeout() {
+        use time::Duration;
+
+        let mut prog = if cfg!(target_os = "windows") {
+            Command::new("cmd").args(&["/C", "ping -n 60 127.0.0.1 > nul"]).spawn().unwrap()
+        } else {
+            Command::new("sleep").arg("60").spawn().unwrap()
+        };
+        assert!(prog.wait_timeout(Duration::from_millis(100)).unwrap().is_none());
+        prog.kill().unwrap();
+        assert!(prog.wait_timeout(Duration::from_secs(60)).unwrap().is_some());
+
+        let mut prog = if cfg!(target_os = "windows") {
+            Command::new("cmd").args(&["/C", "exit 1"]).spawn().unwrap()
+        } else {
+            Command::new("false").spawn().unwrap()
+        };
+        let status = prog.wait_timeout(Duration::from_secs(60)).unwrap().unwrap();
+        assert!(status.code() == Some(1));
+        assert!(prog.wait().unwrap().code() == Some(1));
+    }
+
     #[test]
     #[cfg_attr(target_os = "android", ignore)]
     fn test_wait_with_output_once() {
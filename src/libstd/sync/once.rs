@@ -149,9 +149,9 @@ struct Waiter {
 
 // Helper struct used to clean up after a closure call with a `Drop`
 // implementation to also run on panic.
-struct Finish {
-    panicked: bool,
-    me: &'static Once,
+struct Finish<'a> {
+    set_state_on_drop_to: usize,
+    me: &'a Once,
 }
 
 impl Once {
@@ -225,7 +225,10 @@ impl Once {
         }
 
         let mut f = Some(f);
-        self.call_inner(false, &mut |_| f.take().unwrap()());
+        self.call_inner(false, &mut |_| {
+            f.take().unwrap()();
+            true
+        });
     }
 
     /// Performs the same function as [`call_once`] except ignores poisoning.
@@ -283,10 +286,30 @@ impl Once {
 
         let mut f = Some(f);
         self.call_inner(true, &mut |p| {
-            f.take().unwrap()(&OnceState { poisoned: p })
+            f.take().unwrap()(&OnceState { poisoned: p });
+            true
         });
     }
 
+    // Like `call_once_force`, but `f` decides whether initialization
+    // succeeded. If it returns `false` the `Once` goes back to being
+    // incomplete, neither poisoned nor complete, and the next caller gets to
+    // run its own closure.
+    pub(super) fn try_call_once<F>(&self, f: F) where F: FnOnce() -> bool {
+        if self.is_completed() {
+            return
+        }
+
+        let mut f = Some(f);
+        self.call_inner(true, &mut |_| f.take().unwrap()());
+    }
+
+    // Returns whether some initialization has completed successfully. Any
+    // writes it performed are visible once this returns `true`.
+    pub(super) fn is_completed(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == COMPLETE
+    }
+
     // This is a non-generic function to reduce the monomorphization cost of
     // using `call_once` (this isn't exactly a trivial or small implementation).
     //
@@ -297,11 +320,12 @@ impl Once {
     //
     // Finally, this takes an `FnMut` instead of a `FnOnce` because there's
     // currently no way to take an `FnOnce` and call it via virtual dispatch
-    // without some allocation overhead.
+    // without some allocation overhead. The closure returns whether the
+    // initialization completed; if it didn't, the `Once` is left incomplete.
     #[cold]
-    fn call_inner(&'static self,
+    fn call_inner(&self,
                   ignore_poisoning: bool,
-                  init: &mut FnMut(bool)) {
+                  init: &mut FnMut(bool) -> bool) {
         let mut state = self.state.load(Ordering::SeqCst);
 
         'outer: loop {
@@ -335,11 +359,14 @@ impl Once {
                     // up other waiters both in the normal return and panicking
                     // case.
                     let mut complete = Finish {
-                        panicked: true,
+                        set_state_on_drop_to: POISONED,
                         me: self,
                     };
-                    init(state == POISONED);
-                    complete.panicked = false;
+                    complete.set_state_on_drop_to = if init(state == POISONED) {
+                        COMPLETE
+                    } else {
+                        INCOMPLETE
+                    };
                     return
                 }
 
@@ -390,15 +417,11 @@ impl fmt::Debug for Once {
     }
 }
 
-impl Drop for Finish {
+impl<'a> Drop for Finish<'a> {
     fn drop(&mut self) {
         // Swap out our state with however we finished. We should only ever see
         // an old state which was RUNNING.
-        let queue = if self.panicked {
-            self.me.state.swap(POISONED, Ordering::SeqCst)
-        } else {
-            self.me.state.swap(COMPLETE, Ordering::SeqCst)
-        };
+        let queue = self.me.state.swap(self.set_state_on_drop_to, Ordering::SeqCst);
         assert_eq!(queue & STATE_MASK, RUNNING);
 
         // Decode the RUNNING to a list of waiters, then walk that entire list
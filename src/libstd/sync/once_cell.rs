@@ -0,0 +1,500 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use cell::{Cell, UnsafeCell};
+use fmt;
+use ops::Deref;
+use panic::{RefUnwindSafe, UnwindSafe};
+use sync::Once;
+
+/// A synchronization primitive which can be written to only once.
+///
+/// This type is a thread-safe [`OnceCell`], and can be used in statics.
+/// Unlike [`Once`], it hands back the value the initialization produced, so
+/// a lazily computed global no longer needs a `static mut`.
+///
+/// [`OnceCell`]: ../cell/struct.OnceCell.html
+/// [`Once`]: struct.Once.html
+///
+/// # Examples
+///
+/// ```
+/// #![feature(once_cell)]
+///
+/// use std::sync::OnceCell;
+///
+/// static CELL: OnceCell<String> = OnceCell::new();
+/// assert!(CELL.get().is_none());
+///
+/// std::thread::spawn(|| {
+///     let value: &String = CELL.get_or_init(|| {
+///         "Hello, World!".to_string()
+///     });
+///     assert_eq!(value, "Hello, World!");
+/// }).join().unwrap();
+///
+/// let value: Option<&String> = CELL.get();
+/// assert!(value.is_some());
+/// assert_eq!(value.unwrap().as_str(), "Hello, World!");
+/// ```
+#[unstable(feature = "once_cell", issue = "0")]
+pub struct OnceCell<T> {
+    once: Once,
+    // Written to at most once, by whoever runs `once`, and only read once
+    // `once` has completed.
+    value: UnsafeCell<Option<T>>,
+}
+
+// Sharing a `OnceCell` lets one thread initialize it and others read the
+// value, so `T` must be both Send and Sync.
+#[unstable(feature = "once_cell", issue = "0")]
+unsafe impl<T: Sync + Send> Sync for OnceCell<T> {}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: RefUnwindSafe + UnwindSafe> RefUnwindSafe for OnceCell<T> {}
+
+impl<T> OnceCell<T> {
+    /// Creates a new empty cell.
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub const fn new() -> OnceCell<T> {
+        OnceCell {
+            once: Once::new(),
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    /// Gets the reference to the underlying value.
+    ///
+    /// Returns `None` if the cell is empty, or being initialized. This
+    /// method never blocks.
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn get(&self) -> Option<&T> {
+        if self.once.is_completed() {
+            // Safe because the value is never written again once `once`
+            // has completed.
+            unsafe { (*self.value.get()).as_ref() }
+        } else {
+            None
+        }
+    }
+
+    /// Gets the mutable reference to the underlying value.
+    ///
+    /// Returns `None` if the cell is empty. This method never blocks.
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        // Safe because we have unique access.
+        unsafe { (*self.value.get()).as_mut() }
+    }
+
+    /// Sets the contents of this cell to `value`.
+    ///
+    /// May block if another thread is currently attempting to initialize the
+    /// cell. The cell is guaranteed to contain a value when `set` returns,
+    /// though not necessarily the one provided.
+    ///
+    /// Returns `Ok(())` if the cell's value was set by this call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(once_cell)]
+    ///
+    /// use std::sync::OnceCell;
+    ///
+    /// static CELL: OnceCell<i32> = OnceCell::new();
+    ///
+    /// fn main() {
+    ///     assert!(CELL.get().is_none());
+    ///
+    ///     std::thread::spawn(|| {
+    ///         assert_eq!(CELL.set(92), Ok(()));
+    ///     }).join().unwrap();
+    ///
+    ///     assert_eq!(CELL.set(62), Err(62));
+    ///     assert_eq!(CELL.get(), Some(&92));
+    /// }
+    /// ```
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.get_or_init(|| value.take().unwrap());
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell
+    /// was empty.
+    ///
+    /// Many threads may call `get_or_init` concurrently with different
+    /// initializing functions, but it is guaranteed that only one function
+    /// will be executed.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic is propagated to the caller, and the cell
+    /// remains uninitialized.
+    ///
+    /// It is an error to reentrantly initialize the cell from `f`. The
+    /// exact outcome is unspecified; currently it deadlocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(once_cell)]
+    ///
+    /// use std::sync::OnceCell;
+    ///
+    /// let cell = OnceCell::new();
+    /// let value = cell.get_or_init(|| 92);
+    /// assert_eq!(value, &92);
+    /// let value = cell.get_or_init(|| unreachable!());
+    /// assert_eq!(value, &92);
+    /// ```
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn get_or_init<F>(&self, f: F) -> &T
+        where F: FnOnce() -> T
+    {
+        enum Void {}
+        match self.get_or_try_init(|| Ok::<T, Void>(f())) {
+            Ok(val) => val,
+            Err(void) => match void {},
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell
+    /// was empty. If the cell was empty and `f` failed, an error is returned
+    /// and the cell stays empty.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic is propagated to the caller, and the cell
+    /// remains uninitialized.
+    ///
+    /// It is an error to reentrantly initialize the cell from `f`. The
+    /// exact outcome is unspecified; currently it deadlocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(once_cell)]
+    ///
+    /// use std::sync::OnceCell;
+    ///
+    /// let cell = OnceCell::new();
+    /// assert_eq!(cell.get_or_try_init(|| Err(())), Err(()));
+    /// assert!(cell.get().is_none());
+    /// let value = cell.get_or_try_init(|| -> Result<i32, ()> {
+    ///     Ok(92)
+    /// });
+    /// assert_eq!(value, Ok(&92));
+    /// assert_eq!(cell.get(), Some(&92))
+    /// ```
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn get_or_try_init<F, E>(&self, f: F) -> Result<&T, E>
+        where F: FnOnce() -> Result<T, E>
+    {
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+
+        let mut res = Ok(());
+        {
+            let slot = &self.value;
+            let res = &mut res;
+            self.once.try_call_once(|| {
+                match f() {
+                    Ok(value) => {
+                        unsafe { *slot.get() = Some(value) };
+                        true
+                    }
+                    Err(e) => {
+                        *res = Err(e);
+                        false
+                    }
+                }
+            });
+        }
+        match res {
+            // Either we initialized the cell, or another thread did while we
+            // were waiting.
+            Ok(()) => Ok(self.get().unwrap()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Consumes the cell, returning the wrapped value.
+    ///
+    /// Returns `None` if the cell was empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(once_cell)]
+    ///
+    /// use std::sync::OnceCell;
+    ///
+    /// let cell: OnceCell<String> = OnceCell::new();
+    /// assert_eq!(cell.into_inner(), None);
+    ///
+    /// let cell = OnceCell::new();
+    /// cell.set("hello".to_string()).unwrap();
+    /// assert_eq!(cell.into_inner(), Some("hello".to_string()));
+    /// ```
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn into_inner(self) -> Option<T> {
+        // A `try_call_once` that failed or panicked never stores a value, so
+        // anything in the slot is a completed initialization.
+        self.value.into_inner()
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T> Default for OnceCell<T> {
+    fn default() -> OnceCell<T> {
+        OnceCell::new()
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: fmt::Debug> fmt::Debug for OnceCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.get() {
+            Some(v) => f.debug_tuple("OnceCell").field(v).finish(),
+            None => f.write_str("OnceCell(Uninit)"),
+        }
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: Clone> Clone for OnceCell<T> {
+    fn clone(&self) -> OnceCell<T> {
+        let cell = OnceCell::new();
+        if let Some(value) = self.get() {
+            let _ = cell.set(value.clone());
+        }
+        cell
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T> From<T> for OnceCell<T> {
+    fn from(value: T) -> OnceCell<T> {
+        let cell = OnceCell::new();
+        let _ = cell.set(value);
+        cell
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: PartialEq> PartialEq for OnceCell<T> {
+    fn eq(&self, other: &OnceCell<T>) -> bool {
+        self.get() == other.get()
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: Eq> Eq for OnceCell<T> {}
+
+/// A value which is initialized on the first access.
+///
+/// This type is a thread-safe [`Lazy`], and can be used in statics.
+///
+/// [`Lazy`]: ../cell/struct.Lazy.html
+///
+/// # Examples
+///
+/// ```
+/// #![feature(once_cell)]
+///
+/// use std::collections::HashMap;
+///
+/// use std::sync::Lazy;
+///
+/// static HASHMAP: Lazy<HashMap<i32, String>> = Lazy::new(|| {
+///     println!("initializing");
+///     let mut m = HashMap::new();
+///     m.insert(13, "Spica".to_string());
+///     m.insert(74, "Hoyten".to_string());
+///     m
+/// });
+///
+/// fn main() {
+///     println!("ready");
+///     std::thread::spawn(|| {
+///         println!("{:?}", HASHMAP.get(&13));
+///     }).join().unwrap();
+///     println!("{:?}", HASHMAP.get(&74));
+///
+///     // Prints:
+///     //   ready
+///     //   initializing
+///     //   Some("Spica")
+///     //   Some("Hoyten")
+/// }
+/// ```
+#[unstable(feature = "once_cell", issue = "0")]
+pub struct Lazy<T, F = fn() -> T> {
+    cell: OnceCell<T>,
+    init: Cell<Option<F>>,
+}
+
+// `init` is only taken from inside the cell's initialization, which runs at
+// most once at a time, so sharing a `Lazy` only requires the closure to be
+// Send.
+#[unstable(feature = "once_cell", issue = "0")]
+unsafe impl<T, F: Send> Sync for Lazy<T, F> where OnceCell<T>: Sync {}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T, F: UnwindSafe> RefUnwindSafe for Lazy<T, F> where OnceCell<T>: RefUnwindSafe {}
+
+impl<T, F> Lazy<T, F> {
+    /// Creates a new lazy value with the given initializing function.
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub const fn new(f: F) -> Lazy<T, F> {
+        Lazy { cell: OnceCell::new(), init: Cell::new(Some(f)) }
+    }
+}
+
+impl<T, F: FnOnce() -> T> Lazy<T, F> {
+    /// Forces the evaluation of this lazy value and returns a reference to
+    /// the result.
+    ///
+    /// This is equivalent to the `Deref` impl, but is explicit.
+    ///
+    /// # Panics
+    ///
+    /// If the initializing function panicked on an earlier access, every
+    /// later access panics as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(once_cell)]
+    ///
+    /// use std::sync::Lazy;
+    ///
+    /// let lazy = Lazy::new(|| 92);
+    ///
+    /// assert_eq!(Lazy::force(&lazy), &92);
+    /// assert_eq!(&*lazy, &92);
+    /// ```
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn force(this: &Lazy<T, F>) -> &T {
+        this.cell.get_or_init(|| match this.init.take() {
+            Some(f) => f(),
+            None => panic!("Lazy instance has previously been poisoned"),
+        })
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T, F: FnOnce() -> T> Deref for Lazy<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        Lazy::force(self)
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: Default> Default for Lazy<T> {
+    /// Creates a new lazy value using `Default` as the initializing function.
+    fn default() -> Lazy<T> {
+        Lazy::new(T::default)
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: fmt::Debug, F> fmt::Debug for Lazy<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Lazy").field("cell", &self.cell).field("init", &"..").finish()
+    }
+}
+
+#[cfg(all(test, not(target_os = "emscripten")))]
+mod tests {
+    use panic;
+    use sync::atomic::{AtomicUsize, Ordering};
+    use sync::mpsc::channel;
+    use thread;
+    use super::{Lazy, OnceCell};
+
+    #[test]
+    fn smoke() {
+        static CELL: OnceCell<i32> = OnceCell::new();
+        assert!(CELL.get().is_none());
+
+        thread::spawn(|| {
+            assert_eq!(*CELL.get_or_init(|| 92), 92);
+            assert_eq!(*CELL.get_or_init(|| panic!("Kabom!")), 92);
+        }).join().unwrap();
+
+        assert_eq!(CELL.get(), Some(&92));
+        assert_eq!(CELL.set(62), Err(62));
+    }
+
+    #[test]
+    fn stampede_once() {
+        static CELL: OnceCell<usize> = OnceCell::new();
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+        let (tx, rx) = channel();
+        for _ in 0..10 {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let value = CELL.get_or_init(|| RUNS.fetch_add(1, Ordering::SeqCst) + 1);
+                tx.send(*value).unwrap();
+            });
+        }
+
+        for _ in 0..10 {
+            assert_eq!(rx.recv().unwrap(), 1);
+        }
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn get_or_try_init_leaves_cell_empty_on_error() {
+        let cell: OnceCell<String> = OnceCell::new();
+        assert_eq!(cell.get_or_try_init(|| Err(())), Err(()));
+        assert!(cell.get().is_none());
+
+        let res = panic::catch_unwind(|| {
+            cell.get_or_init(|| panic!());
+        });
+        assert!(res.is_err());
+        assert!(cell.get().is_none());
+
+        assert_eq!(cell.get_or_try_init(|| Ok::<_, ()>("hello".to_string())),
+                   Ok(&"hello".to_string()));
+        assert_eq!(cell.into_inner(), Some("hello".to_string()));
+    }
+
+    #[test]
+    fn lazy_static() {
+        static LAZY: Lazy<Vec<i32>> = Lazy::new(|| vec![1, 2, 3]);
+
+        let t = thread::spawn(|| LAZY.len());
+        assert_eq!(LAZY[1], 2);
+        assert_eq!(t.join().unwrap(), 3);
+    }
+
+    #[test]
+    fn lazy_poisoning() {
+        let x: Lazy<String> = Lazy::new(|| panic!("kaboom"));
+        for _ in 0..2 {
+            let res = panic::catch_unwind(panic::AssertUnwindSafe(|| x.len()));
+            assert!(res.is_err());
+        }
+    }
+}
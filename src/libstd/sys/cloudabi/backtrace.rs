@@ -18,6 +18,11 @@ use unwind as uw;
 
 pub struct BacktraceContext;
 
+/// Returns a context for resolving frames that were captured earlier.
+pub fn symbol_context() -> io::Result<BacktraceContext> {
+    Ok(BacktraceContext)
+}
+
 struct Context<'a> {
     idx: usize,
     frames: &'a mut [Frame],
@@ -40,3 +40,8 @@ pub mod gnu {
 }
 
 pub struct BacktraceContext;
+
+/// Returns a context for resolving frames that were captured earlier.
+pub fn symbol_context() -> ::io::Result<BacktraceContext> {
+    Ok(BacktraceContext)
+}
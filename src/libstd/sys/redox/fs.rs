@@ -12,7 +12,7 @@ use os::unix::prelude::*;
 
 use ffi::{OsString, OsStr};
 use fmt;
-use io::{self, Error, ErrorKind, SeekFrom};
+use io::{self, Error, ErrorKind, IoSlice, IoSliceMut, SeekFrom};
 use path::{Path, PathBuf};
 use sync::Arc;
 use sys::fd::FileDesc;
@@ -33,10 +33,9 @@ pub struct ReadDir {
     root: Arc<PathBuf>,
 }
 
-struct Dir(FileDesc);
-
-unsafe impl Send for Dir {}
-unsafe impl Sync for Dir {}
+// Directory handles just remember the path they were opened with; the `*at`
+// operations are built by joining onto it.
+pub struct Dir { root: PathBuf }
 
 pub struct DirEntry {
     root: Arc<PathBuf>,
@@ -285,10 +284,18 @@ impl File {
         self.0.read(buf)
     }
 
+    pub fn read_vectored(&self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        io::default_read_vectored(|b| self.read(b), bufs)
+    }
+
     pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
         self.0.write(buf)
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        io::default_write_vectored(|b| self.write(b), bufs)
+    }
+
     pub fn flush(&self) -> io::Result<()> { Ok(()) }
 
     pub fn seek(&self, pos: SeekFrom) -> io::Result<u64> {
@@ -402,6 +409,61 @@ pub fn rmdir(p: &Path) -> io::Result<()> {
     Ok(())
 }
 
+impl Dir {
+    pub fn open(p: &Path) -> io::Result<Dir> {
+        if !stat(p)?.file_type().is_dir() {
+            return Err(Error::from_raw_os_error(syscall::ENOTDIR))
+        }
+        Ok(Dir { root: p.to_path_buf() })
+    }
+
+    pub fn open_dir_at(&self, p: &Path) -> io::Result<Dir> {
+        let path = self.root.join(p);
+        let filetype = lstat(&path)?.file_type();
+        if filetype.is_symlink() {
+            return Err(Error::from_raw_os_error(syscall::ELOOP))
+        } else if !filetype.is_dir() {
+            return Err(Error::from_raw_os_error(syscall::ENOTDIR))
+        }
+        Ok(Dir { root: path })
+    }
+
+    pub fn open_file_at(&self, p: &Path, opts: &OpenOptions) -> io::Result<File> {
+        File::open(&self.root.join(p), opts)
+    }
+
+    pub fn create_dir_at(&self, p: &Path) -> io::Result<()> {
+        DirBuilder::new().mkdir(&self.root.join(p))
+    }
+
+    pub fn remove_at(&self, p: &Path) -> io::Result<()> {
+        let path = self.root.join(p);
+        if lstat(&path)?.file_type().is_dir() {
+            rmdir(&path)
+        } else {
+            unlink(&path)
+        }
+    }
+
+    pub fn metadata_at(&self, p: &Path) -> io::Result<FileAttr> {
+        lstat(&self.root.join(p))
+    }
+
+    pub fn read_dir(&self) -> io::Result<ReadDir> {
+        readdir(&self.root)
+    }
+
+    pub fn rename_at(&self, old: &Path, new_dir: &Dir, new: &Path) -> io::Result<()> {
+        rename(&self.root.join(old), &new_dir.root.join(new))
+    }
+}
+
+impl fmt::Debug for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Dir").field("path", &self.root).finish()
+    }
+}
+
 pub fn remove_dir_all(path: &Path) -> io::Result<()> {
     let filetype = lstat(path)?.file_type();
     if filetype.is_symlink() {
@@ -9,7 +9,7 @@
 // except according to those terms.
 
 use cmp;
-use io::{self, Error, ErrorKind, Result};
+use io::{self, Error, ErrorKind, IoSlice, IoSliceMut, Result};
 use mem;
 use net::{SocketAddr, Shutdown};
 use path::Path;
@@ -44,10 +44,18 @@ impl TcpStream {
         self.0.read(buf)
     }
 
+    pub fn read_vectored(&self, bufs: &mut [IoSliceMut]) -> Result<usize> {
+        io::default_read_vectored(|b| self.read(b), bufs)
+    }
+
     pub fn write(&self, buf: &[u8]) -> Result<usize> {
         self.0.write(buf)
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> Result<usize> {
+        io::default_write_vectored(|b| self.write(b), bufs)
+    }
+
     pub fn take_error(&self) -> Result<Option<Error>> {
         Ok(None)
     }
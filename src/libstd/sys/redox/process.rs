@@ -8,6 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use cmp;
 use env::{split_paths};
 use ffi::OsStr;
 use os::unix::ffi::OsStrExt;
@@ -19,7 +20,9 @@ use sys::fd::FileDesc;
 use sys::fs::{File, OpenOptions};
 use sys::pipe::{self, AnonPipe};
 use sys::{cvt, syscall};
-use sys_common::process::{CommandEnv, DefaultEnvKey};
+use sys_common::process::{wait_with_timeout, CommandEnv, DefaultEnvKey};
+use thread;
+use time::Duration;
 
 ////////////////////////////////////////////////////////////////////////////////
 // Command
@@ -540,4 +543,18 @@ impl Process {
             Ok(Some(ExitStatus(status as i32)))
         }
     }
+
+    pub fn wait_timeout(&mut self, dur: Duration) -> io::Result<Option<ExitStatus>> {
+        if let Some(status) = self.try_wait()? {
+            return Ok(Some(status))
+        }
+        // There is no way to be notified of a child exiting with a deadline
+        // here, so poll instead, backing off to keep the overhead down.
+        let mut interval = Duration::from_millis(1);
+        wait_with_timeout(dur, |dur| {
+            thread::sleep(cmp::min(interval, dur));
+            interval = cmp::min(interval * 2, Duration::from_millis(50));
+            self.try_wait()
+        })
+    }
 }
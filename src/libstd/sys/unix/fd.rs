@@ -11,7 +11,7 @@
 #![unstable(reason = "not public", issue = "0", feature = "fd")]
 
 use cmp;
-use io::{self, Read};
+use io::{self, Read, IoSlice, IoSliceMut};
 use libc::{self, c_int, c_void, ssize_t};
 use mem;
 use sync::atomic::{AtomicBool, Ordering};
@@ -39,6 +39,40 @@ fn max_len() -> usize {
     }
 }
 
+// The most buffers `readv` and `writev` take at once. Passing more is an
+// error rather than a partial read or write.
+#[cfg(any(target_os = "linux",
+          target_os = "android",
+          target_os = "emscripten",
+          target_os = "fuchsia",
+          target_os = "solaris",
+          target_os = "haiku"))]
+fn max_iov() -> usize {
+    use sync::atomic::AtomicUsize;
+
+    static LIMIT: AtomicUsize = AtomicUsize::new(0);
+
+    let mut limit = LIMIT.load(Ordering::Relaxed);
+    if limit == 0 {
+        let ret = unsafe { libc::sysconf(libc::_SC_IOV_MAX) };
+        // 16 is the least POSIX allows.
+        limit = if ret > 0 { ret as usize } else { 16 };
+        LIMIT.store(limit, Ordering::Relaxed);
+    }
+    limit
+}
+
+// `IOV_MAX` on macOS and the BSDs.
+#[cfg(not(any(target_os = "linux",
+              target_os = "android",
+              target_os = "emscripten",
+              target_os = "fuchsia",
+              target_os = "solaris",
+              target_os = "haiku")))]
+fn max_iov() -> usize {
+    1024
+}
+
 impl FileDesc {
     pub fn new(fd: c_int) -> FileDesc {
         FileDesc { fd: fd }
@@ -62,6 +96,15 @@ impl FileDesc {
         Ok(ret as usize)
     }
 
+    pub fn read_vectored(&self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        let ret = cvt(unsafe {
+            libc::readv(self.fd,
+                        bufs.as_ptr() as *const libc::iovec,
+                        cmp::min(bufs.len(), max_iov()) as c_int)
+        })?;
+        Ok(ret as usize)
+    }
+
     pub fn read_to_end(&self, buf: &mut Vec<u8>) -> io::Result<usize> {
         let mut me = self;
         (&mut me).read_to_end(buf)
@@ -115,6 +158,15 @@ impl FileDesc {
         Ok(ret as usize)
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        let ret = cvt(unsafe {
+            libc::writev(self.fd,
+                         bufs.as_ptr() as *const libc::iovec,
+                         cmp::min(bufs.len(), max_iov()) as c_int)
+        })?;
+        Ok(ret as usize)
+    }
+
     pub fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
         #[cfg(target_os = "android")]
         use super::android::cvt_pwrite64;
@@ -286,3 +338,31 @@ impl Drop for FileDesc {
         let _ = unsafe { libc::close(self.fd) };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use io::{IoSlice, IoSliceMut};
+    use sys::pipe::anon_pipe;
+    use super::max_iov;
+
+    #[test]
+    fn vectored_past_max_iov() {
+        let (read, write) = anon_pipe().unwrap();
+        let (read, write) = (read.into_fd(), write.into_fd());
+
+        // One byte per buffer, so the pipe has room for everything a single
+        // call can write.
+        let count = 2048;
+        let data = vec![7u8; count];
+        let bufs = data.chunks(1).map(IoSlice::new).collect::<Vec<_>>();
+        let n = write.write_vectored(&bufs).unwrap();
+        assert_eq!(n, max_iov().min(count));
+
+        let mut out = vec![0u8; count];
+        {
+            let mut bufs = out.chunks_mut(1).map(IoSliceMut::new).collect::<Vec<_>>();
+            assert_eq!(read.read_vectored(&mut bufs).unwrap(), n);
+        }
+        assert!(out[..n].iter().all(|&b| b == 7));
+    }
+}
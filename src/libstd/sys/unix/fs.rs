@@ -12,7 +12,7 @@ use os::unix::prelude::*;
 
 use ffi::{CString, CStr, OsString, OsStr};
 use fmt;
-use io::{self, Error, ErrorKind, SeekFrom};
+use io::{self, Error, ErrorKind, IoSlice, IoSliceMut, SeekFrom};
 use libc::{self, c_int, mode_t};
 use mem;
 use path::{Path, PathBuf};
@@ -42,27 +42,291 @@ use libc::{stat as stat64, fstat as fstat64, lstat as lstat64, off_t as off64_t,
               target_os = "l4re",
               target_os = "fuchsia")))]
 use libc::{readdir_r as readdir64_r};
+#[cfg(not(target_os = "solaris"))]
+use libc::{AT_REMOVEDIR, O_DIRECTORY};
+#[cfg(target_os = "solaris")]
+const AT_REMOVEDIR: c_int = 0x1;
+#[cfg(target_os = "solaris")]
+const O_DIRECTORY: c_int = 0x1000000;
+
+// The functions `Dir` is built on. Only `Dir` and `remove_dir_all` use them,
+// as they're missing from the oldest macOS and iOS releases we support.
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+mod at {
+    #[cfg(any(target_os = "linux", target_os = "emscripten"))]
+    pub use libc::{openat64 as openat, fstatat64 as fstatat};
+    #[cfg(target_os = "l4re")]
+    pub use libc::{openat64 as openat, fstatat};
+    #[cfg(not(any(target_os = "linux", target_os = "emscripten", target_os = "l4re")))]
+    pub use libc::{openat, fstatat};
+    pub use libc::{fdopendir, mkdirat, renameat, unlinkat};
+}
+
+// These arrived in macOS 10.10 and iOS 8, so look them up at runtime. When
+// they're missing the wrappers fail with `ENOSYS`.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod at {
+    use libc::{self, c_char, c_int, mode_t};
+    use sys::weak::Weak;
+
+    static OPENAT: Weak<unsafe extern fn(c_int, *const c_char, c_int, ...) -> c_int> =
+        Weak::new("openat");
+    #[cfg(target_os = "macos")]
+    static FSTATAT: Weak<unsafe extern fn(c_int, *const c_char, *mut libc::stat, c_int)
+                                          -> c_int> =
+        Weak::new("fstatat$INODE64");
+    #[cfg(target_os = "ios")]
+    static FSTATAT: Weak<unsafe extern fn(c_int, *const c_char, *mut libc::stat, c_int)
+                                          -> c_int> =
+        Weak::new("fstatat");
+    #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+    static FDOPENDIR: Weak<unsafe extern fn(c_int) -> *mut libc::DIR> =
+        Weak::new("fdopendir$INODE64");
+    #[cfg(all(target_os = "macos", target_arch = "x86"))]
+    static FDOPENDIR: Weak<unsafe extern fn(c_int) -> *mut libc::DIR> =
+        Weak::new("fdopendir$INODE64$UNIX2003");
+    #[cfg(target_os = "ios")]
+    static FDOPENDIR: Weak<unsafe extern fn(c_int) -> *mut libc::DIR> =
+        Weak::new("fdopendir");
+    static MKDIRAT: Weak<unsafe extern fn(c_int, *const c_char, mode_t) -> c_int> =
+        Weak::new("mkdirat");
+    static RENAMEAT: Weak<unsafe extern fn(c_int, *const c_char, c_int, *const c_char)
+                                           -> c_int> =
+        Weak::new("renameat");
+    static UNLINKAT: Weak<unsafe extern fn(c_int, *const c_char, c_int) -> c_int> =
+        Weak::new("unlinkat");
+
+    pub fn available() -> bool {
+        OPENAT.get().is_some() &&
+            FSTATAT.get().is_some() &&
+            FDOPENDIR.get().is_some() &&
+            MKDIRAT.get().is_some() &&
+            RENAMEAT.get().is_some() &&
+            UNLINKAT.get().is_some()
+    }
+
+    unsafe fn enosys() {
+        *libc::__error() = libc::ENOSYS;
+    }
+
+    pub unsafe fn openat(dirfd: c_int, path: *const c_char, flags: c_int, mode: c_int) -> c_int {
+        match OPENAT.get() {
+            Some(f) => f(dirfd, path, flags, mode),
+            None => { enosys(); -1 }
+        }
+    }
+
+    pub unsafe fn fstatat(dirfd: c_int, path: *const c_char, buf: *mut libc::stat,
+                          flags: c_int) -> c_int {
+        match FSTATAT.get() {
+            Some(f) => f(dirfd, path, buf, flags),
+            None => { enosys(); -1 }
+        }
+    }
+
+    pub unsafe fn fdopendir(fd: c_int) -> *mut libc::DIR {
+        match FDOPENDIR.get() {
+            Some(f) => f(fd),
+            None => { enosys(); 0 as *mut _ }
+        }
+    }
+
+    pub unsafe fn mkdirat(dirfd: c_int, path: *const c_char, mode: mode_t) -> c_int {
+        match MKDIRAT.get() {
+            Some(f) => f(dirfd, path, mode),
+            None => { enosys(); -1 }
+        }
+    }
+
+    pub unsafe fn renameat(olddirfd: c_int, old: *const c_char,
+                           newdirfd: c_int, new: *const c_char) -> c_int {
+        match RENAMEAT.get() {
+            Some(f) => f(olddirfd, old, newdirfd, new),
+            None => { enosys(); -1 }
+        }
+    }
+
+    pub unsafe fn unlinkat(dirfd: c_int, path: *const c_char, flags: c_int) -> c_int {
+        match UNLINKAT.get() {
+            Some(f) => f(dirfd, path, flags),
+            None => { enosys(); -1 }
+        }
+    }
+}
 
 pub struct File(FileDesc);
 
 #[derive(Clone)]
 pub struct FileAttr {
     stat: stat64,
+    #[cfg(target_os = "linux")]
+    statx_extra_fields: Option<StatxExtraFields>,
+}
+
+// What `statx` tells us beyond what fits in a `stat64`.
+#[cfg(any(target_os = "linux", target_os = "l4re"))]
+#[derive(Clone)]
+pub struct StatxExtraFields {
+    // `STATX_*` bits for the fields the filesystem actually filled in.
+    pub stx_mask: u32,
+    pub stx_btime_sec: i64,
+    pub stx_btime_nsec: u32,
+    pub stx_attributes: u64,
+    pub stx_attributes_mask: u64,
+    pub stx_mnt_id: u64,
+}
+
+#[cfg(target_os = "linux")]
+const STATX_BTIME: u32 = 0x800;
+#[cfg(target_os = "linux")]
+const STATX_MNT_ID: u32 = 0x1000;
+
+#[cfg(target_os = "linux")]
+unsafe fn try_statx(fd: c_int, path: *const libc::c_char, flags: c_int)
+                    -> Option<io::Result<FileAttr>> {
+    use sync::atomic::{AtomicBool, Ordering};
+
+    // `statx` arrived in Linux 4.11, and the libc bindings don't know about
+    // it yet, so its interface is spelled out here.
+    #[repr(C)]
+    #[allow(non_camel_case_types, dead_code)]
+    struct statx_timestamp {
+        tv_sec: i64,
+        tv_nsec: u32,
+        __reserved: i32,
+    }
+
+    #[repr(C)]
+    #[allow(non_camel_case_types, dead_code)]
+    struct statx {
+        stx_mask: u32,
+        stx_blksize: u32,
+        stx_attributes: u64,
+        stx_nlink: u32,
+        stx_uid: u32,
+        stx_gid: u32,
+        stx_mode: u16,
+        __spare0: u16,
+        stx_ino: u64,
+        stx_size: u64,
+        stx_blocks: u64,
+        stx_attributes_mask: u64,
+        stx_atime: statx_timestamp,
+        stx_btime: statx_timestamp,
+        stx_ctime: statx_timestamp,
+        stx_mtime: statx_timestamp,
+        stx_rdev_major: u32,
+        stx_rdev_minor: u32,
+        stx_dev_major: u32,
+        stx_dev_minor: u32,
+        stx_mnt_id: u64,
+        __spare2: u64,
+        __spare3: [u64; 12],
+    }
+
+    #[cfg(all(target_arch = "x86_64", target_pointer_width = "64"))]
+    const SYS_STATX: libc::c_long = 332;
+    #[cfg(all(target_arch = "x86_64", target_pointer_width = "32"))]
+    const SYS_STATX: libc::c_long = 0x40000000 + 332;
+    #[cfg(any(target_arch = "x86", target_arch = "powerpc", target_arch = "powerpc64"))]
+    const SYS_STATX: libc::c_long = 383;
+    #[cfg(target_arch = "arm")]
+    const SYS_STATX: libc::c_long = 397;
+    #[cfg(target_arch = "aarch64")]
+    const SYS_STATX: libc::c_long = 291;
+    #[cfg(target_arch = "s390x")]
+    const SYS_STATX: libc::c_long = 379;
+    #[cfg(any(target_arch = "sparc", target_arch = "sparc64"))]
+    const SYS_STATX: libc::c_long = 360;
+    #[cfg(target_arch = "mips")]
+    const SYS_STATX: libc::c_long = 4366;
+    #[cfg(target_arch = "mips64")]
+    const SYS_STATX: libc::c_long = 5326;
+
+    const STATX_BASIC_STATS: u32 = 0x7ff;
+
+    // Older kernels return ENOSYS, and seccomp filters that predate the
+    // syscall tend to return EPERM. Either way, fall back to `stat64` for the
+    // rest of the process's life.
+    static HAS_STATX: AtomicBool = AtomicBool::new(true);
+
+    if !HAS_STATX.load(Ordering::Relaxed) {
+        return None
+    }
+
+    let mut buf: statx = mem::zeroed();
+    let mask = STATX_BASIC_STATS | STATX_BTIME | STATX_MNT_ID;
+    if libc::syscall(SYS_STATX, fd, path, flags, mask, &mut buf as *mut statx) == -1 {
+        let err = Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::ENOSYS) => {}
+            Some(libc::EPERM) => {
+                // This might be a genuine permission error instead. A working
+                // `statx` rejects null pointers with EFAULT before looking at
+                // anything else, so ask it to.
+                let probe = libc::syscall(SYS_STATX, 0, ptr::null::<libc::c_char>(), 0,
+                                          STATX_BASIC_STATS, ptr::null_mut::<statx>());
+                if probe == -1 && super::os::errno() == libc::EFAULT {
+                    return Some(Err(err))
+                }
+            }
+            _ => return Some(Err(err)),
+        }
+        HAS_STATX.store(false, Ordering::Relaxed);
+        return None
+    }
+
+    let mut stat: stat64 = mem::zeroed();
+    stat.st_dev = libc::makedev(buf.stx_dev_major, buf.stx_dev_minor) as _;
+    stat.st_ino = buf.stx_ino as _;
+    stat.st_nlink = buf.stx_nlink as _;
+    stat.st_mode = buf.stx_mode as _;
+    stat.st_uid = buf.stx_uid as _;
+    stat.st_gid = buf.stx_gid as _;
+    stat.st_rdev = libc::makedev(buf.stx_rdev_major, buf.stx_rdev_minor) as _;
+    stat.st_size = buf.stx_size as _;
+    stat.st_blksize = buf.stx_blksize as _;
+    stat.st_blocks = buf.stx_blocks as _;
+    stat.st_atime = buf.stx_atime.tv_sec as _;
+    stat.st_atime_nsec = buf.stx_atime.tv_nsec as _;
+    stat.st_mtime = buf.stx_mtime.tv_sec as _;
+    stat.st_mtime_nsec = buf.stx_mtime.tv_nsec as _;
+    stat.st_ctime = buf.stx_ctime.tv_sec as _;
+    stat.st_ctime_nsec = buf.stx_ctime.tv_nsec as _;
+
+    Some(Ok(FileAttr {
+        stat,
+        statx_extra_fields: Some(StatxExtraFields {
+            stx_mask: buf.stx_mask,
+            stx_btime_sec: buf.stx_btime.tv_sec,
+            stx_btime_nsec: buf.stx_btime.tv_nsec,
+            stx_attributes: buf.stx_attributes,
+            stx_attributes_mask: buf.stx_attributes_mask,
+            stx_mnt_id: buf.stx_mnt_id,
+        }),
+    }))
 }
 
 // all DirEntry's will have a reference to this struct
 struct InnerReadDir {
-    dirp: Dir,
+    dirp: DirStream,
     root: PathBuf,
 }
 
 #[derive(Clone)]
 pub struct ReadDir(Arc<InnerReadDir>);
 
-struct Dir(*mut libc::DIR);
+struct DirStream(*mut libc::DIR);
 
-unsafe impl Send for Dir {}
-unsafe impl Sync for Dir {}
+unsafe impl Send for DirStream {}
+unsafe impl Sync for DirStream {}
+
+// A directory opened for use with the `*at` family of functions. The root is
+// only kept around for `Debug` and for the paths of entries read from it.
+pub struct Dir {
+    fd: FileDesc,
+    root: PathBuf,
+}
 
 pub struct DirEntry {
     entry: dirent64,
@@ -99,6 +363,24 @@ pub struct FileType { mode: mode_t }
 pub struct DirBuilder { mode: mode_t }
 
 impl FileAttr {
+    fn from_stat64(stat: stat64) -> FileAttr {
+        FileAttr {
+            stat,
+            #[cfg(target_os = "linux")]
+            statx_extra_fields: None,
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn statx_extra_fields(&self) -> Option<&StatxExtraFields> {
+        self.statx_extra_fields.as_ref()
+    }
+
+    #[cfg(target_os = "l4re")]
+    pub fn statx_extra_fields(&self) -> Option<&StatxExtraFields> {
+        None
+    }
+
     pub fn size(&self) -> u64 { self.stat.st_size as u64 }
     pub fn perm(&self) -> FilePermissions {
         FilePermissions { mode: (self.stat.st_mode as mode_t) }
@@ -167,6 +449,21 @@ impl FileAttr {
                   target_os = "macos",
                   target_os = "ios")))]
     pub fn created(&self) -> io::Result<SystemTime> {
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(ref ext) = self.statx_extra_fields {
+                return if ext.stx_mask & STATX_BTIME != 0 {
+                    Ok(SystemTime::from(libc::timespec {
+                        tv_sec: ext.stx_btime_sec as libc::time_t,
+                        tv_nsec: ext.stx_btime_nsec as _,
+                    }))
+                } else {
+                    Err(io::Error::new(io::ErrorKind::Other,
+                                       "creation time is not available for the filesystem"))
+                }
+            }
+        }
+
         Err(io::Error::new(io::ErrorKind::Other,
                            "creation time is not available on this platform \
                             currently"))
@@ -278,7 +575,7 @@ impl Iterator for ReadDir {
     }
 }
 
-impl Drop for Dir {
+impl Drop for DirStream {
     fn drop(&mut self) {
         let r = unsafe { libc::closedir(self.0) };
         debug_assert_eq!(r, 0);
@@ -297,6 +594,14 @@ impl DirEntry {
     #[cfg(any(target_os = "linux", target_os = "emscripten", target_os = "android"))]
     pub fn metadata(&self) -> io::Result<FileAttr> {
         let fd = cvt(unsafe {dirfd(self.dir.0.dirp.0)})?;
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(ret) = unsafe {
+                try_statx(fd, self.entry.d_name.as_ptr(), libc::AT_SYMLINK_NOFOLLOW)
+            } {
+                return ret
+            }
+        }
         let mut stat: stat64 = unsafe { mem::zeroed() };
         cvt(unsafe {
             fstatat(fd,
@@ -304,7 +609,7 @@ impl DirEntry {
                     &mut stat as *mut _ as *mut _,
                     libc::AT_SYMLINK_NOFOLLOW)
         })?;
-        Ok(FileAttr { stat: stat })
+        Ok(FileAttr::from_stat64(stat))
     }
 
     #[cfg(not(any(target_os = "linux", target_os = "emscripten", target_os = "android")))]
@@ -317,17 +622,31 @@ impl DirEntry {
         lstat(&self.path()).map(|m| m.file_type())
     }
 
+    #[cfg(any(target_os = "solaris", target_os = "haiku"))]
+    fn file_type_from_entry(&self) -> Option<FileType> {
+        None
+    }
+
     #[cfg(not(any(target_os = "solaris", target_os = "haiku")))]
     pub fn file_type(&self) -> io::Result<FileType> {
+        match self.file_type_from_entry() {
+            Some(file_type) => Ok(file_type),
+            None => lstat(&self.path()).map(|m| m.file_type()),
+        }
+    }
+
+    // The type recorded in the entry itself, if the filesystem filled it in.
+    #[cfg(not(any(target_os = "solaris", target_os = "haiku")))]
+    fn file_type_from_entry(&self) -> Option<FileType> {
         match self.entry.d_type {
-            libc::DT_CHR => Ok(FileType { mode: libc::S_IFCHR }),
-            libc::DT_FIFO => Ok(FileType { mode: libc::S_IFIFO }),
-            libc::DT_LNK => Ok(FileType { mode: libc::S_IFLNK }),
-            libc::DT_REG => Ok(FileType { mode: libc::S_IFREG }),
-            libc::DT_SOCK => Ok(FileType { mode: libc::S_IFSOCK }),
-            libc::DT_DIR => Ok(FileType { mode: libc::S_IFDIR }),
-            libc::DT_BLK => Ok(FileType { mode: libc::S_IFBLK }),
-            _ => lstat(&self.path()).map(|m| m.file_type()),
+            libc::DT_CHR => Some(FileType { mode: libc::S_IFCHR }),
+            libc::DT_FIFO => Some(FileType { mode: libc::S_IFIFO }),
+            libc::DT_LNK => Some(FileType { mode: libc::S_IFLNK }),
+            libc::DT_REG => Some(FileType { mode: libc::S_IFREG }),
+            libc::DT_SOCK => Some(FileType { mode: libc::S_IFSOCK }),
+            libc::DT_DIR => Some(FileType { mode: libc::S_IFDIR }),
+            libc::DT_BLK => Some(FileType { mode: libc::S_IFBLK }),
+            _ => None,
         }
     }
 
@@ -450,13 +769,25 @@ impl File {
     }
 
     pub fn open_c(path: &CStr, opts: &OpenOptions) -> io::Result<File> {
+        File::open_with(opts, |flags, mode| unsafe {
+            open64(path.as_ptr(), flags, mode)
+        })
+    }
+
+    fn open_at_c(dirfd: c_int, path: &CStr, opts: &OpenOptions) -> io::Result<File> {
+        File::open_with(opts, |flags, mode| unsafe {
+            at::openat(dirfd, path.as_ptr(), flags, mode)
+        })
+    }
+
+    fn open_with<F>(opts: &OpenOptions, mut open: F) -> io::Result<File>
+        where F: FnMut(c_int, c_int) -> c_int
+    {
         let flags = libc::O_CLOEXEC |
                     opts.get_access_mode()? |
                     opts.get_creation_mode()? |
                     (opts.custom_flags as c_int & !libc::O_ACCMODE);
-        let fd = cvt_r(|| unsafe {
-            open64(path.as_ptr(), flags, opts.mode as c_int)
-        })?;
+        let fd = cvt_r(|| open(flags, opts.mode as c_int))?;
         let fd = FileDesc::new(fd);
 
         // Currently the standard library supports Linux 2.6.18 which did not
@@ -507,11 +838,20 @@ impl File {
     }
 
     pub fn file_attr(&self) -> io::Result<FileAttr> {
+        #[cfg(target_os = "linux")]
+        {
+            let empty = b"\0" as *const u8 as *const libc::c_char;
+            if let Some(ret) = unsafe {
+                try_statx(self.0.raw(), empty, libc::AT_EMPTY_PATH)
+            } {
+                return ret
+            }
+        }
         let mut stat: stat64 = unsafe { mem::zeroed() };
         cvt(unsafe {
             fstat64(self.0.raw(), &mut stat)
         })?;
-        Ok(FileAttr { stat: stat })
+        Ok(FileAttr::from_stat64(stat))
     }
 
     pub fn fsync(&self) -> io::Result<()> {
@@ -549,6 +889,10 @@ impl File {
         self.0.read(buf)
     }
 
+    pub fn read_vectored(&self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.0.read_vectored(bufs)
+    }
+
     pub fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
         self.0.read_at(buf, offset)
     }
@@ -557,6 +901,10 @@ impl File {
         self.0.write(buf)
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.0.write_vectored(bufs)
+    }
+
     pub fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
         self.0.write_at(buf, offset)
     }
@@ -607,6 +955,114 @@ impl DirBuilder {
     }
 }
 
+impl Dir {
+    pub fn open(p: &Path) -> io::Result<Dir> {
+        let fd = open_dir(libc::AT_FDCWD, &cstr(p)?, true)?;
+        Ok(Dir { fd, root: p.to_path_buf() })
+    }
+
+    // Unlike `open`, this refuses to follow a symlink in the last component.
+    pub fn open_dir_at(&self, p: &Path) -> io::Result<Dir> {
+        let fd = open_dir(self.fd.raw(), &cstr(p)?, false)?;
+        Ok(Dir { fd, root: self.root.join(p) })
+    }
+
+    pub fn open_file_at(&self, p: &Path, opts: &OpenOptions) -> io::Result<File> {
+        File::open_at_c(self.fd.raw(), &cstr(p)?, opts)
+    }
+
+    pub fn create_dir_at(&self, p: &Path) -> io::Result<()> {
+        let p = cstr(p)?;
+        cvt(unsafe { at::mkdirat(self.fd.raw(), p.as_ptr(), 0o777) })?;
+        Ok(())
+    }
+
+    pub fn remove_at(&self, p: &Path) -> io::Result<()> {
+        let p = cstr(p)?;
+        let err = match self.unlink_at(&p, 0) {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        // Linux reports EISDIR for directories, POSIX says EPERM.
+        match err.raw_os_error() {
+            Some(libc::EISDIR) | Some(libc::EPERM) => {}
+            _ => return Err(err),
+        }
+        match self.unlink_at(&p, AT_REMOVEDIR) {
+            Err(ref e) if e.raw_os_error() == Some(libc::ENOTDIR) => Err(err),
+            r => r,
+        }
+    }
+
+    fn unlink_at(&self, p: &CStr, flags: c_int) -> io::Result<()> {
+        cvt(unsafe { at::unlinkat(self.fd.raw(), p.as_ptr(), flags) })?;
+        Ok(())
+    }
+
+    pub fn metadata_at(&self, p: &Path) -> io::Result<FileAttr> {
+        let p = cstr(p)?;
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(ret) = unsafe {
+                try_statx(self.fd.raw(), p.as_ptr(), libc::AT_SYMLINK_NOFOLLOW)
+            } {
+                return ret
+            }
+        }
+        let mut stat: stat64 = unsafe { mem::zeroed() };
+        cvt(unsafe {
+            at::fstatat(self.fd.raw(),
+                        p.as_ptr(),
+                        &mut stat as *mut _ as *mut _,
+                        libc::AT_SYMLINK_NOFOLLOW)
+        })?;
+        Ok(FileAttr::from_stat64(stat))
+    }
+
+    pub fn read_dir(&self) -> io::Result<ReadDir> {
+        // The stream takes ownership of the descriptor it's given and moves
+        // its offset around. A duplicate would share that offset with us, so
+        // open the directory afresh instead.
+        let fd = open_dir(self.fd.raw(), &cstr(Path::new("."))?, true)?;
+        unsafe {
+            let ptr = at::fdopendir(fd.raw());
+            if ptr.is_null() {
+                return Err(Error::last_os_error())
+            }
+            fd.into_raw();
+            let inner = InnerReadDir { dirp: DirStream(ptr), root: self.root.clone() };
+            Ok(ReadDir(Arc::new(inner)))
+        }
+    }
+
+    pub fn rename_at(&self, old: &Path, new_dir: &Dir, new: &Path) -> io::Result<()> {
+        let old = cstr(old)?;
+        let new = cstr(new)?;
+        cvt(unsafe {
+            at::renameat(self.fd.raw(), old.as_ptr(), new_dir.fd.raw(), new.as_ptr())
+        })?;
+        Ok(())
+    }
+
+    pub fn fd(&self) -> &FileDesc { &self.fd }
+}
+
+fn open_dir(dirfd: c_int, p: &CStr, follow: bool) -> io::Result<FileDesc> {
+    let mut opts = OpenOptions::new();
+    opts.read(true);
+    opts.custom_flags(O_DIRECTORY | if follow { 0 } else { libc::O_NOFOLLOW });
+    File::open_at_c(dirfd, p, &opts).map(File::into_fd)
+}
+
+impl fmt::Debug for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Dir")
+            .field("fd", &self.fd.raw())
+            .field("path", &self.root)
+            .finish()
+    }
+}
+
 fn cstr(path: &Path) -> io::Result<CString> {
     Ok(CString::new(path.as_os_str().as_bytes())?)
 }
@@ -691,7 +1147,7 @@ pub fn readdir(p: &Path) -> io::Result<ReadDir> {
         if ptr.is_null() {
             Err(Error::last_os_error())
         } else {
-            let inner = InnerReadDir { dirp: Dir(ptr), root };
+            let inner = InnerReadDir { dirp: DirStream(ptr), root };
             Ok(ReadDir(Arc::new(inner)))
         }
     }
@@ -723,19 +1179,71 @@ pub fn rmdir(p: &Path) -> io::Result<()> {
 }
 
 pub fn remove_dir_all(path: &Path) -> io::Result<()> {
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    {
+        if !at::available() {
+            return remove_dir_all_by_path(path)
+        }
+    }
+
+    // Open the directory without following symlinks before looking inside it,
+    // and only ever touch its contents relative to that handle, so that
+    // swapping in a symlink halfway through can't redirect the removal.
+    let c_path = cstr(path)?;
+    let dir = match open_dir(libc::AT_FDCWD, &c_path, false) {
+        Ok(fd) => Dir { fd, root: path.to_path_buf() },
+        Err(e) => {
+            return if lstat(path)?.file_type().is_symlink() {
+                unlink(path)
+            } else {
+                Err(e)
+            }
+        }
+    };
+    remove_dir_all_recursive(&dir)?;
+    rmdir(path)
+}
+
+fn remove_dir_all_recursive(dir: &Dir) -> io::Result<()> {
+    for child in dir.read_dir()? {
+        let child = child?;
+        let name = child.file_name();
+        // An entry of unknown type is looked up relative to `dir` too, rather
+        // than by the path that `DirEntry::file_type` would fall back to.
+        let file_type = match child.file_type_from_entry() {
+            Some(file_type) => file_type,
+            None => dir.metadata_at(Path::new(&name))?.file_type(),
+        };
+        let name = cstr(Path::new(&name))?;
+        if file_type.is_dir() {
+            let fd = open_dir(dir.fd.raw(), &name, false)?;
+            remove_dir_all_recursive(&Dir { fd, root: child.path() })?;
+            dir.unlink_at(&name, AT_REMOVEDIR)?;
+        } else {
+            dir.unlink_at(&name, 0)?;
+        }
+    }
+    Ok(())
+}
+
+// Without the `*at` functions all we can do is remove everything by path,
+// checking for symlinks along the way.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn remove_dir_all_by_path(path: &Path) -> io::Result<()> {
     let filetype = lstat(path)?.file_type();
     if filetype.is_symlink() {
         unlink(path)
     } else {
-        remove_dir_all_recursive(path)
+        remove_dir_all_by_path_recursive(path)
     }
 }
 
-fn remove_dir_all_recursive(path: &Path) -> io::Result<()> {
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn remove_dir_all_by_path_recursive(path: &Path) -> io::Result<()> {
     for child in readdir(path)? {
         let child = child?;
         if child.file_type()?.is_dir() {
-            remove_dir_all_recursive(&child.path())?;
+            remove_dir_all_by_path_recursive(&child.path())?;
         } else {
             unlink(&child.path())?;
         }
@@ -785,20 +1293,34 @@ pub fn link(src: &Path, dst: &Path) -> io::Result<()> {
 
 pub fn stat(p: &Path) -> io::Result<FileAttr> {
     let p = cstr(p)?;
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(ret) = unsafe { try_statx(libc::AT_FDCWD, p.as_ptr(), 0) } {
+            return ret
+        }
+    }
     let mut stat: stat64 = unsafe { mem::zeroed() };
     cvt(unsafe {
         stat64(p.as_ptr(), &mut stat as *mut _ as *mut _)
     })?;
-    Ok(FileAttr { stat: stat })
+    Ok(FileAttr::from_stat64(stat))
 }
 
 pub fn lstat(p: &Path) -> io::Result<FileAttr> {
     let p = cstr(p)?;
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(ret) = unsafe {
+            try_statx(libc::AT_FDCWD, p.as_ptr(), libc::AT_SYMLINK_NOFOLLOW)
+        } {
+            return ret
+        }
+    }
     let mut stat: stat64 = unsafe { mem::zeroed() };
     cvt(unsafe {
         lstat64(p.as_ptr(), &mut stat as *mut _ as *mut _)
     })?;
-    Ok(FileAttr { stat: stat })
+    Ok(FileAttr::from_stat64(stat))
 }
 
 pub fn canonicalize(p: &Path) -> io::Result<PathBuf> {
@@ -18,5 +18,7 @@ mod process_inner;
 #[cfg(target_os = "fuchsia")]
 #[path = "process_fuchsia.rs"]
 mod process_inner;
+#[cfg(not(target_os = "fuchsia"))]
+mod wait_timeout;
 #[cfg(target_os = "fuchsia")]
 mod zircon;
@@ -12,6 +12,7 @@ use io;
 use libc::{self, size_t};
 use mem;
 use ptr;
+use time::Duration;
 
 use sys::process::zircon::{Handle, zx_handle_t};
 use sys::process::process_common::*;
@@ -197,4 +198,26 @@ impl Process {
         }
         Ok(Some(ExitStatus::new(proc_info.rec.return_code)))
     }
+
+    pub fn wait_timeout(&mut self, dur: Duration) -> io::Result<Option<ExitStatus>> {
+        use sys::process::zircon::*;
+
+        let nanos = dur.as_secs().checked_mul(1_000_000_000).and_then(|nanos| {
+            nanos.checked_add(dur.subsec_nanos() as u64)
+        }).unwrap_or(ZX_TIME_INFINITE);
+
+        unsafe {
+            let status = zx_object_wait_one(self.handle.raw(), ZX_TASK_TERMINATED,
+                                            zx_deadline_after(nanos), ptr::null_mut());
+            match status {
+                0 => { }, // Success
+                x if x == ERR_TIMED_OUT => {
+                    return Ok(None);
+                },
+                _ => { zx_cvt(status)?; },
+            }
+        }
+        // The process has terminated, so this won't come back empty.
+        self.try_wait()
+    }
 }
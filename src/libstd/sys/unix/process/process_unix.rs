@@ -14,6 +14,8 @@ use ptr;
 
 use sys::cvt;
 use sys::process::process_common::*;
+use sys::process::wait_timeout::ExitWaiter;
+use time::Duration;
 
 ////////////////////////////////////////////////////////////////////////////////
 // Command
@@ -65,7 +67,7 @@ impl Command {
             }
         };
 
-        let mut p = Process { pid: pid, status: None };
+        let mut p = Process { pid: pid, status: None, exit_waiter: ExitWaiter::new() };
         drop(output);
         let mut bytes = [0; 8];
 
@@ -273,7 +275,7 @@ impl Command {
             }
         }
 
-        let mut p = Process { pid: 0, status: None };
+        let mut p = Process { pid: 0, status: None, exit_waiter: ExitWaiter::new() };
 
         struct PosixSpawnFileActions(libc::posix_spawn_file_actions_t);
 
@@ -357,6 +359,7 @@ impl Command {
 pub struct Process {
     pid: pid_t,
     status: Option<ExitStatus>,
+    exit_waiter: ExitWaiter,
 }
 
 impl Process {
@@ -402,4 +405,15 @@ impl Process {
             Ok(Some(ExitStatus::new(status)))
         }
     }
+
+    pub fn wait_timeout(&mut self, dur: Duration) -> io::Result<Option<ExitStatus>> {
+        if let Some(status) = self.try_wait()? {
+            return Ok(Some(status))
+        }
+        if self.exit_waiter.wait(self.pid, dur)? {
+            self.wait().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
 }
@@ -0,0 +1,236 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Blocking on child exit with a timeout, without reaping the child and
+//! without touching any signal state.
+//!
+//! On Linux 5.3 and later a pidfd for the child becomes readable once it
+//! exits, and on macOS and the BSDs a kqueue reports the exit. Everywhere
+//! else, and on older Linux kernels, there's no way to be notified of a
+//! child exiting short of handling `SIGCHLD`, which belongs to the program,
+//! so the child is polled with `waitid` and `WNOWAIT`, which leaves reaping
+//! it to `wait`. No threads are started either way.
+
+pub use self::imp::ExitWaiter;
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "openbsd",
+          target_os = "netbsd",
+          target_os = "bitrig"))]
+mod imp {
+    use cmp;
+    use io;
+    use libc::{self, c_int, pid_t};
+    use mem;
+    use sys::cvt;
+    use sys::fd::FileDesc;
+    use sys_common::process::wait_with_timeout;
+    use time::Duration;
+
+    pub struct ExitWaiter;
+
+    impl ExitWaiter {
+        pub fn new() -> ExitWaiter {
+            ExitWaiter
+        }
+
+        /// Waits up to `dur` for the child `pid` to exit, returning whether it
+        /// has.
+        pub fn wait(&mut self, pid: pid_t, dur: Duration) -> io::Result<bool> {
+            let kq = FileDesc::new(cvt(unsafe { libc::kqueue() })?);
+            let mut change: libc::kevent = unsafe { mem::zeroed() };
+            change.ident = pid as libc::uintptr_t;
+            change.filter = libc::EVFILT_PROC;
+            change.flags = libc::EV_ADD | libc::EV_ONESHOT;
+            change.fflags = libc::NOTE_EXIT;
+
+            let exited = wait_with_timeout(dur, |dur| {
+                let timeout = libc::timespec {
+                    tv_sec: cmp::min(dur.as_secs(), libc::time_t::max_value() as u64)
+                        as libc::time_t,
+                    tv_nsec: dur.subsec_nanos() as libc::c_long,
+                };
+                let mut event: libc::kevent = unsafe { mem::zeroed() };
+                // Adding the same event again after an interruption is fine.
+                let n = unsafe { libc::kevent(kq.raw(), &change, 1, &mut event, 1, &timeout) };
+                let errno = if n == -1 {
+                    io::Error::last_os_error().raw_os_error().unwrap_or(0)
+                } else if n > 0 && event.flags & libc::EV_ERROR != 0 {
+                    event.data as c_int
+                } else {
+                    return Ok(if n > 0 { Some(()) } else { None })
+                };
+                match errno {
+                    libc::EINTR => Ok(None),
+                    // Some kernels refuse to watch a child that has already
+                    // exited and is only waiting to be reaped.
+                    libc::ESRCH => Ok(Some(())),
+                    _ => Err(io::Error::from_raw_os_error(errno)),
+                }
+            })?;
+            Ok(exited.is_some())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos",
+              target_os = "ios",
+              target_os = "freebsd",
+              target_os = "dragonfly",
+              target_os = "openbsd",
+              target_os = "netbsd",
+              target_os = "bitrig")))]
+mod imp {
+    use cmp;
+    use io;
+    use libc::{self, pid_t};
+    use mem;
+    use sys::cvt_r;
+    use sys_common::process::wait_with_timeout;
+    use thread;
+    use time::Duration;
+    #[cfg(target_os = "linux")]
+    use io::ErrorKind;
+    #[cfg(target_os = "linux")]
+    use libc::c_int;
+    #[cfg(target_os = "linux")]
+    use sys::fd::FileDesc;
+    #[cfg(target_os = "linux")]
+    use u64;
+
+    pub struct ExitWaiter {
+        // `None` until the first wait, and then the pidfd if the kernel could
+        // give us one.
+        #[cfg(target_os = "linux")]
+        pidfd: Option<Option<FileDesc>>,
+    }
+
+    impl ExitWaiter {
+        pub fn new() -> ExitWaiter {
+            ExitWaiter {
+                #[cfg(target_os = "linux")]
+                pidfd: None,
+            }
+        }
+
+        /// Waits up to `dur` for the child `pid` to exit, returning whether it
+        /// has.
+        pub fn wait(&mut self, pid: pid_t, dur: Duration) -> io::Result<bool> {
+            #[cfg(target_os = "linux")]
+            {
+                if self.pidfd.is_none() {
+                    self.pidfd = Some(pidfd_open(pid)?);
+                }
+                if let Some(Some(ref pidfd)) = self.pidfd {
+                    return wait_pidfd(pidfd, dur)
+                }
+            }
+
+            wait_polling(pid, dur)
+        }
+    }
+
+    /// Opens a pidfd for `pid`, or returns `None` if the kernel can't do that.
+    #[cfg(target_os = "linux")]
+    fn pidfd_open(pid: pid_t) -> io::Result<Option<FileDesc>> {
+        use sync::atomic::{AtomicBool, Ordering};
+
+        #[cfg(target_arch = "mips")]
+        const SYS_PIDFD_OPEN: libc::c_long = 4434;
+        #[cfg(target_arch = "mips64")]
+        const SYS_PIDFD_OPEN: libc::c_long = 5434;
+        #[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+        const SYS_PIDFD_OPEN: libc::c_long = 434;
+
+        // Kernels older than 5.3 return ENOSYS, and seccomp filters that don't
+        // know about the syscall tend to return EPERM. Neither is going to
+        // change for the lifetime of the process, so don't keep asking.
+        static HAS_PIDFD_OPEN: AtomicBool = AtomicBool::new(true);
+
+        if !HAS_PIDFD_OPEN.load(Ordering::Relaxed) {
+            return Ok(None)
+        }
+        let fd = unsafe { libc::syscall(SYS_PIDFD_OPEN, pid, 0) };
+        if fd == -1 {
+            let err = io::Error::last_os_error();
+            return match err.raw_os_error() {
+                Some(libc::ENOSYS) | Some(libc::EPERM) => {
+                    HAS_PIDFD_OPEN.store(false, Ordering::Relaxed);
+                    Ok(None)
+                }
+                _ => Err(err),
+            }
+        }
+        // pidfds are always opened with O_CLOEXEC.
+        Ok(Some(FileDesc::new(fd as c_int)))
+    }
+
+    /// Waits up to `dur` for `pidfd` to become readable, returning whether the
+    /// child has exited.
+    #[cfg(target_os = "linux")]
+    fn wait_pidfd(pidfd: &FileDesc, dur: Duration) -> io::Result<bool> {
+        let exited = wait_with_timeout(dur, |dur| {
+            Ok(if poll_readable(pidfd, dur)? { Some(()) } else { None })
+        })?;
+        Ok(exited.is_some())
+    }
+
+    /// Polls the child `pid` for up to `dur`, backing off from 1ms to 50ms
+    /// between checks to keep the overhead down.
+    fn wait_polling(pid: pid_t, dur: Duration) -> io::Result<bool> {
+        let mut interval = Duration::from_millis(1);
+        let exited = wait_with_timeout(dur, |dur| {
+            if has_exited(pid)? {
+                return Ok(Some(()))
+            }
+            thread::sleep(cmp::min(interval, dur));
+            interval = cmp::min(interval * 2, Duration::from_millis(50));
+            Ok(None)
+        })?;
+        Ok(exited.is_some())
+    }
+
+    /// Returns whether the child `pid` has exited, without reaping it.
+    fn has_exited(pid: pid_t) -> io::Result<bool> {
+        let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+        cvt_r(|| unsafe {
+            libc::waitid(libc::P_PID, pid as libc::id_t, &mut info,
+                         libc::WEXITED | libc::WNOHANG | libc::WNOWAIT)
+        })?;
+        // With `WNOHANG` nothing is filled in if the child is still running.
+        Ok(info.si_signo == libc::SIGCHLD)
+    }
+
+    /// Polls `fd` for readability for at most `dur`, returning whether it
+    /// became readable. An interrupted poll counts as a timeout; callers loop
+    /// until their deadline has actually passed.
+    #[cfg(target_os = "linux")]
+    fn poll_readable(fd: &FileDesc, dur: Duration) -> io::Result<bool> {
+        let mut pfd = libc::pollfd { fd: fd.raw(), events: libc::POLLIN, revents: 0 };
+        // Round up, otherwise a sub-millisecond remainder would spin.
+        let ms = dur.as_secs()
+            .checked_mul(1000)
+            .and_then(|ms| ms.checked_add((dur.subsec_nanos() as u64 + 999_999) / 1_000_000))
+            .unwrap_or(u64::MAX);
+        let ms = cmp::min(ms, c_int::max_value() as u64) as c_int;
+        let n = unsafe { libc::poll(&mut pfd, 1, ms) };
+        if n == -1 {
+            let err = io::Error::last_os_error();
+            if err.kind() == ErrorKind::Interrupted {
+                return Ok(false)
+            }
+            return Err(err)
+        }
+        Ok(n > 0)
+    }
+}
@@ -109,6 +109,8 @@ extern {
     pub fn zx_handle_duplicate(handle: zx_handle_t, rights: zx_rights_t,
                                out: *const zx_handle_t) -> zx_handle_t;
 
+    pub fn zx_deadline_after(nanoseconds: zx_time_t) -> zx_time_t;
+
     pub fn zx_object_wait_one(handle: zx_handle_t, signals: zx_signals_t, timeout: zx_time_t,
                               pending: *mut zx_signals_t) -> zx_status_t;
 
@@ -11,7 +11,7 @@
 use ffi::OsString;
 use fmt;
 use hash::{Hash, Hasher};
-use io::{self, SeekFrom};
+use io::{self, IoSlice, IoSliceMut, SeekFrom};
 use path::{Path, PathBuf};
 use sys::time::SystemTime;
 use sys::{unsupported, Void};
@@ -24,6 +24,8 @@ pub struct ReadDir(Void);
 
 pub struct DirEntry(Void);
 
+pub struct Dir(Void);
+
 #[derive(Clone, Debug)]
 pub struct OpenOptions { }
 
@@ -210,10 +212,18 @@ impl File {
         match self.0 {}
     }
 
+    pub fn read_vectored(&self, _bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        match self.0 {}
+    }
+
     pub fn write(&self, _buf: &[u8]) -> io::Result<usize> {
         match self.0 {}
     }
 
+    pub fn write_vectored(&self, _bufs: &[IoSlice]) -> io::Result<usize> {
+        match self.0 {}
+    }
+
     pub fn flush(&self) -> io::Result<()> {
         match self.0 {}
     }
@@ -251,6 +261,46 @@ impl fmt::Debug for File {
     }
 }
 
+impl Dir {
+    pub fn open(_p: &Path) -> io::Result<Dir> {
+        unsupported()
+    }
+
+    pub fn open_dir_at(&self, _p: &Path) -> io::Result<Dir> {
+        match self.0 {}
+    }
+
+    pub fn open_file_at(&self, _p: &Path, _opts: &OpenOptions) -> io::Result<File> {
+        match self.0 {}
+    }
+
+    pub fn create_dir_at(&self, _p: &Path) -> io::Result<()> {
+        match self.0 {}
+    }
+
+    pub fn remove_at(&self, _p: &Path) -> io::Result<()> {
+        match self.0 {}
+    }
+
+    pub fn metadata_at(&self, _p: &Path) -> io::Result<FileAttr> {
+        match self.0 {}
+    }
+
+    pub fn read_dir(&self) -> io::Result<ReadDir> {
+        match self.0 {}
+    }
+
+    pub fn rename_at(&self, _old: &Path, _new_dir: &Dir, _new: &Path) -> io::Result<()> {
+        match self.0 {}
+    }
+}
+
+impl fmt::Debug for Dir {
+    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {}
+    }
+}
+
 pub fn readdir(_p: &Path) -> io::Result<ReadDir> {
     unsupported()
 }
@@ -104,6 +104,26 @@ pub fn unwind_backtrace(frames: &mut [Frame])
     Ok((i, backtrace_context))
 }
 
+/// Returns a context for resolving frames that were captured earlier, with
+/// dbghelp's symbol handler initialized for this process.
+pub fn symbol_context() -> io::Result<BacktraceContext> {
+    let dbghelp = DynamicLibrary::open("dbghelp.dll")?;
+    let SymInitialize = sym!(dbghelp, "SymInitialize", SymInitializeFn)?;
+    let SymCleanup = sym!(dbghelp, "SymCleanup", SymCleanupFn)?;
+
+    let process = unsafe { c::GetCurrentProcess() };
+    let ret = unsafe { SymInitialize(process, ptr::null_mut(), c::TRUE) };
+    if ret != c::TRUE {
+        // Someone else's session is still open, which isn't ours to clean up.
+        return Err(io::Error::last_os_error())
+    }
+    Ok(BacktraceContext {
+        handle: process,
+        SymCleanup,
+        dbghelp,
+    })
+}
+
 type SymInitializeFn =
     unsafe extern "system" fn(c::HANDLE, *mut c_void,
                               c::BOOL) -> c::BOOL;
@@ -12,7 +12,7 @@ use os::windows::prelude::*;
 
 use ffi::OsString;
 use fmt;
-use io::{self, Error, SeekFrom};
+use io::{self, Error, IoSlice, IoSliceMut, SeekFrom};
 use mem;
 use path::{Path, PathBuf};
 use ptr;
@@ -321,6 +321,10 @@ impl File {
         self.handle.read(buf)
     }
 
+    pub fn read_vectored(&self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.handle.read_vectored(bufs)
+    }
+
     pub fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
         self.handle.read_at(buf, offset)
     }
@@ -329,6 +333,10 @@ impl File {
         self.handle.write(buf)
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.handle.write_vectored(bufs)
+    }
+
     pub fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
         self.handle.write_at(buf, offset)
     }
@@ -601,6 +609,63 @@ pub fn rmdir(p: &Path) -> io::Result<()> {
     Ok(())
 }
 
+// There's no handle-relative equivalent of the `*at` functions exposed here,
+// so directory handles just remember the path they were opened with.
+pub struct Dir { root: PathBuf }
+
+impl Dir {
+    pub fn open(p: &Path) -> io::Result<Dir> {
+        if !stat(p)?.file_type().is_directory() {
+            return Err(Error::from_raw_os_error(c::ERROR_DIRECTORY as i32))
+        }
+        Ok(Dir { root: p.to_path_buf() })
+    }
+
+    pub fn open_dir_at(&self, p: &Path) -> io::Result<Dir> {
+        let path = self.root.join(p);
+        if !lstat(&path)?.file_type().is_dir() {
+            return Err(Error::from_raw_os_error(c::ERROR_DIRECTORY as i32))
+        }
+        Ok(Dir { root: path })
+    }
+
+    pub fn open_file_at(&self, p: &Path, opts: &OpenOptions) -> io::Result<File> {
+        File::open(&self.root.join(p), opts)
+    }
+
+    pub fn create_dir_at(&self, p: &Path) -> io::Result<()> {
+        DirBuilder::new().mkdir(&self.root.join(p))
+    }
+
+    pub fn remove_at(&self, p: &Path) -> io::Result<()> {
+        let path = self.root.join(p);
+        let filetype = lstat(&path)?.file_type();
+        if filetype.is_directory() {
+            rmdir(&path)
+        } else {
+            unlink(&path)
+        }
+    }
+
+    pub fn metadata_at(&self, p: &Path) -> io::Result<FileAttr> {
+        lstat(&self.root.join(p))
+    }
+
+    pub fn read_dir(&self) -> io::Result<ReadDir> {
+        readdir(&self.root)
+    }
+
+    pub fn rename_at(&self, old: &Path, new_dir: &Dir, new: &Path) -> io::Result<()> {
+        rename(&self.root.join(old), &new_dir.root.join(new))
+    }
+}
+
+impl fmt::Debug for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Dir").field("path", &self.root).finish()
+    }
+}
+
 pub fn remove_dir_all(path: &Path) -> io::Result<()> {
     let filetype = lstat(path)?.file_type();
     if filetype.is_symlink() {
@@ -11,7 +11,7 @@
 use os::windows::prelude::*;
 
 use ffi::OsStr;
-use io;
+use io::{self, IoSlice, IoSliceMut};
 use mem;
 use path::Path;
 use ptr;
@@ -176,9 +176,17 @@ impl AnonPipe {
         self.inner.read(buf)
     }
 
+    pub fn read_vectored(&self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+
     pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
         self.inner.write(buf)
     }
+
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
 }
 
 pub fn read2(p1: AnonPipe,
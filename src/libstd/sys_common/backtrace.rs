@@ -22,6 +22,7 @@ use ptr;
 
 pub use sys::backtrace::{
     unwind_backtrace,
+    symbol_context,
     resolve_symname,
     foreach_symbol_fileline,
     BacktraceContext
@@ -46,20 +47,33 @@ pub struct Frame {
 }
 
 /// Max number of frames to print.
-const MAX_NB_FRAMES: usize = 100;
+pub const MAX_NB_FRAMES: usize = 100;
+
+static LOCK: Mutex = Mutex::new();
+
+/// Guard for the lock serializing backtrace printing and symbol resolution.
+pub struct BacktraceLock(());
+
+impl Drop for BacktraceLock {
+    fn drop(&mut self) {
+        unsafe { LOCK.unlock() }
+    }
+}
+
+/// Acquires the lock that must be held while resolving symbols.
+///
+/// Some platforms require it, like `SymFromAddr` on Windows, and libbacktrace
+/// sets up its state lazily without synchronization of its own.
+pub fn lock() -> BacktraceLock {
+    unsafe { LOCK.lock() }
+    BacktraceLock(())
+}
 
 /// Prints the current backtrace.
 pub fn print(w: &mut Write, format: PrintFormat) -> io::Result<()> {
-    static LOCK: Mutex = Mutex::new();
-
     // Use a lock to prevent mixed output in multithreading context.
-    // Some platforms also requires it, like `SymFromAddr` on Windows.
-    unsafe {
-        LOCK.lock();
-        let res = _print(w, format);
-        LOCK.unlock();
-        res
-    }
+    let _lock = lock();
+    _print(w, format)
 }
 
 fn _print(w: &mut Write, format: PrintFormat) -> io::Result<()> {
@@ -177,7 +191,7 @@ pub fn log_enabled() -> Option<PrintFormat> {
 ///
 /// These output functions should now be used everywhere to ensure consistency.
 /// You may want to also use `output_fileline`.
-fn output(w: &mut Write, idx: usize, frame: Frame,
+pub fn output(w: &mut Write, idx: usize, frame: Frame,
               s: Option<&str>, format: PrintFormat) -> io::Result<()> {
     // Remove the `17: 0x0 - <unknown>` line.
     if format == PrintFormat::Short && frame.exact_position == ptr::null() {
@@ -202,7 +216,7 @@ fn output(w: &mut Write, idx: usize, frame: Frame,
 ///
 /// See also `output`.
 #[allow(dead_code)]
-fn output_fileline(w: &mut Write,
+pub fn output_fileline(w: &mut Write,
                    file: &[u8],
                    line: u32,
                    format: PrintFormat) -> io::Result<()> {
@@ -15,6 +15,8 @@ use ffi::{OsStr, OsString};
 use env;
 use collections::BTreeMap;
 use borrow::Borrow;
+use io;
+use time::{Duration, Instant};
 
 pub trait EnvKey:
     From<OsString> + Into<OsString> +
@@ -134,3 +136,21 @@ impl<K: EnvKey> CommandEnv<K> {
         }
     }
 }
+
+/// Calls `wait` with what is left of `dur` until it returns `Some`, or the
+/// time is up. `wait` may return `None` early, for instance when it was
+/// interrupted by a signal.
+pub fn wait_with_timeout<T, F>(dur: Duration, mut wait: F) -> io::Result<Option<T>>
+    where F: FnMut(Duration) -> io::Result<Option<T>>
+{
+    let start = Instant::now();
+    loop {
+        let elapsed = start.elapsed();
+        if elapsed >= dur {
+            return Ok(None)
+        }
+        if let Some(res) = wait(dur - elapsed)? {
+            return Ok(Some(res))
+        }
+    }
+}
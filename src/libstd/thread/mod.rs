@@ -171,6 +171,7 @@ use cell::UnsafeCell;
 use ffi::{CStr, CString};
 use fmt;
 use io;
+use marker::PhantomData;
 use panic;
 use panicking;
 use str;
@@ -211,6 +212,15 @@ pub use self::local::{LocalKey, AccessError};
 #[unstable(feature = "libstd_thread_internals", issue = "0")]
 #[doc(hidden)] pub use self::local::os::Key as __OsLocalKeyInner;
 
+////////////////////////////////////////////////////////////////////////////////
+// Scoped threads
+////////////////////////////////////////////////////////////////////////////////
+
+mod scoped;
+
+#[unstable(feature = "scoped_threads", issue = "0")]
+pub use self::scoped::{scope, Scope, ScopedJoinHandle};
+
 ////////////////////////////////////////////////////////////////////////////////
 // Builder
 ////////////////////////////////////////////////////////////////////////////////
@@ -386,6 +396,19 @@ impl Builder {
     #[stable(feature = "rust1", since = "1.0.0")]
     pub fn spawn<F, T>(self, f: F) -> io::Result<JoinHandle<T>> where
         F: FnOnce() -> T, F: Send + 'static, T: Send + 'static
+    {
+        unsafe { self.spawn_unchecked(f, None) }.map(JoinHandle)
+    }
+
+    // Spawns a thread running `f` without requiring it to be `'static`.
+    //
+    // The caller must make sure that the thread is joined before anything
+    // borrowed by `f` or `T` goes away; `scope` does so by counting the
+    // threads spawned in `scope_data` and waiting for all of them to finish.
+    unsafe fn spawn_unchecked<'a, 'scope, F, T>(self, f: F,
+                                                scope_data: Option<Arc<scoped::ScopeData>>)
+                                                -> io::Result<JoinInner<'scope, T>> where
+        F: FnOnce() -> T, F: Send + 'a, T: Send + 'a, 'scope: 'a
     {
         let Builder { name, stack_size } = self;
 
@@ -394,33 +417,42 @@ impl Builder {
         let my_thread = Thread::new(name);
         let their_thread = my_thread.clone();
 
-        let my_packet : Arc<UnsafeCell<Option<Result<T>>>>
-            = Arc::new(UnsafeCell::new(None));
+        let my_packet: Arc<Packet<'scope, T>> = Arc::new(Packet {
+            scope: scope_data,
+            result: UnsafeCell::new(None),
+            _marker: PhantomData,
+        });
         let their_packet = my_packet.clone();
 
         let main = move || {
             if let Some(name) = their_thread.cname() {
                 imp::Thread::set_name(name);
             }
-            unsafe {
-                thread_info::set(imp::guard::current(), their_thread);
-                #[cfg(feature = "backtrace")]
-                let try_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-                    ::sys_common::backtrace::__rust_begin_short_backtrace(f)
-                }));
-                #[cfg(not(feature = "backtrace"))]
-                let try_result = panic::catch_unwind(panic::AssertUnwindSafe(f));
-                *their_packet.get() = Some(try_result);
-            }
+            thread_info::set(imp::guard::current(), their_thread);
+            #[cfg(feature = "backtrace")]
+            let try_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                ::sys_common::backtrace::__rust_begin_short_backtrace(f)
+            }));
+            #[cfg(not(feature = "backtrace"))]
+            let try_result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+            *their_packet.result.get() = Some(try_result);
+            // Dropping our reference to the packet may be what tells an
+            // enclosing scope that this thread is done, so it must be the last
+            // thing touching borrowed data.
+            drop(their_packet);
         };
 
-        Ok(JoinHandle(JoinInner {
-            native: unsafe {
-                Some(imp::Thread::new(stack_size, Box::new(main))?)
-            },
+        if let Some(ref scope_data) = my_packet.scope {
+            scope_data.increment_num_running_threads();
+        }
+
+        // If spawning fails, both halves of the packet are dropped here, which
+        // takes this thread back out of the scope's count.
+        Ok(JoinInner {
+            native: Some(imp::Thread::new(stack_size, Box::new(main))?),
             thread: my_thread,
-            packet: Packet(my_packet),
-        }))
+            packet: my_packet,
+        })
     }
 }
 
@@ -1180,29 +1212,57 @@ pub type Result<T> = ::result::Result<T, Box<Any + Send + 'static>>;
 // parent thread never reads this packet until the child has exited).
 //
 // This packet itself is then stored into a `JoinInner` which in turns is placed
-// in `JoinHandle` and `JoinGuard`. Due to the usage of `UnsafeCell` we need to
-// manually worry about impls like Send and Sync. The type `T` should
-// already always be Send (otherwise the thread could not have been created) and
-// this type is inherently Sync because no methods take &self. Regardless,
-// however, we add inheriting impls for Send/Sync to this type to ensure it's
-// Send/Sync and that future modifications will still appropriately classify it.
-struct Packet<T>(Arc<UnsafeCell<Option<Result<T>>>>);
-
-unsafe impl<T: Send> Send for Packet<T> {}
-unsafe impl<T: Sync> Sync for Packet<T> {}
-
-/// Inner representation for JoinHandle
-struct JoinInner<T> {
+// in `JoinHandle` and `ScopedJoinHandle`. Due to the usage of `UnsafeCell` we
+// need to manually worry about Sync. The result is only ever touched by the
+// child before it exits and by the joining thread after that, never by both at
+// once, so sharing the packet only requires `T` to be Send.
+//
+// Threads spawned in a scope also hold on to the scope's data through the
+// packet. Whichever side drops the packet last drops the result and then
+// tells the scope that the thread is done, so a result borrowing from the
+// scope's environment never outlives it.
+struct Packet<'scope, T> {
+    scope: Option<Arc<scoped::ScopeData>>,
+    result: UnsafeCell<Option<Result<T>>>,
+    _marker: PhantomData<Option<&'scope scoped::ScopeData>>,
+}
+
+unsafe impl<'scope, T: Send> Sync for Packet<'scope, T> {}
+
+impl<'scope, T> Drop for Packet<'scope, T> {
+    fn drop(&mut self) {
+        // A result which is still an `Err` here belongs to a thread that
+        // panicked and was never joined.
+        let unhandled_panic = match unsafe { &*self.result.get() } {
+            &Some(Err(_)) => true,
+            _ => false,
+        };
+        // Drop the result before telling the scope we are done. A panic here
+        // can't be propagated anywhere sensible, so abort instead.
+        let result = &self.result;
+        if panic::catch_unwind(panic::AssertUnwindSafe(|| unsafe {
+            *result.get() = None;
+        })).is_err() {
+            rtabort!("thread result panicked on drop");
+        }
+        if let Some(ref scope) = self.scope {
+            scope.decrement_num_running_threads(unhandled_panic);
+        }
+    }
+}
+
+/// Inner representation for JoinHandle and ScopedJoinHandle
+struct JoinInner<'scope, T> {
     native: Option<imp::Thread>,
     thread: Thread,
-    packet: Packet<T>,
+    packet: Arc<Packet<'scope, T>>,
 }
 
-impl<T> JoinInner<T> {
+impl<'scope, T> JoinInner<'scope, T> {
     fn join(&mut self) -> Result<T> {
         self.native.take().unwrap().join();
         unsafe {
-            (*self.packet.0.get()).take().unwrap()
+            (*self.packet.result.get()).take().unwrap()
         }
     }
 }
@@ -1271,7 +1331,7 @@ impl<T> JoinInner<T> {
 /// [`thread::spawn`]: fn.spawn.html
 /// [`thread::Builder::spawn`]: struct.Builder.html#method.spawn
 #[stable(feature = "rust1", since = "1.0.0")]
-pub struct JoinHandle<T>(JoinInner<T>);
+pub struct JoinHandle<T>(JoinInner<'static, T>);
 
 impl<T> JoinHandle<T> {
     /// Extracts a handle to the underlying thread.
@@ -1354,6 +1414,8 @@ fn _assert_sync_and_send() {
 #[cfg(all(test, not(target_os = "emscripten")))]
 mod tests {
     use any::Any;
+    use panic;
+    use sync::atomic::{AtomicBool, Ordering};
     use sync::mpsc::{channel, Sender};
     use result;
     use super::{Builder};
@@ -1598,6 +1660,55 @@ mod tests {
         assert!(thread::current().id() != spawned_id);
     }
 
+    #[test]
+    fn test_scope_borrows_and_joins() {
+        let mut data = vec![1, 2, 3];
+        let mut sum = 0;
+        thread::scope(|s| {
+            s.spawn(|| sum = data.iter().sum());
+            let t = s.spawn(|| data.len());
+            assert_eq!(t.join().unwrap(), 3);
+        });
+        assert_eq!(sum, 6);
+        data.push(4);
+    }
+
+    #[test]
+    fn test_scope_nested_spawn() {
+        let (tx, rx) = channel();
+        thread::scope(|s| {
+            let tx = &tx;
+            s.spawn(move || {
+                s.spawn(move || tx.send(1).unwrap());
+            });
+        });
+        assert_eq!(rx.try_recv(), Ok(1));
+    }
+
+    #[test]
+    fn test_scope_joined_panic_is_handled() {
+        let res = thread::scope(|s| {
+            s.spawn(|| panic!()).join().is_err()
+        });
+        assert!(res);
+    }
+
+    #[test]
+    fn test_scope_unjoined_panic_propagates() {
+        let done = AtomicBool::new(false);
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            thread::scope(|s| {
+                s.spawn(|| panic!());
+                s.spawn(|| {
+                    thread::sleep(Duration::from_millis(10));
+                    done.store(true, Ordering::SeqCst);
+                });
+            });
+        }));
+        assert!(res.is_err());
+        assert!(done.load(Ordering::SeqCst));
+    }
+
     // NOTE: the corresponding test for stderr is in run-pass/thread-stderr, due
     // to the test harness apparently interfering with stderr configuration.
 }
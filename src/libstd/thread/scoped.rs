@@ -0,0 +1,336 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Threads that may borrow from the stack frame that spawned them.
+
+use super::{current, park, Builder, JoinInner, Result, Thread};
+use fmt;
+use io;
+use marker::PhantomData;
+use panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use sync::Arc;
+use sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use usize;
+
+/// A scope to spawn scoped threads in.
+///
+/// See [`scope`] for details.
+///
+/// [`scope`]: fn.scope.html
+#[unstable(feature = "scoped_threads", issue = "0")]
+pub struct Scope<'scope, 'env: 'scope> {
+    data: Arc<ScopeData>,
+    // Invariance over 'scope, to make sure 'scope cannot shrink, which is
+    // necessary for soundness.
+    //
+    // Without invariance, this would compile fine but be unsound:
+    //
+    // ```compile_fail
+    // #![feature(scoped_threads)]
+    //
+    // std::thread::scope(|s| {
+    //     s.spawn(|| {
+    //         let a = String::from("abcd");
+    //         s.spawn(|| println!("{:?}", a)); // might run after `a` is dropped
+    //     });
+    // });
+    // ```
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+/// An owned permission to join on a scoped thread (block on its termination).
+///
+/// See [`Scope::spawn`] for details.
+///
+/// [`Scope::spawn`]: struct.Scope.html#method.spawn
+#[unstable(feature = "scoped_threads", issue = "0")]
+pub struct ScopedJoinHandle<'scope, T>(JoinInner<'scope, T>);
+
+pub(super) struct ScopeData {
+    num_running_threads: AtomicUsize,
+    a_thread_panicked: AtomicBool,
+    main_thread: Thread,
+}
+
+impl ScopeData {
+    pub(super) fn increment_num_running_threads(&self) {
+        // Overflowing this counter would let the scope return while threads
+        // are still running. Only leaking a huge number of handles can get
+        // anywhere near the limit, so bail out well before that.
+        if self.num_running_threads.fetch_add(1, Ordering::Relaxed) > usize::MAX / 2 {
+            self.decrement_num_running_threads(false);
+            panic!("too many running threads in thread scope");
+        }
+    }
+
+    pub(super) fn decrement_num_running_threads(&self, panic: bool) {
+        if panic {
+            self.a_thread_panicked.store(true, Ordering::Relaxed);
+        }
+        if self.num_running_threads.fetch_sub(1, Ordering::Release) == 1 {
+            self.main_thread.unpark();
+        }
+    }
+}
+
+/// Creates a scope for spawning scoped threads.
+///
+/// The function passed to `scope` will be provided a [`Scope`] object,
+/// through which scoped threads can be [spawned][`Scope::spawn`].
+///
+/// Unlike non-scoped threads, scoped threads can borrow non-`'static` data,
+/// as the scope guarantees all threads will be joined at the end of the scope.
+///
+/// All threads spawned within the scope that haven't been manually joined
+/// will be automatically joined before this function returns.
+///
+/// # Panics
+///
+/// If any of the automatically joined threads panicked, this function will
+/// panic. If the function passed to `scope` panics, the panic is resumed once
+/// all threads have been joined.
+///
+/// If you want to handle panics from spawned threads,
+/// [`join`][`ScopedJoinHandle::join`] them before the end of the scope.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(scoped_threads)]
+/// use std::thread;
+///
+/// let mut a = vec![1, 2, 3];
+/// let mut x = 0;
+///
+/// thread::scope(|s| {
+///     s.spawn(|| {
+///         println!("hello from the first scoped thread");
+///         // We can borrow `a` here.
+///         println!("{:?}", a);
+///     });
+///     s.spawn(|| {
+///         println!("hello from the second scoped thread");
+///         // We can even mutably borrow `x` here,
+///         // because no other threads are using it.
+///         x += a[0] + a[2];
+///     });
+///     println!("hello from the main thread");
+/// });
+///
+/// // After the scope, we can modify and access our variables again:
+/// a.push(4);
+/// assert_eq!(x, a.len());
+/// ```
+///
+/// [`Scope`]: struct.Scope.html
+/// [`Scope::spawn`]: struct.Scope.html#method.spawn
+/// [`ScopedJoinHandle::join`]: struct.ScopedJoinHandle.html#method.join
+#[unstable(feature = "scoped_threads", issue = "0")]
+pub fn scope<'env, F, T>(f: F) -> T
+    where F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T
+{
+    let scope = Scope {
+        data: Arc::new(ScopeData {
+            num_running_threads: AtomicUsize::new(0),
+            a_thread_panicked: AtomicBool::new(false),
+            main_thread: current(),
+        }),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+
+    // Run `f`, but catch panics so we can make sure to wait for all the threads to join.
+    let result = catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+    // Wait until all the threads are finished.
+    while scope.data.num_running_threads.load(Ordering::Acquire) != 0 {
+        park();
+    }
+
+    // Throw any panic from `f`, or the return value of `f` if no thread panicked.
+    match result {
+        Err(e) => resume_unwind(e),
+        Ok(_) if scope.data.a_thread_panicked.load(Ordering::Relaxed) => {
+            panic!("a scoped thread panicked")
+        }
+        Ok(result) => result,
+    }
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Spawns a new thread within a scope, returning a [`ScopedJoinHandle`]
+    /// for it.
+    ///
+    /// Unlike non-scoped threads, threads spawned with this function may
+    /// borrow non-`'static` data from outside the scope. See [`scope`]
+    /// for details.
+    ///
+    /// The join handle provides a [`join`] method that can be used to join
+    /// the spawned thread. If the spawned thread panics, [`join`] will return
+    /// an [`Err`] containing the panic payload.
+    ///
+    /// If the join handle is dropped, the spawned thread will be implicitly
+    /// joined at the end of the scope. In that case, if the spawned thread
+    /// panics, [`scope`] will panic after all threads are joined.
+    ///
+    /// This call will create a thread using default parameters of
+    /// [`Builder`]. If you want to specify the stack size or the name of the
+    /// thread, use [`Builder::spawn_scoped`] instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the OS fails to create a thread; use
+    /// [`Builder::spawn_scoped`] to recover from such errors.
+    ///
+    /// [`ScopedJoinHandle`]: struct.ScopedJoinHandle.html
+    /// [`join`]: struct.ScopedJoinHandle.html#method.join
+    /// [`Err`]: ../../std/result/enum.Result.html#variant.Err
+    /// [`scope`]: fn.scope.html
+    /// [`Builder`]: struct.Builder.html
+    /// [`Builder::spawn_scoped`]: struct.Builder.html#method.spawn_scoped
+    #[unstable(feature = "scoped_threads", issue = "0")]
+    pub fn spawn<F, T>(&'scope self, f: F) -> ScopedJoinHandle<'scope, T>
+        where F: FnOnce() -> T + Send + 'scope,
+              T: Send + 'scope
+    {
+        Builder::new().spawn_scoped(self, f).expect("failed to spawn thread")
+    }
+}
+
+impl Builder {
+    /// Spawns a new scoped thread using the settings set through this
+    /// `Builder`.
+    ///
+    /// Unlike [`Scope::spawn`], this method yields an [`io::Result`] to
+    /// capture any failure to create the thread at the OS level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread name was set and it contained null bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(scoped_threads)]
+    /// use std::thread;
+    ///
+    /// let mut a = vec![1, 2, 3];
+    /// let mut x = 0;
+    ///
+    /// thread::scope(|s| {
+    ///     thread::Builder::new()
+    ///         .name("first".to_string())
+    ///         .spawn_scoped(s, || {
+    ///             println!("hello from the {:?} scoped thread",
+    ///                      thread::current().name());
+    ///             // We can borrow `a` here.
+    ///             println!("{:?}", a);
+    ///         })
+    ///         .unwrap();
+    ///     thread::Builder::new()
+    ///         .name("second".to_string())
+    ///         .spawn_scoped(s, || {
+    ///             println!("hello from the {:?} scoped thread",
+    ///                      thread::current().name());
+    ///             // We can even mutably borrow `x` here,
+    ///             // because no other threads are using it.
+    ///             x += a[0] + a[2];
+    ///         })
+    ///         .unwrap();
+    ///     println!("hello from the main thread");
+    /// });
+    ///
+    /// // After the scope, we can modify and access our variables again:
+    /// a.push(4);
+    /// assert_eq!(x, a.len());
+    /// ```
+    ///
+    /// [`Scope::spawn`]: struct.Scope.html#method.spawn
+    /// [`io::Result`]: ../../std/io/type.Result.html
+    #[unstable(feature = "scoped_threads", issue = "0")]
+    pub fn spawn_scoped<'scope, 'env, F, T>(self, scope: &'scope Scope<'scope, 'env>, f: F)
+                                            -> io::Result<ScopedJoinHandle<'scope, T>>
+        where F: FnOnce() -> T + Send + 'scope,
+              T: Send + 'scope
+    {
+        let inner = unsafe { self.spawn_unchecked(f, Some(scope.data.clone()))? };
+        Ok(ScopedJoinHandle(inner))
+    }
+}
+
+impl<'scope, T> ScopedJoinHandle<'scope, T> {
+    /// Extracts a handle to the underlying thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(scoped_threads)]
+    /// use std::thread;
+    ///
+    /// thread::scope(|s| {
+    ///     let t = s.spawn(|| {
+    ///         println!("hello");
+    ///     });
+    ///     println!("thread id: {:?}", t.thread().id());
+    /// });
+    /// ```
+    #[unstable(feature = "scoped_threads", issue = "0")]
+    pub fn thread(&self) -> &Thread {
+        &self.0.thread
+    }
+
+    /// Waits for the associated thread to finish.
+    ///
+    /// This function will return immediately if the associated thread has
+    /// already finished.
+    ///
+    /// If the associated thread panics, [`Err`] is returned with the panic
+    /// payload.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(scoped_threads)]
+    /// use std::thread;
+    ///
+    /// thread::scope(|s| {
+    ///     let t = s.spawn(|| {
+    ///         panic!("oh no");
+    ///     });
+    ///     assert!(t.join().is_err());
+    /// });
+    /// ```
+    ///
+    /// [`Err`]: ../../std/result/enum.Result.html#variant.Err
+    #[unstable(feature = "scoped_threads", issue = "0")]
+    pub fn join(mut self) -> Result<T> {
+        self.0.join()
+    }
+}
+
+#[unstable(feature = "scoped_threads", issue = "0")]
+impl<'scope, 'env> fmt::Debug for Scope<'scope, 'env> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scope")
+            .field("num_running_threads",
+                   &self.data.num_running_threads.load(Ordering::Relaxed))
+            .field("a_thread_panicked", &self.data.a_thread_panicked.load(Ordering::Relaxed))
+            .field("main_thread", &self.data.main_thread)
+            .finish()
+    }
+}
+
+#[unstable(feature = "scoped_threads", issue = "0")]
+impl<'scope, T> fmt::Debug for ScopedJoinHandle<'scope, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ScopedJoinHandle { .. }")
+    }
+}
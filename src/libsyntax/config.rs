@@ -119,7 +119,8 @@ impl<'a> StripUnconfigured<'a> {
     pub fn in_cfg(&mut self, attrs: &[ast::Attribute]) -> bool {
         attrs.iter().all(|attr| {
             // When not compiling with --test we should not compile the #[test] functions
-            if !self.should_test && is_test_or_bench(attr) {
+            // or any other items collected by the test harness
+            if !self.should_test && (is_test_or_bench(attr) || is_test_case(attr)) {
                 return false;
             }
 
@@ -340,3 +341,7 @@ fn is_cfg(attr: &ast::Attribute) -> bool {
 pub fn is_test_or_bench(attr: &ast::Attribute) -> bool {
     attr.check_name("test") || attr.check_name("bench")
 }
+
+pub fn is_test_case(attr: &ast::Attribute) -> bool {
+    attr.check_name("test_case")
+}
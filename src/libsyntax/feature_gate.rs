@@ -475,6 +475,9 @@ declare_features! (
 
     // 'a: { break 'a; }
     (active, label_break_value, "1.28.0", Some(48594), None),
+
+    // #![test_runner] and #[test_case]
+    (active, custom_test_frameworks, "1.28.0", Some(50297), None),
 );
 
 declare_features! (
@@ -713,6 +716,14 @@ pub const BUILTIN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeG
     ("ignore", Normal, Ungated),
     ("no_implicit_prelude", Normal, Ungated),
     ("reexport_test_harness_main", Normal, Ungated),
+    ("test_runner", CrateLevel, Gated(Stability::Unstable,
+                                      "custom_test_frameworks",
+                                      "custom test frameworks are an unstable feature",
+                                      cfg_fn!(custom_test_frameworks))),
+    ("test_case", Normal, Gated(Stability::Unstable,
+                                "custom_test_frameworks",
+                                "custom test frameworks are an unstable feature",
+                                cfg_fn!(custom_test_frameworks))),
     ("link_args", Normal, Gated(Stability::Unstable,
                                 "link_args",
                                 "the `link_args` attribute is experimental and not \
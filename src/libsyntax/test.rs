@@ -55,12 +55,20 @@ struct Test {
     allow_fail: bool,
 }
 
+/// An item marked with `#[test_case]`, which is handed to the test runner as is.
+struct TestCase {
+    span: Span,
+    path: Vec<Ident>,
+}
+
 struct TestCtxt<'a> {
     span_diagnostic: &'a errors::Handler,
     path: Vec<Ident>,
     ext_cx: ExtCtxt<'a>,
     testfns: Vec<Test>,
+    test_cases: Vec<TestCase>,
     reexport_test_harness_main: Option<Symbol>,
+    test_runner: Option<ast::Path>,
     is_libtest: bool,
     ctxt: SyntaxContext,
     features: &'a Features,
@@ -85,14 +93,42 @@ pub fn modify_for_testing(sess: &ParseSess,
         attr::first_attr_value_str_by_name(&krate.attrs,
                                            "reexport_test_harness_main");
 
+    // Check for #![test_runner(path::to::runner)], which replaces the console
+    // runner of libtest. Like the above this is unconditional so that the
+    // attribute is marked as used.
+    let test_runner = get_test_runner(span_diagnostic, &krate);
+
     if should_test {
-        generate_test_harness(sess, resolver, reexport_test_harness_main,
+        generate_test_harness(sess, resolver, reexport_test_harness_main, test_runner,
                               krate, span_diagnostic, features)
     } else {
         krate
     }
 }
 
+fn get_test_runner(sd: &errors::Handler, krate: &ast::Crate) -> Option<ast::Path> {
+    let test_attr = attr::find_by_name(&krate.attrs, "test_runner")?;
+    let meta_list = match test_attr.meta_item_list() {
+        Some(list) => list,
+        None => {
+            sd.span_err(test_attr.span, "attribute must be of the form: \
+                                         `#![test_runner(path::to::runner)]`");
+            return None;
+        }
+    };
+    if meta_list.len() != 1 {
+        sd.span_err(test_attr.span, "`#![test_runner(..)]` accepts exactly 1 argument");
+        return None;
+    }
+    match meta_list[0].word() {
+        Some(meta_item) => Some(meta_item.ident.clone()),
+        None => {
+            sd.span_err(meta_list[0].span(), "`test_runner` argument must be a path");
+            None
+        }
+    }
+}
+
 struct TestHarnessGenerator<'a> {
     cx: TestCtxt<'a>,
     tests: Vec<Ident>,
@@ -142,6 +178,14 @@ impl<'a> fold::Folder for TestHarnessGenerator<'a> {
                     self.tests.push(i.ident);
                 }
             }
+        } else if is_test_case(&self.cx, &i) {
+            debug!("this is a test case");
+            let test_case = TestCase {
+                span: i.span,
+                path: self.cx.path.clone(),
+            };
+            self.cx.test_cases.push(test_case);
+            self.tests.push(i.ident);
         }
 
         let mut item = i.into_inner();
@@ -268,6 +312,7 @@ fn mk_reexport_mod(cx: &mut TestCtxt,
 fn generate_test_harness(sess: &ParseSess,
                          resolver: &mut Resolver,
                          reexport_test_harness_main: Option<Symbol>,
+                         test_runner: Option<ast::Path>,
                          krate: ast::Crate,
                          sd: &errors::Handler,
                          features: &Features) -> ast::Crate {
@@ -285,7 +330,9 @@ fn generate_test_harness(sess: &ParseSess,
         ext_cx: ExtCtxt::new(sess, econfig, resolver),
         path: Vec::new(),
         testfns: Vec::new(),
+        test_cases: Vec::new(),
         reexport_test_harness_main,
+        test_runner,
         // NB: doesn't consider the value of `--crate-name` passed on the command line.
         is_libtest: attr::find_crate_name(&krate.attrs).map(|s| s == "test").unwrap_or(false),
         toplevel_reexport: None,
@@ -442,6 +489,35 @@ fn is_bench_fn(cx: &TestCtxt, i: &ast::Item) -> bool {
     has_bench_attr && has_bench_signature
 }
 
+fn is_test_case(cx: &TestCtxt, i: &ast::Item) -> bool {
+    if !attr::contains_name(&i.attrs, "test_case") {
+        return false;
+    }
+
+    let diag = cx.span_diagnostic;
+    match i.node {
+        ast::ItemKind::Const(..) => true,
+        // Custom runners are handed references, but the default runner
+        // collects test cases into an array of `TestDescAndFn`s by value,
+        // which can't move out of a static, and which no function is.
+        ast::ItemKind::Fn(..) | ast::ItemKind::Static(..) if cx.test_runner.is_some() => true,
+        ast::ItemKind::Fn(..) => {
+            diag.span_err(i.span, "functions cannot be used as test cases without a \
+                                   `#![test_runner]`, use `#[test]` for functions");
+            false
+        }
+        ast::ItemKind::Static(..) => {
+            diag.span_err(i.span, "statics cannot be used as test cases, use a `const` instead");
+            false
+        }
+        _ => {
+            diag.span_err(i.span,
+                          "only functions, constants and statics may be used as test cases");
+            false
+        }
+    }
+}
+
 fn is_ignored(i: &ast::Item) -> bool {
     attr::contains_name(&i.attrs, "ignore")
 }
@@ -508,6 +584,16 @@ mod __test {
   ];
 }
 
+If the crate names a custom runner with `#![test_runner(path::to::runner)]`,
+`main` instead passes references to everything that was collected straight to
+it, and `extern crate test` is only added if there are `#[test]` functions:
+
+mod __test {
+  fn main() {
+    ::path::to::runner(&[&test_case_1, &test::TestDescAndFn { ... }, ...])
+  }
+}
+
 */
 
 fn mk_std(cx: &TestCtxt) -> P<ast::Item> {
@@ -545,15 +631,30 @@ fn mk_main(cx: &mut TestCtxt) -> P<ast::Item> {
     let sp = ignored_span(cx, DUMMY_SP);
     let ecx = &cx.ext_cx;
 
-    // test::test_main_static
-    let test_main_path =
-        ecx.path(sp, vec![Ident::from_str("test"), Ident::from_str("test_main_static")]);
-
-    // test::test_main_static(...)
-    let test_main_path_expr = ecx.expr_path(test_main_path);
-    let tests_ident_expr = ecx.expr_ident(sp, Ident::from_str("TESTS"));
-    let call_test_main = ecx.expr_call(sp, test_main_path_expr,
-                                       vec![tests_ident_expr]);
+    let call_test_main = match cx.test_runner {
+        Some(ref runner) => {
+            // ::path::to::runner(&[&test_case, &test::TestDescAndFn { .. }, ..])
+            let runner_path = mk_runner_path(cx, runner);
+            let tests = cx.testfns.iter().map(|test| {
+                ecx.expr_addr_of(sp, mk_test_desc_and_fn_rec(cx, test))
+            }).chain(cx.test_cases.iter().map(|test_case| {
+                let span = ignored_span(cx, test_case.span);
+                let path = ecx.path_global(span, visible_path(cx, &test_case.path));
+                ecx.expr_addr_of(span, ecx.expr_path(path))
+            })).collect();
+            ecx.expr_call(sp, ecx.expr_path(runner_path), vec![ecx.expr_vec_slice(sp, tests)])
+        }
+        None => {
+            // test::test_main_static
+            let test_main_path =
+                ecx.path(sp, vec![Ident::from_str("test"), Ident::from_str("test_main_static")]);
+
+            // test::test_main_static(...)
+            let test_main_path_expr = ecx.expr_path(test_main_path);
+            let tests_ident_expr = ecx.expr_ident(sp, Ident::from_str("TESTS"));
+            ecx.expr_call(sp, test_main_path_expr, vec![tests_ident_expr])
+        }
+    };
     let call_test_main = ecx.stmt_expr(call_test_main);
     // #![main]
     let main_meta = ecx.meta_word(sp, Symbol::intern("main"));
@@ -578,20 +679,42 @@ fn mk_main(cx: &mut TestCtxt) -> P<ast::Item> {
     })
 }
 
+/// Path of the custom test runner, relative to the crate root like the paths
+/// of the tests themselves.
+fn mk_runner_path(cx: &TestCtxt, runner: &ast::Path) -> ast::Path {
+    let sp = ignored_span(cx, runner.span);
+    let mut path = vec![];
+    if cx.features.extern_absolute_paths {
+        path.push(keywords::Crate.ident());
+    }
+    path.extend(runner.segments.iter()
+        .map(|segment| segment.ident)
+        .skip_while(|ident| {
+            ident.name == keywords::CrateRoot.name() || ident.name == keywords::Crate.name()
+        }));
+    cx.ext_cx.path_global(sp, path)
+}
+
 fn mk_test_module(cx: &mut TestCtxt) -> (P<ast::Item>, Option<P<ast::Item>>) {
-    // Link to test crate
-    let import = mk_std(cx);
+    let mut items = vec![];
+
+    // Link to test crate, unless a custom test runner doesn't need it
+    if cx.test_runner.is_none() || !cx.testfns.is_empty() {
+        items.push(mk_std(cx));
+    }
 
     // A constant vector of test descriptors.
-    let tests = mk_tests(cx);
+    if cx.test_runner.is_none() {
+        items.push(mk_tests(cx));
+    }
 
     // The synthesized main function which will call the console test runner
-    // with our list of tests
-    let mainfn = mk_main(cx);
+    // (or the custom one) with our list of tests
+    items.push(mk_main(cx));
 
     let testmod = ast::Mod {
         inner: DUMMY_SP,
-        items: vec![import, mainfn, tests],
+        items,
     };
     let item_ = ast::ItemKind::Mod(testmod);
     let mod_ident = Ident::with_empty_ctxt(Symbol::gensym("__test"));
@@ -655,6 +778,24 @@ fn path_name_i(idents: &[Ident]) -> String {
     path_name
 }
 
+/// Path through the re-export modules at which the item at `path` is visible
+/// from the generated test module.
+fn visible_path(cx: &TestCtxt, path: &[Ident]) -> Vec<Ident> {
+    let mut visible_path = vec![];
+    if cx.features.extern_absolute_paths {
+        visible_path.push(keywords::Crate.ident());
+    }
+    match cx.toplevel_reexport {
+        Some(id) => visible_path.push(id),
+        None => {
+            let diag = cx.span_diagnostic;
+            diag.bug("expected to find top-level re-export name, but found None");
+        }
+    };
+    visible_path.extend_from_slice(path);
+    visible_path
+}
+
 fn mk_tests(cx: &TestCtxt) -> P<ast::Item> {
     // The vector of test_descs for this crate
     let test_descs = mk_test_descs(cx);
@@ -681,7 +822,15 @@ fn mk_tests(cx: &TestCtxt) -> P<ast::Item> {
 }
 
 fn mk_test_descs(cx: &TestCtxt) -> P<ast::Expr> {
-    debug!("building test vector from {} tests", cx.testfns.len());
+    debug!("building test vector from {} tests and {} test cases",
+           cx.testfns.len(), cx.test_cases.len());
+
+    // With the default runner, `#[test_case]`s are `test::TestDescAndFn`
+    // constants which are added to the list of tests by value.
+    let test_cases = cx.test_cases.iter().map(|test_case| {
+        let span = ignored_span(cx, test_case.span);
+        cx.ext_cx.expr_path(cx.ext_cx.path_global(span, visible_path(cx, &test_case.path)))
+    });
 
     P(ast::Expr {
         id: ast::DUMMY_NODE_ID,
@@ -690,7 +839,7 @@ fn mk_test_descs(cx: &TestCtxt) -> P<ast::Expr> {
                 id: ast::DUMMY_NODE_ID,
                 node: ast::ExprKind::Array(cx.testfns.iter().map(|test| {
                     mk_test_desc_and_fn_rec(cx, test)
-                }).collect()),
+                }).chain(test_cases).collect()),
                 span: DUMMY_SP,
                 attrs: ast::ThinVec::new(),
             })),
@@ -756,18 +905,7 @@ fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test) -> P<ast::Expr> {
              field("should_panic", fail_expr),
              field("allow_fail", allow_fail_expr)]);
 
-    let mut visible_path = vec![];
-    if cx.features.extern_absolute_paths {
-        visible_path.push(keywords::Crate.ident());
-    }
-    match cx.toplevel_reexport {
-        Some(id) => visible_path.push(id),
-        None => {
-            let diag = cx.span_diagnostic;
-            diag.bug("expected to find top-level re-export name, but found None");
-        }
-    };
-    visible_path.extend_from_slice(&test.path[..]);
+    let visible_path = visible_path(cx, &test.path);
 
     // Rather than directly give the test function to the test
     // harness, we create a wrapper like one of the following:
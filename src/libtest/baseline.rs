@@ -0,0 +1,290 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Saving benchmark results to disk and comparing later runs against them.
+//!
+//! A baseline is a plain text file holding the raw per-iteration timings of
+//! every benchmark that was run, so that later runs can be compared using
+//! rank-based statistics rather than just the medians.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use stats::{self, Stats};
+
+const HEADER: &str = "# libtest benchmark baseline v1";
+
+/// Results with a p-value above this are considered to be noise.
+const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// The samples of a set of benchmarks, keyed by benchmark name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Baseline {
+    benches: BTreeMap<String, Vec<f64>>,
+}
+
+impl Baseline {
+    pub fn new() -> Baseline {
+        Baseline {
+            benches: BTreeMap::new(),
+        }
+    }
+
+    /// Path of the baseline called `name` inside of `dir`.
+    pub fn path(dir: &Path, name: &str) -> io::Result<PathBuf> {
+        if name.is_empty() || name.contains(|c: char| c == '/' || c == '\\') || name == ".."
+            || name == "."
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid baseline name `{}`", name),
+            ));
+        }
+        Ok(dir.join(format!("{}.bench", name)))
+    }
+
+    /// Loads the baseline called `name` from `dir`.
+    pub fn load(dir: &Path, name: &str) -> io::Result<Baseline> {
+        let path = Baseline::path(dir, name)?;
+        let file = File::open(&path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("failed to open baseline `{}`: {}", path.display(), e),
+            )
+        })?;
+        Baseline::read(BufReader::new(file))
+    }
+
+    /// Saves this baseline as `name` into `dir`, merging it with the results
+    /// already stored there so that a filtered run only updates the
+    /// benchmarks it actually ran.
+    pub fn save(&self, dir: &Path, name: &str) -> io::Result<()> {
+        let path = Baseline::path(dir, name)?;
+        let mut merged = if path.exists() {
+            Baseline::load(dir, name)?
+        } else {
+            Baseline::new()
+        };
+        for (bench, samples) in &self.benches {
+            merged.benches.insert(bench.clone(), samples.clone());
+        }
+
+        fs::create_dir_all(dir)?;
+        let mut file = File::create(&path)?;
+        merged.write(&mut file)
+    }
+
+    pub fn read<R: BufRead>(reader: R) -> io::Result<Baseline> {
+        fn invalid(msg: String) -> io::Error {
+            io::Error::new(io::ErrorKind::InvalidData, msg)
+        }
+
+        let mut lines = reader.lines();
+        match lines.next() {
+            Some(Ok(ref line)) if line == HEADER => {}
+            Some(Err(e)) => return Err(e),
+            _ => return Err(invalid("not a benchmark baseline file".to_owned())),
+        }
+
+        let mut baseline = Baseline::new();
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(2, '\t');
+            let name = parts.next().unwrap();
+            let samples = parts
+                .next()
+                .unwrap_or("")
+                .split_whitespace()
+                .map(|s| s.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| invalid(format!("line {}: {}", i + 2, e)))?;
+            baseline.benches.insert(name.to_owned(), samples);
+        }
+        Ok(baseline)
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{}", HEADER)?;
+        for (name, samples) in &self.benches {
+            write!(out, "{}\t", name)?;
+            for (i, sample) in samples.iter().enumerate() {
+                if i != 0 {
+                    write!(out, " ")?;
+                }
+                write!(out, "{}", sample)?;
+            }
+            writeln!(out, "")?;
+        }
+        Ok(())
+    }
+
+    /// Records the per-iteration samples of the benchmark `name`.
+    ///
+    /// Names that can't be represented in the file format are skipped.
+    pub fn insert(&mut self, name: &str, samples: Vec<f64>) {
+        if name.contains(|c: char| c == '\t' || c == '\n' || c == '\r') {
+            return;
+        }
+        self.benches.insert(name.to_owned(), samples);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[f64]> {
+        self.benches.get(name).map(|v| &v[..])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.benches.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The benchmark got slower by more than the noise threshold.
+    Regressed,
+    /// The benchmark got faster by more than the noise threshold.
+    Improved,
+    /// No change could be distinguished from noise.
+    NoChange,
+}
+
+/// The change of a benchmark relative to its baseline.
+///
+/// All changes are expressed as a percentage of the baseline's median, with
+/// positive values meaning that the benchmark got slower.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchComparison {
+    /// Estimated change of the time per iteration.
+    pub change_pct: f64,
+    /// Lower bound of the 95% confidence interval of the change.
+    pub lower_pct: f64,
+    /// Upper bound of the 95% confidence interval of the change.
+    pub upper_pct: f64,
+    /// Probability of observing a difference at least this large if the
+    /// benchmark had not changed at all.
+    pub p_value: f64,
+    pub verdict: Verdict,
+}
+
+/// Compares the samples of a benchmark against those of its baseline.
+///
+/// A change is only reported as a regression or an improvement if it is
+/// statistically significant *and* the whole confidence interval lies beyond
+/// `noise_pct` percent, so that tiny but consistent changes don't fail a run.
+/// Returns `None` if there isn't enough data to compare.
+pub fn compare(old: &[f64], new: &[f64], noise_pct: f64) -> Option<BenchComparison> {
+    if old.len() < 2 || new.len() < 2 {
+        return None;
+    }
+    let base = old.median();
+    if !(base > 0.0) {
+        return None;
+    }
+
+    let p_value = stats::mann_whitney_u(old, new);
+    let (lower, estimate, upper) = stats::shift_confidence_interval(old, new);
+    let (lower_pct, change_pct, upper_pct) = (
+        lower / base * 100.0,
+        estimate / base * 100.0,
+        upper / base * 100.0,
+    );
+
+    let significant = p_value < SIGNIFICANCE_LEVEL;
+    let verdict = if significant && lower_pct > noise_pct {
+        Verdict::Regressed
+    } else if significant && upper_pct < -noise_pct {
+        Verdict::Improved
+    } else {
+        Verdict::NoChange
+    };
+
+    Some(BenchComparison {
+        change_pct,
+        lower_pct,
+        upper_pct,
+        p_value,
+        verdict,
+    })
+}
+
+pub fn fmt_bench_comparison(cmp: &BenchComparison) -> String {
+    format!(
+        "{:+.2}% [{:+.2}% {:+.2}%] (p = {:.3})",
+        cmp.change_pct, cmp.lower_pct, cmp.upper_pct, cmp.p_value
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(base: f64) -> Vec<f64> {
+        (0..50).map(|i| base + (i % 5) as f64).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut baseline = Baseline::new();
+        baseline.insert("bench::a", vec![1.0, 2.5, 3.0]);
+        baseline.insert("bench::b", vec![]);
+        baseline.insert("bench\twith tab", vec![1.0]);
+
+        let mut out = Vec::new();
+        baseline.write(&mut out).unwrap();
+        let read = Baseline::read(&out[..]).unwrap();
+        assert_eq!(read, baseline);
+        assert_eq!(read.get("bench::a"), Some(&[1.0, 2.5, 3.0][..]));
+        assert_eq!(read.get("bench\twith tab"), None);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(Baseline::read(&b"not a baseline\n"[..]).is_err());
+        let bad = format!("{}\nbench::a\t1.0 nope\n", HEADER);
+        assert!(Baseline::read(bad.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_names() {
+        let dir = Path::new("baselines");
+        assert!(Baseline::path(dir, "main").is_ok());
+        assert!(Baseline::path(dir, "").is_err());
+        assert!(Baseline::path(dir, "../main").is_err());
+        assert!(Baseline::path(dir, "..").is_err());
+    }
+
+    #[test]
+    fn compare_detects_changes() {
+        let old = samples(1000.0);
+
+        let same = compare(&old, &old, 2.0).unwrap();
+        assert_eq!(same.verdict, Verdict::NoChange);
+        assert_eq!(same.change_pct, 0.0);
+
+        let slower = compare(&old, &samples(1100.0), 2.0).unwrap();
+        assert_eq!(slower.verdict, Verdict::Regressed);
+        assert!(slower.lower_pct <= 10.0 && 10.0 <= slower.upper_pct);
+
+        let faster = compare(&old, &samples(900.0), 2.0).unwrap();
+        assert_eq!(faster.verdict, Verdict::Improved);
+
+        // Significant, but within the noise threshold.
+        let small = compare(&old, &samples(1010.0), 2.0).unwrap();
+        assert_eq!(small.verdict, Verdict::NoChange);
+
+        assert_eq!(compare(&[1.0], &old, 2.0), None);
+        assert_eq!(compare(&[0.0, 0.0], &old, 2.0), None);
+    }
+}
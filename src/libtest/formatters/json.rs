@@ -120,6 +120,43 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
         }
     }
 
+    fn write_subtest_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        stdout: &[u8],
+    ) -> io::Result<()> {
+        // Consumers pair every result with a preceding "started" event.
+        self.write_test_start(desc)?;
+        self.write_result(desc, result, stdout)
+    }
+
+    fn write_bench_comparison(&mut self, desc: &TestDesc, cmp: &BenchComparison) -> io::Result<()> {
+        let verdict = match cmp.verdict {
+            Verdict::Regressed => "regressed",
+            Verdict::Improved => "improved",
+            Verdict::NoChange => "no_change",
+        };
+
+        let line = format!(
+            "{{ \"type\": \"bench_comparison\", \
+             \"name\": \"{}\", \
+             \"change_pct\": {}, \
+             \"lower_pct\": {}, \
+             \"upper_pct\": {}, \
+             \"p_value\": {}, \
+             \"verdict\": \"{}\" }}",
+            EscapedString(desc.name.as_slice()),
+            cmp.change_pct,
+            cmp.lower_pct,
+            cmp.upper_pct,
+            cmp.p_value,
+            verdict
+        );
+
+        self.write_message(&*line)
+    }
+
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         self.write_message(&*format!(
             r#"{{ "type": "test", "event": "timeout", "name": "{}" }}"#,
@@ -128,6 +165,13 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
     }
 
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        let success = state.failed == 0 && state.regressions.is_empty();
+        let regressed = if state.regressions.is_empty() {
+            "".into()
+        } else {
+            format!(r#", "regressed": {}"#, state.regressions.len())
+        };
+
         self.write_message(&*format!(
             "{{ \"type\": \"suite\", \
              \"event\": \"{}\", \
@@ -136,17 +180,18 @@ impl<T: Write> OutputFormatter for JsonFormatter<T> {
              \"allowed_fail\": {}, \
              \"ignored\": {}, \
              \"measured\": {}, \
-             \"filtered_out\": \"{}\" }}",
-            if state.failed == 0 { "ok" } else { "failed" },
+             \"filtered_out\": \"{}\"{} }}",
+            if success { "ok" } else { "failed" },
             state.passed,
             state.failed + state.allowed_fail,
             state.allowed_fail,
             state.ignored,
             state.measured,
-            state.filtered_out
+            state.filtered_out,
+            regressed
         ))?;
 
-        Ok(state.failed == 0)
+        Ok(success)
     }
 }
 
@@ -28,5 +28,91 @@ pub(crate) trait OutputFormatter {
         result: &TestResult,
         stdout: &[u8],
     ) -> io::Result<()>;
+    /// Reports the result of a subtest. No `write_test_start` precedes it.
+    fn write_subtest_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        stdout: &[u8],
+    ) -> io::Result<()> {
+        self.write_result(desc, result, stdout)
+    }
+    fn write_bench_comparison(
+        &mut self,
+        desc: &TestDesc,
+        cmp: &BenchComparison,
+    ) -> io::Result<()>;
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool>;
 }
+
+pub(crate) fn write_pretty<T: Write>(
+    out: &mut OutputLocation<T>,
+    use_color: bool,
+    word: &str,
+    color: term::color::Color,
+) -> io::Result<()> {
+    match *out {
+        Pretty(ref mut term) => {
+            if use_color {
+                term.fg(color)?;
+            }
+            term.write_all(word.as_bytes())?;
+            if use_color {
+                term.reset()?;
+            }
+            term.flush()
+        }
+        Raw(ref mut stdout) => {
+            stdout.write_all(word.as_bytes())?;
+            stdout.flush()
+        }
+    }
+}
+
+pub(crate) fn write_plain<T: Write>(out: &mut OutputLocation<T>, s: &str) -> io::Result<()> {
+    out.write_all(s.as_bytes())?;
+    out.flush()
+}
+
+/// Lists the benchmarks that regressed against the baseline, for the human
+/// readable formatters.
+pub(crate) fn write_regressions<T: Write>(
+    out: &mut OutputLocation<T>,
+    state: &ConsoleTestState,
+) -> io::Result<()> {
+    write_plain(out, "\nbenchmark regressions:\n")?;
+    let mut regressions = state
+        .regressions
+        .iter()
+        .map(|desc| desc.name.to_string())
+        .collect::<Vec<_>>();
+    regressions.sort();
+    for name in &regressions {
+        write_plain(out, &format!("    {}\n", name))?;
+    }
+    Ok(())
+}
+
+/// Writes the `change:` line following a benchmark's `bench:` line, for the
+/// human readable formatters.
+pub(crate) fn write_bench_comparison<T: Write>(
+    out: &mut OutputLocation<T>,
+    use_color: bool,
+    max_name_len: usize,
+    cmp: &BenchComparison,
+) -> io::Result<()> {
+    // Line the colon up with the one of the preceding `bench:` line.
+    let width = "test  ... bench:".len() + max_name_len;
+    write_plain(out, &format!(
+        "{:>width$} {} ",
+        "change:",
+        baseline::fmt_bench_comparison(cmp),
+        width = width
+    ))?;
+    match cmp.verdict {
+        Verdict::Regressed => write_pretty(out, use_color, "regressed", term::color::RED)?,
+        Verdict::Improved => write_pretty(out, use_color, "improved", term::color::GREEN)?,
+        Verdict::NoChange => write_plain(out, "no change")?,
+    }
+    write_plain(out, "\n")
+}
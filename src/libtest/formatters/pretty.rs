@@ -70,28 +70,11 @@ impl<T: Write> PrettyFormatter<T> {
     }
 
     pub fn write_pretty(&mut self, word: &str, color: term::color::Color) -> io::Result<()> {
-        match self.out {
-            Pretty(ref mut term) => {
-                if self.use_color {
-                    term.fg(color)?;
-                }
-                term.write_all(word.as_bytes())?;
-                if self.use_color {
-                    term.reset()?;
-                }
-                term.flush()
-            }
-            Raw(ref mut stdout) => {
-                stdout.write_all(word.as_bytes())?;
-                stdout.flush()
-            }
-        }
+        super::write_pretty(&mut self.out, self.use_color, word, color)
     }
 
     pub fn write_plain<S: AsRef<str>>(&mut self, s: S) -> io::Result<()> {
-        let s = s.as_ref();
-        self.out.write_all(s.as_bytes())?;
-        self.out.flush()
+        super::write_plain(&mut self.out, s.as_ref())
     }
 
     pub fn write_successes(&mut self, state: &ConsoleTestState) -> io::Result<()> {
@@ -152,6 +135,19 @@ impl<T: Write> PrettyFormatter<T> {
 
         Ok(())
     }
+
+    fn write_outcome(&mut self, result: &TestResult) -> io::Result<()> {
+        match *result {
+            TrOk => self.write_ok(),
+            TrFailed | TrFailedMsg(_) => self.write_failed(),
+            TrIgnored => self.write_ignored(),
+            TrAllowedFail => self.write_allowed_fail(),
+            TrBench(ref bs) => {
+                self.write_bench()?;
+                self.write_plain(&format!(": {}\n", fmt_bench_samples(bs)))
+            }
+        }
+    }
 }
 
 impl<T: Write> OutputFormatter for PrettyFormatter<T> {
@@ -177,16 +173,21 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
             self.write_test_name(desc)?;
         }
 
-        match *result {
-            TrOk => self.write_ok(),
-            TrFailed | TrFailedMsg(_) => self.write_failed(),
-            TrIgnored => self.write_ignored(),
-            TrAllowedFail => self.write_allowed_fail(),
-            TrBench(ref bs) => {
-                self.write_bench()?;
-                self.write_plain(&format!(": {}\n", fmt_bench_samples(bs)))
-            }
-        }
+        self.write_outcome(result)
+    }
+
+    fn write_subtest_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        _: &[u8],
+    ) -> io::Result<()> {
+        self.write_test_name(desc)?;
+        self.write_outcome(result)
+    }
+
+    fn write_bench_comparison(&mut self, _: &TestDesc, cmp: &BenchComparison) -> io::Result<()> {
+        super::write_bench_comparison(&mut self.out, self.use_color, self.max_name_len, cmp)
     }
 
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
@@ -204,10 +205,13 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
         if state.options.display_output {
             self.write_successes(state)?;
         }
-        let success = state.failed == 0;
-        if !success {
+        let success = state.failed == 0 && state.regressions.is_empty();
+        if state.failed != 0 {
             self.write_failures(state)?;
         }
+        if !state.regressions.is_empty() {
+            super::write_regressions(&mut self.out, state)?;
+        }
 
         self.write_plain("\ntest result: ")?;
 
@@ -218,20 +222,33 @@ impl<T: Write> OutputFormatter for PrettyFormatter<T> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let regressed = if state.regressions.is_empty() {
+            String::new()
+        } else {
+            format!("; {} regressed", state.regressions.len())
+        };
+
         let s = if state.allowed_fail > 0 {
             format!(
-                ". {} passed; {} failed ({} allowed); {} ignored; {} measured; {} filtered out\n\n",
+                ". {} passed; {} failed ({} allowed); {} ignored; {} measured; \
+                 {} filtered out{}\n\n",
                 state.passed,
                 state.failed + state.allowed_fail,
                 state.allowed_fail,
                 state.ignored,
                 state.measured,
-                state.filtered_out
+                state.filtered_out,
+                regressed
             )
         } else {
             format!(
-                ". {} passed; {} failed; {} ignored; {} measured; {} filtered out\n\n",
-                state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+                ". {} passed; {} failed; {} ignored; {} measured; {} filtered out{}\n\n",
+                state.passed,
+                state.failed,
+                state.ignored,
+                state.measured,
+                state.filtered_out,
+                regressed
             )
         };
 
@@ -74,28 +74,11 @@ impl<T: Write> TerseFormatter<T> {
     }
 
     pub fn write_pretty(&mut self, word: &str, color: term::color::Color) -> io::Result<()> {
-        match self.out {
-            Pretty(ref mut term) => {
-                if self.use_color {
-                    term.fg(color)?;
-                }
-                term.write_all(word.as_bytes())?;
-                if self.use_color {
-                    term.reset()?;
-                }
-                term.flush()
-            }
-            Raw(ref mut stdout) => {
-                stdout.write_all(word.as_bytes())?;
-                stdout.flush()
-            }
-        }
+        super::write_pretty(&mut self.out, self.use_color, word, color)
     }
 
     pub fn write_plain<S: AsRef<str>>(&mut self, s: S) -> io::Result<()> {
-        let s = s.as_ref();
-        self.out.write_all(s.as_bytes())?;
-        self.out.flush()
+        super::write_plain(&mut self.out, s.as_ref())
     }
 
     pub fn write_outputs(&mut self, state: &ConsoleTestState) -> io::Result<()> {
@@ -192,6 +175,10 @@ impl<T: Write> OutputFormatter for TerseFormatter<T> {
         }
     }
 
+    fn write_bench_comparison(&mut self, _: &TestDesc, cmp: &BenchComparison) -> io::Result<()> {
+        super::write_bench_comparison(&mut self.out, self.use_color, self.max_name_len, cmp)
+    }
+
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         self.write_plain(&format!(
             "test {} has been running for over {} seconds\n",
@@ -203,10 +190,13 @@ impl<T: Write> OutputFormatter for TerseFormatter<T> {
         if state.options.display_output {
             self.write_outputs(state)?;
         }
-        let success = state.failed == 0;
-        if !success {
+        let success = state.failed == 0 && state.regressions.is_empty();
+        if state.failed != 0 {
             self.write_failures(state)?;
         }
+        if !state.regressions.is_empty() {
+            super::write_regressions(&mut self.out, state)?;
+        }
 
         self.write_plain("\ntest result: ")?;
 
@@ -217,20 +207,33 @@ impl<T: Write> OutputFormatter for TerseFormatter<T> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let regressed = if state.regressions.is_empty() {
+            String::new()
+        } else {
+            format!("; {} regressed", state.regressions.len())
+        };
+
         let s = if state.allowed_fail > 0 {
             format!(
-                ". {} passed; {} failed ({} allowed); {} ignored; {} measured; {} filtered out\n\n",
+                ". {} passed; {} failed ({} allowed); {} ignored; {} measured; \
+                 {} filtered out{}\n\n",
                 state.passed,
                 state.failed + state.allowed_fail,
                 state.allowed_fail,
                 state.ignored,
                 state.measured,
-                state.filtered_out
+                state.filtered_out,
+                regressed
             )
         } else {
             format!(
-                ". {} passed; {} failed; {} ignored; {} measured; {} filtered out\n\n",
-                state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+                ". {} passed; {} failed; {} ignored; {} measured; {} filtered out{}\n\n",
+                state.passed,
+                state.failed,
+                state.ignored,
+                state.measured,
+                state.filtered_out,
+                regressed
             )
         };
 
@@ -71,9 +71,14 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::process;
 
 const TEST_WARN_TIMEOUT_S: u64 = 60;
+/// Where `--save-baseline` and `--baseline` keep baselines unless told otherwise.
+pub const DEFAULT_BASELINE_DIR: &str = "target/bench-baselines";
+/// The change, in percent, below which a benchmark is reported as unchanged.
+pub const DEFAULT_NOISE_THRESHOLD_PCT: f64 = 2.0;
 const QUIET_MODE_MAX_COLUMN: usize = 100; // insert a '\n' after 100 tests in quiet mode
 
 // to be used by rustc to compile tests in libtest
@@ -85,8 +90,10 @@ pub mod test {
 }
 
 pub mod stats;
+mod baseline;
 mod formatters;
 
+use baseline::{Baseline, BenchComparison, Verdict};
 use formatters::{JsonFormatter, OutputFormatter, PrettyFormatter, TerseFormatter};
 
 // The name of a test. By convention this follows the rules for rust
@@ -200,6 +207,7 @@ impl fmt::Debug for TestFn {
 pub struct Bencher {
     mode: BenchMode,
     summary: Option<stats::Summary>,
+    samples: Vec<f64>,
     pub bytes: u64,
 }
 
@@ -345,6 +353,7 @@ pub struct TestOpts {
     pub list: bool,
     pub filter: Option<String>,
     pub filter_exact: bool,
+    pub subtest_filter: Option<String>,
     pub run_ignored: bool,
     pub run_tests: bool,
     pub bench_benchmarks: bool,
@@ -354,6 +363,10 @@ pub struct TestOpts {
     pub format: OutputFormat,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
+    pub save_baseline: Option<String>,
+    pub baseline: Option<String>,
+    pub baseline_dir: PathBuf,
+    pub noise_threshold: f64,
     pub options: Options,
 }
 
@@ -364,6 +377,7 @@ impl TestOpts {
             list: false,
             filter: None,
             filter_exact: false,
+            subtest_filter: None,
             run_ignored: false,
             run_tests: false,
             bench_benchmarks: false,
@@ -373,6 +387,10 @@ impl TestOpts {
             format: OutputFormat::Pretty,
             test_threads: None,
             skip: vec![],
+            save_baseline: None,
+            baseline: None,
+            baseline_dir: PathBuf::from(DEFAULT_BASELINE_DIR),
+            noise_threshold: DEFAULT_NOISE_THRESHOLD_PCT,
             options: Options::new(),
         }
     }
@@ -426,6 +444,13 @@ fn optgroups() -> getopts::Options {
             "exact",
             "Exactly match filters rather than by substring",
         )
+        .optopt(
+            "",
+            "subtest",
+            "Only run the subtests whose names contain FILTER, \
+             using PARENT/CHILD for nested subtests",
+            "FILTER",
+        )
         .optopt(
             "",
             "color",
@@ -444,6 +469,33 @@ fn optgroups() -> getopts::Options {
             json   = Output a json document",
             "pretty|terse|json",
         )
+        .optopt(
+            "",
+            "save-baseline",
+            "Save the results of the benchmarks under the given name",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "baseline",
+            "Compare the results of the benchmarks against a saved \
+             baseline",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "baseline-dir",
+            "Directory holding saved baselines (default: \
+             target/bench-baselines)",
+            "PATH",
+        )
+        .optopt(
+            "",
+            "noise-threshold",
+            "Changes smaller than this percentage are never reported \
+             as regressions (default: 2)",
+            "PCT",
+        )
         .optopt(
             "Z",
             "",
@@ -460,7 +512,10 @@ fn usage(binary: &str, options: &getopts::Options) {
         r#"{usage}
 
 The FILTER string is tested against the name of all tests, and only those
-tests whose names contain the filter are run.
+tests whose names contain the filter are run. Subtests created with
+test::subtest are reported as TEST/SUBTEST, and --subtest FILTER only runs the
+subtests whose names contain FILTER; nested subtests are selected with a
+filter of the form PARENT/CHILD.
 
 By default, all tests are run in parallel. This can be altered with the
 --test-threads flag or the RUST_TEST_THREADS environment variable when running
@@ -470,6 +525,10 @@ All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
 
+The results of benchmarks can be saved with --save-baseline and compared in a
+later run with --baseline. Changes are tested for statistical significance, and
+the run fails if any benchmark regressed by more than the --noise-threshold.
+
 Test Attributes:
 
     #[test]        - Indicates a function is a test to be run. This function
@@ -606,10 +665,42 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         }
     };
 
+    let subtest_filter = matches.opt_str("subtest");
+    if !allow_unstable && subtest_filter.is_some() {
+        return Some(Err("--subtest is only accepted on the nightly compiler".into()));
+    }
+
+    let save_baseline = matches.opt_str("save-baseline");
+    let baseline = matches.opt_str("baseline");
+    let baseline_dir = matches.opt_str("baseline-dir");
+    let noise_threshold = matches.opt_str("noise-threshold");
+    if !allow_unstable && (save_baseline.is_some() || baseline.is_some()
+        || baseline_dir.is_some() || noise_threshold.is_some())
+    {
+        return Some(Err(
+            "benchmark baselines are only accepted on the nightly compiler".into(),
+        ));
+    }
+    let baseline_dir = PathBuf::from(baseline_dir.as_ref().map_or(DEFAULT_BASELINE_DIR, |s| &**s));
+    let noise_threshold = match noise_threshold {
+        Some(pct) => match pct.parse::<f64>() {
+            Ok(pct) if pct >= 0.0 => pct,
+            _ => {
+                return Some(Err(format!(
+                    "argument for --noise-threshold must be a non-negative \
+                     number (was {})",
+                    pct
+                )))
+            }
+        },
+        None => DEFAULT_NOISE_THRESHOLD_PCT,
+    };
+
     let test_opts = TestOpts {
         list,
         filter,
         filter_exact: exact,
+        subtest_filter,
         run_ignored,
         run_tests,
         bench_benchmarks,
@@ -619,6 +710,10 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         format,
         test_threads,
         skip: matches.opt_strs("skip"),
+        save_baseline,
+        baseline,
+        baseline_dir,
+        noise_threshold,
         options: Options::new(),
     };
 
@@ -628,6 +723,7 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
 #[derive(Clone, PartialEq)]
 pub struct BenchSamples {
     ns_iter_summ: stats::Summary,
+    ns_iter_samples: Vec<f64>,
     mb_s: usize,
 }
 
@@ -676,6 +772,9 @@ struct ConsoleTestState {
     metrics: MetricMap,
     failures: Vec<(TestDesc, Vec<u8>)>,
     not_failures: Vec<(TestDesc, Vec<u8>)>,
+    baseline: Option<Baseline>,
+    new_baseline: Baseline,
+    regressions: Vec<TestDesc>,
     options: Options,
 }
 
@@ -685,6 +784,10 @@ impl ConsoleTestState {
             Some(ref path) => Some(File::create(path)?),
             None => None,
         };
+        let baseline = match opts.baseline {
+            Some(ref name) => Some(Baseline::load(&opts.baseline_dir, name)?),
+            None => None,
+        };
 
         Ok(ConsoleTestState {
             log_out,
@@ -698,6 +801,9 @@ impl ConsoleTestState {
             metrics: MetricMap::new(),
             failures: Vec::new(),
             not_failures: Vec::new(),
+            baseline,
+            new_baseline: Baseline::new(),
+            regressions: Vec::new(),
             options: opts.options,
         })
     }
@@ -728,6 +834,18 @@ impl ConsoleTestState {
     fn current_test_count(&self) -> usize {
         self.passed + self.failed + self.ignored + self.measured + self.allowed_fail
     }
+
+    /// Compares the samples of a benchmark against the loaded baseline, if
+    /// there is one and it contains that benchmark.
+    fn compare_to_baseline(
+        &self,
+        test: &TestDesc,
+        bs: &BenchSamples,
+        noise_pct: f64,
+    ) -> Option<BenchComparison> {
+        let old = self.baseline.as_ref()?.get(test.name.as_slice())?;
+        baseline::compare(old, &bs.ns_iter_samples, noise_pct)
+    }
 }
 
 // Format a number with thousands separators
@@ -839,6 +957,7 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
 pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Result<bool> {
     fn callback(
         event: &TestEvent,
+        opts: &TestOpts,
         st: &mut ConsoleTestState,
         out: &mut OutputFormatter,
     ) -> io::Result<()> {
@@ -853,35 +972,65 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
             TeResult(test, result, stdout) => {
                 st.write_log_result(&test, &result)?;
                 out.write_result(&test, &result, &*stdout)?;
-                match result {
-                    TrOk => {
-                        st.passed += 1;
-                        st.not_failures.push((test, stdout));
-                    }
-                    TrIgnored => st.ignored += 1,
-                    TrAllowedFail => st.allowed_fail += 1,
-                    TrBench(bs) => {
-                        st.metrics.insert_metric(
-                            test.name.as_slice(),
-                            bs.ns_iter_summ.median,
-                            bs.ns_iter_summ.max - bs.ns_iter_summ.min,
-                        );
-                        st.measured += 1
-                    }
-                    TrFailed => {
-                        st.failed += 1;
-                        st.failures.push((test, stdout));
-                    }
-                    TrFailedMsg(msg) => {
-                        st.failed += 1;
-                        let mut stdout = stdout;
-                        stdout.extend_from_slice(format!("note: {}", msg).as_bytes());
-                        st.failures.push((test, stdout));
+                record_result(opts, st, out, test, result, stdout)
+            }
+            TeSubResult(test, result, stdout) => {
+                // Subtests are only discovered while running their parent.
+                st.total += 1;
+                st.write_log_result(&test, &result)?;
+                out.write_subtest_result(&test, &result, &*stdout)?;
+                record_result(opts, st, out, test, result, stdout)
+            }
+        }
+    }
+
+    fn record_result(
+        opts: &TestOpts,
+        st: &mut ConsoleTestState,
+        out: &mut OutputFormatter,
+        test: TestDesc,
+        result: TestResult,
+        stdout: Vec<u8>,
+    ) -> io::Result<()> {
+        match result {
+            TrOk => {
+                st.passed += 1;
+                st.not_failures.push((test, stdout));
+            }
+            TrIgnored => st.ignored += 1,
+            TrAllowedFail => st.allowed_fail += 1,
+            TrBench(bs) => {
+                st.metrics.insert_metric(
+                    test.name.as_slice(),
+                    bs.ns_iter_summ.median,
+                    bs.ns_iter_summ.max - bs.ns_iter_summ.min,
+                );
+                st.measured += 1;
+                let cmp = st.compare_to_baseline(&test, &bs, opts.noise_threshold);
+                if let Some(cmp) = cmp {
+                    out.write_bench_comparison(&test, &cmp)?;
+                    if cmp.verdict == Verdict::Regressed {
+                        st.regressions.push(test.clone());
                     }
                 }
-                Ok(())
+                // A bench that never called `iter` has no samples, and mustn't
+                // replace the ones saved for it by an earlier run.
+                if !bs.ns_iter_samples.is_empty() {
+                    st.new_baseline.insert(test.name.as_slice(), bs.ns_iter_samples);
+                }
+            }
+            TrFailed => {
+                st.failed += 1;
+                st.failures.push((test, stdout));
+            }
+            TrFailedMsg(msg) => {
+                st.failed += 1;
+                let mut stdout = stdout;
+                stdout.extend_from_slice(format!("note: {}", msg).as_bytes());
+                st.failures.push((test, stdout));
             }
         }
+        Ok(())
     }
 
     let output = match term::stdout() {
@@ -920,10 +1069,16 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         }
     }
 
-    run_tests(opts, tests, |x| callback(&x, &mut st, &mut *out))?;
+    run_tests(opts, tests, |x| callback(&x, opts, &mut st, &mut *out))?;
 
     assert!(st.current_test_count() == st.total);
 
+    if let Some(ref name) = opts.save_baseline {
+        if !st.new_baseline.is_empty() {
+            st.new_baseline.save(&opts.baseline_dir, name)?;
+        }
+    }
+
     return out.write_run_finish(&st);
 }
 
@@ -958,6 +1113,9 @@ fn should_sort_failures_before_printing_them() {
         failures: vec![(test_b, Vec::new()), (test_a, Vec::new())],
         options: Options::new(),
         not_failures: Vec::new(),
+        baseline: None,
+        new_baseline: Baseline::new(),
+        regressions: Vec::new(),
     };
 
     out.write_failures(&st).unwrap();
@@ -1012,6 +1170,7 @@ pub enum TestEvent {
     TeFiltered(Vec<TestDesc>),
     TeWait(TestDesc),
     TeResult(TestDesc, TestResult, Vec<u8>),
+    TeSubResult(TestDesc, TestResult, Vec<u8>),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
 }
@@ -1032,7 +1191,7 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
 where
     F: FnMut(TestEvent) -> io::Result<()>,
 {
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
     use std::sync::mpsc::RecvTimeoutError;
 
     let tests_len = tests.len();
@@ -1103,20 +1262,47 @@ where
         })
     };
 
+    // Subtests send their results on the same channel as the test running
+    // them, before the result of that test. They are held back until then,
+    // so that they are reported right after their parent.
+    let mut subtest_results: Vec<MonitorMsg> = Vec::new();
+
+    fn take_subtest_results(results: &mut Vec<MonitorMsg>, parent: &TestDesc) -> Vec<MonitorMsg> {
+        let prefix = format!("{}/", parent.name);
+        let (subtests, rest) = results
+            .drain(..)
+            .partition(|&(ref desc, _, _)| desc.name.as_slice().starts_with(&prefix));
+        *results = rest;
+        subtests
+    }
+
     if concurrency == 1 {
         while !remaining.is_empty() {
             let test = remaining.pop().unwrap();
-            callback(TeWait(test.desc.clone()))?;
+            let desc = test.desc.clone();
+            callback(TeWait(desc.clone()))?;
             run_test(opts, !opts.run_tests, test, tx.clone());
-            let (test, result, stdout) = rx.recv().unwrap();
-            callback(TeResult(test, result, stdout))?;
+            loop {
+                let (test, result, stdout) = rx.recv().unwrap();
+                if test == desc {
+                    callback(TeResult(test, result, stdout))?;
+                    break;
+                }
+                subtest_results.push((test, result, stdout));
+            }
+            for (test, result, stdout) in take_subtest_results(&mut subtest_results, &desc) {
+                callback(TeSubResult(test, result, stdout))?;
+            }
         }
     } else {
+        let mut in_flight: HashSet<TestDesc> = HashSet::new();
+
         while pending > 0 || !remaining.is_empty() {
             while pending < concurrency && !remaining.is_empty() {
                 let test = remaining.pop().unwrap();
                 let timeout = Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S);
                 running_tests.insert(test.desc.clone(), timeout);
+                in_flight.insert(test.desc.clone());
                 callback(TeWait(test.desc.clone()))?; //here no pad
                 run_test(opts, !opts.run_tests, test, tx.clone());
                 pending += 1;
@@ -1139,9 +1325,17 @@ where
             }
 
             let (desc, result, stdout) = res.unwrap();
+            if !in_flight.remove(&desc) {
+                subtest_results.push((desc, result, stdout));
+                continue;
+            }
             running_tests.remove(&desc);
 
+            let parent = desc.clone();
             callback(TeResult(desc, result, stdout))?;
+            for (desc, result, stdout) in take_subtest_results(&mut subtest_results, &parent) {
+                callback(TeSubResult(desc, result, stdout))?;
+            }
             pending -= 1;
         }
     }
@@ -1293,13 +1487,7 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         None => filtered,
         Some(ref filter) => filtered
             .into_iter()
-            .filter(|test| {
-                if opts.filter_exact {
-                    test.desc.name.as_slice() == &filter[..]
-                } else {
-                    test.desc.name.as_slice().contains(&filter[..])
-                }
-            })
+            .filter(|test| matches_filter(test.desc.name.as_slice(), filter, opts.filter_exact))
             .collect(),
     };
 
@@ -1307,13 +1495,9 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
     filtered = filtered
         .into_iter()
         .filter(|t| {
-            !opts.skip.iter().any(|sf| {
-                if opts.filter_exact {
-                    t.desc.name.as_slice() == &sf[..]
-                } else {
-                    t.desc.name.as_slice().contains(&sf[..])
-                }
-            })
+            !opts.skip
+                .iter()
+                .any(|sf| matches_filter(t.desc.name.as_slice(), sf, opts.filter_exact))
         })
         .collect();
 
@@ -1344,6 +1528,14 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
     filtered
 }
 
+fn matches_filter(name: &str, filter: &str, exact: bool) -> bool {
+    if exact {
+        name == filter
+    } else {
+        name.contains(filter)
+    }
+}
+
 pub fn convert_benchmarks_to_tests(tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
     // convert benchmarks to tests, if we're not benchmarking them
     tests
@@ -1386,6 +1578,7 @@ pub fn run_test(
         desc: TestDesc,
         monitor_ch: Sender<MonitorMsg>,
         nocapture: bool,
+        subtests: SubtestCtxt,
         testfn: Box<FnBox() + Send>,
     ) {
         // Buffer for capturing standard I/O
@@ -1403,14 +1596,22 @@ pub fn run_test(
                 None
             };
 
+            SUBTEST_CTXT.with(|ctxt| *ctxt.borrow_mut() = Some(subtests));
             let result = catch_unwind(AssertUnwindSafe(testfn));
+            let subtests = SUBTEST_CTXT.with(|ctxt| ctxt.borrow_mut().take());
 
             if let Some((printio, panicio)) = oldio {
                 io::set_print(printio);
                 io::set_panic(panicio);
             };
 
-            let test_result = calc_result(&desc, result);
+            let failed_subtests = subtests.map_or(0, |s| s.failed);
+            let test_result = match calc_result(&desc, result) {
+                TrOk if failed_subtests > 0 && desc.should_panic == ShouldPanic::No => {
+                    failed_subtests_result(&desc, failed_subtests)
+                }
+                result => result,
+            };
             let stdout = data.lock().unwrap().to_vec();
             monitor_ch
                 .send((desc.clone(), test_result, stdout))
@@ -1429,6 +1630,17 @@ pub fn run_test(
         }
     }
 
+    let subtests = SubtestCtxt {
+        name: desc.name.as_slice().to_owned(),
+        filter: opts.subtest_filter.clone(),
+        filter_exact: opts.filter_exact,
+        skip: opts.skip.clone(),
+        nocapture: opts.nocapture,
+        allow_fail: desc.allow_fail,
+        monitor_ch: monitor_ch.clone(),
+        failed: 0,
+    };
+
     match testfn {
         DynBenchFn(bencher) => {
             ::bench::benchmark(desc, monitor_ch, opts.nocapture, |harness| {
@@ -1442,12 +1654,13 @@ pub fn run_test(
         }
         DynTestFn(f) => {
             let cb = move || __rust_begin_short_backtrace(f);
-            run_test_inner(desc, monitor_ch, opts.nocapture, Box::new(cb))
+            run_test_inner(desc, monitor_ch, opts.nocapture, subtests, Box::new(cb))
         }
         StaticTestFn(f) => run_test_inner(
             desc,
             monitor_ch,
             opts.nocapture,
+            subtests,
             Box::new(move || __rust_begin_short_backtrace(f)),
         ),
     }
@@ -1483,6 +1696,127 @@ fn calc_result(desc: &TestDesc, task_result: Result<(), Box<Any + Send>>) -> Tes
     }
 }
 
+fn failed_subtests_result(desc: &TestDesc, failed: usize) -> TestResult {
+    if desc.allow_fail {
+        TrAllowedFail
+    } else {
+        match failed {
+            1 => TrFailedMsg("1 subtest failed".to_owned()),
+            n => TrFailedMsg(format!("{} subtests failed", n)),
+        }
+    }
+}
+
+/// The test or subtest running on the current thread.
+struct SubtestCtxt {
+    name: String,
+    /// The part of `--subtest` that applies to the subtests of this test.
+    filter: Option<String>,
+    filter_exact: bool,
+    skip: Vec<String>,
+    nocapture: bool,
+    allow_fail: bool,
+    monitor_ch: Sender<MonitorMsg>,
+    /// The number of subtests of this test that failed so far.
+    failed: usize,
+}
+
+thread_local! {
+    static SUBTEST_CTXT: RefCell<Option<SubtestCtxt>> = RefCell::new(None)
+}
+
+/// Runs `f` as a subtest of the test running on the current thread.
+///
+/// Subtests are named `test/name`, and are reported individually after the
+/// test that ran them. `--subtest` runs the matching subtests only, and
+/// subtests can be nested. A panicking subtest fails the test, but
+/// doesn't stop it, so that the remaining subtests are still run.
+///
+/// Returns whether the subtest passed, which also is the case if it was
+/// filtered out. Outside of a test, `f` is simply called.
+pub fn subtest<F: FnOnce()>(name: &str, f: F) -> bool {
+    let mut parent = match SUBTEST_CTXT.with(|ctxt| ctxt.borrow_mut().take()) {
+        Some(parent) => parent,
+        None => {
+            f();
+            return true;
+        }
+    };
+
+    let full_name = format!("{}/{}", parent.name, name);
+    let (selected, filter) = match parent.filter {
+        Some(ref filter) => {
+            let mut parts = filter.splitn(2, '/');
+            let part = parts.next().unwrap();
+            let selected = matches_filter(name, part, parent.filter_exact);
+            (selected, parts.next().map(|rest| rest.to_owned()))
+        }
+        None => (true, None),
+    };
+    let skipped = parent
+        .skip
+        .iter()
+        .any(|sf| matches_filter(&full_name, sf, parent.filter_exact));
+    if !selected || skipped {
+        SUBTEST_CTXT.with(|ctxt| *ctxt.borrow_mut() = Some(parent));
+        return true;
+    }
+
+    let ctxt = SubtestCtxt {
+        name: full_name.clone(),
+        filter,
+        filter_exact: parent.filter_exact,
+        skip: parent.skip.clone(),
+        nocapture: parent.nocapture,
+        allow_fail: parent.allow_fail,
+        monitor_ch: parent.monitor_ch.clone(),
+        failed: 0,
+    };
+
+    let data = Arc::new(Mutex::new(Vec::new()));
+    let oldio = if !ctxt.nocapture {
+        Some((
+            io::set_print(Some(Box::new(Sink(data.clone())))),
+            io::set_panic(Some(Box::new(Sink(data.clone())))),
+        ))
+    } else {
+        None
+    };
+
+    SUBTEST_CTXT.with(|c| *c.borrow_mut() = Some(ctxt));
+    let result = catch_unwind(AssertUnwindSafe(f));
+    let ctxt = SUBTEST_CTXT.with(|c| c.borrow_mut().take()).unwrap();
+
+    if let Some((printio, panicio)) = oldio {
+        io::set_print(printio);
+        io::set_panic(panicio);
+    }
+
+    let desc = TestDesc {
+        name: DynTestName(full_name),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        allow_fail: parent.allow_fail,
+    };
+    let test_result = match calc_result(&desc, result) {
+        TrOk if ctxt.failed > 0 => failed_subtests_result(&desc, ctxt.failed),
+        result => result,
+    };
+    let passed = test_result == TrOk;
+    if !passed {
+        parent.failed += 1;
+    }
+
+    let stdout = data.lock().unwrap().to_vec();
+    // The subtest ran on the same thread as its test, which hasn't reported
+    // yet, so the receiving end is only gone if the harness bailed out early,
+    // e.g. on an error writing its output.
+    let _ = parent.monitor_ch.send((desc, test_result, stdout));
+
+    SUBTEST_CTXT.with(|c| *c.borrow_mut() = Some(parent));
+    passed
+}
+
 #[derive(Clone, PartialEq)]
 pub struct MetricMap(BTreeMap<String, Metric>);
 
@@ -1549,7 +1883,9 @@ impl Bencher {
             return;
         }
 
-        self.summary = Some(iter(&mut inner));
+        let (summary, samples) = iter_samples(&mut inner);
+        self.summary = Some(summary);
+        self.samples = samples;
     }
 
     pub fn bench<F>(&mut self, mut f: F) -> Option<stats::Summary>
@@ -1577,6 +1913,15 @@ where
 }
 
 pub fn iter<T, F>(inner: &mut F) -> stats::Summary
+where
+    F: FnMut() -> T,
+{
+    iter_samples(inner).0
+}
+
+// Like `iter`, but also returns the (winsorized) per-iteration samples the
+// summary was computed from.
+fn iter_samples<T, F>(inner: &mut F) -> (stats::Summary, Vec<f64>)
 where
     F: FnMut() -> T,
 {
@@ -1622,13 +1967,13 @@ where
         if loop_run > Duration::from_millis(100) && summ.median_abs_dev_pct < 1.0
             && summ.median - summ5.median < summ5.median_abs_dev
         {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         total_run = total_run + loop_run;
         // Longest we ever run for is 3s.
         if total_run > Duration::from_secs(3) {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         // If we overflow here just return the results so far. We check a
@@ -1638,7 +1983,7 @@ where
         n = match n.checked_mul(10) {
             Some(_) => n * 2,
             None => {
-                return summ5;
+                return (summ5, samples.to_vec());
             }
         };
     }
@@ -1648,6 +1993,7 @@ pub mod bench {
     use std::panic::{catch_unwind, AssertUnwindSafe};
     use std::cmp;
     use std::io;
+    use std::mem;
     use std::sync::{Arc, Mutex};
     use stats;
     use super::{BenchMode, BenchSamples, Bencher, MonitorMsg, Sender, Sink, TestDesc, TestResult};
@@ -1659,6 +2005,7 @@ pub mod bench {
         let mut bs = Bencher {
             mode: BenchMode::Auto,
             summary: None,
+            samples: Vec::new(),
             bytes: 0,
         };
 
@@ -1689,6 +2036,7 @@ pub mod bench {
 
                 let bs = BenchSamples {
                     ns_iter_summ,
+                    ns_iter_samples: mem::replace(&mut bs.samples, Vec::new()),
                     mb_s: mb_s as usize,
                 };
                 TestResult::TrBench(bs)
@@ -1699,6 +2047,7 @@ pub mod bench {
                 let samples: &mut [f64] = &mut [0.0_f64; 1];
                 let bs = BenchSamples {
                     ns_iter_summ: stats::Summary::new(samples),
+                    ns_iter_samples: Vec::new(),
                     mb_s: 0,
                 };
                 TestResult::TrBench(bs)
@@ -1717,6 +2066,7 @@ pub mod bench {
         let mut bs = Bencher {
             mode: BenchMode::Single,
             summary: None,
+            samples: Vec::new(),
             bytes: 0,
         };
         bs.bench(f);
@@ -1864,6 +2214,36 @@ mod tests {
         assert!((opts.run_ignored));
     }
 
+    #[test]
+    fn parse_baseline_flags() {
+        let args = vec![
+            "progname".to_string(),
+            "--bench".to_string(),
+            "--baseline".to_string(),
+            "main".to_string(),
+        ];
+        assert!(parse_opts(&args).unwrap().is_err());
+
+        let args = vec![
+            "progname".to_string(),
+            "--bench".to_string(),
+            "-Zunstable-options".to_string(),
+            "--save-baseline".to_string(),
+            "new".to_string(),
+            "--baseline".to_string(),
+            "main".to_string(),
+            "--noise-threshold".to_string(),
+            "5".to_string(),
+        ];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_baseline_flags"),
+        };
+        assert_eq!(opts.save_baseline, Some("new".to_string()));
+        assert_eq!(opts.baseline, Some("main".to_string()));
+        assert_eq!(opts.noise_threshold, 5.0);
+    }
+
     #[test]
     pub fn filter_for_ignored_option() {
         // When we run ignored tests the test filter should filter out all the
@@ -1994,6 +2374,67 @@ mod tests {
         assert_eq!(exact.len(), 1);
     }
 
+    fn subtest_table() -> TestDescAndFn {
+        TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName("table"),
+                ignore: false,
+                should_panic: ShouldPanic::No,
+                allow_fail: false,
+            },
+            testfn: DynTestFn(Box::new(|| {
+                for &(name, input) in &[("one", 1), ("two", 2), ("three", 3)] {
+                    ::subtest(name, || assert!(input != 2));
+                }
+            })),
+        }
+    }
+
+    #[test]
+    pub fn subtests_are_reported() {
+        let (tx, rx) = channel();
+        run_test(&TestOpts::new(), false, subtest_table(), tx);
+        let results = rx.iter()
+            .map(|(desc, res, _)| (desc.name.to_string(), res))
+            .collect::<Vec<_>>();
+        assert!(
+            results == vec![
+                ("table/one".to_string(), TrOk),
+                ("table/two".to_string(), TrFailed),
+                ("table/three".to_string(), TrOk),
+                ("table".to_string(), TrFailedMsg("1 subtest failed".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn filter_subtests() {
+        // A filter containing a slash, e.g. a path, still only matches test names.
+        let path_filter = TestOpts {
+            filter: Some("table/t".into()),
+            ..TestOpts::new()
+        };
+        assert!(filter_tests(&path_filter, vec![subtest_table()]).is_empty());
+
+        let opts = TestOpts {
+            subtest_filter: Some("t".into()),
+            skip: vec!["table/three".into()],
+            ..TestOpts::new()
+        };
+
+        let (tx, rx) = channel();
+        run_test(&opts, false, subtest_table(), tx);
+        let results = rx.iter()
+            .map(|(desc, res, _)| (desc.name.to_string(), res))
+            .collect::<Vec<_>>();
+        assert!(
+            results == vec![
+                ("table/two".to_string(), TrFailed),
+                ("table".to_string(), TrFailedMsg("1 subtest failed".to_string())),
+            ]
+        );
+    }
+
     #[test]
     pub fn sort_tests() {
         let mut opts = TestOpts::new();
@@ -11,6 +11,7 @@
 #![allow(missing_docs)]
 #![allow(deprecated)] // Float
 
+use std::cmp;
 use std::cmp::Ordering::{self, Equal, Greater, Less};
 use std::mem;
 
@@ -325,12 +326,103 @@ pub fn winsorize(samples: &mut [f64], pct: f64) {
     }
 }
 
+/// Two-sided Mann-Whitney U test (also known as the Wilcoxon rank-sum test) of the hypothesis
+/// that two independent sample sets are drawn from the same distribution. Returns the p-value,
+/// computed with the normal approximation including tie and continuity corrections, which is
+/// accurate for the sample sizes produced by the benchmark harness.
+///
+/// See: <https://en.wikipedia.org/wiki/Mann%E2%80%93Whitney_U_test>
+pub fn mann_whitney_u(x: &[f64], y: &[f64]) -> f64 {
+    assert!(!x.is_empty() && !y.is_empty());
+    let mut all: Vec<(f64, bool)> = x.iter()
+        .map(|&v| (v, true))
+        .chain(y.iter().map(|&v| (v, false)))
+        .collect();
+    all.sort_by(|a, b| local_cmp(a.0, b.0));
+
+    // Assign ranks, giving tied values the average of the ranks they span.
+    let n = all.len();
+    let mut rank_sum_x = 0.0;
+    let mut tie_correction = 0.0;
+    let mut i = 0;
+    while i < n {
+        let mut j = i + 1;
+        while j < n && local_cmp(all[j].0, all[i].0) == Equal {
+            j += 1;
+        }
+        let rank = (i + j + 1) as f64 / 2.0;
+        for &(_, from_x) in &all[i..j] {
+            if from_x {
+                rank_sum_x += rank;
+            }
+        }
+        let t = (j - i) as f64;
+        tie_correction += t * t * t - t;
+        i = j;
+    }
+
+    let (n1, n2, n) = (x.len() as f64, y.len() as f64, n as f64);
+    let u = rank_sum_x - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let var = n1 * n2 / 12.0 * ((n + 1.0) - tie_correction / (n * (n - 1.0)));
+    if var <= 0.0 {
+        // Every sample is identical, so there is no evidence of any difference.
+        return 1.0;
+    }
+    let z = ((u - mean).abs() - 0.5).max(0.0) / var.sqrt();
+    (2.0 * (1.0 - standard_normal_cdf(z))).min(1.0)
+}
+
+/// Hodges-Lehmann estimate of the shift between two independent sample sets, along with a
+/// distribution-free 95% confidence interval for it. The shift is the median of all pairwise
+/// differences `y[j] - x[i]`, so a positive value means the samples in `y` tend to be larger.
+/// Returns `(lower, estimate, upper)`.
+///
+/// See: <https://en.wikipedia.org/wiki/Hodges%E2%80%93Lehmann_estimator>
+pub fn shift_confidence_interval(x: &[f64], y: &[f64]) -> (f64, f64, f64) {
+    assert!(!x.is_empty() && !y.is_empty());
+    let mut diffs = Vec::with_capacity(x.len() * y.len());
+    for &a in x {
+        for &b in y {
+            diffs.push(b - a);
+        }
+    }
+    local_sort(&mut diffs);
+
+    // Two-sided 97.5th percentile of the standard normal distribution.
+    let z = 1.959963984540054;
+    let (n1, n2) = (x.len() as f64, y.len() as f64);
+    let mn = n1 * n2;
+    let spread = z * (mn * (n1 + n2 + 1.0) / 12.0).sqrt();
+    let k = (mn / 2.0 - spread).floor().max(0.0) as usize;
+    let k = cmp::min(k, (diffs.len() - 1) / 2);
+
+    let estimate = percentile_of_sorted(&diffs, 50.0);
+    (diffs[k], estimate, diffs[diffs.len() - 1 - k])
+}
+
+// Cumulative distribution function of the standard normal distribution, using the
+// Abramowitz and Stegun approximation 7.1.26 of `erf` (maximum absolute error 1.5e-7).
+fn standard_normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / 2f64.sqrt();
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741
+        + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
 // Test vectors generated from R, using the script src/etc/stat-test-vectors.r.
 
 #[cfg(test)]
 mod tests {
     use stats::Stats;
     use stats::Summary;
+    use stats::{mann_whitney_u, shift_confidence_interval};
     use std::f64;
     use std::io::prelude::*;
     use std::io;
@@ -903,6 +995,34 @@ mod tests {
     fn test_sum_f64_between_ints_that_sum_to_0() {
         assert_eq!([1e30f64, 1.2f64, -1e30f64].sum(), 1.2);
     }
+
+    #[test]
+    fn test_mann_whitney_u() {
+        let xs = &[1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_approx_eq!(mann_whitney_u(xs, xs), 1.0);
+        assert_approx_eq!(mann_whitney_u(&[7.0, 7.0], &[7.0, 7.0, 7.0]), 1.0);
+
+        // Matches R's `wilcox.test(1:3, 4:6, exact = FALSE)`.
+        let p = mann_whitney_u(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]);
+        assert!((p - 0.08086).abs() < 1.0e-4, "unexpected p-value {}", p);
+
+        let xs = (0..50).map(|i| 1000.0 + (i % 7) as f64).collect::<Vec<_>>();
+        let ys = xs.iter().map(|x| x + 50.0).collect::<Vec<_>>();
+        assert!(mann_whitney_u(&xs, &ys) < 1.0e-6);
+    }
+
+    #[test]
+    fn test_shift_confidence_interval() {
+        let xs = (0..50).map(|i| 1000.0 + (i % 7) as f64).collect::<Vec<_>>();
+        let ys = xs.iter().map(|x| x + 50.0).collect::<Vec<_>>();
+        let (lo, estimate, hi) = shift_confidence_interval(&xs, &ys);
+        assert_eq!(estimate, 50.0);
+        assert!(lo <= 50.0 && 50.0 <= hi);
+        assert!(hi - lo < 7.0);
+
+        let (lo, estimate, hi) = shift_confidence_interval(&[1.0], &[3.0]);
+        assert_eq!((lo, estimate, hi), (2.0, 2.0, 2.0));
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,36 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_name = "foo"]
+
+/// Some documentation.
+pub struct Bar {
+    pub baz: u32,
+}
+
+impl Bar {
+    pub fn new() -> Bar {
+        Bar { baz: 0 }
+    }
+}
+
+pub trait Shape {
+    fn area(&self) -> u32;
+}
+
+impl Shape for Bar {
+    fn area(&self) -> u32 {
+        self.baz
+    }
+}
+
+pub fn process<T>(value: T) -> T where T: Clone {
+    value.clone()
+}
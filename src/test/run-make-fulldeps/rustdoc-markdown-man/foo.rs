@@ -0,0 +1,40 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_name = "foo"]
+
+//! The crate documentation.
+
+/// A bar, see also [`baz`].
+///
+/// # Examples
+///
+/// ```
+/// # fn main() {
+/// let bar = foo::Bar::new();
+/// # }
+/// ```
+pub struct Bar {
+    /// A field.
+    pub field: u32,
+}
+
+impl Bar {
+    /// Creates a bar.
+    pub fn new() -> Bar {
+        Bar { field: 0 }
+    }
+}
+
+/// Some functions.
+pub mod baz {
+    /// Does nothing.
+    pub fn nothing() {}
+}
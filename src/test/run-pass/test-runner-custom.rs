@@ -0,0 +1,54 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+
+#![feature(custom_test_frameworks)]
+#![test_runner(runner)]
+
+trait Testable {
+    fn name(&self) -> &'static str;
+    fn run(&self) -> bool;
+}
+
+struct Case(&'static str, fn() -> bool);
+
+impl Testable for Case {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn run(&self) -> bool {
+        (self.1)()
+    }
+}
+
+fn runner(tests: &[&Testable]) {
+    let mut names = tests.iter().map(|t| t.name()).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["in_module", "top_level"]);
+    for test in tests {
+        assert!(test.run(), "{} failed", test.name());
+    }
+}
+
+fn always_true() -> bool {
+    true
+}
+
+#[test_case]
+const TOP_LEVEL: Case = Case("top_level", always_true);
+
+mod tests {
+    use super::{always_true, Case};
+
+    #[test_case]
+    static IN_MODULE: Case = Case("in_module", always_true);
+}
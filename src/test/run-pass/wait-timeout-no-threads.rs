@@ -0,0 +1,61 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ignore-cloudabi no processes
+// ignore-emscripten no processes
+// ignore-windows no kill command
+
+// Waiting on a child with a timeout mustn't leave anything running in this
+// process once the wait has timed out and the `Child` is dropped.
+
+#![feature(process_wait_timeout)]
+
+use std::env;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+fn threads() -> usize {
+    std::fs::read_dir("/proc/self/task").unwrap().count()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn threads() -> usize {
+    0
+}
+
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+    if args.len() != 1 {
+        match &args[1][..] {
+            "sleep" => thread::sleep(Duration::new(1_000, 0)),
+            _ => {}
+        }
+        return
+    }
+
+    let before = threads();
+    let mut children = Vec::new();
+    for _ in 0..4 {
+        let mut me = Command::new(env::current_exe().unwrap())
+                             .arg("sleep")
+                             .spawn()
+                             .unwrap();
+        assert!(me.wait_timeout(Duration::from_millis(10)).unwrap().is_none());
+        children.push(me.id());
+        drop(me);
+    }
+    assert_eq!(threads(), before);
+
+    for id in children {
+        Command::new("kill").arg(id.to_string()).status().unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// compile-flags:-Z unstable-options --show-coverage
+// compile-pass
+
+//! Crate docs.
+
+/// A documented struct.
+///
+/// ```
+/// let _ = coverage::Documented { field: 0 };
+/// ```
+pub struct Documented {
+    /// A documented field.
+    pub field: u32,
+}
+
+pub struct Undocumented;
+
+impl Undocumented {
+    /// A documented method.
+    pub fn method(&self) {}
+}
+
+pub mod inner {
+    /// A documented function.
+    pub fn documented() {}
+
+    pub fn undocumented() {}
+}
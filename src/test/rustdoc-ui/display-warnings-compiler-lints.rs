@@ -0,0 +1,23 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-pass
+// compile-flags:-Z unstable-options --display-warnings
+
+// The lints of the compiler stay silent even with `--display-warnings`, denied or not.
+
+#![deny(unused)]
+
+fn unused() {
+    let x = 1;
+}
+
+/// See https://example.com/docs.
+pub fn foo() {}
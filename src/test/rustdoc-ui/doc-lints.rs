@@ -0,0 +1,29 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-pass
+
+/// See https://example.com/docs, or <https://example.com>.
+pub fn foo() {}
+
+#[doc = "```should-panic\npanic!();\n```"]
+pub fn bar() {}
+
+/// Calls [`private`].
+pub fn baz() {}
+
+fn private() {}
+
+/// Calls [`private`], which is fine, since this isn't documented.
+fn private_caller() {}
+
+#[allow(bare_urls)]
+/// See https://example.com/docs.
+pub fn allowed() {}
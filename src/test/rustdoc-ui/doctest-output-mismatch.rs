@@ -0,0 +1,28 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:--test
+// normalize-stdout-test: "src/test/rustdoc-ui" -> "$$DIR"
+// normalize-stdout-test: "librustdoc/test\.rs:[0-9]+:[0-9]+" -> "librustdoc/test.rs:LL:CC"
+// failure-status: 101
+// rustc-env:RUST_BACKTRACE=0
+
+#![crate_name = "foo"]
+
+/// ```
+/// println!("{}", foo::double(2));
+/// ```
+///
+/// ```output
+/// 5
+/// ```
+pub fn double(x: u8) -> u8 {
+    x * 2
+}
@@ -0,0 +1,20 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Selecting one of the unstable output formats doesn't warn that
+// `--output-format` is deprecated.
+
+// compile-flags:-Z unstable-options --output-format markdown
+// compile-pass
+
+//! Crate docs.
+
+/// A documented function.
+pub fn foo() {}
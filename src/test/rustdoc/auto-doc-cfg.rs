@@ -0,0 +1,47 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z unstable-options --auto-doc-cfg unix,windows --auto-doc-cfg target_os
+
+#![crate_name = "foo"]
+
+// @has foo/fn.everywhere.html
+// @count - '//*[@class="stab portability"]' 0
+#[cfg(not(feature = "nope"))]
+pub fn everywhere() {}
+
+// @has foo/fn.desktop.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on Unix or Windows only.'
+#[cfg(all(any(unix, windows), not(debug_assertions_nope)))]
+pub fn desktop() {}
+
+// @has foo/fn.not_fuchsia.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on non-Fuchsia only.'
+#[cfg(not(target_os = "fuchsia"))]
+pub fn not_fuchsia() {}
+
+// The requirements of a module are shown on its items as well, but only once.
+
+// @has foo/desktop/fn.also_desktop.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on Unix or Windows only.'
+// @has foo/desktop/fn.not_fuchsia.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on (Unix or Windows) and non-Fuchsia only.'
+#[cfg(any(unix, windows))]
+pub mod desktop {
+    #[cfg(any(unix, windows))]
+    pub fn also_desktop() {}
+
+    #[cfg(not(target_os = "fuchsia"))]
+    pub fn not_fuchsia() {}
+}
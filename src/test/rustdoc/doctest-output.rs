@@ -0,0 +1,34 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:--test
+
+#![crate_name = "foo"]
+
+/// ```
+/// for i in 0..3 {
+///     println!("{}", foo::double(i));
+/// }
+/// ```
+///
+/// This prints:
+///
+/// ```output
+/// 0
+/// 2
+/// 4
+/// ```
+///
+/// ```text
+/// Not the output of a doctest.
+/// ```
+pub fn double(x: u8) -> u8 {
+    x * 2
+}
@@ -0,0 +1,29 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// compile-flags: -Z unstable-options --full-text-index
+
+#![crate_name = "foo"]
+
+// @has 'search-index-full.js' 'fullTextIndex["foo"]'
+// @has - '"allocat"'
+// @has - '"buffer"'
+// @!has - '"example"'
+// @!has - '"the"'
+// @!has - '"let"'
+// @has foo/index.html '//script[@src="../search-index-full.js"]'
+
+/// Allocates the memory of a new [buffer](https://www.example.com/).
+///
+/// ```
+/// let buffer = foo::Buffer;
+/// ```
+pub struct Buffer;
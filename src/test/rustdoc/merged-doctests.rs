@@ -0,0 +1,67 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:--test -Z unstable-options --merge-doctests
+
+#![crate_name = "foo"]
+
+/// The merged binary picks the doctest to run from this variable, so this only
+/// passes if the doctests were actually compiled together:
+///
+/// ```
+/// assert!(std::env::var("RUSTDOC_MERGED_DOCTEST").is_ok());
+/// ```
+///
+/// ```
+/// struct Thing(u8);
+/// assert_eq!(foo::double(Thing(2).0), 4);
+/// ```
+///
+/// ```
+/// use foo::double;
+/// struct Thing(u16);
+/// assert_eq!(double(Thing(3).0 as u8), 6);
+/// ```
+///
+/// ```should_panic
+/// assert_eq!(foo::double(1), 3);
+/// ```
+///
+/// ```
+/// #![allow(dead_code)]
+/// fn helper() {}
+/// assert_eq!(foo::double(0), 0);
+/// ```
+///
+/// ```
+/// fn main() {
+///     assert_eq!(foo::double(5), 10);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// let x: u8 = foo::double("two");
+/// ```
+///
+/// ```no_run
+/// loop {}
+/// ```
+///
+/// This one doesn't link, which mustn't keep the others from being merged:
+///
+/// ```no_run
+/// extern "C" {
+///     fn rustdoc_merged_doctests_missing_symbol();
+/// }
+/// unsafe { rustdoc_merged_doctests_missing_symbol() }
+/// ```
+pub fn double(x: u8) -> u8 {
+    x * 2
+}
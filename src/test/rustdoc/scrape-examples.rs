@@ -0,0 +1,31 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z unstable-options --scrape-examples {{src-base}}/auxiliary/scrape-examples-usage.rs
+// ignore-tidy-linelength
+
+#![crate_name = "foo"]
+
+// @has foo/fn.connect.html '//div[@class="docblock scraped-examples"]/h5' 'Examples found in repository'
+// @has - '//div[@class="scraped-example-title"]' 'scrape-examples-usage.rs (line 16)'
+// @has - '//div[@class="scraped-example-title"]' 'scrape-examples-usage.rs (line 18)'
+pub fn connect(_addr: &str) -> Connection {
+    Connection
+}
+
+pub struct Connection;
+
+impl Connection {
+    // @has foo/struct.Connection.html '//div[@class="scraped-example-title"]' 'scrape-examples-usage.rs (line 17)'
+    pub fn send(&self, _msg: &str) {}
+}
+
+// @!has foo/fn.unused.html '//div[@class="docblock scraped-examples"]'
+pub fn unused() {}
@@ -0,0 +1,21 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+
+#![feature(custom_test_frameworks)]
+
+#[test_case]
+static CASE: u32 = 0;
+//~^ ERROR statics cannot be used as test cases, use a `const` instead
+
+#[test_case]
+fn case() {}
+//~^ ERROR functions cannot be used as test cases without a `#![test_runner]`
@@ -32,6 +32,7 @@ pub enum Mode {
     Incremental,
     RunMake,
     Ui,
+    UiJson,
     MirOpt,
 }
 
@@ -67,6 +68,7 @@ impl FromStr for Mode {
             "incremental" => Ok(Incremental),
             "run-make" => Ok(RunMake),
             "ui" => Ok(Ui),
+            "ui-json" => Ok(UiJson),
             "mir-opt" => Ok(MirOpt),
             _ => Err(()),
         }
@@ -90,6 +92,7 @@ impl fmt::Display for Mode {
             Incremental => "incremental",
             RunMake => "run-make",
             Ui => "ui",
+            UiJson => "ui-json",
             MirOpt => "mir-opt",
         };
         fmt::Display::fmt(s, f)
@@ -237,6 +240,10 @@ pub struct Config {
     /// where to find the remote test client process, if we're using it
     pub remote_test_client: Option<PathBuf>,
 
+    /// A command line to run the compiled test programs under, e.g. an
+    /// emulator. Takes precedence over the remote test client.
+    pub runner: Option<String>,
+
     /// mode describing what file the actual ui output will be compared to
     pub compare_mode: Option<CompareMode>,
 
@@ -280,10 +287,11 @@ pub fn expected_output_path(
     testpaths.file.with_extension(extension)
 }
 
-pub const UI_EXTENSIONS: &[&str] = &[UI_STDERR, UI_STDOUT, UI_FIXED];
+pub const UI_EXTENSIONS: &[&str] = &[UI_STDERR, UI_STDOUT, UI_FIXED, UI_JSON];
 pub const UI_STDERR: &str = "stderr";
 pub const UI_STDOUT: &str = "stdout";
 pub const UI_FIXED: &str = "fixed";
+pub const UI_JSON: &str = "json";
 
 /// Absolute path to the directory where all output for all tests in the given
 /// `relative_dir` group should reside. Example:
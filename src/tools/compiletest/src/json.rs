@@ -10,7 +10,7 @@
 
 use errors::{Error, ErrorKind};
 use runtest::ProcRes;
-use serde_json;
+use serde_json::{self, Value};
 use std::path::Path;
 use std::str::FromStr;
 
@@ -79,6 +79,64 @@ pub fn extract_rendered(output: &str, proc_res: &ProcRes) -> String {
         .collect()
 }
 
+/// Turns the JSON diagnostics emitted by the compiler into the form stored
+/// in the `.json` files of ui-json tests: one pretty-printed diagnostic after
+/// the other, with object keys in sorted order.
+///
+/// The human readable rendering and the long explanations of error codes are
+/// removed, as are byte offsets, which depend on the line endings of the
+/// checkout. `normalize_path` is applied to every string, so that paths don't
+/// depend on where the tests are run.
+pub fn normalize_diagnostics(
+    output: &str,
+    proc_res: &ProcRes,
+    normalize_path: &Fn(&str) -> String,
+) -> String {
+    let mut normalized = String::new();
+    for line in output.lines().filter(|line| line.starts_with('{')) {
+        let mut diagnostic = match serde_json::from_str::<Value>(line) {
+            Ok(diagnostic) => diagnostic,
+            Err(error) => {
+                proc_res.fatal(Some(&format!(
+                    "failed to decode compiler output as json: \
+                     `{}`\noutput: {}\nline: {}",
+                    error, line, output
+                )));
+            }
+        };
+        normalize_value(&mut diagnostic, normalize_path);
+        normalized.push_str(&serde_json::to_string_pretty(&diagnostic).unwrap());
+        normalized.push('\n');
+    }
+    normalized
+}
+
+fn normalize_value(value: &mut Value, normalize_path: &Fn(&str) -> String) {
+    match *value {
+        Value::String(ref mut s) => *s = normalize_path(s),
+        Value::Array(ref mut values) => {
+            for value in values {
+                normalize_value(value, normalize_path);
+            }
+        }
+        Value::Object(ref mut map) => {
+            for key in &["rendered", "explanation", "byte_start", "byte_end"] {
+                map.remove(*key);
+            }
+            for (key, value) in map.iter_mut() {
+                normalize_value(value, normalize_path);
+                // Windows paths
+                if key == "file_name" {
+                    if let Value::String(ref mut s) = *value {
+                        *s = s.replace("\\", "/");
+                    }
+                }
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
 pub fn parse_output(file_name: &str, output: &str, proc_res: &ProcRes) -> Vec<Error> {
     output
         .lines()
@@ -237,6 +237,13 @@ pub fn parse_config(args: Vec<String>) -> Config {
             "path to the remote test client",
             "PATH",
         )
+        .optopt(
+            "",
+            "runner",
+            "command to run test programs under (eg. an emulator), which \
+             must propagate their output and exit status",
+            "COMMAND",
+        )
         .optopt(
             "",
             "compare-mode",
@@ -339,6 +346,7 @@ pub fn parse_config(args: Vec<String>) -> Config {
         quiet: matches.opt_present("quiet"),
         color,
         remote_test_client: matches.opt_str("remote-test-client").map(PathBuf::from),
+        runner: matches.opt_str("runner"),
         compare_mode: matches.opt_str("compare-mode").map(CompareMode::parse),
 
         cc: matches.opt_str("cc").unwrap(),
@@ -376,6 +384,7 @@ pub fn log_config(config: &Config) {
     );
     logv(c, format!("filter_exact: {}", config.filter_exact));
     logv(c, format!("runtool: {}", opt_str(&config.runtool)));
+    logv(c, format!("runner: {}", opt_str(&config.runner)));
     logv(
         c,
         format!("host-rustcflags: {}", opt_str(&config.host_rustcflags)),
@@ -506,6 +515,7 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
     test::TestOpts {
         filter: config.filter.clone(),
         filter_exact: config.filter_exact,
+        subtest_filter: None,
         run_ignored: config.run_ignored,
         format: if config.quiet {
             test::OutputFormat::Terse
@@ -522,6 +532,10 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         color: config.color,
         test_threads: None,
         skip: vec![],
+        save_baseline: None,
+        baseline: None,
+        baseline_dir: PathBuf::from(test::DEFAULT_BASELINE_DIR),
+        noise_threshold: test::DEFAULT_NOISE_THRESHOLD_PCT,
         list: false,
         options: test::Options::new(),
     }
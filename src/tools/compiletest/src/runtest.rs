@@ -9,12 +9,12 @@
 // except according to those terms.
 
 use common::CompareMode;
-use common::{expected_output_path, UI_FIXED, UI_STDERR, UI_STDOUT};
+use common::{expected_output_path, UI_FIXED, UI_JSON, UI_STDERR, UI_STDOUT};
 use common::{output_base_dir, output_base_name, output_testname_unique};
 use common::{Codegen, CodegenUnits, DebugInfoGdb, DebugInfoLldb, Rustdoc};
 use common::{CompileFail, ParseFail, Pretty, RunFail, RunPass, RunPassValgrind};
 use common::{Config, TestPaths};
-use common::{Incremental, MirOpt, RunMake, Ui};
+use common::{Incremental, MirOpt, RunMake, Ui, UiJson};
 use diff;
 use errors::{self, Error, ErrorKind};
 use filetime::FileTime;
@@ -258,6 +258,7 @@ impl<'test> TestCx<'test> {
             Incremental => self.run_incremental_test(),
             RunMake => self.run_rmake_test(),
             Ui => self.run_ui_test(),
+            UiJson => self.run_ui_json_test(),
             MirOpt => self.run_mir_opt_test(),
         }
     }
@@ -1312,7 +1313,7 @@ impl<'test> TestCx<'test> {
         rustc.arg("-L").arg(&self.aux_output_dir_name());
 
         match self.config.mode {
-            CompileFail | Ui => {
+            CompileFail | Ui | UiJson => {
                 // compile-fail and ui tests tend to have tons of unused code as
                 // it's just testing various pieces of the compile, but we don't
                 // want to actually assert warnings about all this code. Instead
@@ -1396,7 +1397,7 @@ impl<'test> TestCx<'test> {
             // `support-lib.so`. It will then execute the program on the
             // emulator with the arguments specified (in the environment we give
             // the process) and then report back the same result.
-            _ if self.config.remote_test_client.is_some() => {
+            _ if self.config.remote_test_client.is_some() && self.config.runner.is_none() => {
                 let aux_dir = self.aux_output_dir_name();
                 let ProcArgs { mut prog, args } = self.make_run_args();
                 if let Ok(entries) = aux_dir.read_dir() {
@@ -1690,6 +1691,12 @@ impl<'test> TestCx<'test> {
                     rustc.arg("-Zui-testing");
                 }
             }
+            UiJson => {
+                rustc.args(&["--error-format", "json"]);
+                if !self.props.disable_ui_testing_normalization {
+                    rustc.arg("-Zui-testing");
+                }
+            }
             MirOpt => {
                 rustc.args(&[
                     "-Zdump-mir=all",
@@ -1784,12 +1791,13 @@ impl<'test> TestCx<'test> {
     }
 
     fn make_run_args(&self) -> ProcArgs {
-        // If we've got another tool to run under (valgrind),
-        // then split apart its command
-        let mut args = self.split_maybe_args(&self.config.runtool);
+        // If we've got a runner for the target (e.g. an emulator) or another
+        // tool to run under (valgrind), then split apart their commands
+        let mut args = self.split_maybe_args(&self.config.runner);
+        args.extend(self.split_maybe_args(&self.config.runtool));
 
         // If this is emscripten, then run tests under nodejs
-        if self.config.target.contains("emscripten") {
+        if self.config.runner.is_none() && self.config.target.contains("emscripten") {
             if let Some(ref p) = self.config.nodejs {
                 args.push(p.clone());
             } else {
@@ -1799,7 +1807,7 @@ impl<'test> TestCx<'test> {
 
         // If this is otherwise wasm , then run tests under nodejs with our
         // shim
-        if self.config.target.contains("wasm32") {
+        if self.config.runner.is_none() && self.config.target.contains("wasm32") {
             if let Some(ref p) = self.config.nodejs {
                 args.push(p.clone());
             } else {
@@ -2683,6 +2691,29 @@ impl<'test> TestCx<'test> {
         }
     }
 
+    fn run_ui_json_test(&self) {
+        let proc_res = self.compile_test();
+        self.check_if_test_should_compile(&proc_res);
+        self.check_no_compiler_crash(&proc_res);
+
+        let parent_dir = self.testpaths.file.parent().unwrap().display().to_string();
+        let src_dir = self.config.src_base.parent().unwrap().parent().unwrap();
+        let src_dir = src_dir.display().to_string();
+        let normalize_path = |s: &str| s.replace(&parent_dir, "$DIR").replace(&src_dir, "$SRC_DIR");
+        let mut diagnostics =
+            json::normalize_diagnostics(&proc_res.stderr, &proc_res, &normalize_path);
+        for rule in &self.props.normalize_stderr {
+            let re = Regex::new(&rule.0).expect("bad regex in custom normalization rule");
+            diagnostics = re.replace_all(&diagnostics, &rule.1[..]).into_owned();
+        }
+
+        let expected_json = self.load_expected_output(UI_JSON);
+        if self.compare_output(UI_JSON, &diagnostics, &expected_json) > 0 {
+            println!("To update references, rerun the tests and pass the `--bless` flag");
+            self.fatal_proc_rec("the JSON diagnostics differed from the expected ones", &proc_res);
+        }
+    }
+
     fn run_mir_opt_test(&self) {
         let proc_res = self.compile_test();
 
@@ -9,17 +9,22 @@
 // except according to those terms.
 
 //! Tidy check to ensure that there are no stray `.stderr` files in UI test directories.
+//! The same goes for the `.json` files of ui-json tests.
 
 use std::fs;
 use std::path::Path;
 
 pub fn check(path: &Path, bad: &mut bool) {
     super::walk_many(
-        &[&path.join("test/ui"), &path.join("test/ui-fulldeps")],
+        &[
+            &path.join("test/ui"),
+            &path.join("test/ui-fulldeps"),
+            &path.join("test/ui-json"),
+        ],
         &mut |_| false,
         &mut |file_path| {
             if let Some(ext) = file_path.extension() {
-                if ext == "stderr" || ext == "stdout" {
+                if ext == "stderr" || ext == "stdout" || ext == "json" {
                     // Test output filenames have the format:
                     // $testname.stderr
                     // $testname.$mode.stderr